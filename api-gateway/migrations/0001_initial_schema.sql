@@ -0,0 +1,79 @@
+-- Gateway schema. IDs are UUID blobs and timestamps RFC 3339 text, as sqlx
+-- stores them in SQLite. Tables use IF NOT EXISTS so databases created before
+-- migrations were tracked are adopted as they are.
+
+CREATE TABLE IF NOT EXISTS users (
+    id BLOB PRIMARY KEY NOT NULL,
+    near_account_id TEXT UNIQUE,
+    email TEXT,
+    username TEXT NOT NULL UNIQUE,
+    password_hash TEXT,
+    is_active BOOLEAN NOT NULL DEFAULT 1,
+    is_admin BOOLEAN NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    last_login_at TEXT,
+    email_verified BOOLEAN NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS api_keys (
+    id BLOB PRIMARY KEY NOT NULL,
+    user_id BLOB NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    key_hash TEXT NOT NULL,
+    prefix TEXT NOT NULL UNIQUE,
+    is_active BOOLEAN NOT NULL DEFAULT 1,
+    rate_limit_override INTEGER,
+    scopes TEXT NOT NULL DEFAULT '[]', -- JSON array
+    created_at TEXT NOT NULL,
+    last_used_at TEXT,
+    expires_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_user_id ON api_keys (user_id);
+
+-- SQLite has no enum types; `task_status` is text limited to the
+-- lowercase `TaskStatus` variants
+CREATE TABLE IF NOT EXISTS tasks (
+    id BLOB PRIMARY KEY NOT NULL,
+    user_id BLOB NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    contract_task_id INTEGER,
+    task_type TEXT NOT NULL,
+    model_name TEXT NOT NULL,
+    input_data TEXT NOT NULL,
+    parameters TEXT, -- JSON
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN (
+        'pending', 'submitted', 'assigned', 'inprogress',
+        'completed', 'failed', 'cancelled', 'expired'
+    )),
+    priority INTEGER NOT NULL DEFAULT 0,
+    estimated_cost TEXT NOT NULL, -- yoctoNEAR
+    actual_cost TEXT,
+    assigned_node_id TEXT,
+    result_data TEXT,
+    proof_hash TEXT,
+    error_message TEXT,
+    created_at TEXT NOT NULL,
+    started_at TEXT,
+    completed_at TEXT,
+    expires_at TEXT NOT NULL,
+    webhook_url TEXT,
+    webhook_secret TEXT,
+    webhook_status TEXT, -- delivered | failed
+    retried_from BLOB REFERENCES tasks (id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_tasks_user_id ON tasks (user_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_tasks_status ON tasks (status);
+CREATE INDEX IF NOT EXISTS idx_tasks_contract_task_id ON tasks (contract_task_id);
+
+CREATE TABLE IF NOT EXISTS password_reset_tokens (
+    id BLOB PRIMARY KEY NOT NULL,
+    user_id BLOB NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL UNIQUE,
+    expires_at TEXT NOT NULL,
+    used_at TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_password_reset_tokens_user_id ON password_reset_tokens (user_id);
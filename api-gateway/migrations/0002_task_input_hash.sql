@@ -0,0 +1,3 @@
+-- SHA-256 of input_data for tasks whose input is kept off-chain. Their
+-- on-chain description carries this hash and a reference instead of the input.
+ALTER TABLE tasks ADD COLUMN input_hash TEXT;
@@ -0,0 +1,25 @@
+-- NEP-297 events logged by the contract, copied in by the event indexer. A
+-- log is identified by the receipt that emitted it and its position among that
+-- receipt's logs, so indexing a block twice never duplicates its events. Only
+-- final blocks are indexed, so rows are never rolled back by a reorg.
+CREATE TABLE IF NOT EXISTS events (
+    block_height INTEGER NOT NULL, -- Block the transaction was included in
+    receipt_id TEXT NOT NULL,
+    log_index INTEGER NOT NULL,
+    block_timestamp TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    node_id TEXT,
+    task_id INTEGER,
+    data TEXT NOT NULL, -- JSON array, the event's `data`
+    PRIMARY KEY (block_height, receipt_id, log_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_events_event_type ON events (event_type);
+CREATE INDEX IF NOT EXISTS idx_events_node_id ON events (node_id);
+CREATE INDEX IF NOT EXISTS idx_events_task_id ON events (task_id);
+
+-- Last block an indexer has fully processed
+CREATE TABLE IF NOT EXISTS indexer_cursors (
+    name TEXT PRIMARY KEY NOT NULL,
+    block_height INTEGER NOT NULL
+);
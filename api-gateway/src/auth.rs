@@ -1,16 +1,20 @@
-use axum::{extract::State, http::StatusCode, Json};
+use axum::{extract::State, http::{header, Method, StatusCode}, response::{IntoResponse, Response}, Json};
 use bcrypt::{hash, verify, DEFAULT_COST};
 use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::Validation;
 use serde::{Deserialize, Serialize};
 use near_crypto::{PublicKey, Signature};
 use near_primitives::account::id::AccountId;
+use std::future::Future;
+use tracing::warn;
 use uuid::Uuid;
 use crate::{
-    database::{create_user, get_user_by_username, get_user_by_account_id, create_api_key, verify_api_key},
+    database::{create_user, get_active_api_key_by_prefix, get_user_by_username, get_user_by_account_id},
     errors::{ApiError, ApiResult},
-    models::{User, CreateUserRequest, LoginRequest, NearLoginRequest, AuthResponse, ApiKey},
+    models::{User, CreateUserRequest, LoginRequest, NearLoginRequest, NearChallengeRequest, NearChallengeResponse, AuthResponse, ApiKey},
+    near_challenge::{challenge_message, generate_challenge, parse_challenge_message, redeem_challenge, store_challenge, CHALLENGE_TTL_SECS},
     handlers::AppState,
+    jwt_keys::JwtKeys,
 };
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -39,26 +43,30 @@ impl Claims {
     }
 }
 
-pub fn create_jwt_token(user: &User, secret: &str, token_type: &str) -> ApiResult<String> {
+pub fn create_jwt_token(user: &User, keys: &JwtKeys, token_type: &str) -> ApiResult<String> {
     let duration = if token_type == "api_key" { 24 * 30 } else { 24 }; // 30 days for API keys, 24 hours for access tokens
     let claims = Claims::new(user, token_type, duration);
     
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret.as_ref()),
-    )
-    .map_err(|e| ApiError::Internal(format!("Failed to create JWT: {}", e)))
+    keys.sign(&claims)
+        .map_err(|e| ApiError::Internal(format!("Failed to create JWT: {}", e)))
 }
 
-pub fn verify_jwt_token(token: &str, secret: &str) -> ApiResult<Claims> {
-    decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(secret.as_ref()),
-        &Validation::default(),
-    )
-    .map(|data| data.claims)
-    .map_err(|e| ApiError::Unauthorized(format!("Invalid token: {}", e)))
+// How long after expiry an access token can still be exchanged for a new one
+pub const REFRESH_GRACE_PERIOD_SECS: u64 = 15 * 60;
+
+pub fn verify_jwt_token(token: &str, keys: &JwtKeys) -> ApiResult<Claims> {
+    keys.verify(token, &Validation::default())
+        .map_err(|e| ApiError::Unauthorized(format!("Invalid token: {}", e)))
+}
+
+/// Like `verify_jwt_token`, but also accepts tokens that expired less than
+/// `REFRESH_GRACE_PERIOD_SECS` ago. Only use this to issue a replacement token.
+pub fn verify_jwt_token_for_refresh(token: &str, keys: &JwtKeys) -> ApiResult<Claims> {
+    let mut validation = Validation::default();
+    validation.leeway = REFRESH_GRACE_PERIOD_SECS;
+    
+    keys.verify(token, &validation)
+        .map_err(|e| ApiError::Unauthorized(format!("Invalid token: {}", e)))
 }
 
 pub async fn register_user(
@@ -106,7 +114,7 @@ pub async fn register_user(
     ).await?;
 
     // Generate JWT token
-    let access_token = create_jwt_token(&user, &state.config.jwt_secret, "access")?;
+    let access_token = create_jwt_token(&user, &state.jwt_keys, "access")?;
 
     Ok(Json(AuthResponse {
         access_token,
@@ -119,7 +127,12 @@ pub async fn register_user(
 pub async fn login_user(
     State(state): State<AppState>,
     Json(request): Json<LoginRequest>,
-) -> ApiResult<Json<AuthResponse>> {
+) -> Response {
+    let account = format!("user:{}", request.username);
+    throttled_login(&state, &account, password_login(&state, request)).await
+}
+
+async fn password_login(state: &AppState, request: LoginRequest) -> ApiResult<Json<AuthResponse>> {
     // Get user from database
     let user = get_user_by_username(&state.db_pool, &request.username).await
         .map_err(|_| ApiError::Unauthorized("Invalid credentials".to_string()))?;
@@ -131,7 +144,7 @@ pub async fn login_user(
     }
 
     // Generate JWT token
-    let access_token = create_jwt_token(&user, &state.config.jwt_secret, "access")?;
+    let access_token = create_jwt_token(&user, &state.jwt_keys, "access")?;
 
     Ok(Json(AuthResponse {
         access_token,
@@ -141,10 +154,35 @@ pub async fn login_user(
     }))
 }
 
+/// Issue a single-use challenge for a Near wallet login. The wallet signs the
+/// returned `message`, which `near_wallet_login` accepts once.
+pub async fn issue_near_challenge(
+    State(state): State<AppState>,
+    Json(request): Json<NearChallengeRequest>,
+) -> ApiResult<Json<NearChallengeResponse>> {
+    let account_id: AccountId = request.account_id.parse()
+        .map_err(|_| ApiError::BadRequest("Invalid Near account ID".to_string()))?;
+
+    let challenge = generate_challenge();
+    store_challenge(&state.redis_client, account_id.as_str(), &challenge).await
+        .map_err(|e| ApiError::Internal(format!("Failed to store login challenge: {}", e)))?;
+
+    Ok(Json(NearChallengeResponse {
+        message: challenge_message(account_id.as_str(), &challenge),
+        challenge,
+        expires_in: CHALLENGE_TTL_SECS,
+    }))
+}
+
 pub async fn near_wallet_login(
     State(state): State<AppState>,
     Json(request): Json<NearLoginRequest>,
-) -> ApiResult<Json<AuthResponse>> {
+) -> Response {
+    let account = format!("near:{}", request.account_id);
+    throttled_login(&state, &account, wallet_login(&state, request)).await
+}
+
+async fn wallet_login(state: &AppState, request: NearLoginRequest) -> ApiResult<Json<AuthResponse>> {
     // Parse Near account ID
     let account_id: AccountId = request.account_id.parse()
         .map_err(|_| ApiError::BadRequest("Invalid Near account ID".to_string()))?;
@@ -163,20 +201,26 @@ pub async fn near_wallet_login(
         return Err(ApiError::Unauthorized("Invalid signature".to_string()));
     }
 
-    // Check if message is recent (within 5 minutes)
-    let message_parts: Vec<&str> = request.message.split('|').collect();
-    if message_parts.len() != 2 {
-        return Err(ApiError::BadRequest("Invalid message format".to_string()));
-    }
-    
-    let timestamp: i64 = message_parts[1].parse()
-        .map_err(|_| ApiError::BadRequest("Invalid timestamp in message".to_string()))?;
+    // The key must also belong to the claimed account
+    crate::handlers::auth::verify_key_ownership(state, &account_id, &request.public_key).await?;
+
+    // The message must carry a challenge from `issue_near_challenge`
+    let (challenge, timestamp) = parse_challenge_message(&request.message)
+        .ok_or_else(|| ApiError::BadRequest("Invalid message format. Request a challenge first".to_string()))?;
     
+    // Check if message is recent (within 5 minutes)
     let now = Utc::now().timestamp();
-    if (now - timestamp).abs() > 300 { // 5 minutes
+    if (now - timestamp).abs() > CHALLENGE_TTL_SECS as i64 {
         return Err(ApiError::Unauthorized("Message timestamp too old".to_string()));
     }
 
+    // Spent last, so a request failing an earlier check doesn't burn it
+    let redeemed = redeem_challenge(&state.redis_client, account_id.as_str(), challenge).await
+        .map_err(|e| ApiError::Internal(format!("Failed to check login challenge: {}", e)))?;
+    if !redeemed {
+        return Err(ApiError::Unauthorized("Login challenge is unknown, expired or already used".to_string()));
+    }
+
     // Get or create user
     let user = match get_user_by_account_id(&state.db_pool, &request.account_id).await {
         Ok(user) => user,
@@ -190,7 +234,7 @@ pub async fn near_wallet_login(
             create_user(
                 &state.db_pool,
                 &username,
-                &email,
+                &Some(email),
                 &dummy_password,
                 Some(&request.account_id),
             ).await?
@@ -198,7 +242,7 @@ pub async fn near_wallet_login(
     };
 
     // Generate JWT token
-    let access_token = create_jwt_token(&user, &state.config.jwt_secret, "access")?;
+    let access_token = create_jwt_token(&user, &state.jwt_keys, "access")?;
 
     Ok(Json(AuthResponse {
         access_token,
@@ -208,38 +252,286 @@ pub async fn near_wallet_login(
     }))
 }
 
+// Refuses a locked-out account before its credentials are checked, and
+// counts rejected attempts towards the lockout. The IP limiter still applies.
+async fn throttled_login<F>(state: &AppState, account: &str, login: F) -> Response
+where
+    F: Future<Output = ApiResult<Json<AuthResponse>>>,
+{
+    if let Some(retry_after) = state.login_throttle.locked_out(account).await {
+        warn!("Login for {} refused: too many failed attempts", account);
+        return (
+            [(header::RETRY_AFTER, retry_after.to_string())],
+            ApiError::TooManyRequests("Too many failed login attempts. Try again later".to_string()),
+        ).into_response();
+    }
+
+    let result = login.await;
+    match &result {
+        Ok(_) => state.login_throttle.reset(account).await,
+        Err(ApiError::Unauthorized(_)) => state.login_throttle.record_failure(account).await,
+        Err(_) => {}
+    }
+    result.into_response()
+}
+
+// API keys look like `deai_<prefix>_<secret>`. Only the prefix and a bcrypt
+// hash of the whole key are stored, so the key is shown to its owner once.
+const API_KEY_MARKER: &str = "deai_";
+const API_KEY_PREFIX_LEN: usize = 8;
+// Keys carry 128 random bits, so a lower work factor than passwords is enough
+const API_KEY_HASH_COST: u32 = 10;
+
+fn generate_api_key() -> (String, String) {
+    let prefix = Uuid::new_v4().simple().to_string()[..API_KEY_PREFIX_LEN].to_string();
+    let secret = Uuid::new_v4().simple().to_string();
+    (format!("{}{}_{}", API_KEY_MARKER, prefix, secret), prefix)
+}
+
+/// Prefix of an opaque API key, or `None` if the token is not shaped like one.
+pub fn api_key_prefix(token: &str) -> Option<&str> {
+    let (prefix, secret) = token.strip_prefix(API_KEY_MARKER)?.split_once('_')?;
+    
+    if prefix.len() != API_KEY_PREFIX_LEN || secret.is_empty() {
+        return None;
+    }
+    Some(prefix)
+}
+
+pub const SCOPE_TASKS_READ: &str = "tasks:read";
+pub const SCOPE_TASKS_WRITE: &str = "tasks:write";
+pub const SCOPE_NODES_READ: &str = "nodes:read";
+pub const SCOPE_ACCOUNT_READ: &str = "account:read";
+pub const SCOPE_ACCOUNT_WRITE: &str = "account:write";
+pub const SCOPE_ADMIN: &str = "admin";
+
+/// Every scope an API key can hold. Sessions, and keys created without
+/// explicit scopes, are granted all of them.
+pub const ALL_SCOPES: &[&str] = &[
+    SCOPE_TASKS_READ,
+    SCOPE_TASKS_WRITE,
+    SCOPE_NODES_READ,
+    SCOPE_ACCOUNT_READ,
+    SCOPE_ACCOUNT_WRITE,
+    SCOPE_ADMIN,
+];
+
+pub fn all_scopes() -> Vec<String> {
+    ALL_SCOPES.iter().map(|scope| scope.to_string()).collect()
+}
+
+/// Check requested scopes against the known set, dropping duplicates.
+/// No scopes means every scope, matching keys created before scopes existed.
+pub fn normalize_scopes(requested: Vec<String>) -> ApiResult<Vec<String>> {
+    if requested.is_empty() {
+        return Ok(all_scopes());
+    }
+    
+    let mut scopes = Vec::with_capacity(requested.len());
+    for scope in requested {
+        if !ALL_SCOPES.contains(&scope.as_str()) {
+            return Err(ApiError::BadRequest(format!("Unknown API key scope: {}", scope)));
+        }
+        if !scopes.contains(&scope) {
+            scopes.push(scope);
+        }
+    }
+    Ok(scopes)
+}
+
+/// Scope a request needs, or `None` if any authenticated caller may make it.
+pub fn required_scope(method: &Method, path: &str) -> Option<&'static str> {
+    let is_read = method == Method::GET || method == Method::HEAD;
+    let under = |prefix: &str| path == prefix || path.starts_with(&format!("{}/", prefix));
+    
+    if under("/api/v1/admin") {
+        Some(SCOPE_ADMIN)
+    } else if under("/api/v1/tasks") {
+        Some(if is_read { SCOPE_TASKS_READ } else { SCOPE_TASKS_WRITE })
+    } else if under("/api/v1/nodes") || under("/api/v1/network") {
+        Some(SCOPE_NODES_READ)
+    } else if under("/api/v1/user") {
+        Some(if is_read { SCOPE_ACCOUNT_READ } else { SCOPE_ACCOUNT_WRITE })
+    } else {
+        None
+    }
+}
+
+/// Create an API key for the user. The returned plaintext key is not stored
+/// and must be handed back to the caller in `ApiKeyResponse.key`.
 pub async fn create_user_api_key(
     user_id: Uuid,
     name: String,
     expires_in_days: Option<i32>,
+    scopes: Vec<String>,
     state: &AppState,
-) -> ApiResult<ApiKey> {
+) -> ApiResult<(ApiKey, String)> {
     let expires_at = expires_in_days.map(|days| Utc::now() + Duration::days(days as i64));
+    let scopes = normalize_scopes(scopes)?;
     
-    // Generate API key token
-    let user = crate::database::get_user_by_id(&state.db_pool, user_id).await?;
-    let token = create_jwt_token(&user, &state.config.jwt_secret, "api_key")?;
+    let (key, prefix) = generate_api_key();
+    let key_hash = hash(&key, API_KEY_HASH_COST)
+        .map_err(|e| ApiError::Internal(format!("Failed to hash API key: {}", e)))?;
     
-    create_api_key(
-        &state.db_pool,
-        user_id,
-        &name,
-        &token,
-        expires_at,
-    ).await
+    let api_key = sqlx::query_as::<_, ApiKey>(
+        r#"
+        INSERT INTO api_keys (id, user_id, name, key_hash, prefix, is_active, scopes, created_at, expires_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, 1, ?6, ?7, ?8)
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(name)
+    .bind(key_hash)
+    .bind(prefix)
+    .bind(sqlx::types::Json(&scopes))
+    .bind(Utc::now())
+    .bind(expires_at)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| ApiError::Database(e.to_string()))?;
+    
+    Ok((api_key, key))
 }
 
 pub async fn verify_user_api_key(token: &str, state: &AppState) -> ApiResult<(User, ApiKey)> {
-    // Verify JWT token first
-    let claims = verify_jwt_token(token, &state.config.jwt_secret)?;
+    let invalid = || ApiError::Unauthorized("Invalid API key".to_string());
+    
+    let prefix = api_key_prefix(token).ok_or_else(invalid)?;
     
-    if claims.token_type != "api_key" {
-        return Err(ApiError::Unauthorized("Invalid token type".to_string()));
+    let api_key = get_active_api_key_by_prefix(&state.db_pool, prefix).await?
+        .ok_or_else(invalid)?;
+    
+    if api_key.expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+        return Err(ApiError::Unauthorized("API key has expired".to_string()));
+    }
+    
+    if !verify(token, &api_key.key_hash)
+        .map_err(|e| ApiError::Internal(format!("Failed to verify API key: {}", e)))? {
+        return Err(invalid());
     }
     
-    // Verify API key in database
-    let api_key = verify_api_key(&state.db_pool, token).await?;
+    sqlx::query!(
+        "UPDATE api_keys SET last_used_at = ?1 WHERE id = ?2",
+        Utc::now(),
+        api_key.id
+    )
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| ApiError::Database(e.to_string()))?;
+    
     let user = crate::database::get_user_by_id(&state.db_pool, api_key.user_id).await?;
     
     Ok((user, api_key))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+
+    const PRIMARY_SECRET: &str = "primary-secret-at-least-32-characters-long";
+    const PREVIOUS_SECRET: &str = "previous-secret-at-least-32-characters-long";
+
+    fn sign_token(secret: &str) -> String {
+        sign_token_expiring(secret, Duration::hours(1))
+    }
+
+    fn sign_token_expiring(secret: &str, expires_in: Duration) -> String {
+        let now = Utc::now();
+        let claims = Claims {
+            sub: Uuid::new_v4().to_string(),
+            username: "alice".to_string(),
+            account_id: None,
+            exp: (now + expires_in).timestamp() as usize,
+            iat: now.timestamp() as usize,
+            token_type: "access".to_string(),
+        };
+
+        encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_ref())).unwrap()
+    }
+
+    #[test]
+    fn test_previous_secret_verifies_during_rotation() {
+        let token = sign_token(PREVIOUS_SECRET);
+
+        let claims = verify_jwt_token(&token, &JwtKeys::hmac(&[PRIMARY_SECRET, PREVIOUS_SECRET])).unwrap();
+        assert_eq!(claims.username, "alice");
+    }
+
+    #[test]
+    fn test_retired_secret_is_rejected() {
+        let token = sign_token(PREVIOUS_SECRET);
+
+        assert!(verify_jwt_token(&token, &JwtKeys::hmac(&[PRIMARY_SECRET])).is_err());
+    }
+
+    #[test]
+    fn test_primary_secret_verifies() {
+        let token = sign_token(PRIMARY_SECRET);
+
+        assert!(verify_jwt_token(&token, &JwtKeys::hmac(&[PRIMARY_SECRET, PREVIOUS_SECRET])).is_ok());
+    }
+
+    #[test]
+    fn test_generated_api_key_prefix() {
+        let (key, prefix) = generate_api_key();
+
+        assert!(key.starts_with("deai_"));
+        assert_eq!(api_key_prefix(&key), Some(prefix.as_str()));
+        assert_ne!(generate_api_key().0, key);
+    }
+
+    #[test]
+    fn test_api_key_prefix_rejects_other_tokens() {
+        assert_eq!(api_key_prefix(&sign_token(PRIMARY_SECRET)), None);
+        assert_eq!(api_key_prefix("deai_short_secret"), None);
+        assert_eq!(api_key_prefix("deai_abcdef12_"), None);
+        assert_eq!(api_key_prefix("deai_abcdef12"), None);
+    }
+
+    #[test]
+    fn test_missing_scopes_default_to_all() {
+        assert_eq!(normalize_scopes(Vec::new()).unwrap(), all_scopes());
+    }
+
+    #[test]
+    fn test_scopes_are_validated_and_deduplicated() {
+        let scopes = normalize_scopes(vec![
+            "tasks:read".to_string(),
+            "nodes:read".to_string(),
+            "tasks:read".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(scopes, vec!["tasks:read", "nodes:read"]);
+
+        assert!(normalize_scopes(vec!["tasks:delete".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_required_scope_for_routes() {
+        assert_eq!(required_scope(&Method::POST, "/api/v1/tasks"), Some(SCOPE_TASKS_WRITE));
+        assert_eq!(required_scope(&Method::GET, "/api/v1/tasks"), Some(SCOPE_TASKS_READ));
+        assert_eq!(required_scope(&Method::POST, "/api/v1/tasks/abc/cancel"), Some(SCOPE_TASKS_WRITE));
+        assert_eq!(required_scope(&Method::GET, "/api/v1/nodes/node-1/metrics"), Some(SCOPE_NODES_READ));
+        assert_eq!(required_scope(&Method::GET, "/api/v1/user/usage"), Some(SCOPE_ACCOUNT_READ));
+        assert_eq!(required_scope(&Method::POST, "/api/v1/admin/users/abc/suspend"), Some(SCOPE_ADMIN));
+        assert_eq!(required_scope(&Method::GET, "/api/v1/tasksx"), None);
+    }
+
+    #[test]
+    fn test_recently_expired_token_can_be_refreshed() {
+        let token = sign_token_expiring(PRIMARY_SECRET, Duration::minutes(-5));
+
+        assert!(verify_jwt_token(&token, &JwtKeys::hmac(&[PRIMARY_SECRET])).is_err());
+        assert!(verify_jwt_token_for_refresh(&token, &JwtKeys::hmac(&[PRIMARY_SECRET])).is_ok());
+    }
+
+    #[test]
+    fn test_token_past_grace_period_cannot_be_refreshed() {
+        let token = sign_token_expiring(PRIMARY_SECRET, Duration::minutes(-30));
+
+        assert!(verify_jwt_token_for_refresh(&token, &JwtKeys::hmac(&[PRIMARY_SECRET])).is_err());
+    }
+}
@@ -0,0 +1,132 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use redis::AsyncCommands;
+use serde::{de::DeserializeOwned, Serialize};
+use std::future::Future;
+use tracing::{debug, warn};
+
+use crate::{
+    handlers::AppState,
+    models::{CachedResponse, NodeInfo},
+    near_client::ContractStats,
+};
+
+const ACTIVE_NODES_KEY: &str = "chain_cache:active_nodes";
+const CONTRACT_STATS_KEY: &str = "chain_cache:contract_stats";
+
+/// Active nodes from the contract, served from Redis while fresh.
+/// `bypass` skips the cached copy and refreshes it.
+pub async fn active_nodes(state: &AppState, bypass: bool) -> Result<CachedResponse<Vec<NodeInfo>>> {
+    get_or_fetch(state, ACTIVE_NODES_KEY, bypass, || state.near_client.get_active_nodes()).await
+}
+
+/// Contract-wide counters, served from Redis while fresh.
+pub async fn contract_stats(state: &AppState, bypass: bool) -> Result<CachedResponse<ContractStats>> {
+    get_or_fetch(state, CONTRACT_STATS_KEY, bypass, || state.near_client.get_contract_stats()).await
+}
+
+/// Keep both entries warm so requests are served from Redis even right after
+/// an entry would have expired. Refreshes at two thirds of the TTL.
+pub fn spawn_background_refresh(state: AppState) -> tokio::task::JoinHandle<()> {
+    let period = std::time::Duration::from_millis((state.config.chain_cache.ttl_seconds * 1000 * 2 / 3).max(1000));
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = active_nodes(&state, true).await {
+                warn!("Background refresh of active nodes failed: {}", e);
+            }
+            if let Err(e) = contract_stats(&state, true).await {
+                warn!("Background refresh of contract stats failed: {}", e);
+            }
+        }
+    })
+}
+
+async fn get_or_fetch<T, F, Fut>(state: &AppState, key: &str, bypass: bool, fetch: F) -> Result<CachedResponse<T>>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    if !bypass {
+        if let Some(cached) = read_cached(state, key).await {
+            debug!("Serving {} from cache", key);
+            return Ok(cached);
+        }
+    }
+
+    let ttl_seconds = state.config.chain_cache.ttl_seconds;
+    let entry = cache_entry(fetch().await?, ttl_seconds, Utc::now());
+    write_cached(state, key, &entry, ttl_seconds).await;
+
+    Ok(entry)
+}
+
+fn cache_entry<T>(data: T, ttl_seconds: u64, now: DateTime<Utc>) -> CachedResponse<T> {
+    CachedResponse {
+        data,
+        fetched_at: now,
+        stale_at: now + Duration::seconds(ttl_seconds as i64),
+    }
+}
+
+// Like the rate limiter, an unavailable Redis degrades to direct RPC calls
+async fn read_cached<T: DeserializeOwned>(state: &AppState, key: &str) -> Option<CachedResponse<T>> {
+    let mut conn = state.redis_client.get_multiplexed_async_connection().await.ok()?;
+    let cached: Option<String> = conn.get(key).await.ok()?;
+
+    serde_json::from_str(&cached?).ok()
+}
+
+async fn write_cached<T: Serialize>(state: &AppState, key: &str, entry: &CachedResponse<T>, ttl_seconds: u64) {
+    let Ok(payload) = serde_json::to_string(entry) else {
+        return;
+    };
+
+    let result = match state.redis_client.get_multiplexed_async_connection().await {
+        Ok(mut conn) => conn.set_ex::<_, _, ()>(key, payload, ttl_seconds).await,
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
+        warn!("Failed to cache {}: {}", key, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_cache_entry_goes_stale_after_ttl() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+
+        let entry = cache_entry(vec![1, 2, 3], 15, now);
+
+        assert_eq!(entry.fetched_at, now);
+        assert_eq!(entry.stale_at, now + Duration::seconds(15));
+    }
+
+    #[test]
+    fn test_cache_entry_round_trips_through_json() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let stats = ContractStats {
+            active_nodes: 3,
+            total_nodes: 5,
+            active_tasks: 7,
+            paused: false,
+            max_tasks_per_node: 5,
+            average_task_time_ms: Some(1200.0),
+        };
+
+        let payload = serde_json::to_string(&cache_entry(stats.clone(), 15, now)).unwrap();
+        let decoded: CachedResponse<ContractStats> = serde_json::from_str(&payload).unwrap();
+
+        assert_eq!(decoded.data, stats);
+        assert_eq!(decoded.stale_at, now + Duration::seconds(15));
+    }
+}
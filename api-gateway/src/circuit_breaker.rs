@@ -0,0 +1,257 @@
+use serde::Serialize;
+use std::fmt::{self, Debug, Display};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::{config::NearConfig, errors::ApiError, rpc_retry::is_retryable};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    Closed,   // Calls go through
+    Open,     // Calls fail fast until the cooldown ends
+    HalfOpen, // One probe call decides whether to close again
+}
+
+/// Why a call was refused without reaching the RPC node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitOpen;
+
+impl Display for CircuitOpen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "circuit breaker is open after repeated failures")
+    }
+}
+
+impl std::error::Error for CircuitOpen {}
+
+#[derive(Debug)]
+pub enum BreakerError<E> {
+    Open(CircuitOpen),
+    Rpc(E),
+}
+
+impl<E: Display> Display for BreakerError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Open(_) => write!(f, "NEAR RPC calls suspended"),
+            Self::Rpc(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for BreakerError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Open(open) => Some(open),
+            Self::Rpc(e) => e.source(),
+        }
+    }
+}
+
+struct Inner {
+    state: BreakerState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    // A probe that never reports back, e.g. because its request was dropped,
+    // is replaced by a new one after another cooldown
+    probe_started_at: Option<Instant>,
+}
+
+/// Stops calling the NEAR RPC node once it keeps failing, so requests fail
+/// fast instead of each waiting out its own retries. Only failures that mean
+/// the node is unreachable count; a contract panic is a working RPC.
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    inner: Mutex<Inner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            inner: Mutex::new(Inner {
+                state: BreakerState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                probe_started_at: None,
+            }),
+        }
+    }
+
+    pub fn from_config(config: &NearConfig) -> Self {
+        Self::new(
+            config.rpc_breaker_failure_threshold,
+            Duration::from_secs(config.rpc_breaker_cooldown_seconds),
+        )
+    }
+
+    /// The state the next call would see.
+    pub fn state(&self) -> BreakerState {
+        let inner = self.inner.lock().unwrap();
+        match inner.state {
+            BreakerState::Open if self.cooldown_elapsed(inner.opened_at) => BreakerState::HalfOpen,
+            state => state,
+        }
+    }
+
+    /// Run `call` unless the breaker is open, and count its outcome.
+    pub async fn run<T, E, Fut>(&self, call: Fut) -> Result<T, BreakerError<E>>
+    where
+        E: Debug,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        self.acquire().map_err(BreakerError::Open)?;
+
+        let result = call.await;
+        let unreachable = matches!(&result, Err(e) if is_retryable(&format!("{:?}", e)));
+        self.record(!unreachable);
+
+        result.map_err(BreakerError::Rpc)
+    }
+
+    fn acquire(&self) -> Result<(), CircuitOpen> {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.state {
+            BreakerState::Closed => Ok(()),
+            BreakerState::Open if self.cooldown_elapsed(inner.opened_at) => {
+                info!("NEAR RPC circuit breaker half-open, probing");
+                inner.state = BreakerState::HalfOpen;
+                inner.probe_started_at = Some(Instant::now());
+                Ok(())
+            }
+            BreakerState::HalfOpen if self.cooldown_elapsed(inner.probe_started_at) => {
+                inner.probe_started_at = Some(Instant::now());
+                Ok(())
+            }
+            BreakerState::Open | BreakerState::HalfOpen => Err(CircuitOpen),
+        }
+    }
+
+    fn record(&self, succeeded: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        if succeeded {
+            if inner.state != BreakerState::Closed {
+                info!("NEAR RPC recovered, circuit breaker closed");
+            }
+            inner.state = BreakerState::Closed;
+            inner.consecutive_failures = 0;
+            inner.opened_at = None;
+            inner.probe_started_at = None;
+            return;
+        }
+
+        inner.consecutive_failures += 1;
+        if inner.state == BreakerState::HalfOpen || inner.consecutive_failures >= self.failure_threshold {
+            // Calls started before it opened don't extend the cooldown
+            if inner.state != BreakerState::Open {
+                warn!("NEAR RPC circuit breaker open after {} consecutive failures, retrying in {}s",
+                      inner.consecutive_failures, self.cooldown.as_secs());
+                inner.state = BreakerState::Open;
+                inner.opened_at = Some(Instant::now());
+                inner.probe_started_at = None;
+            }
+        }
+    }
+
+    fn cooldown_elapsed(&self, since: Option<Instant>) -> bool {
+        since.map_or(true, |since| since.elapsed() >= self.cooldown)
+    }
+}
+
+/// The API error for a failed NEAR call: 503 while the breaker is open, so
+/// clients back off, or whatever `otherwise` makes of it.
+pub fn api_error<F>(error: anyhow::Error, otherwise: F) -> ApiError
+where
+    F: FnOnce(anyhow::Error) -> ApiError,
+{
+    if is_circuit_open(&error) {
+        ApiError::ServiceUnavailable("NEAR RPC is unavailable, try again shortly".to_string())
+    } else {
+        otherwise(error)
+    }
+}
+
+pub fn is_circuit_open(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| cause.is::<CircuitOpen>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    const UNREACHABLE: &str = "TransportError(SendError(Server returned 502))";
+    const CONTRACT_PANIC: &str = "ServerError(HandlerError(ContractExecutionError { vm_error: \"Smart contract panicked\" }))";
+
+    async fn call(breaker: &CircuitBreaker, error: Option<&'static str>) -> Result<u64, BreakerError<&'static str>> {
+        breaker.run(async move { error.map_or(Ok(42), Err) }).await
+    }
+
+    #[tokio::test]
+    async fn test_breaker_opens_probes_and_closes() {
+        let breaker = CircuitBreaker::new(3, Duration::from_millis(50));
+
+        for _ in 0..2 {
+            assert!(matches!(call(&breaker, Some(UNREACHABLE)).await, Err(BreakerError::Rpc(_))));
+        }
+        assert_eq!(breaker.state(), BreakerState::Closed);
+
+        assert!(call(&breaker, Some(UNREACHABLE)).await.is_err());
+        assert_eq!(breaker.state(), BreakerState::Open);
+
+        // Refused without running the call
+        assert!(matches!(call(&breaker, None).await, Err(BreakerError::Open(_))));
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+
+        // A failed probe opens it again for another cooldown
+        assert!(matches!(call(&breaker, Some(UNREACHABLE)).await, Err(BreakerError::Rpc(_))));
+        assert_eq!(breaker.state(), BreakerState::Open);
+        assert!(matches!(call(&breaker, None).await, Err(BreakerError::Open(_))));
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(call(&breaker, None).await.unwrap(), 42);
+        assert_eq!(breaker.state(), BreakerState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_only_one_probe_while_half_open() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(50));
+        assert!(call(&breaker, Some(UNREACHABLE)).await.is_err());
+        tokio::time::sleep(Duration::from_millis(60)).await;
+
+        assert!(breaker.acquire().is_ok());
+        assert_eq!(breaker.acquire(), Err(CircuitOpen));
+    }
+
+    #[tokio::test]
+    async fn test_contract_errors_do_not_trip_the_breaker() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(30));
+
+        for _ in 0..5 {
+            assert!(call(&breaker, Some(CONTRACT_PANIC)).await.is_err());
+        }
+        assert_eq!(breaker.state(), BreakerState::Closed);
+
+        // Failures must be consecutive
+        assert!(call(&breaker, Some(UNREACHABLE)).await.is_err());
+        assert!(call(&breaker, None).await.is_ok());
+        assert!(call(&breaker, Some(UNREACHABLE)).await.is_err());
+        assert_eq!(breaker.state(), BreakerState::Closed);
+    }
+
+    #[test]
+    fn test_open_breaker_is_found_through_context() {
+        let open: Result<(), BreakerError<std::io::Error>> = Err(BreakerError::Open(CircuitOpen));
+        assert!(is_circuit_open(&open.context("Failed to query contract").unwrap_err()));
+
+        let rpc: Result<(), BreakerError<std::io::Error>> = Err(BreakerError::Rpc(std::io::Error::other("timeout")));
+        assert!(!is_circuit_open(&rpc.context("Failed to query contract").unwrap_err()));
+    }
+}
@@ -4,13 +4,28 @@ use std::env;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
+    pub environment: String,
     pub port: u16,
+    pub public_url: String, // Where nodes reach the gateway, e.g. for off-chain task input
+    pub shutdown_timeout_seconds: u64,
     pub database_url: String,
+    pub database: DatabaseConfig,
     pub redis_url: String,
     pub jwt_secret: String,
+    pub jwt_previous_secrets: Vec<String>,
+    pub jwt_algorithm: String, // HS256 signs with `jwt_secret`, RS256 with the key pair below
+    pub jwt_rsa_private_key_path: Option<String>,
+    pub jwt_rsa_public_key_paths: Vec<String>, // Current key first, then retired ones still accepted
     pub near: NearConfig,
     pub rate_limits: RateLimitConfig,
     pub admin: AdminConfig,
+    pub routing: RoutingConfig,
+    pub email: EmailConfig,
+    pub cors: CorsConfig,
+    pub chain_cache: ChainCacheConfig,
+    pub pricing: PricingConfig,
+    pub task_sync: TaskSyncConfig,
+    pub event_indexer: EventIndexerConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +35,31 @@ pub struct NearConfig {
     pub rpc_url: String,
     pub wallet_url: String,
     pub explorer_url: String,
+    pub signer_account_id: String,
+    pub signer_private_key: String,
+    pub rpc_max_attempts: u32,
+    pub rpc_retry_base_delay_ms: u64,
+    pub rpc_breaker_failure_threshold: u32,
+    pub rpc_breaker_cooldown_seconds: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseConfig {
+    pub max_connections: u32,
+    pub acquire_timeout_seconds: u64,
+    pub idle_timeout_seconds: u64,
+    pub busy_timeout_ms: u64,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 10,
+            acquire_timeout_seconds: 5,
+            idle_timeout_seconds: 600,
+            busy_timeout_ms: 5000,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +68,8 @@ pub struct RateLimitConfig {
     pub requests_per_hour: u32,
     pub max_concurrent_tasks: u32,
     pub task_submission_cooldown_seconds: u64,
+    pub login_max_failures: u32,    // Failed logins per account before it is locked out
+    pub login_lockout_seconds: u64, // Window the failures are counted over
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,25 +78,131 @@ pub struct AdminConfig {
     pub metrics_retention_days: u32,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingConfig {
+    pub coalesce_tasks: u32,
+    pub coalesce_window_seconds: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailConfig {
+    pub require_verified_email: bool,
+    pub verification_url: String,
+    pub verification_token_hours: i64,
+    pub password_reset_url: String,
+    pub password_reset_token_minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainCacheConfig {
+    pub ttl_seconds: u64,
+    pub background_refresh: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskSyncConfig {
+    pub enabled: bool,
+    pub interval_seconds: u64,
+    pub batch_size: u32,
+    pub max_backoff_seconds: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventIndexerConfig {
+    pub enabled: bool,
+    pub interval_seconds: u64,
+    pub max_blocks_per_pass: u64,
+    pub start_block: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricingConfig {
+    pub surge_threshold: f64,
+    pub max_surge_multiplier: f64,
+}
+
 impl AppConfig {
     pub fn load() -> Result<Self> {
         dotenvy::dotenv().ok();
         
         let config = Self {
+            environment: env::var("APP_ENV")
+                .unwrap_or_else(|_| "development".to_string()),
+            
             port: env::var("PORT")
                 .unwrap_or_else(|_| "3000".to_string())
                 .parse()
                 .unwrap_or(3000),
+            public_url: env::var("PUBLIC_URL")
+                .unwrap_or_else(|_| "http://localhost:3000".to_string()),
+            
+            // How long in-flight requests may run after SIGTERM before exiting
+            shutdown_timeout_seconds: env::var("SHUTDOWN_TIMEOUT_SECONDS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
             
             database_url: env::var("DATABASE_URL")
                 .unwrap_or_else(|_| "sqlite:deai_gateway.db".to_string()),
             
+            database: {
+                let defaults = DatabaseConfig::default();
+                DatabaseConfig {
+                    max_connections: env::var("DATABASE_MAX_CONNECTIONS")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(defaults.max_connections),
+                    // How long a request waits for a free connection
+                    acquire_timeout_seconds: env::var("DATABASE_ACQUIRE_TIMEOUT_SECONDS")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(defaults.acquire_timeout_seconds),
+                    idle_timeout_seconds: env::var("DATABASE_IDLE_TIMEOUT_SECONDS")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(defaults.idle_timeout_seconds),
+                    // How long SQLite waits for a write lock before "database is locked"
+                    busy_timeout_ms: env::var("DATABASE_BUSY_TIMEOUT_MS")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(defaults.busy_timeout_ms),
+                }
+            },
+            
             redis_url: env::var("REDIS_URL")
                 .unwrap_or_else(|_| "redis://localhost:6379".to_string()),
             
             jwt_secret: env::var("JWT_SECRET")
                 .unwrap_or_else(|_| "your-super-secret-jwt-key-change-in-production".to_string()),
             
+            // Secrets still accepted for verification during rotation, newest first
+            jwt_previous_secrets: env::var("JWT_PREVIOUS_SECRETS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            
+            jwt_algorithm: env::var("JWT_ALGORITHM")
+                .unwrap_or_else(|_| "HS256".to_string())
+                .to_uppercase(),
+            
+            jwt_rsa_private_key_path: env::var("JWT_RSA_PRIVATE_KEY_PATH").ok(),
+            
+            jwt_rsa_public_key_paths: env::var("JWT_RSA_PUBLIC_KEY_PATHS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            
             near: NearConfig {
                 network_id: env::var("NEAR_NETWORK_ID")
                     .unwrap_or_else(|_| "testnet".to_string()),
@@ -66,6 +214,30 @@ impl AppConfig {
                     .unwrap_or_else(|_| "https://testnet.mynearwallet.com".to_string()),
                 explorer_url: env::var("NEAR_EXPLORER_URL")
                     .unwrap_or_else(|_| "https://testnet.nearblocks.io".to_string()),
+                // Account that signs and pays for task submissions
+                signer_account_id: env::var("NEAR_SIGNER_ACCOUNT_ID")
+                    .unwrap_or_else(|_| "deai-gateway.testnet".to_string()),
+                signer_private_key: env::var("NEAR_SIGNER_PRIVATE_KEY")
+                    .unwrap_or_default(),
+                // Transient RPC failures (timeouts, 5xx, rate limits) are retried
+                rpc_max_attempts: env::var("NEAR_RPC_MAX_ATTEMPTS")
+                    .unwrap_or_else(|_| "3".to_string())
+                    .parse()
+                    .unwrap_or(3),
+                rpc_retry_base_delay_ms: env::var("NEAR_RPC_RETRY_BASE_DELAY_MS")
+                    .unwrap_or_else(|_| "250".to_string())
+                    .parse()
+                    .unwrap_or(250),
+                // Consecutive unreachable-RPC failures before calls fail fast,
+                // and how long they do before one call probes again
+                rpc_breaker_failure_threshold: env::var("NEAR_RPC_BREAKER_FAILURE_THRESHOLD")
+                    .unwrap_or_else(|_| "5".to_string())
+                    .parse()
+                    .unwrap_or(5),
+                rpc_breaker_cooldown_seconds: env::var("NEAR_RPC_BREAKER_COOLDOWN_SECONDS")
+                    .unwrap_or_else(|_| "30".to_string())
+                    .parse()
+                    .unwrap_or(30),
             },
             
             rate_limits: RateLimitConfig {
@@ -85,6 +257,14 @@ impl AppConfig {
                     .unwrap_or_else(|_| "5".to_string())
                     .parse()
                     .unwrap_or(5),
+                login_max_failures: env::var("AUTH_MAX_FAILED_LOGINS")
+                    .unwrap_or_else(|_| "5".to_string())
+                    .parse()
+                    .unwrap_or(5),
+                login_lockout_seconds: env::var("AUTH_LOCKOUT_WINDOW_SECONDS")
+                    .unwrap_or_else(|_| "900".to_string())
+                    .parse()
+                    .unwrap_or(900),
             },
             
             admin: AdminConfig {
@@ -98,6 +278,114 @@ impl AppConfig {
                     .parse()
                     .unwrap_or(30),
             },
+            
+            routing: RoutingConfig {
+                coalesce_tasks: env::var("ROUTING_COALESCE_TASKS")
+                    .unwrap_or_else(|_| "4".to_string())
+                    .parse()
+                    .unwrap_or(4),
+                coalesce_window_seconds: env::var("ROUTING_COALESCE_WINDOW_SECONDS")
+                    .unwrap_or_else(|_| "300".to_string())
+                    .parse()
+                    .unwrap_or(300),
+            },
+            
+            email: EmailConfig {
+                // Only verified accounts may submit tasks when enabled
+                require_verified_email: env::var("REQUIRE_VERIFIED_EMAIL")
+                    .map(|v| v == "true")
+                    .unwrap_or(false),
+                // Frontend page that receives the token as `?token=`
+                verification_url: env::var("EMAIL_VERIFICATION_URL")
+                    .unwrap_or_else(|_| "http://localhost:3000/verify-email".to_string()),
+                verification_token_hours: env::var("EMAIL_VERIFICATION_TOKEN_HOURS")
+                    .unwrap_or_else(|_| "24".to_string())
+                    .parse()
+                    .unwrap_or(24),
+                // Frontend page that receives the reset token as `?token=`
+                password_reset_url: env::var("PASSWORD_RESET_URL")
+                    .unwrap_or_else(|_| "http://localhost:3000/reset-password".to_string()),
+                password_reset_token_minutes: env::var("PASSWORD_RESET_TOKEN_MINUTES")
+                    .unwrap_or_else(|_| "30".to_string())
+                    .parse()
+                    .unwrap_or(30),
+            },
+            
+            cors: CorsConfig {
+                // "*" allows any origin, for local development only
+                allowed_origins: parse_list(&env::var("CORS_ALLOWED_ORIGINS")
+                    .unwrap_or_else(|_| "http://localhost:3000".to_string())),
+                allowed_methods: parse_list(&env::var("CORS_ALLOWED_METHODS")
+                    .unwrap_or_else(|_| "GET,POST,PUT,DELETE,OPTIONS".to_string())),
+                allowed_headers: parse_list(&env::var("CORS_ALLOWED_HEADERS")
+                    .unwrap_or_else(|_| "authorization,content-type".to_string())),
+            },
+            
+            chain_cache: ChainCacheConfig {
+                // How long node lists and contract stats are served from Redis
+                ttl_seconds: env::var("CHAIN_CACHE_TTL_SECONDS")
+                    .unwrap_or_else(|_| "15".to_string())
+                    .parse()
+                    .unwrap_or(15),
+                // Refresh entries before they expire so requests never wait on RPC
+                background_refresh: env::var("CHAIN_CACHE_BACKGROUND_REFRESH")
+                    .map(|v| v == "true")
+                    .unwrap_or(false),
+            },
+            
+            pricing: PricingConfig {
+                // Active tasks per active node above which prices surge
+                surge_threshold: env::var("PRICING_SURGE_THRESHOLD")
+                    .unwrap_or_else(|_| "1.0".to_string())
+                    .parse()
+                    .unwrap_or(1.0),
+                max_surge_multiplier: env::var("PRICING_MAX_SURGE_MULTIPLIER")
+                    .unwrap_or_else(|_| "3.0".to_string())
+                    .parse()
+                    .unwrap_or(3.0),
+            },
+            
+            task_sync: TaskSyncConfig {
+                // Copy on-chain task progress back into the database
+                enabled: env::var("TASK_SYNC_ENABLED")
+                    .map(|v| v != "false")
+                    .unwrap_or(true),
+                interval_seconds: env::var("TASK_SYNC_INTERVAL_SECONDS")
+                    .unwrap_or_else(|_| "10".to_string())
+                    .parse()
+                    .unwrap_or(10),
+                // Tasks checked per pass, one RPC view call or two each
+                batch_size: env::var("TASK_SYNC_BATCH_SIZE")
+                    .unwrap_or_else(|_| "50".to_string())
+                    .parse()
+                    .unwrap_or(50),
+                // Longest wait between passes while RPC calls keep failing
+                max_backoff_seconds: env::var("TASK_SYNC_MAX_BACKOFF_SECONDS")
+                    .unwrap_or_else(|_| "300".to_string())
+                    .parse()
+                    .unwrap_or(300),
+            },
+            
+            event_indexer: EventIndexerConfig {
+                // Scans every final block for contract events; off by default
+                // as it costs a few RPC calls per block
+                enabled: env::var("EVENT_INDEXER_ENABLED")
+                    .map(|v| v == "true")
+                    .unwrap_or(false),
+                interval_seconds: env::var("EVENT_INDEXER_INTERVAL_SECONDS")
+                    .unwrap_or_else(|_| "5".to_string())
+                    .parse()
+                    .unwrap_or(5),
+                max_blocks_per_pass: env::var("EVENT_INDEXER_MAX_BLOCKS_PER_PASS")
+                    .unwrap_or_else(|_| "100".to_string())
+                    .parse()
+                    .unwrap_or(100),
+                // Only used before the first block is indexed; without it the
+                // indexer starts at the current final block
+                start_block: env::var("EVENT_INDEXER_START_BLOCK")
+                    .ok()
+                    .and_then(|v| v.parse().ok()),
+            },
         };
         
         config.validate()?;
@@ -109,18 +397,106 @@ impl AppConfig {
             anyhow::bail!("JWT secret must be at least 32 characters long");
         }
         
+        if self.jwt_previous_secrets.iter().any(|secret| secret.len() < 32) {
+            anyhow::bail!("Previous JWT secrets must be at least 32 characters long");
+        }
+        
+        match self.jwt_algorithm.as_str() {
+            "HS256" => {}
+            "RS256" => {
+                if self.jwt_rsa_private_key_path.is_none() || self.jwt_rsa_public_key_paths.is_empty() {
+                    anyhow::bail!("RS256 requires JWT_RSA_PRIVATE_KEY_PATH and JWT_RSA_PUBLIC_KEY_PATHS");
+                }
+            }
+            other => anyhow::bail!("Unsupported JWT algorithm: {} (expected HS256 or RS256)", other),
+        }
+        
         if self.near.contract_account_id.is_empty() {
             anyhow::bail!("Near contract account ID cannot be empty");
         }
         
+        if self.near.signer_private_key.is_empty() {
+            anyhow::bail!("NEAR_SIGNER_PRIVATE_KEY must be set to submit tasks on-chain");
+        }
+        
+        if self.database.max_connections == 0 {
+            anyhow::bail!("DATABASE_MAX_CONNECTIONS must be greater than 0");
+        }
+        
+        if self.near.rpc_max_attempts == 0 {
+            anyhow::bail!("NEAR_RPC_MAX_ATTEMPTS must be at least 1");
+        }
+        
+        if self.near.rpc_breaker_failure_threshold == 0 || self.near.rpc_breaker_cooldown_seconds == 0 {
+            anyhow::bail!("NEAR_RPC_BREAKER_FAILURE_THRESHOLD and NEAR_RPC_BREAKER_COOLDOWN_SECONDS must be greater than 0");
+        }
+        
         if self.rate_limits.requests_per_minute == 0 {
             anyhow::bail!("Rate limit per minute must be greater than 0");
         }
         
+        if self.rate_limits.login_max_failures == 0 || self.rate_limits.login_lockout_seconds == 0 {
+            anyhow::bail!("AUTH_MAX_FAILED_LOGINS and AUTH_LOCKOUT_WINDOW_SECONDS must be greater than 0");
+        }
+        
+        if self.pricing.surge_threshold <= 0.0 {
+            anyhow::bail!("PRICING_SURGE_THRESHOLD must be greater than 0");
+        }
+        
+        if self.pricing.max_surge_multiplier < 1.0 {
+            anyhow::bail!("PRICING_MAX_SURGE_MULTIPLIER must be at least 1.0");
+        }
+        
+        if self.task_sync.interval_seconds == 0 || self.task_sync.batch_size == 0 {
+            anyhow::bail!("TASK_SYNC_INTERVAL_SECONDS and TASK_SYNC_BATCH_SIZE must be greater than 0");
+        }
+        
+        if self.event_indexer.interval_seconds == 0 || self.event_indexer.max_blocks_per_pass == 0 {
+            anyhow::bail!("EVENT_INDEXER_INTERVAL_SECONDS and EVENT_INDEXER_MAX_BLOCKS_PER_PASS must be greater than 0");
+        }
+        
+        if !self.public_url.starts_with("http://") && !self.public_url.starts_with("https://") {
+            anyhow::bail!("PUBLIC_URL must be an http(s) URL");
+        }
+        
+        if self.cors.allowed_origins.is_empty() {
+            anyhow::bail!("CORS_ALLOWED_ORIGINS must list at least one origin");
+        }
+        
+        if self.is_production() && self.cors.allows_any_origin() {
+            anyhow::bail!("CORS_ALLOWED_ORIGINS cannot be \"*\" in production");
+        }
+        
         Ok(())
     }
     
+    /// Secrets accepted when verifying tokens: the primary first, then any
+    /// previous secrets that have not been retired yet.
+    pub fn jwt_verification_secrets(&self) -> Vec<&str> {
+        std::iter::once(self.jwt_secret.as_str())
+            .chain(self.jwt_previous_secrets.iter().map(String::as_str))
+            .collect()
+    }
+    
+    pub fn is_production(&self) -> bool {
+        self.environment == "production"
+    }
+    
     pub fn is_admin_account(&self, account_id: &str) -> bool {
         self.admin.admin_accounts.contains(&account_id.to_string())
     }
-}
\ No newline at end of file
+}
+
+impl CorsConfig {
+    pub fn allows_any_origin(&self) -> bool {
+        self.allowed_origins.iter().any(|origin| origin == "*")
+    }
+}
+
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
@@ -0,0 +1,78 @@
+use anyhow::{Context, Result};
+use axum::http::{HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::config::CorsConfig;
+
+/// Build the CORS layer from config. Only the configured origins are echoed
+/// back, and credentials are allowed for them alone. A `*` origin falls back
+/// to a permissive layer, which `AppConfig::validate` forbids in production.
+pub fn cors_layer(config: &CorsConfig) -> Result<CorsLayer> {
+    if config.allows_any_origin() {
+        return Ok(CorsLayer::permissive());
+    }
+
+    let origins = config.allowed_origins.iter()
+        .map(|origin| HeaderValue::from_str(origin).with_context(|| format!("Invalid CORS origin: {}", origin)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let methods = config.allowed_methods.iter()
+        .map(|method| Method::from_bytes(method.to_uppercase().as_bytes()).with_context(|| format!("Invalid CORS method: {}", method)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let headers = config.allowed_headers.iter()
+        .map(|header| HeaderName::from_bytes(header.to_lowercase().as_bytes()).with_context(|| format!("Invalid CORS header: {}", header)))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .allow_credentials(true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn create_config(origins: &[&str]) -> CorsConfig {
+        CorsConfig {
+            allowed_origins: origins.iter().map(|s| s.to_string()).collect(),
+            allowed_methods: vec!["GET".to_string(), "post".to_string()],
+            allowed_headers: vec!["Authorization".to_string(), "content-type".to_string()],
+        }
+    }
+
+    async fn allowed_origin(config: &CorsConfig, origin: &str) -> Option<String> {
+        let app = Router::new()
+            .route("/", get(|| async { "OK" }))
+            .layer(cors_layer(config).unwrap());
+
+        let response = app
+            .oneshot(Request::builder().uri("/").header("origin", origin).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        response.headers()
+            .get("access-control-allow-origin")
+            .map(|value| value.to_str().unwrap().to_string())
+    }
+
+    #[tokio::test]
+    async fn test_only_configured_origins_are_echoed() {
+        let config = create_config(&["https://app.deai.network"]);
+
+        assert_eq!(
+            allowed_origin(&config, "https://app.deai.network").await.as_deref(),
+            Some("https://app.deai.network")
+        );
+        assert_eq!(allowed_origin(&config, "https://evil.example.com").await, None);
+    }
+
+    #[test]
+    fn test_invalid_origin_is_rejected() {
+        assert!(cors_layer(&create_config(&["https://app.deai.network\n"])).is_err());
+    }
+}
@@ -0,0 +1,226 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous},
+    SqlitePool,
+};
+use std::str::FromStr;
+use std::time::Duration;
+use uuid::Uuid;
+use crate::{
+    config::DatabaseConfig,
+    errors::{ApiError, ApiResult},
+    models::{ApiKey, User},
+};
+
+/// Open the database, creating the file if needed, and bring its schema up to
+/// date with `migrations/`. Applied migrations are recorded in the database,
+/// so running this on every start is safe. Fails fast if the database can't
+/// answer a query.
+pub async fn init_database(database_url: &str, config: &DatabaseConfig) -> Result<SqlitePool> {
+    // WAL lets reads run alongside a write, and the busy timeout makes a
+    // writer wait for the lock instead of failing with "database is locked"
+    let options = SqliteConnectOptions::from_str(database_url)
+        .with_context(|| format!("Invalid DATABASE_URL: {}", database_url))?
+        .create_if_missing(true)
+        .foreign_keys(true)
+        .journal_mode(SqliteJournalMode::Wal)
+        .synchronous(SqliteSynchronous::Normal)
+        .busy_timeout(Duration::from_millis(config.busy_timeout_ms));
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(config.max_connections)
+        .acquire_timeout(Duration::from_secs(config.acquire_timeout_seconds))
+        .idle_timeout(Duration::from_secs(config.idle_timeout_seconds))
+        .connect_with(options)
+        .await
+        .with_context(|| format!("Failed to connect to database at {}", database_url))?;
+
+    check_connection(&pool)
+        .await
+        .with_context(|| format!("Database at {} is not answering queries", database_url))?;
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .context("Failed to run database migrations")?;
+
+    tracing::info!("Database ready");
+    Ok(pool)
+}
+
+pub async fn check_connection(pool: &SqlitePool) -> Result<()> {
+    sqlx::query("SELECT 1").execute(pool).await?;
+    Ok(())
+}
+
+/// Connections the pool currently holds open, for
+/// `SystemMetrics.database_connections`.
+pub fn connection_count(pool: &SqlitePool) -> u32 {
+    pool.size()
+}
+
+pub async fn create_user(
+    pool: &SqlitePool,
+    username: &str,
+    email: &Option<String>,
+    password_hash: &str,
+    near_account_id: Option<&str>,
+) -> ApiResult<User> {
+    let now = Utc::now();
+
+    sqlx::query_as::<_, User>(
+        r#"
+        INSERT INTO users (
+            id, username, email, password_hash, near_account_id,
+            is_active, is_admin, created_at, updated_at
+        ) VALUES (?1, ?2, ?3, ?4, ?5, 1, 0, ?6, ?6)
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(username)
+    .bind(email)
+    .bind(password_hash)
+    .bind(near_account_id)
+    .bind(now)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| ApiError::Database(e.to_string()))
+}
+
+pub async fn get_user_by_id(pool: &SqlitePool, user_id: Uuid) -> ApiResult<User> {
+    sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ?1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| ApiError::Database(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("User not found".to_string()))
+}
+
+pub async fn get_user_by_username(pool: &SqlitePool, username: &str) -> ApiResult<User> {
+    sqlx::query_as::<_, User>("SELECT * FROM users WHERE username = ?1")
+        .bind(username)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| ApiError::Database(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("User not found".to_string()))
+}
+
+pub async fn get_user_by_account_id(pool: &SqlitePool, account_id: &str) -> ApiResult<User> {
+    sqlx::query_as::<_, User>("SELECT * FROM users WHERE near_account_id = ?1")
+        .bind(account_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| ApiError::Database(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("User not found".to_string()))
+}
+
+// Served by the index behind `prefix`'s UNIQUE constraint, so verifying a
+// key costs one lookup and one bcrypt check however many keys exist
+const ACTIVE_API_KEY_BY_PREFIX: &str = "SELECT * FROM api_keys WHERE prefix = ?1 AND is_active = 1";
+
+pub async fn get_active_api_key_by_prefix(pool: &SqlitePool, prefix: &str) -> ApiResult<Option<ApiKey>> {
+    sqlx::query_as::<_, ApiKey>(ACTIVE_API_KEY_BY_PREFIX)
+        .bind(prefix)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| ApiError::Database(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_migrations_are_idempotent() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = format!("sqlite:{}", dir.path().join("gateway.db").display());
+
+        let pool = init_database(&url, &DatabaseConfig::default()).await.unwrap();
+        let user = create_user(&pool, "alice", &None, "hash", Some("alice.testnet")).await.unwrap();
+        pool.close().await;
+
+        // A restart finds the schema in place and keeps the data
+        let pool = init_database(&url, &DatabaseConfig::default()).await.unwrap();
+        assert_eq!(get_user_by_account_id(&pool, "alice.testnet").await.unwrap().id, user.id);
+        assert!(get_user_by_username(&pool, "bob").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_runs_in_wal_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = format!("sqlite:{}", dir.path().join("gateway.db").display());
+        let pool = init_database(&url, &DatabaseConfig::default()).await.unwrap();
+
+        let (journal_mode,): (String,) = sqlx::query_as("PRAGMA journal_mode").fetch_one(&pool).await.unwrap();
+        assert_eq!(journal_mode, "wal");
+        assert!(connection_count(&pool) >= 1);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_database_fails_fast() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = format!("sqlite:{}", dir.path().join("missing").join("gateway.db").display());
+
+        let error = init_database(&url, &DatabaseConfig::default()).await.unwrap_err();
+        assert!(format!("{:#}", error).contains("Failed to connect to database"));
+    }
+
+    #[tokio::test]
+    async fn test_task_status_is_constrained() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = format!("sqlite:{}", dir.path().join("gateway.db").display());
+        let pool = init_database(&url, &DatabaseConfig::default()).await.unwrap();
+        let user = create_user(&pool, "alice", &None, "hash", None).await.unwrap();
+
+        let insert = |status: &'static str| {
+            sqlx::query(
+                "INSERT INTO tasks (id, user_id, task_type, model_name, input_data, status, estimated_cost, created_at, expires_at)
+                 VALUES (?1, ?2, 'inference', 'gpt2', 'hello', ?3, '1000', ?4, ?4)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(user.id)
+            .bind(status)
+            .bind(Utc::now())
+            .execute(&pool)
+        };
+
+        assert!(insert("pending").await.is_ok());
+        assert!(insert("exploded").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_api_key_lookup_uses_prefix_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = format!("sqlite:{}", dir.path().join("gateway.db").display());
+        let pool = init_database(&url, &DatabaseConfig::default()).await.unwrap();
+        let user = create_user(&pool, "alice", &None, "hash", None).await.unwrap();
+
+        for i in 0..1000 {
+            sqlx::query(
+                "INSERT INTO api_keys (id, user_id, name, key_hash, prefix, created_at)
+                 VALUES (?1, ?2, 'key', 'hash', ?3, ?4)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(user.id)
+            .bind(format!("{:08x}", i))
+            .bind(Utc::now())
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let key = get_active_api_key_by_prefix(&pool, "000003e7").await.unwrap().unwrap();
+        assert_eq!(key.prefix, "000003e7");
+        assert!(get_active_api_key_by_prefix(&pool, "ffffffff").await.unwrap().is_none());
+
+        let plan: Vec<(i64, i64, i64, String)> = sqlx::query_as(&format!("EXPLAIN QUERY PLAN {}", ACTIVE_API_KEY_BY_PREFIX))
+            .bind("000003e7")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(plan.len(), 1, "{:?}", plan);
+        assert!(plan[0].3.starts_with("SEARCH api_keys USING INDEX"), "{:?}", plan);
+    }
+}
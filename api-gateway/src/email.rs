@@ -0,0 +1,186 @@
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use jsonwebtoken::{decode, encode, errors::ErrorKind, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::errors::{ApiError, ApiResult};
+
+const EMAIL_VERIFICATION_PURPOSE: &str = "email_verification";
+
+/// Delivers transactional email. Swap the implementation in `AppState` to
+/// plug in a real provider.
+#[async_trait]
+pub trait EmailSender: Send + Sync {
+    async fn send(&self, to: &str, subject: &str, body: &str) -> anyhow::Result<()>;
+}
+
+/// Writes emails to the log instead of sending them, for development.
+pub struct LogEmailSender;
+
+#[async_trait]
+impl EmailSender for LogEmailSender {
+    async fn send(&self, to: &str, subject: &str, body: &str) -> anyhow::Result<()> {
+        info!("Email to {}: {}\n{}", to, subject, body);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EmailVerificationClaims {
+    sub: String,     // User ID
+    email: String,   // Address being verified
+    purpose: String, // Keeps these apart from session tokens signed with the same secret
+    exp: usize,
+}
+
+pub fn create_verification_token(user_id: Uuid, email: &str, secret: &str, valid_hours: i64) -> ApiResult<String> {
+    let claims = EmailVerificationClaims {
+        sub: user_id.to_string(),
+        email: email.to_string(),
+        purpose: EMAIL_VERIFICATION_PURPOSE.to_string(),
+        exp: (Utc::now() + Duration::hours(valid_hours)).timestamp() as usize,
+    };
+
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_ref()))
+        .map_err(|e| ApiError::Internal(format!("Failed to create verification token: {}", e)))
+}
+
+/// Return the user id and email address a verification token was issued for.
+pub fn verify_verification_token(token: &str, secrets: &[&str]) -> ApiResult<(Uuid, String)> {
+    for secret in secrets {
+        match decode::<EmailVerificationClaims>(token, &DecodingKey::from_secret(secret.as_ref()), &Validation::default()) {
+            Ok(data) => {
+                let claims = data.claims;
+                if claims.purpose != EMAIL_VERIFICATION_PURPOSE {
+                    break;
+                }
+
+                let user_id = Uuid::parse_str(&claims.sub)
+                    .map_err(|_| ApiError::BadRequest("Invalid verification token".to_string()))?;
+                return Ok((user_id, claims.email));
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::ExpiredSignature) => {
+                return Err(ApiError::BadRequest(
+                    "Verification token has expired, request a new one".to_string(),
+                ));
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::InvalidSignature) => continue,
+            Err(_) => break,
+        }
+    }
+
+    Err(ApiError::BadRequest("Invalid verification token".to_string()))
+}
+
+pub async fn send_verification_email(
+    sender: &dyn EmailSender,
+    to: &str,
+    token: &str,
+    verification_url: &str,
+) -> anyhow::Result<()> {
+    let body = format!(
+        "Confirm your email address for DeAI by opening this link:\n\n{}?token={}\n\nIf you did not create an account, you can ignore this email.",
+        verification_url, token
+    );
+
+    sender.send(to, "Verify your DeAI email address", &body).await
+}
+
+pub async fn send_password_reset_email(
+    sender: &dyn EmailSender,
+    to: &str,
+    token: &str,
+    reset_url: &str,
+    valid_minutes: i64,
+) -> anyhow::Result<()> {
+    let body = format!(
+        "A password reset was requested for your DeAI account. Choose a new password here:\n\n{}?token={}\n\nThe link expires in {} minutes and works once. If you did not ask for this, you can ignore this email.",
+        reset_url, token, valid_minutes
+    );
+
+    sender.send(to, "Reset your DeAI password", &body).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    const SECRET: &str = "verification-secret-at-least-32-characters";
+
+    #[derive(Default)]
+    struct MockEmailSender {
+        sent: Mutex<Vec<(String, String)>>,
+    }
+
+    #[async_trait]
+    impl EmailSender for MockEmailSender {
+        async fn send(&self, to: &str, _subject: &str, body: &str) -> anyhow::Result<()> {
+            self.sent.lock().unwrap().push((to.to_string(), body.to_string()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_verification_token_round_trip() {
+        let user_id = Uuid::new_v4();
+        let token = create_verification_token(user_id, "alice@example.com", SECRET, 24).unwrap();
+
+        let (verified_id, email) = verify_verification_token(&token, &[SECRET]).unwrap();
+        assert_eq!(verified_id, user_id);
+        assert_eq!(email, "alice@example.com");
+    }
+
+    #[test]
+    fn test_expired_verification_token_is_rejected() {
+        let token = create_verification_token(Uuid::new_v4(), "alice@example.com", SECRET, -1).unwrap();
+
+        match verify_verification_token(&token, &[SECRET]) {
+            Err(ApiError::BadRequest(message)) => assert!(message.contains("expired")),
+            other => panic!("expected expired token error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_session_token_is_not_a_verification_token() {
+        let claims = serde_json::json!({
+            "sub": Uuid::new_v4().to_string(),
+            "email": "alice@example.com",
+            "purpose": "access",
+            "exp": (Utc::now() + Duration::hours(1)).timestamp(),
+        });
+        let token = encode(&Header::default(), &claims, &EncodingKey::from_secret(SECRET.as_ref())).unwrap();
+
+        assert!(verify_verification_token(&token, &[SECRET]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_send_verification_email() {
+        let sender = MockEmailSender::default();
+
+        send_verification_email(&sender, "alice@example.com", "abc123", "https://app.deai.network/verify-email")
+            .await
+            .unwrap();
+
+        let sent = sender.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].0, "alice@example.com");
+        assert!(sent[0].1.contains("https://app.deai.network/verify-email?token=abc123"));
+    }
+
+    #[tokio::test]
+    async fn test_send_password_reset_email() {
+        let sender = MockEmailSender::default();
+
+        send_password_reset_email(&sender, "alice@example.com", "reset123", "https://app.deai.network/reset-password", 30)
+            .await
+            .unwrap();
+
+        let sent = sender.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].1.contains("https://app.deai.network/reset-password?token=reset123"));
+        assert!(sent[0].1.contains("30 minutes"));
+    }
+}
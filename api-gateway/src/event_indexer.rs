@@ -0,0 +1,212 @@
+use anyhow::Result;
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::Value;
+use sqlx::{Executor, Sqlite, SqlitePool};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::{handlers::AppState, near_client::BlockLogs};
+
+const CURSOR_NAME: &str = "contract_events";
+const EVENT_STANDARD: &str = "deai";
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// A contract event as stored in the `events` table.
+#[derive(Debug, PartialEq)]
+struct IndexedEvent {
+    event_type: String,
+    node_id: Option<String>,
+    task_id: Option<u64>,
+    data: Value,
+}
+
+/// Tail the contract's NEP-297 events into the `events` table, one final
+/// block at a time. Progress is kept in `indexer_cursors`, so a restart
+/// picks up after the last indexed block.
+pub fn spawn_event_indexer(state: AppState) -> tokio::task::JoinHandle<()> {
+    let config = state.config.event_indexer.clone();
+    let interval = Duration::from_secs(config.interval_seconds);
+
+    tokio::spawn(async move {
+        let mut delay = interval;
+        loop {
+            tokio::time::sleep(delay).await;
+
+            delay = match index_blocks(&state, config.max_blocks_per_pass, config.start_block).await {
+                Ok(indexed) => {
+                    if indexed > 0 {
+                        debug!("Indexed {} contract events", indexed);
+                    }
+                    interval
+                }
+                Err(e) => {
+                    let next = (delay * 2).min(MAX_BACKOFF.max(interval));
+                    warn!("Event indexing failed, retrying in {}s: {:#}", next.as_secs(), e);
+                    next
+                }
+            };
+        }
+    })
+}
+
+// Works through at most `max_blocks` blocks after the cursor. Returns how
+// many new events were stored.
+async fn index_blocks(state: &AppState, max_blocks: u64, start_block: Option<u64>) -> Result<usize> {
+    let final_height = state.near_client.get_final_block_height().await?;
+    let last_indexed = match load_cursor(&state.db_pool).await? {
+        Some(height) => height,
+        None => start_block.map_or(final_height, |height| height.saturating_sub(1)),
+    };
+
+    let mut indexed = 0;
+    for height in (last_indexed + 1)..=final_height.min(last_indexed + max_blocks) {
+        match state.near_client.get_block_logs(height).await? {
+            Some(block) => indexed += store_block(&state.db_pool, &block).await?,
+            None => save_cursor(&state.db_pool, height).await?,
+        }
+    }
+
+    Ok(indexed)
+}
+
+async fn load_cursor(pool: &SqlitePool) -> Result<Option<u64>> {
+    let height: Option<i64> = sqlx::query_scalar("SELECT block_height FROM indexer_cursors WHERE name = ?1")
+        .bind(CURSOR_NAME)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(height.map(|height| height as u64))
+}
+
+async fn save_cursor<'e, E>(executor: E, height: u64) -> Result<()>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query(
+        "INSERT INTO indexer_cursors (name, block_height) VALUES (?1, ?2)
+         ON CONFLICT (name) DO UPDATE SET block_height = excluded.block_height",
+    )
+    .bind(CURSOR_NAME)
+    .bind(height as i64)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+// The block's events and the cursor move together, so a crash midway leaves
+// the block to be indexed again. Events already stored are skipped.
+async fn store_block(pool: &SqlitePool, block: &BlockLogs) -> Result<usize> {
+    let block_timestamp: DateTime<Utc> = Utc.timestamp_nanos(block.timestamp_nanos as i64);
+    let mut db_tx = pool.begin().await?;
+    let mut stored = 0;
+
+    for log in &block.logs {
+        let Some(event) = parse_event(&log.log) else {
+            continue;
+        };
+
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO events
+                (block_height, receipt_id, log_index, block_timestamp, event_type, node_id, task_id, data)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )
+        .bind(block.height as i64)
+        .bind(&log.receipt_id)
+        .bind(log.log_index)
+        .bind(block_timestamp)
+        .bind(&event.event_type)
+        .bind(&event.node_id)
+        .bind(event.task_id.map(|id| id as i64))
+        .bind(event.data.to_string())
+        .execute(&mut *db_tx)
+        .await?;
+
+        stored += result.rows_affected() as usize;
+    }
+
+    save_cursor(&mut *db_tx, block.height).await?;
+    db_tx.commit().await?;
+    Ok(stored)
+}
+
+// Only the contract's own standard; plain log lines, audit records and
+// token events are skipped
+fn parse_event(log: &str) -> Option<IndexedEvent> {
+    let event: Value = serde_json::from_str(log.strip_prefix("EVENT_JSON:")?).ok()?;
+    if event["standard"] != EVENT_STANDARD {
+        return None;
+    }
+
+    let data = event.get("data").cloned().unwrap_or(Value::Array(Vec::new()));
+    let first = data.get(0);
+    // Events name the node under the field fitting their subject
+    let node_id = first.and_then(|entry| {
+        ["assignee", "node_id", "account_id"].iter()
+            .find_map(|field| entry.get(field)?.as_str())
+            .map(str::to_string)
+    });
+
+    Some(IndexedEvent {
+        event_type: event["event"].as_str()?.to_string(),
+        node_id,
+        task_id: first.and_then(|entry| entry["task_id"].as_u64()),
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::DatabaseConfig, database::init_database, near_client::ContractLog};
+
+    fn log(receipt_id: &str, log_index: u32, log: &str) -> ContractLog {
+        ContractLog {
+            receipt_id: receipt_id.to_string(),
+            log_index,
+            log: log.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_event() {
+        let event = parse_event(r#"EVENT_JSON:{"standard":"deai","version":"1.0.0","event":"task_assigned","data":[{"task_id":3,"assignee":"node.testnet","timeout_at":0}]}"#).unwrap();
+        assert_eq!(event.event_type, "task_assigned");
+        assert_eq!(event.node_id.as_deref(), Some("node.testnet"));
+        assert_eq!(event.task_id, Some(3));
+
+        let registered = parse_event(r#"EVENT_JSON:{"standard":"deai","version":"1.0.0","event":"node_registered","data":[{"account_id":"node.testnet","stake":"1","public_ip":"1.2.3.4","api_endpoint":"http://1.2.3.4"}]}"#).unwrap();
+        assert_eq!(registered.node_id.as_deref(), Some("node.testnet"));
+        assert_eq!(registered.task_id, None);
+    }
+
+    #[test]
+    fn test_other_logs_are_skipped() {
+        assert!(parse_event("Task submitted: 7, requester: gateway.testnet, amount: 100, fee: 0").is_none());
+        assert!(parse_event(r#"EVENT_JSON:{"standard":"deai-audit","version":"1.0.0","event":"call","data":[{"method":"submit_task"}]}"#).is_none());
+        assert!(parse_event(r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_mint","data":[]}"#).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reindexed_block_is_not_duplicated() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = init_database(&format!("sqlite:{}", dir.path().join("gateway.db").display()), &DatabaseConfig::default()).await.unwrap();
+
+        let block = BlockLogs {
+            height: 100,
+            timestamp_nanos: 1_700_000_000_000_000_000,
+            logs: vec![
+                log("receipt-a", 0, "Task submitted: 1, requester: gateway.testnet, amount: 100, fee: 0"),
+                log("receipt-a", 1, r#"EVENT_JSON:{"standard":"deai","version":"1.0.0","event":"task_submitted","data":[{"task_id":1,"requester":"gateway.testnet","reward_amount":"100","priority":"Normal"}]}"#),
+                log("receipt-b", 0, r#"EVENT_JSON:{"standard":"deai","version":"1.0.0","event":"task_assigned","data":[{"task_id":1,"assignee":"node.testnet","timeout_at":0}]}"#),
+            ],
+        };
+
+        assert_eq!(store_block(&pool, &block).await.unwrap(), 2);
+        assert_eq!(load_cursor(&pool).await.unwrap(), Some(100));
+
+        assert_eq!(store_block(&pool, &block).await.unwrap(), 0);
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM events").fetch_one(&pool).await.unwrap();
+        assert_eq!(count, 2);
+    }
+}
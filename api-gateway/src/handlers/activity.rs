@@ -0,0 +1,120 @@
+use axum::{
+    extract::{Query, State},
+    response::Json,
+};
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+
+use crate::{
+    models::*,
+    handlers::AppState,
+    errors::{ApiError, ApiResult},
+};
+
+// Accepted `sort_by` values for the activity feed, newest first by default
+const ACTIVITY_SORT_COLUMNS: &[(&str, &str)] = &[
+    ("block_height", "block_height"),
+];
+
+fn push_activity_filters(builder: &mut QueryBuilder<'_, Sqlite>, filters: &ActivityFilterQuery) {
+    builder.push(" WHERE 1 = 1");
+
+    if let Some(event_type) = &filters.event_type {
+        builder.push(" AND event_type = ").push_bind(event_type.clone());
+    }
+    if let Some(node_id) = &filters.node_id {
+        builder.push(" AND node_id = ").push_bind(node_id.clone());
+    }
+    if let Some(task_id) = filters.task_id {
+        builder.push(" AND task_id = ").push_bind(task_id);
+    }
+}
+
+/// One page of indexed contract events plus the total matching the same
+/// filters. `order_by` must come from `PaginationQuery::order_by`.
+async fn fetch_activity(
+    pool: &SqlitePool,
+    filters: &ActivityFilterQuery,
+    order_by: &str,
+    limit: u32,
+    offset: u32,
+) -> Result<(Vec<ActivityEvent>, u64), sqlx::Error> {
+    let mut count_query = QueryBuilder::new("SELECT COUNT(*) FROM events");
+    push_activity_filters(&mut count_query, filters);
+    let total: i64 = count_query.build_query_scalar().fetch_one(pool).await?;
+
+    // Events of one receipt stay in the order they were logged
+    let mut page_query = QueryBuilder::new("SELECT * FROM events");
+    push_activity_filters(&mut page_query, filters);
+    page_query
+        .push(format!(" ORDER BY {}, receipt_id, log_index LIMIT ", order_by))
+        .push_bind(limit)
+        .push(" OFFSET ")
+        .push_bind(offset);
+    let events = page_query.build_query_as::<ActivityEvent>().fetch_all(pool).await?;
+
+    Ok((events, total as u64))
+}
+
+pub async fn list_activity(
+    State(state): State<AppState>,
+    Query(pagination): Query<PaginationQuery>,
+    Query(filters): Query<ActivityFilterQuery>,
+) -> ApiResult<Json<PaginatedResponse<ActivityEvent>>> {
+    let (page, limit) = pagination.normalize();
+    let order_by = pagination.order_by(ACTIVITY_SORT_COLUMNS)
+        .map_err(ApiError::BadRequest)?;
+
+    let (events, total) = fetch_activity(&state.db_pool, &filters, &order_by, limit, pagination.offset())
+        .await
+        .map_err(|e| ApiError::Database(e.to_string()))?;
+
+    Ok(Json(PaginatedResponse::new(events, page, limit, total)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::DatabaseConfig, database::init_database};
+    use chrono::Utc;
+
+    async fn insert_event(pool: &SqlitePool, block_height: i64, event_type: &str, node_id: Option<&str>, task_id: Option<i64>) {
+        sqlx::query(
+            "INSERT INTO events (block_height, receipt_id, log_index, block_timestamp, event_type, node_id, task_id, data)
+             VALUES (?1, 'receipt', 0, ?2, ?3, ?4, ?5, '[]')",
+        )
+        .bind(block_height)
+        .bind(Utc::now())
+        .bind(event_type)
+        .bind(node_id)
+        .bind(task_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_activity_filters_and_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = init_database(&format!("sqlite:{}", dir.path().join("gateway.db").display()), &DatabaseConfig::default()).await.unwrap();
+
+        insert_event(&pool, 10, "node_registered", Some("node.testnet"), None).await;
+        insert_event(&pool, 11, "task_assigned", Some("node.testnet"), Some(1)).await;
+        insert_event(&pool, 12, "task_assigned", Some("other.testnet"), Some(2)).await;
+        insert_event(&pool, 13, "task_completed", Some("node.testnet"), Some(1)).await;
+
+        let (events, total) = fetch_activity(&pool, &ActivityFilterQuery::default(), "block_height DESC", 2, 0).await.unwrap();
+        assert_eq!(total, 4);
+        assert_eq!(events.iter().map(|e| e.block_height).collect::<Vec<_>>(), vec![13, 12]);
+
+        let by_node = ActivityFilterQuery { node_id: Some("node.testnet".to_string()), ..Default::default() };
+        assert_eq!(fetch_activity(&pool, &by_node, "block_height DESC", 20, 0).await.unwrap().1, 3);
+
+        let by_task = ActivityFilterQuery { task_id: Some(1), ..Default::default() };
+        assert_eq!(fetch_activity(&pool, &by_task, "block_height DESC", 20, 0).await.unwrap().1, 2);
+
+        let by_type = ActivityFilterQuery { event_type: Some("task_assigned".to_string()), ..by_node };
+        let (events, total) = fetch_activity(&pool, &by_type, "block_height DESC", 20, 0).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(events[0].task_id, Some(1));
+    }
+}
@@ -1,7 +1,6 @@
 use axum::{extract::State, response::Json};
 use bcrypt::{hash, verify, DEFAULT_COST};
 use chrono::{Duration, Utc};
-use jsonwebtoken::{encode, EncodingKey, Header};
 use uuid::Uuid;
 use validator::Validate;
 use tracing::{info, warn};
@@ -9,7 +8,10 @@ use tracing::{info, warn};
 use crate::{
     models::*,
     handlers::AppState,
-    auth::{Claims, BEARER},
+    auth::{verify_jwt_token_for_refresh, Claims, BEARER},
+    email::{create_verification_token, send_password_reset_email, send_verification_email, verify_verification_token},
+    password_reset::{generate_reset_token, redeem_reset_token, store_reset_token},
+    circuit_breaker,
     errors::{ApiError, ApiResult},
 };
 
@@ -81,6 +83,14 @@ pub async fn register_user(
     
     info!("New user registered: {} ({})", user.username, user.id);
     
+    // Registration succeeds even if the email can't be sent; the user can
+    // ask for it again
+    if let Some(email) = &user.email {
+        if let Err(e) = send_verification(&state, &user, email).await {
+            warn!("Failed to send verification email to user {}: {}", user.id, e);
+        }
+    }
+    
     // Generate JWT token
     let token = generate_jwt_token(&state, &user)?;
     
@@ -164,11 +174,18 @@ pub async fn near_wallet_login(
     State(state): State<AppState>,
     Json(request): Json<NearWalletLoginRequest>,
 ) -> ApiResult<Json<AuthResponse>> {
+    let account_id: near_primitives::types::AccountId = request.account_id.parse()
+        .map_err(|_| ApiError::BadRequest("Invalid Near account ID".to_string()))?;
+    
     // Verify Near wallet signature
     if !verify_near_signature(&request).await? {
         return Err(ApiError::Unauthorized("Invalid Near wallet signature".to_string()));
     }
     
+    // A valid signature only proves control of the key; the key must also
+    // belong to the claimed account
+    verify_key_ownership(&state, &account_id, &request.public_key).await?;
+    
     // Find or create user by Near account ID
     let user = match sqlx::query_as!(
         User,
@@ -196,7 +213,7 @@ pub async fn near_wallet_login(
             // Create new user
             let user_id = Uuid::new_v4();
             let now = Utc::now();
-            let username = format!("near_{}", request.account_id.replace('.', "_"));
+            let username = format!("near_{}", account_id.as_str().replace('.', "_"));
             
             sqlx::query_as!(
                 User,
@@ -244,8 +261,225 @@ pub async fn near_wallet_login(
     Ok(Json(response))
 }
 
+/// Exchange a valid, or recently expired, access token for a fresh one.
+pub async fn refresh_token(
+    State(state): State<AppState>,
+    Json(request): Json<RefreshTokenRequest>,
+) -> ApiResult<Json<AuthResponse>> {
+    let claims = verify_jwt_token_for_refresh(&request.access_token, &state.jwt_keys)?;
+    
+    // API keys are managed separately and never turn into session tokens
+    if claims.token_type == "api_key" {
+        return Err(ApiError::Unauthorized("API keys cannot be refreshed".to_string()));
+    }
+    
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| ApiError::Unauthorized("Invalid token subject".to_string()))?;
+    
+    // Deactivated users lose the ability to extend their session
+    let user = sqlx::query_as!(
+        User,
+        "SELECT * FROM users WHERE id = ?1 AND is_active = true",
+        user_id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| ApiError::Database(e.to_string()))?
+    .ok_or_else(|| ApiError::Unauthorized("User not found or inactive".to_string()))?;
+    
+    info!("Refreshed access token for user: {} ({})", user.username, user.id);
+    
+    let token = generate_jwt_token(&state, &user)?;
+    
+    let response = AuthResponse {
+        access_token: token,
+        token_type: BEARER.to_string(),
+        expires_in: 3600, // 1 hour
+        user: UserProfile {
+            id: user.id,
+            username: user.username,
+            email: user.email,
+            near_account_id: user.near_account_id,
+            is_admin: user.is_admin,
+            created_at: user.created_at,
+        },
+    };
+    
+    Ok(Json(response))
+}
+
+/// Mark the user's email as verified using the token from their verification email.
+pub async fn verify_email(
+    State(state): State<AppState>,
+    Json(request): Json<VerifyEmailRequest>,
+) -> ApiResult<Json<EmailVerificationResponse>> {
+    let (user_id, email) = verify_verification_token(
+        &request.token,
+        &state.config.jwt_verification_secrets(),
+    )?;
+    
+    let user = sqlx::query_as!(
+        User,
+        "SELECT * FROM users WHERE id = ?1",
+        user_id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| ApiError::Database(e.to_string()))?
+    .ok_or_else(|| ApiError::NotFound("User not found".to_string()))?;
+    
+    if user.email_verified {
+        return Ok(Json(EmailVerificationResponse {
+            email_verified: true,
+            message: "Email already verified".to_string(),
+        }));
+    }
+    
+    // A token issued before the user changed their address proves nothing
+    if user.email.as_deref() != Some(email.as_str()) {
+        return Err(ApiError::BadRequest("Verification token does not match the current email address".to_string()));
+    }
+    
+    sqlx::query!(
+        "UPDATE users SET email_verified = true, updated_at = ?1 WHERE id = ?2",
+        Utc::now(),
+        user.id
+    )
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| ApiError::Database(e.to_string()))?;
+    
+    info!("Email verified for user: {} ({})", user.username, user.id);
+    
+    Ok(Json(EmailVerificationResponse {
+        email_verified: true,
+        message: "Email verified".to_string(),
+    }))
+}
+
+pub async fn resend_verification(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> ApiResult<Json<EmailVerificationResponse>> {
+    let user = sqlx::query_as!(
+        User,
+        "SELECT * FROM users WHERE id = ?1",
+        claims.user_id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| ApiError::Database(e.to_string()))?
+    .ok_or_else(|| ApiError::NotFound("User not found".to_string()))?;
+    
+    if user.email_verified {
+        return Ok(Json(EmailVerificationResponse {
+            email_verified: true,
+            message: "Email already verified".to_string(),
+        }));
+    }
+    
+    let email = user.email.as_deref()
+        .ok_or_else(|| ApiError::BadRequest("Account has no email address".to_string()))?;
+    
+    send_verification(&state, &user, email).await
+        .map_err(|e| ApiError::Internal(format!("Failed to send verification email: {}", e)))?;
+    
+    Ok(Json(EmailVerificationResponse {
+        email_verified: false,
+        message: "Verification email sent".to_string(),
+    }))
+}
+
+/// Email a password reset link. The response is the same whether or not the
+/// account exists, so it can't be used to discover accounts.
+pub async fn forgot_password(
+    State(state): State<AppState>,
+    Json(request): Json<ForgotPasswordRequest>,
+) -> ApiResult<Json<MessageResponse>> {
+    let user = sqlx::query_as!(
+        User,
+        "SELECT * FROM users WHERE (username = ?1 OR email = ?1) AND is_active = true AND password_hash IS NOT NULL",
+        request.identifier
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| ApiError::Database(e.to_string()))?;
+    
+    if let Some((user, email)) = user.and_then(|user| user.email.clone().map(|email| (user, email))) {
+        let valid_minutes = state.config.email.password_reset_token_minutes;
+        let token = generate_reset_token();
+        
+        store_reset_token(&state.db_pool, user.id, &token, Duration::minutes(valid_minutes))
+            .await
+            .map_err(|e| ApiError::Database(e.to_string()))?;
+        
+        if let Err(e) = send_password_reset_email(
+            state.email_sender.as_ref(),
+            &email,
+            &token,
+            &state.config.email.password_reset_url,
+            valid_minutes,
+        ).await {
+            warn!("Failed to send password reset email to user {}: {}", user.id, e);
+        }
+        
+        info!("Password reset requested for user: {} ({})", user.username, user.id);
+    }
+    
+    Ok(Json(MessageResponse {
+        message: "If an account matches, a password reset email has been sent".to_string(),
+    }))
+}
+
+pub async fn reset_password(
+    State(state): State<AppState>,
+    Json(request): Json<ResetPasswordRequest>,
+) -> ApiResult<Json<MessageResponse>> {
+    request.validate().map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    
+    let user_id = redeem_reset_token(&state.db_pool, &request.token, Utc::now())
+        .await
+        .map_err(|e| ApiError::Database(e.to_string()))?
+        .ok_or_else(|| ApiError::BadRequest("Invalid or expired reset token".to_string()))?;
+    
+    let password_hash = hash(&request.new_password, DEFAULT_COST)
+        .map_err(|e| ApiError::Internal(format!("Password hashing failed: {}", e)))?;
+    
+    sqlx::query!(
+        "UPDATE users SET password_hash = ?1, updated_at = ?2 WHERE id = ?3",
+        password_hash,
+        Utc::now(),
+        user_id
+    )
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| ApiError::Database(e.to_string()))?;
+    
+    info!("Password reset completed for user {}", user_id);
+    
+    Ok(Json(MessageResponse {
+        message: "Password has been reset".to_string(),
+    }))
+}
+
 // Helper functions
 
+async fn send_verification(state: &AppState, user: &User, email: &str) -> anyhow::Result<()> {
+    let token = create_verification_token(
+        user.id,
+        email,
+        &state.config.jwt_secret,
+        state.config.email.verification_token_hours,
+    )?;
+    
+    send_verification_email(
+        state.email_sender.as_ref(),
+        email,
+        &token,
+        &state.config.email.verification_url,
+    ).await
+}
+
 fn generate_jwt_token(state: &AppState, user: &User) -> ApiResult<String> {
     let expiration = Utc::now() + Duration::hours(1);
     
@@ -257,16 +491,28 @@ fn generate_jwt_token(state: &AppState, user: &User) -> ApiResult<String> {
         exp: expiration.timestamp() as usize,
     };
     
-    let token = encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(state.config.jwt_secret.as_ref()),
-    )
-    .map_err(|e| ApiError::Internal(format!("Token generation failed: {}", e)))?;
+    let token = state.jwt_keys.sign(&claims)
+        .map_err(|e| ApiError::Internal(format!("Token generation failed: {}", e)))?;
     
     Ok(token)
 }
 
+/// Reject the login unless `public_key` is an access key on `account_id`.
+pub(crate) async fn verify_key_ownership(state: &AppState, account_id: &near_primitives::types::AccountId, public_key: &str) -> ApiResult<()> {
+    let public_key: near_crypto::PublicKey = public_key.parse()
+        .map_err(|_| ApiError::BadRequest("Invalid public key format".to_string()))?;
+    
+    let owned = state.near_client.has_access_key(account_id, &public_key).await
+        .map_err(|e| circuit_breaker::api_error(e, |e| ApiError::Internal(format!("Failed to verify access key: {:#}", e))))?;
+    
+    if !owned {
+        warn!("Rejected Near login for {}: key is not an access key of the account", account_id);
+        return Err(ApiError::Unauthorized("Public key is not an access key of this account".to_string()));
+    }
+    
+    Ok(())
+}
+
 async fn verify_near_signature(request: &NearWalletLoginRequest) -> ApiResult<bool> {
     use near_crypto::{PublicKey, Signature};
     use std::str::FromStr;
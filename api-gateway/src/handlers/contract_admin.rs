@@ -0,0 +1,186 @@
+use axum::{extract::State, response::Json, Extension};
+use tracing::info;
+
+use crate::{
+    models::*,
+    handlers::AppState,
+    middleware::AuthenticatedUser,
+    circuit_breaker,
+    errors::{ApiError, ApiResult},
+    near_client::ContractParams,
+};
+
+// Bounds the contract enforces, checked here so a bad value is refused before
+// a transaction is paid for
+const MAX_TASKS_PER_NODE_LIMIT: u32 = 100;
+const MIN_TASK_TIMEOUT_SECONDS: u64 = 5 * 60;
+const MAX_TASK_TIMEOUT_SECONDS: u64 = 24 * 60 * 60;
+
+/// A validated `UpdateContractParamsRequest`.
+#[derive(Debug)]
+struct ParamChanges {
+    min_stake: Option<u128>,
+    max_tasks_per_node: Option<u32>,
+    task_timeout_seconds: Option<u64>,
+}
+
+pub async fn get_contract_params(State(state): State<AppState>) -> ApiResult<Json<ContractParams>> {
+    let params = read_params(&state).await?;
+    Ok(Json(params))
+}
+
+/// Change owner-tunable contract settings through the gateway's signer,
+/// which must be the contract owner. Returns the settings read back from
+/// the contract afterwards.
+pub async fn update_contract_params(
+    State(state): State<AppState>,
+    Extension(admin): Extension<AuthenticatedUser>,
+    Json(request): Json<UpdateContractParamsRequest>,
+) -> ApiResult<Json<ContractParams>> {
+    let changes = validate_changes(&request)?;
+    let old = read_params(&state).await?;
+
+    if let Some(min_stake) = changes.min_stake {
+        state.near_client.update_min_stake(min_stake).await
+            .map_err(|e| circuit_breaker::api_error(e, |e| contract_error(&format!("{:#}", e))))?;
+    }
+    if let Some(max_tasks) = changes.max_tasks_per_node {
+        state.near_client.update_max_tasks_per_node(max_tasks).await
+            .map_err(|e| circuit_breaker::api_error(e, |e| contract_error(&format!("{:#}", e))))?;
+    }
+    if let Some(timeout_seconds) = changes.task_timeout_seconds {
+        state.near_client.update_task_timeout(timeout_seconds).await
+            .map_err(|e| circuit_breaker::api_error(e, |e| contract_error(&format!("{:#}", e))))?;
+    }
+
+    let new = read_params(&state).await?;
+    info!(
+        "Admin {} updated contract params: min_stake {} -> {}, max_tasks_per_node {} -> {}, task_timeout_seconds {} -> {}",
+        admin.user.id,
+        old.min_stake, new.min_stake,
+        old.max_tasks_per_node, new.max_tasks_per_node,
+        old.task_timeout_seconds, new.task_timeout_seconds,
+    );
+
+    Ok(Json(new))
+}
+
+pub async fn pause_contract(
+    State(state): State<AppState>,
+    Extension(admin): Extension<AuthenticatedUser>,
+) -> ApiResult<Json<ContractParams>> {
+    set_paused(&state, &admin, true).await
+}
+
+pub async fn unpause_contract(
+    State(state): State<AppState>,
+    Extension(admin): Extension<AuthenticatedUser>,
+) -> ApiResult<Json<ContractParams>> {
+    set_paused(&state, &admin, false).await
+}
+
+async fn set_paused(state: &AppState, admin: &AuthenticatedUser, paused: bool) -> ApiResult<Json<ContractParams>> {
+    state.near_client.set_paused(paused).await
+        .map_err(|e| circuit_breaker::api_error(e, |e| contract_error(&format!("{:#}", e))))?;
+
+    let params = read_params(state).await?;
+    info!("Admin {} {} the contract", admin.user.id, if paused { "paused" } else { "unpaused" });
+
+    Ok(Json(params))
+}
+
+async fn read_params(state: &AppState) -> ApiResult<ContractParams> {
+    state.near_client.get_contract_params().await
+        .map_err(|e| circuit_breaker::api_error(e, |e| ApiError::Internal(format!("Failed to read contract params: {:#}", e))))
+}
+
+fn validate_changes(request: &UpdateContractParamsRequest) -> ApiResult<ParamChanges> {
+    if request.min_stake.is_none() && request.max_tasks_per_node.is_none() && request.task_timeout_seconds.is_none() {
+        return Err(ApiError::BadRequest("No contract params to update".to_string()));
+    }
+
+    let min_stake = request.min_stake.as_deref()
+        .map(|amount| match amount.parse::<u128>() {
+            Ok(amount) if amount > 0 => Ok(amount),
+            _ => Err(ApiError::BadRequest("min_stake must be a positive yoctoNEAR amount".to_string())),
+        })
+        .transpose()?;
+
+    if let Some(max_tasks) = request.max_tasks_per_node {
+        if max_tasks == 0 || max_tasks > MAX_TASKS_PER_NODE_LIMIT {
+            return Err(ApiError::BadRequest(format!(
+                "max_tasks_per_node must be between 1 and {}", MAX_TASKS_PER_NODE_LIMIT
+            )));
+        }
+    }
+
+    if let Some(timeout) = request.task_timeout_seconds {
+        if !(MIN_TASK_TIMEOUT_SECONDS..=MAX_TASK_TIMEOUT_SECONDS).contains(&timeout) {
+            return Err(ApiError::BadRequest(format!(
+                "task_timeout_seconds must be between {} and {}", MIN_TASK_TIMEOUT_SECONDS, MAX_TASK_TIMEOUT_SECONDS
+            )));
+        }
+    }
+
+    Ok(ParamChanges {
+        min_stake,
+        max_tasks_per_node: request.max_tasks_per_node,
+        task_timeout_seconds: request.task_timeout_seconds,
+    })
+}
+
+// Contract panics that mean the request was wrong rather than the gateway
+fn contract_error(error: &str) -> ApiError {
+    if error.contains("Contract already paused") {
+        ApiError::Conflict("Contract is already paused".to_string())
+    } else if error.contains("Contract not paused") {
+        ApiError::Conflict("Contract is not paused".to_string())
+    } else if error.contains("Only owner can call this method") {
+        ApiError::Forbidden("The gateway signer is not the contract owner".to_string())
+    } else {
+        ApiError::Internal(format!("Contract call failed: {}", error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_within_contract_bounds_are_accepted() {
+        let request = UpdateContractParamsRequest {
+            min_stake: Some("2000000000000000000000000".to_string()),
+            max_tasks_per_node: Some(100),
+            task_timeout_seconds: Some(300),
+        };
+
+        let changes = validate_changes(&request).unwrap();
+        assert_eq!(changes.min_stake, Some(2_000_000_000_000_000_000_000_000));
+        assert_eq!(changes.max_tasks_per_node, Some(100));
+        assert_eq!(changes.task_timeout_seconds, Some(300));
+    }
+
+    #[test]
+    fn test_changes_outside_contract_bounds_are_rejected() {
+        let invalid = [
+            UpdateContractParamsRequest { min_stake: Some("0".to_string()), ..Default::default() },
+            UpdateContractParamsRequest { min_stake: Some("lots".to_string()), ..Default::default() },
+            UpdateContractParamsRequest { max_tasks_per_node: Some(0), ..Default::default() },
+            UpdateContractParamsRequest { max_tasks_per_node: Some(101), ..Default::default() },
+            UpdateContractParamsRequest { task_timeout_seconds: Some(299), ..Default::default() },
+            UpdateContractParamsRequest { task_timeout_seconds: Some(86_401), ..Default::default() },
+            UpdateContractParamsRequest::default(),
+        ];
+
+        for request in invalid {
+            assert!(validate_changes(&request).is_err(), "{:?} should be rejected", request);
+        }
+    }
+
+    #[test]
+    fn test_contract_error_mapping() {
+        assert!(matches!(contract_error("Smart contract panicked: Contract already paused"), ApiError::Conflict(_)));
+        assert!(matches!(contract_error("Smart contract panicked: Only owner can call this method"), ApiError::Forbidden(_)));
+        assert!(matches!(contract_error("Failed to send transaction"), ApiError::Internal(_)));
+    }
+}
@@ -0,0 +1,195 @@
+use axum::{
+    extract::{Query, State},
+    response::Json,
+    Extension,
+};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+
+use crate::{
+    models::*,
+    handlers::AppState,
+    middleware::AuthenticatedUser,
+    errors::{ApiError, ApiResult},
+    near_client::ContractStats,
+    chain_cache,
+};
+
+pub async fn list_active_nodes(
+    State(state): State<AppState>,
+    Query(query): Query<CacheQuery>,
+    caller: Option<Extension<AuthenticatedUser>>,
+) -> ApiResult<Json<CachedResponse<Vec<NodeInfo>>>> {
+    let nodes = chain_cache::active_nodes(&state, bypass_cache(&query, caller.as_deref()))
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to fetch active nodes: {}", e)))?;
+
+    Ok(Json(nodes))
+}
+
+pub async fn get_network_stats(
+    State(state): State<AppState>,
+    Query(query): Query<CacheQuery>,
+    caller: Option<Extension<AuthenticatedUser>>,
+) -> ApiResult<Json<CachedResponse<NetworkStats>>> {
+    let bypass = bypass_cache(&query, caller.as_deref());
+
+    let stats = chain_cache::contract_stats(&state, bypass)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to fetch contract stats: {}", e)))?;
+    let nodes = chain_cache::active_nodes(&state, bypass)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to fetch active nodes: {}", e)))?;
+
+    let task_counts = fetch_task_counts(&state.db_pool, Utc::now() - Duration::hours(24))
+        .await
+        .map_err(|e| ApiError::Database(e.to_string()))?;
+
+    // The response is only as fresh as the older of the two cached reads
+    Ok(Json(CachedResponse {
+        data: build_network_stats(&stats.data, &nodes.data, &task_counts),
+        fetched_at: stats.fetched_at.min(nodes.fetched_at),
+        stale_at: stats.stale_at.min(nodes.stale_at),
+    }))
+}
+
+// `?fresh=true` from anyone but an admin is ignored and served from cache
+fn bypass_cache(query: &CacheQuery, caller: Option<&AuthenticatedUser>) -> bool {
+    query.fresh && caller.is_some_and(|caller| caller.user.is_admin)
+}
+
+struct TaskCounts {
+    total_tasks: i64,
+    completed_last_24h: i64,
+    average_task_time_ms: Option<f64>,
+}
+
+async fn fetch_task_counts(pool: &SqlitePool, since: DateTime<Utc>) -> Result<TaskCounts, sqlx::Error> {
+    let (total_tasks, completed_last_24h, average_task_time_ms): (i64, i64, Option<f64>) = sqlx::query_as(
+        r#"
+        SELECT
+            COUNT(*),
+            COALESCE(SUM(CASE WHEN status = 'completed' AND completed_at >= ?1 THEN 1 ELSE 0 END), 0),
+            AVG(CASE WHEN status = 'completed' AND completed_at >= ?1 AND started_at IS NOT NULL
+                THEN (julianday(completed_at) - julianday(started_at)) * 86400000.0 END)
+        FROM tasks
+        "#,
+    )
+    .bind(since)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(TaskCounts { total_tasks, completed_last_24h, average_task_time_ms })
+}
+
+fn build_network_stats(stats: &ContractStats, nodes: &[NodeInfo], task_counts: &TaskCounts) -> NetworkStats {
+    let total_staked: u128 = nodes.iter()
+        .filter_map(|node| node.stake_amount.parse::<u128>().ok())
+        .fold(0, u128::saturating_add);
+
+    let network_utilization_percent = utilization_percent(stats);
+
+    NetworkStats {
+        total_nodes: stats.total_nodes as u32,
+        active_nodes: stats.active_nodes as u32,
+        total_tasks: task_counts.total_tasks as u64,
+        pending_tasks: stats.active_tasks as u32,
+        completed_tasks_24h: task_counts.completed_last_24h as u32,
+        // Measured on-chain for every task; the gateway's own records only
+        // cover tasks submitted through it
+        average_task_time_ms: stats.average_task_time_ms.or(task_counts.average_task_time_ms),
+        total_staked_near: total_staked.to_string(),
+        network_utilization_percent,
+    }
+}
+
+// Share of the active nodes' task slots in use. Stats cached before the
+// contract reported its per-node limit count one slot per node.
+fn utilization_percent(stats: &ContractStats) -> f32 {
+    let capacity = stats.active_nodes * stats.max_tasks_per_node.max(1) as u64;
+    if capacity == 0 {
+        return 0.0;
+    }
+
+    (stats.active_tasks as f32 / capacity as f32 * 100.0).clamp(0.0, 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_node(stake_amount: &str) -> NodeInfo {
+        NodeInfo {
+            account_id: "node.testnet".to_string(),
+            public_ip: "203.0.113.10".to_string(),
+            gpu_specs: "RTX 4090".to_string(),
+            cpu_specs: "16 cores".to_string(),
+            api_endpoint: "https://node.example.com".to_string(),
+            is_active: true,
+            last_heartbeat: Utc::now(),
+            total_tasks_completed: 0,
+            reputation_score: 100,
+            stake_amount: stake_amount.to_string(),
+            capabilities: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_build_network_stats() {
+        let stats = ContractStats {
+            active_nodes: 4,
+            total_nodes: 6,
+            active_tasks: 6,
+            paused: false,
+            max_tasks_per_node: 2,
+            average_task_time_ms: Some(2500.0),
+        };
+        let nodes = [
+            create_node("5000000000000000000000000"),
+            create_node("7000000000000000000000000"),
+        ];
+        let task_counts = TaskCounts { total_tasks: 40, completed_last_24h: 12, average_task_time_ms: Some(1500.0) };
+
+        let network = build_network_stats(&stats, &nodes, &task_counts);
+
+        assert_eq!(network.total_nodes, 6);
+        assert_eq!(network.pending_tasks, 6);
+        assert_eq!(network.completed_tasks_24h, 12);
+        assert_eq!(network.total_staked_near, "12000000000000000000000000");
+        assert_eq!(network.network_utilization_percent, 75.0);
+        assert_eq!(network.average_task_time_ms, Some(2500.0));
+    }
+
+    #[test]
+    fn test_utilization_without_active_nodes() {
+        let stats = ContractStats {
+            active_nodes: 0,
+            total_nodes: 2,
+            active_tasks: 5,
+            paused: false,
+            max_tasks_per_node: 5,
+            average_task_time_ms: None,
+        };
+        let task_counts = TaskCounts { total_tasks: 0, completed_last_24h: 0, average_task_time_ms: Some(900.0) };
+
+        let network = build_network_stats(&stats, &[], &task_counts);
+
+        assert_eq!(network.network_utilization_percent, 0.0);
+        // Falls back to the gateway's own records without on-chain timing
+        assert_eq!(network.average_task_time_ms, Some(900.0));
+    }
+
+    #[test]
+    fn test_utilization_is_clamped() {
+        let stats = ContractStats {
+            active_nodes: 2,
+            total_nodes: 2,
+            active_tasks: 50,
+            paused: false,
+            max_tasks_per_node: 5,
+            average_task_time_ms: None,
+        };
+
+        assert_eq!(utilization_percent(&stats), 100.0);
+    }
+}
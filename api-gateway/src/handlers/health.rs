@@ -0,0 +1,114 @@
+use anyhow::Result;
+use axum::{extract::State, http::StatusCode, response::Json};
+use serde::Serialize;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::{circuit_breaker::BreakerState, database, handlers::AppState};
+
+// A dependency slower than this counts as down, so a hung RPC node can't
+// hold the probe past the orchestrator's own timeout
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Serialize)]
+pub struct DependencyStatus {
+    pub healthy: bool,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessReport {
+    pub ready: bool,
+    pub database: DependencyStatus,
+    pub redis: DependencyStatus,
+    pub near_rpc: DependencyStatus,
+    pub near_rpc_circuit: BreakerState,
+}
+
+/// Readiness probe: 200 only when the database, Redis and NEAR RPC all
+/// answer, 503 with the per-dependency breakdown otherwise. `/health` stays
+/// a pure liveness probe. While the RPC circuit breaker is open the NEAR
+/// check fails fast without contacting the node.
+pub async fn readiness(State(state): State<AppState>) -> (StatusCode, Json<ReadinessReport>) {
+    let (database, redis, near_rpc) = tokio::join!(
+        check(database::check_connection(&state.db_pool)),
+        check(ping_redis(&state.redis_client)),
+        check(state.near_client.check_rpc()),
+    );
+
+    let report = readiness_report(database, redis, near_rpc, state.near_client.circuit_state());
+    if !report.ready {
+        warn!("Readiness check failed: {:?}", report);
+    }
+    let status = if report.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+
+    (status, Json(report))
+}
+
+async fn ping_redis(client: &redis::Client) -> Result<()> {
+    let mut conn = client.get_multiplexed_async_connection().await?;
+    let _: String = redis::cmd("PING").query_async(&mut conn).await?;
+    Ok(())
+}
+
+async fn check<F>(probe: F) -> DependencyStatus
+where
+    F: Future<Output = Result<()>>,
+{
+    let started = Instant::now();
+    let error = match tokio::time::timeout(CHECK_TIMEOUT, probe).await {
+        Ok(Ok(())) => None,
+        Ok(Err(e)) => Some(format!("{:#}", e)),
+        Err(_) => Some(format!("No answer within {}s", CHECK_TIMEOUT.as_secs())),
+    };
+
+    DependencyStatus {
+        healthy: error.is_none(),
+        latency_ms: started.elapsed().as_millis() as u64,
+        error,
+    }
+}
+
+fn readiness_report(
+    database: DependencyStatus,
+    redis: DependencyStatus,
+    near_rpc: DependencyStatus,
+    near_rpc_circuit: BreakerState,
+) -> ReadinessReport {
+    ReadinessReport {
+        ready: database.healthy && redis.healthy && near_rpc.healthy,
+        database,
+        redis,
+        near_rpc,
+        near_rpc_circuit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_ready_only_when_every_dependency_is_healthy() {
+        let report = readiness_report(
+            check(async { Ok(()) }).await,
+            check(async { Ok(()) }).await,
+            check(async { Ok(()) }).await,
+            BreakerState::Closed,
+        );
+        assert!(report.ready);
+
+        let report = readiness_report(
+            check(async { Ok(()) }).await,
+            check(async { Err(anyhow::anyhow!("Connection refused")) }).await,
+            check(async { Ok(()) }).await,
+            BreakerState::Closed,
+        );
+        assert!(!report.ready);
+        assert!(report.database.healthy);
+        assert_eq!(report.redis.error.as_deref(), Some("Connection refused"));
+    }
+}
@@ -1,10 +1,17 @@
 use sqlx::SqlitePool;
 use redis::Client as RedisClient;
 use std::sync::Arc;
-use crate::{config::AppConfig, near_client::NearClient};
+use crate::{config::AppConfig, email::EmailSender, jwt_keys::JwtKeys, login_throttle::LoginThrottle, near_client::NearClient, routing::ModelRouter};
 
+pub mod activity;
 pub mod auth;
+pub mod contract_admin;
+pub mod directory;
+pub mod health;
+pub mod moderation;
+pub mod node_metrics;
 pub mod tasks;
+pub mod usage;
 pub mod nodes;
 pub mod network;
 pub mod users;
@@ -16,4 +23,8 @@ pub struct AppState {
     pub db_pool: SqlitePool,
     pub redis_client: RedisClient,
     pub near_client: Arc<NearClient>,
+    pub model_router: Arc<ModelRouter>,
+    pub email_sender: Arc<dyn EmailSender>,
+    pub login_throttle: Arc<LoginThrottle>,
+    pub jwt_keys: Arc<JwtKeys>,
 }
\ No newline at end of file
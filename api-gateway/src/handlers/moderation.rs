@@ -0,0 +1,172 @@
+use axum::{
+    extract::{Path, State},
+    response::Json,
+    Extension,
+};
+use chrono::Utc;
+use sqlx::SqlitePool;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    models::*,
+    handlers::{tasks::task_response, AppState},
+    middleware::AuthenticatedUser,
+    circuit_breaker,
+    errors::{ApiError, ApiResult},
+};
+
+pub async fn suspend_user(
+    State(state): State<AppState>,
+    Extension(admin): Extension<AuthenticatedUser>,
+    Path(user_id): Path<Uuid>,
+) -> ApiResult<Json<UserProfile>> {
+    if user_id == admin.user.id {
+        return Err(ApiError::BadRequest("Admins cannot suspend themselves".to_string()));
+    }
+
+    let user = update_user_active(&state.db_pool, user_id, false).await?;
+    info!("Admin {} suspended user {}", admin.user.id, user_id);
+
+    Ok(Json(user.into()))
+}
+
+pub async fn reactivate_user(
+    State(state): State<AppState>,
+    Extension(admin): Extension<AuthenticatedUser>,
+    Path(user_id): Path<Uuid>,
+) -> ApiResult<Json<UserProfile>> {
+    let user = update_user_active(&state.db_pool, user_id, true).await?;
+    info!("Admin {} reactivated user {}", admin.user.id, user_id);
+
+    Ok(Json(user.into()))
+}
+
+/// Time out a task whose node went dark, so the requester is refunded and the
+/// node slashed without anyone needing the NEAR CLI.
+pub async fn timeout_task(
+    State(state): State<AppState>,
+    Extension(admin): Extension<AuthenticatedUser>,
+    Path(task_id): Path<Uuid>,
+) -> ApiResult<Json<TaskResponse>> {
+    let task = sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE id = ?1")
+        .bind(task_id)
+        .fetch_optional(&state.db_pool)
+        .await
+        .map_err(|e| ApiError::Database(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("Task not found".to_string()))?;
+
+    let contract_task_id = task.contract_task_id
+        .ok_or_else(|| ApiError::BadRequest("Task was never submitted on-chain".to_string()))?;
+
+    state.near_client.timeout_task(contract_task_id as u64).await
+        .map_err(|e| circuit_breaker::api_error(e, |e| contract_error(&format!("{:#}", e))))?;
+
+    let task = sqlx::query_as::<_, Task>(
+        "UPDATE tasks SET status = 'expired', completed_at = ?1, error_message = 'Timed out on-chain' WHERE id = ?2 RETURNING *",
+    )
+    .bind(Utc::now())
+    .bind(task_id)
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| ApiError::Database(e.to_string()))?;
+
+    info!("Admin {} timed out task {} (contract task {})", admin.user.id, task_id, contract_task_id);
+
+    Ok(Json(task_response(task, None)))
+}
+
+// Contract panics that mean the request was wrong rather than the gateway
+fn contract_error(error: &str) -> ApiError {
+    if error.contains("Task not found") {
+        ApiError::NotFound("Task not found on-chain".to_string())
+    } else if error.contains("Task has not timed out yet") {
+        ApiError::BadRequest("Task has not timed out yet".to_string())
+    } else if error.contains("Task not active") {
+        ApiError::BadRequest("Task is not assigned to a node".to_string())
+    } else {
+        ApiError::Internal(format!("Failed to time out task: {}", error))
+    }
+}
+
+// The auth middleware rejects inactive users, so this takes effect on the
+// user's next request
+async fn update_user_active(pool: &SqlitePool, user_id: Uuid, is_active: bool) -> ApiResult<User> {
+    sqlx::query_as::<_, User>("UPDATE users SET is_active = ?1, updated_at = ?2 WHERE id = ?3 RETURNING *")
+        .bind(is_active)
+        .bind(Utc::now())
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| ApiError::Database(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("User not found".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn create_test_pool() -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::query(
+            r#"
+            CREATE TABLE users (
+                id BLOB PRIMARY KEY, near_account_id TEXT, email TEXT, username TEXT NOT NULL,
+                password_hash TEXT, is_active BOOLEAN NOT NULL, is_admin BOOLEAN NOT NULL,
+                created_at TEXT NOT NULL, updated_at TEXT NOT NULL, last_login_at TEXT,
+                email_verified BOOLEAN NOT NULL DEFAULT 0
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool
+    }
+
+    async fn insert_user(pool: &SqlitePool) -> Uuid {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO users (id, username, is_active, is_admin, created_at, updated_at) VALUES (?1, 'alice', 1, 0, ?2, ?2)",
+        )
+        .bind(id)
+        .bind(Utc::now())
+        .execute(pool)
+        .await
+        .unwrap();
+        id
+    }
+
+    #[tokio::test]
+    async fn test_suspend_and_reactivate_user() {
+        let pool = create_test_pool().await;
+        let user_id = insert_user(&pool).await;
+
+        let user = update_user_active(&pool, user_id, false).await.unwrap();
+        assert!(!user.is_active);
+
+        let user = update_user_active(&pool, user_id, true).await.unwrap();
+        assert!(user.is_active);
+    }
+
+    #[test]
+    fn test_contract_error_mapping() {
+        assert!(matches!(
+            contract_error("Transaction abc failed: ActionError { kind: FunctionCallError(ExecutionError(\"Smart contract panicked: Task has not timed out yet\")) }"),
+            ApiError::BadRequest(_)
+        ));
+        assert!(matches!(contract_error("Smart contract panicked: Task not active"), ApiError::BadRequest(_)));
+        assert!(matches!(contract_error("Smart contract panicked: Task not found"), ApiError::NotFound(_)));
+        assert!(matches!(contract_error("Failed to send transaction"), ApiError::Internal(_)));
+    }
+
+    #[tokio::test]
+    async fn test_update_unknown_user() {
+        let pool = create_test_pool().await;
+
+        assert!(matches!(
+            update_user_active(&pool, Uuid::new_v4(), false).await,
+            Err(ApiError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,168 @@
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+use chrono::{DateTime, Utc};
+use redis::AsyncCommands;
+use tracing::{debug, warn};
+
+use crate::{
+    models::*,
+    handlers::AppState,
+    circuit_breaker,
+    errors::{ApiError, ApiResult},
+    near_client::{parse_yocto, ContractNodeInfo, ContractTask},
+};
+
+// Metrics are served from Redis for this long before the contract is queried again
+const NODE_METRICS_CACHE_TTL_SECS: u64 = 30;
+
+// Each timeout slashes a tenth of the node's stake (see `timeout_task` in the contract)
+const SLASH_FRACTION: u128 = 10;
+
+pub async fn get_node_metrics(
+    State(state): State<AppState>,
+    Path(node_id): Path<String>,
+) -> ApiResult<Json<NodeMetrics>> {
+    let cache_key = format!("node_metrics:{}", node_id);
+
+    if let Some(metrics) = read_cached_metrics(&state, &cache_key).await {
+        debug!("Serving cached metrics for node {}", node_id);
+        return Ok(Json(metrics));
+    }
+
+    let node = state.near_client.get_node_info(&node_id)
+        .await
+        .map_err(|e| circuit_breaker::api_error(e, |e| ApiError::Internal(format!("Failed to fetch node info: {}", e))))?
+        .ok_or_else(|| ApiError::NotFound("Node not found".to_string()))?;
+
+    let assigned_tasks = state.near_client.get_assigned_tasks(&node_id)
+        .await
+        .map_err(|e| circuit_breaker::api_error(e, |e| ApiError::Internal(format!("Failed to fetch assigned tasks: {}", e))))?;
+
+    let metrics = compute_node_metrics(&node, &assigned_tasks, Utc::now());
+    write_cached_metrics(&state, &cache_key, &metrics).await;
+
+    Ok(Json(metrics))
+}
+
+fn compute_node_metrics(node: &ContractNodeInfo, assigned_tasks: &[ContractTask], now: DateTime<Utc>) -> NodeMetrics {
+    let stake = parse_yocto(&node.stake);
+    let slashed_amount = parse_yocto(&node.slashed_amount);
+
+    // The contract doesn't count slashes, but each one takes a fixed share of
+    // the stake, so the count can be recovered from the slashed total
+    let slash_size = stake / SLASH_FRACTION;
+    let estimated_slash_count = if slash_size == 0 {
+        0
+    } else {
+        slashed_amount.div_ceil(slash_size) as u64
+    };
+
+    let attempts = node.total_tasks_completed + estimated_slash_count;
+    let success_rate = (attempts > 0)
+        .then(|| node.total_tasks_completed as f64 / attempts as f64);
+
+    let tasks_in_progress = assigned_tasks.iter()
+        .filter(|task| matches!(task.status.as_str(), "Assigned" | "InProgress"))
+        .count() as u64;
+
+    let now_nanos = now.timestamp_nanos_opt().unwrap_or(i64::MAX) as u64;
+    let heartbeat_age_seconds = now_nanos.saturating_sub(node.last_heartbeat) / 1_000_000_000;
+
+    NodeMetrics {
+        account_id: node.account_id.clone(),
+        is_active: node.is_active,
+        reputation_score: node.reputation_score,
+        total_tasks_completed: node.total_tasks_completed,
+        tasks_in_progress,
+        estimated_slash_count,
+        success_rate,
+        heartbeat_age_seconds,
+        stake_amount: stake.to_string(),
+        slashed_amount: slashed_amount.to_string(),
+        effective_stake: stake.saturating_sub(slashed_amount).to_string(),
+        fetched_at: now,
+    }
+}
+
+// Caching is best effort: Redis errors fall through to the contract
+async fn read_cached_metrics(state: &AppState, key: &str) -> Option<NodeMetrics> {
+    let mut conn = state.redis_client.get_multiplexed_async_connection().await.ok()?;
+    let cached: Option<String> = conn.get(key).await.ok()?;
+
+    serde_json::from_str(&cached?).ok()
+}
+
+async fn write_cached_metrics(state: &AppState, key: &str, metrics: &NodeMetrics) {
+    let Ok(payload) = serde_json::to_string(metrics) else {
+        return;
+    };
+
+    let result = match state.redis_client.get_multiplexed_async_connection().await {
+        Ok(mut conn) => conn.set_ex::<_, _, ()>(key, payload, NODE_METRICS_CACHE_TTL_SECS).await,
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
+        warn!("Failed to cache node metrics: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    const ONE_NEAR: u128 = 1_000_000_000_000_000_000_000_000;
+
+    fn create_node(stake: u128, slashed_amount: u128, total_tasks_completed: u64, last_heartbeat: u64) -> ContractNodeInfo {
+        ContractNodeInfo {
+            account_id: "node.testnet".to_string(),
+            stake: json!(stake.to_string()),
+            public_ip: "203.0.113.10".to_string(),
+            gpu_specs: "RTX 4090".to_string(),
+            cpu_specs: "16 cores".to_string(),
+            api_endpoint: "https://node.example.com".to_string(),
+            is_active: true,
+            last_heartbeat,
+            total_tasks_completed,
+            reputation_score: 90,
+            slashed_amount: json!(slashed_amount.to_string()),
+            capabilities: Vec::new(),
+        }
+    }
+
+    fn create_task(id: u64, status: &str) -> ContractTask {
+        ContractTask { id, status: status.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_compute_node_metrics() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let heartbeat = (now - chrono::Duration::seconds(45)).timestamp_nanos_opt().unwrap() as u64;
+        let node = create_node(10 * ONE_NEAR, 2 * ONE_NEAR, 18, heartbeat);
+        let tasks = [create_task(1, "Assigned"), create_task(2, "InProgress"), create_task(3, "Completed")];
+
+        let metrics = compute_node_metrics(&node, &tasks, now);
+
+        assert_eq!(metrics.tasks_in_progress, 2);
+        assert_eq!(metrics.estimated_slash_count, 2);
+        assert_eq!(metrics.success_rate, Some(0.9));
+        assert_eq!(metrics.heartbeat_age_seconds, 45);
+        assert_eq!(metrics.effective_stake, (8 * ONE_NEAR).to_string());
+    }
+
+    #[test]
+    fn test_compute_node_metrics_for_new_node() {
+        let now = Utc::now();
+        let node = create_node(ONE_NEAR, 0, 0, now.timestamp_nanos_opt().unwrap() as u64);
+
+        let metrics = compute_node_metrics(&node, &[], now);
+
+        assert_eq!(metrics.success_rate, None);
+        assert_eq!(metrics.estimated_slash_count, 0);
+        assert_eq!(metrics.effective_stake, ONE_NEAR.to_string());
+    }
+}
@@ -1,18 +1,34 @@
 use axum::{
-    extract::{Path, Query, State},
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
     http::StatusCode,
-    response::Json,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Json, Response,
+    },
 };
+use axum::http::HeaderMap;
+use futures::stream::{self, Stream};
+use sha2::{Digest, Sha256};
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+use std::{collections::{BTreeMap, VecDeque}, convert::Infallible, time::Duration};
 use uuid::Uuid;
 use chrono::Utc;
 use validator::Validate;
-use tracing::{info, warn, error};
+use tracing::{error, info, warn, Instrument};
 
 use crate::{
     models::*,
     handlers::AppState,
     auth::Claims,
+    circuit_breaker,
     errors::{ApiError, ApiResult},
+    near_client::MAX_TASKS_BATCH,
+    node_auth,
+    pricing,
+    webhooks,
 };
 
 pub async fn submit_task(
@@ -20,20 +36,14 @@ pub async fn submit_task(
     claims: Claims,
     Json(request): Json<SubmitTaskRequest>,
 ) -> ApiResult<Json<TaskResponse>> {
-    // Validate request
-    request.validate().map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    ensure_email_verified(&state, claims.user_id).await?;
+    let webhook_secret = validate_submission(&request).await?;
     
     info!("Submitting task for user {}: {} with model {}", 
           claims.user_id, request.task_type, request.model_name);
     
     // Check user's current task quota
-    let active_tasks = sqlx::query_scalar!(
-        "SELECT COUNT(*) FROM tasks WHERE user_id = ?1 AND status IN ('pending', 'submitted', 'assigned', 'in_progress')",
-        claims.user_id
-    )
-    .fetch_one(&state.db_pool)
-    .await
-    .map_err(|e| ApiError::Database(e.to_string()))?;
+    let active_tasks = count_active_tasks(&state, claims.user_id).await?;
     
     if active_tasks >= state.config.rate_limits.max_concurrent_tasks as i64 {
         return Err(ApiError::TooManyRequests(
@@ -41,24 +51,230 @@ pub async fn submit_task(
         ));
     }
     
+    // Create task record
+    let surge_multiplier = pricing::current_surge_multiplier(&state).await;
+    let task = insert_task(&state.db_pool, claims.user_id, request, surge_multiplier, webhook_secret, None)
+        .await
+        .map_err(|e| ApiError::Database(e.to_string()))?;
+    
+    // Submit task to Near contract
+    match submit_task_to_near(&state, &task).await {
+        Ok(contract_task_id) => {
+            mark_task_submitted(&state, task.id, contract_task_id).await?;
+            info!("Task {} submitted to Near contract with ID {}", task.id, contract_task_id);
+        }
+        Err(e) => {
+            mark_task_failed(&state, task.id, &e.to_string()).await?;
+            error!("Failed to submit task {} to Near: {}", task.id, e);
+            return Err(circuit_breaker::api_error(e, |_| {
+                ApiError::Internal("Failed to submit task to blockchain".to_string())
+            }));
+        }
+    }
+    
+    Ok(Json(creation_response(task, TaskStatus::Submitted)))
+}
+
+/// Preview what submitting a task would cost without creating it.
+pub async fn estimate_task(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(request): Json<SubmitTaskRequest>,
+) -> ApiResult<Json<CostEstimate>> {
+    request.validate().map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    let surge_multiplier = pricing::current_surge_multiplier(&state).await;
+    let estimate = estimate_task_cost(&request, surge_multiplier);
+    info!("Estimated {} yoctoNEAR for a {} task from user {}",
+          estimate.estimated_cost, request.task_type, claims.user_id);
+
+    Ok(Json(estimate))
+}
+
+// Most tasks accepted by one batch submission request
+const MAX_BATCH_SUBMISSION: usize = 50;
+
+/// Submit several tasks at once. Each entry of the response matches the
+/// request at the same index and carries either the task or an error.
+pub async fn submit_task_batch(
+    State(state): State<AppState>,
+    claims: Claims,
+    Json(requests): Json<Vec<SubmitTaskRequest>>,
+) -> ApiResult<Json<Vec<BatchTaskResult>>> {
+    if requests.is_empty() || requests.len() > MAX_BATCH_SUBMISSION {
+        return Err(ApiError::BadRequest(format!(
+            "Batch must contain between 1 and {} tasks", MAX_BATCH_SUBMISSION
+        )));
+    }
+    
+    ensure_email_verified(&state, claims.user_id).await?;
+    
+    info!("Submitting batch of {} tasks for user {}", requests.len(), claims.user_id);
+    
+    let mut results: Vec<BatchTaskResult> = (0..requests.len())
+        .map(|index| BatchTaskResult { index, task: None, error: None })
+        .collect();
+    
+    // Invalid entries get an error instead of failing the whole batch
+    let mut accepted = Vec::with_capacity(requests.len());
+    for (index, request) in requests.into_iter().enumerate() {
+        match validate_submission(&request).await {
+            Ok(webhook_secret) => accepted.push((index, request, webhook_secret)),
+            Err(e) => results[index].error = Some(e.to_string()),
+        }
+    }
+    
+    if accepted.is_empty() {
+        return Ok(Json(results));
+    }
+    
+    // The quota applies to the batch as a whole
+    let active_tasks = count_active_tasks(&state, claims.user_id).await?;
+    
+    if active_tasks + accepted.len() as i64 > state.config.rate_limits.max_concurrent_tasks as i64 {
+        return Err(ApiError::TooManyRequests(
+            "Batch would exceed maximum concurrent tasks".to_string()
+        ));
+    }
+    
+    // Create all task records together
+    let mut tx = state.db_pool.begin()
+        .await
+        .map_err(|e| ApiError::Database(e.to_string()))?;
+    
+    let surge_multiplier = pricing::current_surge_multiplier(&state).await;
+    let mut tasks = Vec::with_capacity(accepted.len());
+    for (index, request, webhook_secret) in accepted {
+        let task = insert_task(&mut *tx, claims.user_id, request, surge_multiplier, webhook_secret, None)
+            .await
+            .map_err(|e| ApiError::Database(e.to_string()))?;
+        tasks.push((index, task));
+    }
+    
+    tx.commit().await.map_err(|e| ApiError::Database(e.to_string()))?;
+    
+    // Each contract call charges one cost for all its tasks, so group by cost
+    let mut by_cost: BTreeMap<String, Vec<(usize, Task)>> = BTreeMap::new();
+    for (index, task) in tasks {
+        by_cost.entry(task.estimated_cost.clone()).or_default().push((index, task));
+    }
+    
+    let nodes = active_nodes_for_routing(&state).await;
+    
+    for (cost, group) in by_cost {
+        let mut group = group.into_iter().peekable();
+        
+        while group.peek().is_some() {
+            let chunk: Vec<(usize, Task)> = group.by_ref().take(MAX_TASKS_BATCH).collect();
+            
+            let descriptions = chunk.iter()
+                .map(|(_, task)| {
+                    let preferred_node = state.model_router.recommend_node(&task.model_name, &nodes);
+                    task_description(task, preferred_node, &state.config.public_url)
+                })
+                .collect();
+            
+            let submitted = match cost.parse() {
+                Ok(cost) => state.near_client.submit_tasks_batch(descriptions, cost).await,
+                Err(e) => Err(anyhow::anyhow!("Invalid estimated cost {}: {}", cost, e)),
+            };
+            
+            match submitted {
+                Ok(contract_task_ids) => {
+                    for ((index, task), contract_task_id) in chunk.into_iter().zip(contract_task_ids) {
+                        mark_task_submitted(&state, task.id, contract_task_id as i64).await?;
+                        results[index].task = Some(creation_response(task, TaskStatus::Submitted));
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to submit batch of {} tasks to Near: {}", chunk.len(), e);
+                    
+                    for (index, task) in chunk {
+                        mark_task_failed(&state, task.id, &e.to_string()).await?;
+                        results[index].task = Some(creation_response(task, TaskStatus::Failed));
+                        results[index].error = Some("Failed to submit task to blockchain".to_string());
+                    }
+                }
+            }
+        }
+    }
+    
+    Ok(Json(results))
+}
+
+// Task submission can be limited to verified accounts via REQUIRE_VERIFIED_EMAIL
+async fn ensure_email_verified(state: &AppState, user_id: Uuid) -> ApiResult<()> {
+    if !state.config.email.require_verified_email {
+        return Ok(());
+    }
+    
+    let email_verified = sqlx::query_scalar!(
+        "SELECT email_verified FROM users WHERE id = ?1",
+        user_id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| ApiError::Database(e.to_string()))?
+    .unwrap_or(false);
+    
+    if !email_verified {
+        return Err(ApiError::Forbidden("Verify your email address before submitting tasks".to_string()));
+    }
+    Ok(())
+}
+
+// Validate a submission and return the webhook secret to store, if any
+async fn validate_submission(request: &SubmitTaskRequest) -> ApiResult<Option<String>> {
+    request.validate().map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    
+    if let Some(callback_url) = &request.callback_url {
+        webhooks::validate_callback_url(callback_url).await
+            .map_err(ApiError::BadRequest)?;
+    }
+    
+    Ok(request.callback_url.as_ref().map(|_| webhooks::generate_secret()))
+}
+
+async fn count_active_tasks(state: &AppState, user_id: Uuid) -> ApiResult<i64> {
+    sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM tasks WHERE user_id = ?1 AND status IN ('pending', 'submitted', 'assigned', 'in_progress')",
+        user_id
+    )
+    .fetch_one(&state.db_pool)
+    .await
+    .map_err(|e| ApiError::Database(e.to_string()))
+}
+
+async fn insert_task<'e, E>(
+    executor: E,
+    user_id: Uuid,
+    request: SubmitTaskRequest,
+    surge_multiplier: f64,
+    webhook_secret: Option<String>,
+    retried_from: Option<Uuid>,
+) -> Result<Task, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = Sqlite>,
+{
     // Estimate cost (simplified - could be more sophisticated)
-    let estimated_cost = estimate_task_cost(&request);
+    let estimated_cost = estimate_task_cost(&request, surge_multiplier).estimated_cost;
     
-    // Create task record
     let task_id = Uuid::new_v4();
     let expires_at = Utc::now() + chrono::Duration::hours(24); // 24-hour expiry
+    let input_hash = request.off_chain_input.unwrap_or(false).then(|| input_digest(&request.input_data));
     
-    let task = sqlx::query_as!(
+    sqlx::query_as!(
         Task,
         r#"
         INSERT INTO tasks (
             id, user_id, task_type, model_name, input_data, parameters,
-            status, priority, estimated_cost, expires_at, created_at
-        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'pending', ?7, ?8, ?9, ?10)
+            status, priority, estimated_cost, expires_at, created_at,
+            webhook_url, webhook_secret, retried_from, input_hash
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'pending', ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
         RETURNING *
         "#,
         task_id,
-        claims.user_id,
+        user_id,
         request.task_type,
         request.model_name,
         request.input_data,
@@ -66,59 +282,68 @@ pub async fn submit_task(
         request.priority.unwrap_or(0),
         estimated_cost,
         expires_at,
-        Utc::now()
+        Utc::now(),
+        request.callback_url,
+        webhook_secret,
+        retried_from,
+        input_hash
     )
-    .fetch_one(&state.db_pool)
+    .fetch_one(executor)
+    .await
+}
+
+async fn mark_task_submitted(state: &AppState, task_id: Uuid, contract_task_id: i64) -> ApiResult<()> {
+    sqlx::query!(
+        "UPDATE tasks SET contract_task_id = ?1, status = 'submitted' WHERE id = ?2",
+        contract_task_id,
+        task_id
+    )
+    .execute(&state.db_pool)
     .await
     .map_err(|e| ApiError::Database(e.to_string()))?;
     
-    // Submit task to Near contract
-    match submit_task_to_near(&state, &task).await {
-        Ok(contract_task_id) => {
-            // Update task with contract task ID
-            sqlx::query!(
-                "UPDATE tasks SET contract_task_id = ?1, status = 'submitted' WHERE id = ?2",
-                contract_task_id,
-                task_id
-            )
-            .execute(&state.db_pool)
-            .await
-            .map_err(|e| ApiError::Database(e.to_string()))?;
-            
-            info!("Task {} submitted to Near contract with ID {}", task_id, contract_task_id);
-        }
-        Err(e) => {
-            // Mark task as failed
-            sqlx::query!(
-                "UPDATE tasks SET status = 'failed', error_message = ?1 WHERE id = ?2",
-                e.to_string(),
-                task_id
-            )
-            .execute(&state.db_pool)
-            .await
-            .map_err(|e| ApiError::Database(e.to_string()))?;
-            
-            error!("Failed to submit task {} to Near: {}", task_id, e);
-            return Err(ApiError::Internal("Failed to submit task to blockchain".to_string()));
-        }
-    }
+    Ok(())
+}
+
+async fn mark_task_failed(state: &AppState, task_id: Uuid, error_message: &str) -> ApiResult<()> {
+    sqlx::query!(
+        "UPDATE tasks SET status = 'failed', error_message = ?1 WHERE id = ?2",
+        error_message,
+        task_id
+    )
+    .execute(&state.db_pool)
+    .await
+    .map_err(|e| ApiError::Database(e.to_string()))?;
     
-    let response = TaskResponse {
+    // Keep the request span so delivery logs carry the request id
+    tokio::spawn(webhooks::deliver_task_result(state.clone(), task_id).in_current_span());
+    Ok(())
+}
+
+// Response for a task just created by this request, the only time its
+// webhook secret is returned
+fn creation_response(task: Task, status: TaskStatus) -> TaskResponse {
+    let webhook_secret = task.webhook_secret.clone();
+    TaskResponse { status, ..task_response(task, webhook_secret) }
+}
+
+pub(crate) fn task_response(task: Task, webhook_secret: Option<String>) -> TaskResponse {
+    TaskResponse {
         id: task.id,
         task_type: task.task_type,
         model_name: task.model_name,
-        status: TaskStatus::Submitted,
+        status: task.status,
         priority: task.priority,
         estimated_cost: task.estimated_cost,
-        actual_cost: None,
-        assigned_node_id: None,
+        actual_cost: task.actual_cost,
+        assigned_node_id: task.assigned_node_id,
         created_at: task.created_at,
-        started_at: None,
-        completed_at: None,
+        started_at: task.started_at,
+        completed_at: task.completed_at,
         expires_at: task.expires_at,
-    };
-    
-    Ok(Json(response))
+        webhook_secret,
+        retried_from: task.retried_from,
+    }
 }
 
 pub async fn get_task(
@@ -137,24 +362,92 @@ pub async fn get_task(
     .map_err(|e| ApiError::Database(e.to_string()))?
     .ok_or_else(|| ApiError::NotFound("Task not found".to_string()))?;
     
-    let response = TaskResponse {
-        id: task.id,
-        task_type: task.task_type,
-        model_name: task.model_name,
-        status: task.status,
-        priority: task.priority,
-        estimated_cost: task.estimated_cost,
-        actual_cost: task.actual_cost,
-        assigned_node_id: task.assigned_node_id,
-        created_at: task.created_at,
-        started_at: task.started_at,
-        completed_at: task.completed_at,
-        expires_at: task.expires_at,
-    };
+    let response = task_response(task, None);
     
     Ok(Json(response))
 }
 
+// How often live status streams (WebSocket and SSE) re-read the task
+const TASK_STATUS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+pub async fn task_status_ws(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(task_id): Path<Uuid>,
+    ws: WebSocketUpgrade,
+) -> ApiResult<Response> {
+    // Reject the upgrade unless the task belongs to the caller
+    sqlx::query_scalar!(
+        "SELECT id FROM tasks WHERE id = ?1 AND user_id = ?2",
+        task_id,
+        claims.user_id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| ApiError::Database(e.to_string()))?
+    .ok_or_else(|| ApiError::NotFound("Task not found".to_string()))?;
+    
+    Ok(ws.on_upgrade(move |socket| stream_task_status(socket, state, task_id)))
+}
+
+async fn stream_task_status(mut socket: WebSocket, state: AppState, task_id: Uuid) {
+    let mut last_status: Option<String> = None;
+    let mut interval = tokio::time::interval(TASK_STATUS_POLL_INTERVAL);
+    
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | None | Some(Err(_)) => return,
+                _ => continue,
+            },
+        }
+        
+        let task = match sqlx::query_as!(Task, "SELECT * FROM tasks WHERE id = ?1", task_id)
+            .fetch_optional(&state.db_pool)
+            .await
+        {
+            Ok(Some(task)) => task,
+            Ok(None) => break,
+            Err(e) => {
+                warn!("Failed to poll task {} for websocket: {}", task_id, e);
+                continue;
+            }
+        };
+        
+        let status = format!("{:?}", task.status);
+        if last_status.as_deref() == Some(status.as_str()) {
+            continue;
+        }
+        last_status = Some(status);
+        
+        let is_terminal = matches!(
+            task.status,
+            TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled | TaskStatus::Expired
+        );
+        
+        let response = task_response(task, None);
+        
+        let frame = match serde_json::to_string(&response) {
+            Ok(frame) => frame,
+            Err(e) => {
+                error!("Failed to serialize task {} update: {}", task_id, e);
+                break;
+            }
+        };
+        
+        if socket.send(Message::Text(frame)).await.is_err() {
+            return;
+        }
+        
+        if is_terminal {
+            break;
+        }
+    }
+    
+    let _ = socket.send(Message::Close(None)).await;
+}
+
 pub async fn get_task_result(
     State(state): State<AppState>,
     claims: Claims,
@@ -196,49 +489,219 @@ pub async fn get_task_result(
     Ok(Json(response))
 }
 
+/// Input of a task submitted with `off_chain_input`, for the node it was
+/// assigned to. Nodes sign the request with their NEAR key instead of
+/// sending a user token.
+pub async fn get_task_input(
+    State(state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> ApiResult<Json<TaskInputResponse>> {
+    let node_id = node_auth::verify_node_request(&state, &headers, |timestamp| {
+        node_auth::task_input_message(task_id, timestamp)
+    })
+    .await?;
+
+    let task = sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE id = ?1")
+        .bind(task_id)
+        .fetch_optional(&state.db_pool)
+        .await
+        .map_err(|e| ApiError::Database(e.to_string()))?
+        .ok_or_else(|| ApiError::NotFound("Task not found".to_string()))?;
+
+    let (Some(input_hash), Some(contract_task_id)) = (task.input_hash, task.contract_task_id) else {
+        return Err(ApiError::NotFound("Task has no off-chain input".to_string()));
+    };
+
+    // The assignment is read from the contract, which the database may lag
+    let chain_task = state.near_client.get_task(contract_task_id as u64).await
+        .map_err(|e| circuit_breaker::api_error(e, |e| ApiError::Internal(format!("Failed to fetch task from contract: {}", e))))?
+        .ok_or_else(|| ApiError::NotFound("Task not found on-chain".to_string()))?;
+    if !chain_task.is_assigned_to(node_id.as_str()) {
+        return Err(ApiError::Forbidden("Task is not assigned to this node".to_string()));
+    }
+
+    info!("Node {} fetched input of task {}", node_id, task_id);
+
+    Ok(Json(TaskInputResponse {
+        task_id,
+        input: task.input_data,
+        sha256: input_hash,
+    }))
+}
+
+// Progress of one SSE subscriber
+struct TaskEventStream {
+    state: AppState,
+    task_id: Uuid,
+    last_status: Option<String>,
+    pending: VecDeque<Event>,
+    polled: bool,
+    finished: bool,
+}
+
+pub async fn task_events(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(task_id): Path<Uuid>,
+) -> ApiResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    // Same ownership check as get_task
+    sqlx::query_scalar!(
+        "SELECT id FROM tasks WHERE id = ?1 AND user_id = ?2",
+        task_id,
+        claims.user_id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| ApiError::Database(e.to_string()))?
+    .ok_or_else(|| ApiError::NotFound("Task not found".to_string()))?;
+    
+    let initial = TaskEventStream {
+        state,
+        task_id,
+        last_status: None,
+        pending: VecDeque::new(),
+        polled: false,
+        finished: false,
+    };
+    
+    let events = stream::unfold(initial, |mut stream| async move {
+        loop {
+            if let Some(event) = stream.pending.pop_front() {
+                return Some((Ok(event), stream));
+            }
+            if stream.finished {
+                return None;
+            }
+            
+            if stream.polled {
+                tokio::time::sleep(TASK_STATUS_POLL_INTERVAL).await;
+            }
+            stream.polled = true;
+            
+            let task = match sqlx::query_as!(Task, "SELECT * FROM tasks WHERE id = ?1", stream.task_id)
+                .fetch_optional(&stream.state.db_pool)
+                .await
+            {
+                Ok(Some(task)) => task,
+                Ok(None) => return None,
+                Err(e) => {
+                    warn!("Failed to poll task {} for events: {}", stream.task_id, e);
+                    continue;
+                }
+            };
+            
+            let status = format!("{:?}", task.status);
+            if stream.last_status.as_deref() == Some(status.as_str()) {
+                continue;
+            }
+            stream.last_status = Some(status);
+            
+            let status_frame = task_response(task.clone(), None);
+            if let Ok(event) = Event::default().event("status").json_data(&status_frame) {
+                stream.pending.push_back(event);
+            }
+            
+            if matches!(
+                task.status,
+                TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled | TaskStatus::Expired
+            ) {
+                let execution_time_ms = match (task.started_at, task.completed_at) {
+                    (Some(started), Some(completed)) => Some((completed - started).num_milliseconds()),
+                    _ => None,
+                };
+                
+                let result_frame = TaskResultResponse {
+                    task_id: task.id,
+                    status: task.status,
+                    result_data: task.result_data.as_deref().and_then(|data| serde_json::from_str(data).ok()),
+                    proof_hash: task.proof_hash,
+                    error_message: task.error_message,
+                    execution_time_ms,
+                    completed_at: task.completed_at,
+                };
+                if let Ok(event) = Event::default().event("result").json_data(&result_frame) {
+                    stream.pending.push_back(event);
+                }
+                stream.finished = true;
+            }
+        }
+    });
+    
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+// Accepted `sort_by` values for task listings. Costs are stored as yoctoNEAR
+// strings, so they are compared numerically rather than lexically.
+const TASK_SORT_COLUMNS: &[(&str, &str)] = &[
+    ("created_at", "created_at"),
+    ("status", "status"),
+    ("priority", "priority"),
+    ("estimated_cost", "CAST(estimated_cost AS REAL)"),
+];
+
+// Push the user and optional status/type conditions, all as bound parameters
+fn push_task_filters(builder: &mut QueryBuilder<'_, Sqlite>, user_id: Uuid, filters: &TaskFilterQuery) {
+    builder.push(" WHERE user_id = ").push_bind(user_id);
+    
+    if let Some(status) = &filters.status {
+        builder.push(" AND status = ").push_bind(status.to_lowercase());
+    }
+    if let Some(task_type) = &filters.task_type {
+        builder.push(" AND task_type = ").push_bind(task_type.clone());
+    }
+}
+
+/// One page of a user's tasks plus the total matching the same filters.
+/// `order_by` must come from `PaginationQuery::order_by`.
+async fn fetch_user_tasks(
+    pool: &SqlitePool,
+    user_id: Uuid,
+    filters: &TaskFilterQuery,
+    order_by: &str,
+    limit: u32,
+    offset: u32,
+) -> Result<(Vec<Task>, u64), sqlx::Error> {
+    let mut count_query = QueryBuilder::new("SELECT COUNT(*) FROM tasks");
+    push_task_filters(&mut count_query, user_id, filters);
+    let total: i64 = count_query.build_query_scalar().fetch_one(pool).await?;
+    
+    // id breaks ties so pages stay stable
+    let mut page_query = QueryBuilder::new("SELECT * FROM tasks");
+    push_task_filters(&mut page_query, user_id, filters);
+    page_query
+        .push(format!(" ORDER BY {}, id LIMIT ", order_by))
+        .push_bind(limit)
+        .push(" OFFSET ")
+        .push_bind(offset);
+    let tasks = page_query.build_query_as::<Task>().fetch_all(pool).await?;
+    
+    Ok((tasks, total as u64))
+}
+
 pub async fn list_user_tasks(
     State(state): State<AppState>,
     claims: Claims,
     Query(pagination): Query<PaginationQuery>,
+    Query(filters): Query<TaskFilterQuery>,
 ) -> ApiResult<Json<PaginatedResponse<TaskResponse>>> {
     let (page, limit) = pagination.normalize();
     let offset = pagination.offset();
+    let order_by = pagination.order_by(TASK_SORT_COLUMNS)
+        .map_err(ApiError::BadRequest)?;
     
-    // Get total count
-    let total = sqlx::query_scalar!(
-        "SELECT COUNT(*) FROM tasks WHERE user_id = ?1",
-        claims.user_id
-    )
-    .fetch_one(&state.db_pool)
-    .await
-    .map_err(|e| ApiError::Database(e.to_string()))? as u64;
-    
-    // Get tasks
-    let tasks = sqlx::query_as!(
-        Task,
-        "SELECT * FROM tasks WHERE user_id = ?1 ORDER BY created_at DESC LIMIT ?2 OFFSET ?3",
+    let (tasks, total) = fetch_user_tasks(
+        &state.db_pool,
         claims.user_id,
+        &filters,
+        &order_by,
         limit,
-        offset
+        offset,
     )
-    .fetch_all(&state.db_pool)
     .await
     .map_err(|e| ApiError::Database(e.to_string()))?;
     
-    let task_responses: Vec<TaskResponse> = tasks.into_iter().map(|task| TaskResponse {
-        id: task.id,
-        task_type: task.task_type,
-        model_name: task.model_name,
-        status: task.status,
-        priority: task.priority,
-        estimated_cost: task.estimated_cost,
-        actual_cost: task.actual_cost,
-        assigned_node_id: task.assigned_node_id,
-        created_at: task.created_at,
-        started_at: task.started_at,
-        completed_at: task.completed_at,
-        expires_at: task.expires_at,
-    }).collect();
+    let task_responses: Vec<TaskResponse> = tasks.into_iter().map(|task| task_response(task, None)).collect();
     
     let response = PaginatedResponse::new(task_responses, page, limit, total);
     Ok(Json(response))
@@ -280,27 +743,86 @@ pub async fn cancel_task(
     
     info!("Task {} cancelled by user {}", task_id, claims.user_id);
     
-    let response = TaskResponse {
-        id: updated_task.id,
-        task_type: updated_task.task_type,
-        model_name: updated_task.model_name,
-        status: updated_task.status,
-        priority: updated_task.priority,
-        estimated_cost: updated_task.estimated_cost,
-        actual_cost: updated_task.actual_cost,
-        assigned_node_id: updated_task.assigned_node_id,
-        created_at: updated_task.created_at,
-        started_at: updated_task.started_at,
-        completed_at: updated_task.completed_at,
-        expires_at: updated_task.expires_at,
+    let response = task_response(updated_task, None);
+    
+    Ok(Json(response))
+}
+
+/// Resubmit a failed or expired task as a new task with the same inputs.
+pub async fn retry_task(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(task_id): Path<Uuid>,
+) -> ApiResult<Json<TaskResponse>> {
+    let task = sqlx::query_as!(
+        Task,
+        "SELECT * FROM tasks WHERE id = ?1 AND user_id = ?2",
+        task_id,
+        claims.user_id
+    )
+    .fetch_optional(&state.db_pool)
+    .await
+    .map_err(|e| ApiError::Database(e.to_string()))?
+    .ok_or_else(|| ApiError::NotFound("Task not found".to_string()))?;
+    
+    if !matches!(task.status, TaskStatus::Failed | TaskStatus::Expired) {
+        return Err(ApiError::BadRequest("Only failed or expired tasks can be retried".to_string()));
+    }
+    
+    ensure_email_verified(&state, claims.user_id).await?;
+    
+    let active_tasks = count_active_tasks(&state, claims.user_id).await?;
+    
+    if active_tasks >= state.config.rate_limits.max_concurrent_tasks as i64 {
+        return Err(ApiError::TooManyRequests(
+            "Maximum concurrent tasks exceeded".to_string()
+        ));
+    }
+    
+    // The retry keeps the original callback and its secret, so receivers
+    // verifying signatures don't need to learn a new one
+    let request = SubmitTaskRequest {
+        task_type: task.task_type,
+        model_name: task.model_name,
+        input_data: task.input_data,
+        parameters: task.parameters.as_deref().and_then(|p| serde_json::from_str(p).ok()),
+        priority: Some(task.priority),
+        max_cost: None,
+        callback_url: task.webhook_url,
+        off_chain_input: Some(task.input_hash.is_some()),
     };
     
+    let surge_multiplier = pricing::current_surge_multiplier(&state).await;
+    let retry = insert_task(&state.db_pool, claims.user_id, request, surge_multiplier, task.webhook_secret, Some(task_id))
+        .await
+        .map_err(|e| ApiError::Database(e.to_string()))?;
+    
+    match submit_task_to_near(&state, &retry).await {
+        Ok(contract_task_id) => {
+            mark_task_submitted(&state, retry.id, contract_task_id).await?;
+            info!("Task {} retried as {} with contract ID {}", task_id, retry.id, contract_task_id);
+        }
+        Err(e) => {
+            mark_task_failed(&state, retry.id, &e.to_string()).await?;
+            error!("Failed to submit retry {} of task {} to Near: {}", retry.id, task_id, e);
+            return Err(circuit_breaker::api_error(e, |_| {
+                ApiError::Internal("Failed to submit task to blockchain".to_string())
+            }));
+        }
+    }
+    
+    let mut response = creation_response(retry, TaskStatus::Submitted);
+    // The secret was handed out with the original task
+    response.webhook_secret = None;
+    
     Ok(Json(response))
 }
 
 // Helper functions
 
-fn estimate_task_cost(request: &SubmitTaskRequest) -> String {
+/// The cost `submit_task` charges for a request, with how it was derived.
+/// Shared with the estimate endpoint so the preview matches the charge.
+pub fn estimate_task_cost(request: &SubmitTaskRequest, surge_multiplier: f64) -> CostEstimate {
     // Simple cost estimation based on task type and model
     // In production, this would be more sophisticated
     let base_cost = match request.task_type.as_str() {
@@ -313,26 +835,228 @@ fn estimate_task_cost(request: &SubmitTaskRequest) -> String {
     
     // Adjust based on input size
     let input_multiplier = (request.input_data.len() as f64 / 1000.0).max(1.0);
-    let final_cost = (base_cost as f64 * input_multiplier) as u128;
+    let final_cost = (base_cost as f64 * input_multiplier * surge_multiplier) as u128;
     
-    final_cost.to_string()
+    CostEstimate {
+        estimated_cost: final_cost.to_string(),
+        base_cost: base_cost.to_string(),
+        input_multiplier,
+        surge_multiplier,
+    }
 }
 
 async fn submit_task_to_near(state: &AppState, task: &Task) -> anyhow::Result<i64> {
-    // Convert task to Near contract format
-    let task_description = serde_json::json!({
+    let nodes = active_nodes_for_routing(state).await;
+    let preferred_node = state.model_router.recommend_node(&task.model_name, &nodes);
+    
+    // Submit to Near contract and use the task ID the contract assigned
+    let near_task_id = state.near_client
+        .submit_task(task_description(task, preferred_node, &state.config.public_url), task.estimated_cost.parse()?)
+        .await?;
+    
+    Ok(near_task_id as i64)
+}
+
+// Candidates for steering bursts of tasks for the same model to one node so
+// it warms its cache first. Routing is best effort, so failures yield none.
+async fn active_nodes_for_routing(state: &AppState) -> Vec<NodeInfo> {
+    match state.near_client.get_active_nodes().await {
+        Ok(nodes) => {
+            state.model_router.prune_expired();
+            nodes
+        }
+        Err(e) => {
+            warn!("Failed to fetch active nodes for routing: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+// Convert task to Near contract format. Off-chain input is replaced by a
+// reference the assigned node resolves through `get_task_input`.
+fn task_description(task: &Task, preferred_node: Option<String>, public_url: &str) -> String {
+    let mut description = serde_json::json!({
         "model": task.model_name,
-        "input": task.input_data,
         "task_type": task.task_type,
-        "parameters": task.parameters.as_ref().and_then(|p| serde_json::from_str::<serde_json::Value>(p).ok())
+        "parameters": task.parameters.as_ref().and_then(|p| serde_json::from_str::<serde_json::Value>(p).ok()),
+        "preferred_node": preferred_node
     });
     
-    // Submit to Near contract
-    let result = state.near_client
-        .submit_task(task_description.to_string(), task.estimated_cost.parse()?)
-        .await?;
+    match &task.input_hash {
+        Some(input_hash) => {
+            description["input_ref"] = serde_json::json!({
+                "task_id": task.id,
+                "url": format!("{}/api/v1/tasks/{}/input", public_url.trim_end_matches('/'), task.id),
+                "sha256": input_hash,
+            });
+        }
+        None => description["input"] = task.input_data.clone().into(),
+    }
     
-    // Extract task ID from transaction result
-    // This would need to parse the actual transaction result
-    Ok(task.id.as_u128() as i64)
-}
\ No newline at end of file
+    description.to_string()
+}
+
+fn input_digest(input: &str) -> String {
+    hex::encode(Sha256::digest(input.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sort_query(sort_by: Option<&str>, sort_order: Option<&str>) -> PaginationQuery {
+        PaginationQuery {
+            page: None,
+            limit: None,
+            sort_by: sort_by.map(String::from),
+            sort_order: sort_order.map(String::from),
+        }
+    }
+
+    fn estimate_request(task_type: &str, input_len: usize) -> SubmitTaskRequest {
+        SubmitTaskRequest {
+            task_type: task_type.to_string(),
+            model_name: "gpt2".to_string(),
+            input_data: "x".repeat(input_len),
+            parameters: None,
+            priority: None,
+            max_cost: None,
+            callback_url: None,
+            off_chain_input: None,
+        }
+    }
+
+    #[test]
+    fn test_estimate_task_cost_breakdown() {
+        let estimate = estimate_task_cost(&estimate_request("inference", 10), 1.0);
+        assert_eq!(estimate.base_cost, "10000000000000000000000");
+        assert_eq!(estimate.input_multiplier, 1.0);
+        assert_eq!(estimate.estimated_cost, estimate.base_cost);
+
+        let estimate = estimate_task_cost(&estimate_request("text_generation", 3000), 1.0);
+        assert_eq!(estimate.base_cost, "50000000000000000000000");
+        assert_eq!(estimate.input_multiplier, 3.0);
+        assert_eq!(estimate.estimated_cost, "150000000000000000000000");
+    }
+
+    #[test]
+    fn test_estimate_task_cost_applies_surge() {
+        let estimate = estimate_task_cost(&estimate_request("inference", 10), 2.0);
+
+        assert_eq!(estimate.surge_multiplier, 2.0);
+        assert_eq!(estimate.estimated_cost, "20000000000000000000000");
+    }
+
+    #[test]
+    fn test_task_order_by_defaults_to_newest_first() {
+        assert_eq!(sort_query(None, None).order_by(TASK_SORT_COLUMNS).unwrap(), "created_at DESC");
+    }
+
+    #[test]
+    fn test_task_order_by_priority_ascending() {
+        let order_by = sort_query(Some("priority"), Some("asc")).order_by(TASK_SORT_COLUMNS);
+
+        assert_eq!(order_by.unwrap(), "priority ASC");
+    }
+
+    #[test]
+    fn test_task_order_by_rejects_unknown_input() {
+        let injected = sort_query(Some("created_at; DROP TABLE tasks"), None);
+        assert!(injected.order_by(TASK_SORT_COLUMNS).is_err());
+        assert!(sort_query(Some("input_data"), None).order_by(TASK_SORT_COLUMNS).is_err());
+        assert!(sort_query(Some("priority"), Some("sideways")).order_by(TASK_SORT_COLUMNS).is_err());
+    }
+
+    async fn create_test_pool() -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::query(
+            r#"
+            CREATE TABLE tasks (
+                id BLOB PRIMARY KEY, user_id BLOB NOT NULL, contract_task_id INTEGER,
+                task_type TEXT NOT NULL, model_name TEXT NOT NULL, input_data TEXT NOT NULL,
+                parameters TEXT, status TEXT NOT NULL, priority INTEGER NOT NULL,
+                estimated_cost TEXT NOT NULL, actual_cost TEXT, assigned_node_id TEXT,
+                result_data TEXT, proof_hash TEXT, error_message TEXT,
+                created_at TEXT NOT NULL, started_at TEXT, completed_at TEXT, expires_at TEXT NOT NULL,
+                webhook_url TEXT, webhook_secret TEXT, webhook_status TEXT, retried_from BLOB,
+                input_hash TEXT
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool
+    }
+
+    async fn insert_task(pool: &SqlitePool, user_id: Uuid, task_type: &str, status: &str, priority: i32) -> Uuid {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO tasks (id, user_id, task_type, model_name, input_data, status, priority, estimated_cost, created_at, expires_at)
+             VALUES (?1, ?2, ?3, 'llama-7b', 'hello', ?4, ?5, '1000', ?6, ?6)",
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(task_type)
+        .bind(status)
+        .bind(priority)
+        .bind(Utc::now())
+        .execute(pool)
+        .await
+        .unwrap();
+        id
+    }
+
+    #[tokio::test]
+    async fn test_fetch_user_tasks_filters_by_status_and_type() {
+        let pool = create_test_pool().await;
+        let user_id = Uuid::new_v4();
+
+        let first = insert_task(&pool, user_id, "inference", "completed", 1).await;
+        let second = insert_task(&pool, user_id, "inference", "completed", 2).await;
+        let third = insert_task(&pool, user_id, "inference", "completed", 3).await;
+        insert_task(&pool, user_id, "inference", "failed", 4).await;
+        insert_task(&pool, user_id, "training", "completed", 5).await;
+        insert_task(&pool, Uuid::new_v4(), "inference", "completed", 6).await;
+
+        let filters = TaskFilterQuery {
+            status: Some("completed".to_string()),
+            task_type: Some("inference".to_string()),
+        };
+
+        let (tasks, total) = fetch_user_tasks(&pool, user_id, &filters, "priority ASC", 2, 0).await.unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(tasks.iter().map(|t| t.id).collect::<Vec<_>>(), vec![first, second]);
+
+        let (tasks, total) = fetch_user_tasks(&pool, user_id, &filters, "priority ASC", 2, 2).await.unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(tasks.iter().map(|t| t.id).collect::<Vec<_>>(), vec![third]);
+
+        let (_, total) = fetch_user_tasks(&pool, user_id, &TaskFilterQuery::default(), "created_at DESC", 20, 0).await.unwrap();
+        assert_eq!(total, 5);
+    }
+
+    #[tokio::test]
+    async fn test_off_chain_input_is_replaced_by_reference() {
+        let pool = create_test_pool().await;
+        let id = insert_task(&pool, Uuid::new_v4(), "inference", "pending", 1).await;
+        let mut task = sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE id = ?1")
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        let on_chain: serde_json::Value = serde_json::from_str(&task_description(&task, None, "https://api.example.com")).unwrap();
+        assert_eq!(on_chain["input"], "hello");
+        assert!(on_chain.get("input_ref").is_none());
+
+        task.input_hash = Some(input_digest(&task.input_data));
+        let off_chain: serde_json::Value = serde_json::from_str(&task_description(&task, None, "https://api.example.com/")).unwrap();
+        assert!(off_chain.get("input").is_none());
+        assert_eq!(off_chain["input_ref"]["url"], format!("https://api.example.com/api/v1/tasks/{}/input", id));
+        assert_eq!(
+            off_chain["input_ref"]["sha256"],
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+    }
+}
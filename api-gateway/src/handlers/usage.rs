@@ -0,0 +1,115 @@
+use axum::{extract::State, response::Json};
+use chrono::{DateTime, Datelike, Utc};
+use sqlx::SqlitePool;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    models::*,
+    handlers::AppState,
+    auth::Claims,
+    errors::{ApiError, ApiResult},
+};
+
+pub async fn get_usage_stats(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> ApiResult<Json<UserUsageStats>> {
+    let stats = fetch_usage_stats(&state.db_pool, claims.user_id, start_of_month(Utc::now()))
+        .await
+        .map_err(|e| ApiError::Database(e.to_string()))?;
+
+    Ok(Json(stats))
+}
+
+async fn fetch_usage_stats(
+    pool: &SqlitePool,
+    user_id: Uuid,
+    month_start: DateTime<Utc>,
+) -> Result<UserUsageStats, sqlx::Error> {
+    // SUM over no rows is NULL, hence the COALESCEs for users without tasks
+    let (total_tasks, completed_tasks, failed_tasks, current_month_tasks): (i64, i64, i64, i64) =
+        sqlx::query_as(
+            r#"
+            SELECT
+                COUNT(*),
+                COALESCE(SUM(CASE WHEN status = 'completed' THEN 1 ELSE 0 END), 0),
+                COALESCE(SUM(CASE WHEN status = 'failed' THEN 1 ELSE 0 END), 0),
+                COALESCE(SUM(CASE WHEN created_at >= ?2 THEN 1 ELSE 0 END), 0)
+            FROM tasks WHERE user_id = ?1
+            "#,
+        )
+        .bind(user_id)
+        .bind(month_start)
+        .fetch_one(pool)
+        .await?;
+
+    // Costs are yoctoNEAR strings that overflow SQLite integers and lose
+    // precision as REAL, so they are summed here as u128
+    let costs: Vec<(String, DateTime<Utc>)> = sqlx::query_as(
+        "SELECT actual_cost, created_at FROM tasks WHERE user_id = ?1 AND actual_cost IS NOT NULL",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let total_cost = sum_costs(costs.iter().map(|(cost, _)| cost.as_str()));
+    let current_month_cost = sum_costs(
+        costs.iter()
+            .filter(|(_, created_at)| *created_at >= month_start)
+            .map(|(cost, _)| cost.as_str()),
+    );
+
+    Ok(UserUsageStats {
+        user_id,
+        total_tasks,
+        completed_tasks,
+        failed_tasks,
+        total_cost: total_cost.to_string(),
+        current_month_tasks,
+        current_month_cost: current_month_cost.to_string(),
+    })
+}
+
+fn sum_costs<'a>(costs: impl Iterator<Item = &'a str>) -> u128 {
+    costs.fold(0u128, |total, cost| match cost.parse::<u128>() {
+        Ok(cost) => total.saturating_add(cost),
+        Err(_) => {
+            warn!("Skipping unparseable task cost in usage stats: {}", cost);
+            total
+        }
+    })
+}
+
+fn start_of_month(now: DateTime<Utc>) -> DateTime<Utc> {
+    now.date_naive()
+        .with_day(1)
+        .and_then(|day| day.and_hms_opt(0, 0, 0))
+        .map(|start| start.and_utc())
+        .unwrap_or(now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_sum_costs_keeps_yocto_precision() {
+        // 2^64 + 1 does not survive a round trip through f64
+        let costs = ["18446744073709551617", "1000000000000000000000000", "not-a-number"];
+
+        assert_eq!(
+            sum_costs(costs.into_iter()),
+            18446744073709551617 + 1_000_000_000_000_000_000_000_000
+        );
+        assert_eq!(sum_costs(std::iter::empty()), 0);
+    }
+
+    #[test]
+    fn test_start_of_month() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 17, 15, 42, 7).unwrap();
+
+        assert_eq!(start_of_month(now), Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap());
+    }
+}
@@ -0,0 +1,172 @@
+use anyhow::{Context, Result};
+use jsonwebtoken::{
+    decode, decode_header, encode,
+    errors::{Error, ErrorKind},
+    Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha256};
+use crate::config::AppConfig;
+
+/// Keys for session tokens. New tokens are signed with the current key and
+/// name it in their `kid` header; verification also accepts keys retired by
+/// a rotation until they are dropped from the config.
+pub struct JwtKeys {
+    algorithm: Algorithm,
+    kid: String,
+    encoding_key: EncodingKey,
+    // Current key first
+    decoding_keys: Vec<(String, DecodingKey)>,
+}
+
+impl JwtKeys {
+    pub fn from_config(config: &AppConfig) -> Result<Self> {
+        if config.jwt_algorithm != "RS256" {
+            return Ok(Self::hmac(&config.jwt_verification_secrets()));
+        }
+
+        let private_key_path = config.jwt_rsa_private_key_path.as_deref()
+            .context("JWT_RSA_PRIVATE_KEY_PATH is not set")?;
+        let private_key = std::fs::read(private_key_path)
+            .with_context(|| format!("Failed to read JWT private key {}", private_key_path))?;
+
+        let public_keys = config.jwt_rsa_public_key_paths.iter()
+            .map(|path| std::fs::read(path).with_context(|| format!("Failed to read JWT public key {}", path)))
+            .collect::<Result<Vec<_>>>()?;
+
+        Self::rsa(&private_key, &public_keys)
+    }
+
+    /// HS256 keys from shared secrets, the current one first.
+    pub fn hmac(secrets: &[&str]) -> Self {
+        Self {
+            algorithm: Algorithm::HS256,
+            kid: key_id(secrets[0].as_bytes()),
+            encoding_key: EncodingKey::from_secret(secrets[0].as_bytes()),
+            decoding_keys: secrets.iter()
+                .map(|secret| (key_id(secret.as_bytes()), DecodingKey::from_secret(secret.as_bytes())))
+                .collect(),
+        }
+    }
+
+    /// RS256 keys from PEM files. The first public key must match the
+    /// private key; the others verify tokens signed before a rotation.
+    pub fn rsa(private_key_pem: &[u8], public_key_pems: &[Vec<u8>]) -> Result<Self> {
+        let current_public_key = public_key_pems.first()
+            .context("At least one JWT public key is required")?;
+
+        let decoding_keys = public_key_pems.iter()
+            .map(|pem| {
+                let key = DecodingKey::from_rsa_pem(pem).context("Invalid JWT public key")?;
+                Ok((key_id(pem), key))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            algorithm: Algorithm::RS256,
+            kid: key_id(current_public_key),
+            encoding_key: EncodingKey::from_rsa_pem(private_key_pem).context("Invalid JWT private key")?,
+            decoding_keys,
+        })
+    }
+
+    pub fn sign<T: Serialize>(&self, claims: &T) -> Result<String, Error> {
+        let mut header = Header::new(self.algorithm);
+        header.kid = Some(self.kid.clone());
+
+        encode(&header, claims, &self.encoding_key)
+    }
+
+    /// Decode and check a token. Tokens naming a key are only checked against
+    /// that key; tokens issued before keys had ids are tried against each.
+    pub fn verify<T: DeserializeOwned>(&self, token: &str, validation: &Validation) -> Result<T, Error> {
+        let mut validation = validation.clone();
+        validation.algorithms = vec![self.algorithm];
+
+        let kid = decode_header(token)?.kid;
+        let candidates = self.decoding_keys.iter()
+            .filter(|(key_kid, _)| kid.as_ref().map_or(true, |kid| kid == key_kid));
+
+        let mut last_error = Error::from(ErrorKind::InvalidSignature);
+        for (_, key) in candidates {
+            match decode::<T>(token, key, &validation) {
+                Ok(data) => return Ok(data.claims),
+                Err(e) if matches!(e.kind(), ErrorKind::InvalidSignature) => last_error = e,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(last_error)
+    }
+}
+
+// Derived from the key itself, so every replica agrees without extra config
+fn key_id(key_material: &[u8]) -> String {
+    hex::encode(&Sha256::digest(key_material)[..8])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    const CURRENT_SECRET: &str = "current-secret-at-least-32-characters-long";
+    const RETIRED_SECRET: &str = "retired-secret-at-least-32-characters-long";
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct TestClaims {
+        sub: String,
+        exp: usize,
+    }
+
+    fn claims() -> TestClaims {
+        TestClaims {
+            sub: "alice".to_string(),
+            exp: (chrono::Utc::now().timestamp() + 3600) as usize,
+        }
+    }
+
+    #[test]
+    fn test_tokens_name_the_current_key() {
+        let keys = JwtKeys::hmac(&[CURRENT_SECRET, RETIRED_SECRET]);
+        let token = keys.sign(&claims()).unwrap();
+
+        assert_eq!(decode_header(&token).unwrap().kid, Some(key_id(CURRENT_SECRET.as_bytes())));
+        assert_eq!(keys.verify::<TestClaims>(&token, &Validation::default()).unwrap(), claims());
+    }
+
+    #[test]
+    fn test_rotation_keeps_old_tokens_valid() {
+        let before = JwtKeys::hmac(&[RETIRED_SECRET]);
+        let token = before.sign(&claims()).unwrap();
+
+        let during = JwtKeys::hmac(&[CURRENT_SECRET, RETIRED_SECRET]);
+        assert!(during.verify::<TestClaims>(&token, &Validation::default()).is_ok());
+
+        let after = JwtKeys::hmac(&[CURRENT_SECRET]);
+        assert!(after.verify::<TestClaims>(&token, &Validation::default()).is_err());
+    }
+
+    #[test]
+    fn test_tokens_without_kid_are_tried_against_each_key() {
+        let token = encode(&Header::default(), &claims(), &EncodingKey::from_secret(RETIRED_SECRET.as_bytes())).unwrap();
+
+        let keys = JwtKeys::hmac(&[CURRENT_SECRET, RETIRED_SECRET]);
+        assert!(keys.verify::<TestClaims>(&token, &Validation::default()).is_ok());
+    }
+
+    #[test]
+    fn test_rs256_round_trip() {
+        let private_key = include_bytes!("../testdata/jwt_rs256_private.pem");
+        let public_key = include_bytes!("../testdata/jwt_rs256_public.pem").to_vec();
+        let keys = JwtKeys::rsa(private_key, &[public_key]).unwrap();
+
+        let token = keys.sign(&claims()).unwrap();
+        assert_eq!(decode_header(&token).unwrap().alg, Algorithm::RS256);
+        assert_eq!(keys.verify::<TestClaims>(&token, &Validation::default()).unwrap(), claims());
+
+        // An HS256 token can't pass for RS256 by signing with the public key
+        let hmac = JwtKeys::hmac(&[CURRENT_SECRET]).sign(&claims()).unwrap();
+        assert!(keys.verify::<TestClaims>(&hmac, &Validation::default()).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use redis::{AsyncCommands, RedisResult};
+use tracing::warn;
+use crate::config::RateLimitConfig;
+
+/// Locks an account out of password and wallet login after too many failed
+/// attempts within a window, whichever IP they come from. Counters live in
+/// Redis, with an in-process fallback while Redis is unreachable.
+#[derive(Debug)]
+pub struct LoginThrottle {
+    redis_client: redis::Client,
+    max_failures: u32,
+    window_seconds: u64,
+    // Failure count and the time its window ends, per account
+    memory_store: Mutex<HashMap<String, (u32, u64)>>,
+}
+
+impl LoginThrottle {
+    pub fn new(redis_client: redis::Client, config: &RateLimitConfig) -> Self {
+        Self {
+            redis_client,
+            max_failures: config.login_max_failures,
+            window_seconds: config.login_lockout_seconds,
+            memory_store: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Seconds until `account` may try again, or `None` if it isn't locked out.
+    pub async fn locked_out(&self, account: &str) -> Option<u64> {
+        match self.redis_locked_out(account).await {
+            Ok(retry_after) => retry_after,
+            Err(e) => {
+                warn!("Login throttle falling back to memory: {}", e);
+                self.memory_locked_out(account)
+            }
+        }
+    }
+
+    pub async fn record_failure(&self, account: &str) {
+        if let Err(e) = self.redis_record_failure(account).await {
+            warn!("Login throttle falling back to memory: {}", e);
+            self.memory_record_failure(account);
+        }
+    }
+
+    /// Clear the failures after a successful login.
+    pub async fn reset(&self, account: &str) {
+        self.memory_store.lock().unwrap().remove(account);
+        if let Err(e) = self.redis_reset(account).await {
+            warn!("Failed to reset login throttle for {}: {}", account, e);
+        }
+    }
+
+    async fn redis_locked_out(&self, account: &str) -> RedisResult<Option<u64>> {
+        let mut conn = self.redis_client.get_multiplexed_async_connection().await?;
+        let key = failures_key(account);
+
+        let failures: Option<u32> = conn.get(&key).await?;
+        if failures.unwrap_or(0) < self.max_failures {
+            return Ok(None);
+        }
+
+        let ttl: i64 = conn.ttl(&key).await?;
+        Ok(Some(ttl.max(1) as u64))
+    }
+
+    // The window starts at the first failure and isn't extended by later ones
+    async fn redis_record_failure(&self, account: &str) -> RedisResult<()> {
+        let mut conn = self.redis_client.get_multiplexed_async_connection().await?;
+        let key = failures_key(account);
+
+        let failures: u32 = conn.incr(&key, 1u32).await?;
+        if failures == 1 {
+            let _: () = conn.expire(&key, self.window_seconds as i64).await?;
+        }
+        Ok(())
+    }
+
+    async fn redis_reset(&self, account: &str) -> RedisResult<()> {
+        let mut conn = self.redis_client.get_multiplexed_async_connection().await?;
+        conn.del(failures_key(account)).await
+    }
+
+    fn memory_locked_out(&self, account: &str) -> Option<u64> {
+        let now = now_secs();
+        let store = self.memory_store.lock().unwrap();
+
+        match store.get(account) {
+            Some(&(failures, window_ends)) if failures >= self.max_failures && window_ends > now => {
+                Some(window_ends - now)
+            }
+            _ => None,
+        }
+    }
+
+    fn memory_record_failure(&self, account: &str) {
+        let now = now_secs();
+        let mut store = self.memory_store.lock().unwrap();
+
+        let entry = store.entry(account.to_string()).or_insert((0, now + self.window_seconds));
+        if entry.1 <= now {
+            *entry = (0, now + self.window_seconds);
+        }
+        entry.0 += 1;
+    }
+}
+
+fn failures_key(account: &str) -> String {
+    format!("login_failures:{}", account)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_throttle(max_failures: u32) -> LoginThrottle {
+        let config = RateLimitConfig {
+            requests_per_minute: 60,
+            requests_per_hour: 1000,
+            max_concurrent_tasks: 10,
+            task_submission_cooldown_seconds: 5,
+            login_max_failures: max_failures,
+            login_lockout_seconds: 900,
+        };
+        // Opening a client does not connect, so only the in-memory store is used
+        LoginThrottle::new(redis::Client::open("redis://127.0.0.1:1/").unwrap(), &config)
+    }
+
+    #[tokio::test]
+    async fn test_locked_out_after_too_many_bad_passwords() {
+        let throttle = create_test_throttle(5);
+
+        for _ in 0..5 {
+            assert_eq!(throttle.locked_out("user:alice").await, None);
+            throttle.record_failure("user:alice").await;
+        }
+
+        // The sixth attempt is refused before the password is checked
+        let retry_after = throttle.locked_out("user:alice").await.unwrap();
+        assert!(retry_after > 0 && retry_after <= 900);
+
+        // Other accounts are unaffected
+        assert_eq!(throttle.locked_out("user:bob").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_successful_login_resets_failures() {
+        let throttle = create_test_throttle(2);
+
+        throttle.record_failure("user:alice").await;
+        throttle.reset("user:alice").await;
+        throttle.record_failure("user:alice").await;
+
+        assert_eq!(throttle.locked_out("user:alice").await, None);
+    }
+}
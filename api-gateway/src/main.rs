@@ -6,13 +6,15 @@ use axum::{
 use std::net::SocketAddr;
 use tower::ServiceBuilder;
 use tower_http::{
-    cors::CorsLayer,
     trace::TraceLayer,
     limit::RequestBodyLimitLayer,
 };
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+mod chain_cache;
+mod circuit_breaker;
 mod config;
+mod cors;
 mod handlers;
 mod auth;
 mod models;
@@ -21,10 +23,22 @@ mod near_client;
 mod rate_limit;
 mod middleware;
 mod errors;
+mod routing;
+mod rpc_retry;
+mod webhooks;
+mod email;
+mod event_indexer;
+mod jwt_keys;
+mod login_throttle;
+mod near_challenge;
+mod node_auth;
+mod password_reset;
+mod pricing;
+mod task_sync;
 
 use config::AppConfig;
 use handlers::*;
-use middleware::{auth_middleware, rate_limit_middleware};
+use middleware::{admin_middleware, auth_middleware, rate_limit_middleware, request_id_middleware};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -41,59 +55,106 @@ async fn main() -> Result<()> {
     let config = AppConfig::load()?;
     
     // Initialize database
-    let db_pool = database::init_database(&config.database_url).await?;
+    let db_pool = database::init_database(&config.database_url, &config.database).await?;
     
     // Initialize Redis for rate limiting
     let redis_client = redis::Client::open(config.redis_url.clone())?;
     
+    // Session token keys, read once so a bad key file fails startup
+    let jwt_keys = jwt_keys::JwtKeys::from_config(&config)?;
+    
     // Initialize Near client
     let near_client = near_client::NearClient::new(&config).await?;
     
     // Build application state
     let app_state = handlers::AppState {
         config: config.clone(),
-        db_pool,
-        redis_client,
+        db_pool: db_pool.clone(),
+        redis_client: redis_client.clone(),
         near_client: std::sync::Arc::new(near_client),
+        model_router: std::sync::Arc::new(routing::ModelRouter::new(&config.routing)),
+        email_sender: std::sync::Arc::new(email::LogEmailSender),
+        login_throttle: std::sync::Arc::new(login_throttle::LoginThrottle::new(redis_client, &config.rate_limits)),
+        jwt_keys: std::sync::Arc::new(jwt_keys),
     };
 
+    if config.chain_cache.background_refresh {
+        chain_cache::spawn_background_refresh(app_state.clone());
+    }
+
+    if config.task_sync.enabled {
+        task_sync::spawn_task_sync(app_state.clone());
+    }
+
+    if config.event_indexer.enabled {
+        event_indexer::spawn_event_indexer(app_state.clone());
+    }
+
+    // Admin routes, only reachable by authenticated admins
+    let admin_routes = Router::new()
+        .route("/api/v1/admin/users", get(admin::list_users))
+        .route("/api/v1/admin/users/:user_id/suspend", post(moderation::suspend_user))
+        .route("/api/v1/admin/users/:user_id/reactivate", post(moderation::reactivate_user))
+        .route("/api/v1/admin/tasks", get(admin::list_all_tasks))
+        .route("/api/v1/admin/tasks/:task_id/timeout", post(moderation::timeout_task))
+        .route("/api/v1/admin/nodes", get(admin::list_all_nodes))
+        .route("/api/v1/admin/system/metrics", get(admin::get_system_metrics))
+        .route("/api/v1/admin/contract/params", get(contract_admin::get_contract_params))
+        .route("/api/v1/admin/contract/params", post(contract_admin::update_contract_params))
+        .route("/api/v1/admin/contract/pause", post(contract_admin::pause_contract))
+        .route("/api/v1/admin/contract/unpause", post(contract_admin::unpause_contract))
+        .route_layer(axum::middleware::from_fn(admin_middleware));
+
     // Build our application with routes
     let app = Router::new()
         // Public routes
         .route("/health", get(health_check))
+        .route("/ready", get(health::readiness))
         .route("/api/v1/auth/register", post(auth::register_user))
         .route("/api/v1/auth/login", post(auth::login_user))
+        .route("/api/v1/auth/near-challenge", post(auth::issue_near_challenge))
         .route("/api/v1/auth/near-login", post(auth::near_wallet_login))
+        .route("/api/v1/auth/refresh", post(auth::refresh_token))
+        .route("/api/v1/auth/verify-email", post(auth::verify_email))
+        .route("/api/v1/auth/resend-verification", post(auth::resend_verification))
+        .route("/api/v1/auth/forgot-password", post(auth::forgot_password))
+        .route("/api/v1/auth/reset-password", post(auth::reset_password))
         
         // Protected routes
         .route("/api/v1/tasks", post(tasks::submit_task))
+        .route("/api/v1/tasks/batch", post(tasks::submit_task_batch))
+        .route("/api/v1/tasks/estimate", post(tasks::estimate_task))
         .route("/api/v1/tasks/:task_id", get(tasks::get_task))
         .route("/api/v1/tasks/:task_id/result", get(tasks::get_task_result))
+        .route("/api/v1/tasks/:task_id/input", get(tasks::get_task_input))
         .route("/api/v1/tasks", get(tasks::list_user_tasks))
         .route("/api/v1/tasks/:task_id/cancel", post(tasks::cancel_task))
+        .route("/api/v1/tasks/:task_id/retry", post(tasks::retry_task))
+        .route("/api/v1/tasks/:task_id/ws", get(tasks::task_status_ws))
+        .route("/api/v1/tasks/:task_id/events", get(tasks::task_events))
         
         // Node information
-        .route("/api/v1/nodes", get(nodes::list_active_nodes))
+        .route("/api/v1/nodes", get(directory::list_active_nodes))
         .route("/api/v1/nodes/:node_id", get(nodes::get_node_info))
-        .route("/api/v1/network/stats", get(network::get_network_stats))
+        .route("/api/v1/nodes/:node_id/metrics", get(node_metrics::get_node_metrics))
+        .route("/api/v1/network/stats", get(directory::get_network_stats))
+        .route("/api/v1/activity", get(activity::list_activity))
         
         // User account management
         .route("/api/v1/user/profile", get(users::get_profile))
         .route("/api/v1/user/api-keys", post(users::create_api_key))
         .route("/api/v1/user/api-keys", get(users::list_api_keys))
         .route("/api/v1/user/api-keys/:key_id", post(users::revoke_api_key))
-        .route("/api/v1/user/usage", get(users::get_usage_stats))
+        .route("/api/v1/user/usage", get(usage::get_usage_stats))
         
         // Admin routes
-        .route("/api/v1/admin/users", get(admin::list_users))
-        .route("/api/v1/admin/tasks", get(admin::list_all_tasks))
-        .route("/api/v1/admin/nodes", get(admin::list_all_nodes))
-        .route("/api/v1/admin/system/metrics", get(admin::get_system_metrics))
+        .merge(admin_routes)
         
         .layer(
             ServiceBuilder::new()
+                .layer(axum::middleware::from_fn(request_id_middleware))
                 .layer(TraceLayer::new_for_http())
-                .layer(CorsLayer::permissive())
+                .layer(cors::cors_layer(&config.cors)?)
                 .layer(RequestBodyLimitLayer::new(10 * 1024 * 1024)) // 10MB limit
                 .layer(axum::middleware::from_fn_with_state(
                     app_state.clone(),
@@ -111,11 +172,69 @@ async fn main() -> Result<()> {
     tracing::info!("🚀 DeAI API Gateway listening on {}", addr);
     
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    
+    // Once a shutdown signal arrives, in-flight requests get a bounded time to finish
+    let (shutdown_tx, mut shutdown_rx) = tokio::sync::watch::channel(false);
+    let server = axum::serve(listener, app).with_graceful_shutdown(async move {
+        shutdown_signal().await;
+        tracing::info!("Shutdown signal received, no longer accepting connections");
+        let _ = shutdown_tx.send(true);
+    });
+    let drain_deadline = async move {
+        let _ = shutdown_rx.changed().await;
+        tokio::time::sleep(std::time::Duration::from_secs(config.shutdown_timeout_seconds)).await;
+    };
+    
+    tokio::select! {
+        result = server => {
+            result?;
+            tracing::info!("All in-flight requests completed");
+        }
+        _ = drain_deadline => {
+            tracing::warn!(
+                "In-flight requests still running after {}s, shutting down anyway",
+                config.shutdown_timeout_seconds
+            );
+        }
+    }
+    
+    // Redis connections are opened per request and are dropped with their handlers
+    db_pool.close().await;
+    tracing::info!("Database pool closed, gateway stopped");
 
     Ok(())
 }
 
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+    
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
 async fn health_check() -> &'static str {
     "OK"
 }
\ No newline at end of file
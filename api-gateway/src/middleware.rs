@@ -1,23 +1,42 @@
 use axum::{
+    body::Body,
     extract::{Request, State},
-    http::{HeaderMap, StatusCode},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     middleware::Next,
     response::Response,
 };
 use std::net::IpAddr;
+use tracing::Instrument;
 use crate::{
-    auth::{verify_jwt_token, verify_user_api_key},
-    rate_limit::RateLimiter,
+    auth::{all_scopes, api_key_prefix, required_scope, verify_jwt_token, verify_user_api_key},
+    rate_limit::{RateLimitConfig, RateLimiter},
     errors::{ApiError, ApiResult},
     handlers::AppState,
-    models::User,
+    models::{ApiKey, User},
 };
 
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+// Error bodies are small; anything larger is passed through untouched
+const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+
+/// Correlation id for a request, taken from `X-Request-Id` or generated.
+#[derive(Clone, Debug)]
+pub struct RequestId(pub String);
+
 // Extension types for storing user info in request
 #[derive(Clone, Debug)]
 pub struct AuthenticatedUser {
     pub user: User,
     pub is_api_key: bool,
+    pub api_key: Option<ApiKey>,
+    pub scopes: Vec<String>,
+}
+
+impl AuthenticatedUser {
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|granted| granted == scope)
+    }
 }
 
 pub async fn auth_middleware(
@@ -25,66 +44,148 @@ pub async fn auth_middleware(
     mut request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    // Skip authentication for public routes
+    // Public routes don't require a token, but a caller that sends a valid one
+    // is still identified so handlers can offer extras such as admin cache bypass
     let path = request.uri().path();
     if is_public_route(path) {
+        if let Some(token) = bearer_token(request.headers()) {
+            if let Ok(authenticated_user) = authenticate(&state, &token).await {
+                request.extensions_mut().insert(authenticated_user);
+            }
+        }
         return Ok(next.run(request).await);
     }
 
-    // Extract authorization header
-    let auth_header = request
-        .headers()
+    let token = bearer_token(request.headers()).ok_or(StatusCode::UNAUTHORIZED)?;
+    let authenticated_user = authenticate(&state, &token).await?;
+    ensure_scope(&authenticated_user, request.method(), request.uri().path())?;
+
+    // Store user info in request extensions
+    request.extensions_mut().insert(authenticated_user);
+
+    Ok(next.run(request).await)
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<String> {
+    headers
         .get("authorization")
         .and_then(|h| h.to_str().ok())
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(str::to_string)
+}
 
-    // Parse Bearer token
-    if !auth_header.starts_with("Bearer ") {
-        return Err(StatusCode::UNAUTHORIZED);
-    }
-    
-    let token = &auth_header[7..]; // Remove "Bearer " prefix
-
-    // Try to authenticate with JWT token first, then API key
-    let authenticated_user = match verify_jwt_token(token, &state.config.jwt_secret) {
-        Ok(claims) => {
-            if claims.token_type == "api_key" {
-                // Verify API key in database
-                match verify_user_api_key(token, &state).await {
-                    Ok((user, _api_key)) => AuthenticatedUser {
-                        user,
-                        is_api_key: true,
-                    },
-                    Err(_) => return Err(StatusCode::UNAUTHORIZED),
-                }
-            } else {
-                // Regular JWT access token
-                let user = match crate::database::get_user_by_id(
-                    &state.db_pool,
-                    uuid::Uuid::parse_str(&claims.sub).map_err(|_| StatusCode::UNAUTHORIZED)?,
-                ).await {
-                    Ok(user) => user,
-                    Err(_) => return Err(StatusCode::UNAUTHORIZED),
-                };
-                
-                AuthenticatedUser {
-                    user,
-                    is_api_key: false,
-                }
-            }
+async fn authenticate(state: &AppState, token: &str) -> Result<AuthenticatedUser, StatusCode> {
+    // Opaque API keys are recognised by their prefix; anything else must be a JWT
+    let authenticated_user = if api_key_prefix(token).is_some() {
+        match verify_user_api_key(token, state).await {
+            Ok((user, api_key)) => AuthenticatedUser {
+                user,
+                is_api_key: true,
+                scopes: api_key.scopes.clone(),
+                api_key: Some(api_key),
+            },
+            Err(_) => return Err(StatusCode::UNAUTHORIZED),
+        }
+    } else {
+        let claims = verify_jwt_token(token, &state.jwt_keys)
+            .map_err(|_| StatusCode::UNAUTHORIZED)?;
+        
+        // JWT-style API keys from before keys were hashed are no longer accepted
+        if claims.token_type != "access" {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        
+        let user = match crate::database::get_user_by_id(
+            &state.db_pool,
+            uuid::Uuid::parse_str(&claims.sub).map_err(|_| StatusCode::UNAUTHORIZED)?,
+        ).await {
+            Ok(user) => user,
+            Err(_) => return Err(StatusCode::UNAUTHORIZED),
+        };
+        
+        AuthenticatedUser {
+            user,
+            is_api_key: false,
+            api_key: None,
+            scopes: all_scopes(),
         }
-        Err(_) => return Err(StatusCode::UNAUTHORIZED),
     };
 
-    // Check if user is active
-    if !authenticated_user.user.is_active {
-        return Err(StatusCode::FORBIDDEN);
+    // Suspended accounts are locked out on their next request
+    ensure_active(&authenticated_user.user)?;
+
+    Ok(authenticated_user)
+}
+
+/// Tag the request with a correlation id. Everything logged while handling it,
+/// including NEAR transactions it triggers, runs inside a span carrying the id.
+/// The id is echoed in the `X-Request-Id` response header and JSON error bodies.
+pub async fn request_id_middleware(mut request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    
+    request.extensions_mut().insert(RequestId(request_id.clone()));
+    
+    let span = tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        method = %request.method(),
+        path = %request.uri().path(),
+    );
+    
+    let response = next.run(request).instrument(span).await;
+    let mut response = if response.status().is_client_error() || response.status().is_server_error() {
+        add_request_id_to_error_body(response, &request_id).await
+    } else {
+        response
+    };
+    
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
     }
+    response
+}
 
-    // Store user info in request extensions
-    request.extensions_mut().insert(authenticated_user);
+// Accept client ids that are safe to log and echo back
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 128
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
 
-    Ok(next.run(request).await)
+async fn add_request_id_to_error_body(response: Response, request_id: &str) -> Response {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("application/json"));
+    
+    if !is_json {
+        return response;
+    }
+    
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, MAX_ERROR_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return Response::from_parts(parts, Body::empty()),
+    };
+    
+    let body = match serde_json::from_slice::<serde_json::Value>(&bytes) {
+        Ok(serde_json::Value::Object(mut error)) => {
+            error.insert("request_id".to_string(), request_id.into());
+            let body = serde_json::to_vec(&error).unwrap_or_else(|_| bytes.to_vec());
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Body::from(body)
+        }
+        _ => Body::from(bytes),
+    };
+    
+    Response::from_parts(parts, body)
 }
 
 pub async fn rate_limit_middleware(
@@ -99,10 +200,21 @@ pub async fn rate_limit_middleware(
     
     // Determine rate limit identifier and config
     let (identifier, config) = if let Some(auth_user) = &user_info {
-        // Use user-based rate limiting
-        let tier = &auth_user.user.tier;
-        let config = rate_limiter.get_user_tier_config(tier);
-        (format!("user:{}", auth_user.user.id), config)
+        match &auth_user.api_key {
+            // Each API key gets its own bucket, sized by its override if set
+            Some(api_key) => {
+                let config = match api_key.rate_limit_override {
+                    Some(limit) => RateLimitConfig::from_requests_per_minute(limit.max(0) as u32),
+                    None => rate_limiter.get_user_tier_config(&auth_user.user.tier),
+                };
+                (format!("api_key:{}", api_key.id), config)
+            }
+            // Use user-based rate limiting
+            None => {
+                let config = rate_limiter.get_user_tier_config(&auth_user.user.tier);
+                (format!("user:{}", auth_user.user.id), config)
+            }
+        }
     } else {
         // Use IP-based rate limiting for unauthenticated requests
         let ip = get_client_ip(&request);
@@ -154,16 +266,44 @@ pub async fn admin_middleware(
     Ok(next.run(request).await)
 }
 
+fn ensure_active(user: &User) -> Result<(), StatusCode> {
+    if !user.is_active {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(())
+}
+
+/// Reject requests the caller's API key is not scoped for. Keys also can't
+/// manage keys, or a narrowly scoped key could mint a broader one.
+fn ensure_scope(auth_user: &AuthenticatedUser, method: &axum::http::Method, path: &str) -> Result<(), StatusCode> {
+    if auth_user.is_api_key && path.starts_with("/api/v1/user/api-keys") {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    
+    match required_scope(method, path) {
+        Some(scope) if !auth_user.has_scope(scope) => Err(StatusCode::FORBIDDEN),
+        _ => Ok(()),
+    }
+}
+
 fn is_public_route(path: &str) -> bool {
     matches!(
         path,
         "/health"
+            | "/ready"
             | "/api/v1/auth/register"
             | "/api/v1/auth/login"
+            | "/api/v1/auth/near-challenge"
             | "/api/v1/auth/near-login"
+            | "/api/v1/auth/refresh"
+            | "/api/v1/auth/verify-email"
+            | "/api/v1/auth/forgot-password"
+            | "/api/v1/auth/reset-password"
             | "/api/v1/network/stats"
             | "/api/v1/nodes"
     ) || path.starts_with("/api/v1/nodes/") && !path.contains("/admin/")
+        // Nodes sign these requests themselves, checked by the handler
+        || path.starts_with("/api/v1/tasks/") && path.ends_with("/input")
 }
 
 fn get_client_ip(request: &Request) -> IpAddr {
@@ -213,4 +353,104 @@ pub fn check_user_access(auth_user: &AuthenticatedUser, resource_user_id: uuid::
         return Err(ApiError::Forbidden("Access denied".to_string()));
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware::from_fn, routing::get, Json, Router};
+    use chrono::Utc;
+    use tower::ServiceExt;
+
+    fn create_user(is_active: bool) -> User {
+        User {
+            id: uuid::Uuid::new_v4(),
+            near_account_id: None,
+            email: None,
+            username: "alice".to_string(),
+            password_hash: None,
+            is_active,
+            is_admin: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_login_at: None,
+            email_verified: true,
+        }
+    }
+
+    #[test]
+    fn test_suspended_user_is_forbidden() {
+        assert_eq!(ensure_active(&create_user(false)), Err(StatusCode::FORBIDDEN));
+        assert_eq!(ensure_active(&create_user(true)), Ok(()));
+    }
+
+    fn authenticate_with_scopes(scopes: &[&str]) -> AuthenticatedUser {
+        AuthenticatedUser {
+            user: create_user(true),
+            is_api_key: true,
+            api_key: None,
+            scopes: scopes.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_read_only_key_cannot_submit_tasks() {
+        use axum::http::Method;
+
+        let read_only = authenticate_with_scopes(&["tasks:read"]);
+        assert_eq!(ensure_scope(&read_only, &Method::GET, "/api/v1/tasks"), Ok(()));
+        assert_eq!(ensure_scope(&read_only, &Method::POST, "/api/v1/tasks"), Err(StatusCode::FORBIDDEN));
+
+        let full_access = authenticate_with_scopes(crate::auth::ALL_SCOPES);
+        assert_eq!(ensure_scope(&full_access, &Method::POST, "/api/v1/tasks"), Ok(()));
+        assert_eq!(
+            ensure_scope(&full_access, &Method::POST, "/api/v1/user/api-keys"),
+            Err(StatusCode::FORBIDDEN)
+        );
+    }
+
+    fn create_app() -> Router {
+        Router::new()
+            .route("/ok", get(|| async { "OK" }))
+            .route("/fail", get(|| async {
+                (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": "bad_request", "message": "nope" })))
+            }))
+            .layer(from_fn(request_id_middleware))
+    }
+
+    async fn send(path: &str, request_id: Option<&str>) -> Response {
+        let mut request = Request::builder().uri(path);
+        if let Some(id) = request_id {
+            request = request.header(REQUEST_ID_HEADER, id);
+        }
+        create_app().oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_client_request_id_is_echoed() {
+        let response = send("/ok", Some("client-abc-123")).await;
+
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "client-abc-123");
+    }
+
+    #[tokio::test]
+    async fn test_request_id_is_generated_when_missing_or_invalid() {
+        for request_id in [None, Some("bad id\twith spaces")] {
+            let response = send("/ok", request_id).await;
+            let generated = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+
+            assert!(uuid::Uuid::parse_str(generated).is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_error_body_includes_request_id() {
+        let response = send("/fail", Some("req-42")).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["request_id"], "req-42");
+        assert_eq!(error["message"], "nope");
+    }
+}
@@ -4,6 +4,8 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::circuit_breaker::BreakerState;
+
 // User models
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct User {
@@ -17,6 +19,7 @@ pub struct User {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub last_login_at: Option<DateTime<Utc>>,
+    pub email_verified: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
@@ -36,6 +39,51 @@ pub struct LoginRequest {
     pub password: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefreshTokenRequest {
+    pub access_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyEmailRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ForgotPasswordRequest {
+    pub identifier: String, // username or email
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    #[validate(length(min = 8))]
+    pub new_password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MessageResponse {
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EmailVerificationResponse {
+    pub email_verified: bool,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NearChallengeRequest {
+    pub account_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NearChallengeResponse {
+    pub challenge: String,
+    pub message: String, // Sign exactly this and send it back as `message`
+    pub expires_in: u64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NearWalletLoginRequest {
     pub account_id: String,
@@ -62,6 +110,19 @@ pub struct UserProfile {
     pub created_at: DateTime<Utc>,
 }
 
+impl From<User> for UserProfile {
+    fn from(user: User) -> Self {
+        Self {
+            id: user.id,
+            username: user.username,
+            email: user.email,
+            near_account_id: user.near_account_id,
+            is_admin: user.is_admin,
+            created_at: user.created_at,
+        }
+    }
+}
+
 // API Key models
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct ApiKey {
@@ -72,6 +133,8 @@ pub struct ApiKey {
     pub prefix: String,
     pub is_active: bool,
     pub rate_limit_override: Option<i32>,
+    #[sqlx(json)]
+    pub scopes: Vec<String>, // Stored as a JSON array
     pub created_at: DateTime<Utc>,
     pub last_used_at: Option<DateTime<Utc>>,
     pub expires_at: Option<DateTime<Utc>>,
@@ -83,6 +146,8 @@ pub struct CreateApiKeyRequest {
     pub name: String,
     pub rate_limit_override: Option<i32>,
     pub expires_in_days: Option<i32>,
+    #[serde(default)]
+    pub scopes: Vec<String>, // Empty grants every scope
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -91,6 +156,7 @@ pub struct ApiKeyResponse {
     pub name: String,
     pub prefix: String,
     pub key: Option<String>, // Only returned on creation
+    pub scopes: Vec<String>,
     pub is_active: bool,
     pub created_at: DateTime<Utc>,
     pub last_used_at: Option<DateTime<Utc>>,
@@ -119,9 +185,15 @@ pub struct Task {
     pub started_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
     pub expires_at: DateTime<Utc>,
+    pub webhook_url: Option<String>,
+    #[serde(skip_serializing)]
+    pub webhook_secret: Option<String>,
+    pub webhook_status: Option<String>, // delivered | failed
+    pub retried_from: Option<Uuid>,
+    pub input_hash: Option<String>, // Set when the input is kept off-chain
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "task_status", rename_all = "lowercase")]
 pub enum TaskStatus {
     Pending,
@@ -145,6 +217,30 @@ pub struct SubmitTaskRequest {
     pub parameters: Option<serde_json::Value>,
     pub priority: Option<i32>,
     pub max_cost: Option<String>, // In yoctoNEAR
+    #[validate(length(max = 2048))]
+    pub callback_url: Option<String>, // https only, results are POSTed here
+    pub off_chain_input: Option<bool>, // Keep the input off the public ledger; nodes fetch it from the gateway
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskInputResponse {
+    pub task_id: Uuid,
+    pub input: String,
+    pub sha256: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CostEstimate {
+    pub estimated_cost: String, // In yoctoNEAR
+    pub base_cost: String, // By task type, in yoctoNEAR
+    pub input_multiplier: f64, // Input bytes / 1000, at least 1
+    pub surge_multiplier: f64, // Network load, 1.0 when not busy
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TaskFilterQuery {
+    pub status: Option<String>, // e.g. "completed"
+    pub task_type: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -161,6 +257,19 @@ pub struct TaskResponse {
     pub started_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
     pub expires_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook_secret: Option<String>, // Only returned on submission
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retried_from: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchTaskResult {
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<TaskResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -187,6 +296,46 @@ pub struct NodeInfo {
     pub total_tasks_completed: u64,
     pub reputation_score: u32,
     pub stake_amount: String,
+    #[serde(default)]
+    pub capabilities: Vec<String>, // Task types the contract routes to the node; empty means any
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NodeMetrics {
+    pub account_id: String,
+    pub is_active: bool,
+    pub reputation_score: u32,
+    pub total_tasks_completed: u64,
+    pub tasks_in_progress: u64,
+    pub estimated_slash_count: u64,
+    pub success_rate: Option<f64>, // None until the node has any history
+    pub heartbeat_age_seconds: u64,
+    pub stake_amount: String,
+    pub slashed_amount: String,
+    pub effective_stake: String, // stake minus slashed amount
+    pub fetched_at: DateTime<Utc>,
+}
+
+// Activity feed models
+/// A contract event, as indexed from the chain.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ActivityEvent {
+    pub block_height: i64,
+    pub receipt_id: String,
+    pub log_index: i64,
+    pub block_timestamp: DateTime<Utc>,
+    pub event_type: String, // e.g. "task_completed"
+    pub node_id: Option<String>,
+    pub task_id: Option<i64>, // Contract task id
+    #[sqlx(json)]
+    pub data: serde_json::Value,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ActivityFilterQuery {
+    pub event_type: Option<String>,
+    pub node_id: Option<String>,
+    pub task_id: Option<i64>,
 }
 
 // Usage and statistics models
@@ -213,6 +362,20 @@ pub struct NetworkStats {
     pub network_utilization_percent: f32,
 }
 
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CacheQuery {
+    #[serde(default)]
+    pub fresh: bool, // Admins only: bypass the cache
+}
+
+/// Contract data served through the gateway's Redis cache.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResponse<T> {
+    pub data: T,
+    pub fetched_at: DateTime<Utc>,
+    pub stale_at: DateTime<Utc>, // Refetched from the contract after this
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SystemMetrics {
     pub active_users: u32,
@@ -222,9 +385,18 @@ pub struct SystemMetrics {
     pub database_connections: u32,
     pub redis_connections: u32,
     pub uptime_seconds: u64,
+    pub near_rpc_circuit: BreakerState,
 }
 
 // Error response models
+/// Fields left out are not changed.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdateContractParamsRequest {
+    pub min_stake: Option<String>, // yoctoNEAR
+    pub max_tasks_per_node: Option<u32>,
+    pub task_timeout_seconds: Option<u64>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ErrorResponse {
     pub error: String,
@@ -269,6 +441,28 @@ impl PaginationQuery {
         let (page, limit) = self.normalize();
         (page - 1) * limit
     }
+    
+    /// Build an `ORDER BY` clause from `sort_by`/`sort_order`. `columns` maps
+    /// the accepted `sort_by` values to their SQL expressions, so only those
+    /// fixed expressions ever reach the query. Unset fields fall back to the
+    /// first column, descending.
+    pub fn order_by(&self, columns: &[(&str, &'static str)]) -> Result<String, String> {
+        let expression = match self.sort_by.as_deref() {
+            None => columns[0].1,
+            Some(sort_by) => columns.iter()
+                .find(|(name, _)| *name == sort_by)
+                .map(|(_, expression)| *expression)
+                .ok_or_else(|| format!("Cannot sort by '{}'", sort_by))?,
+        };
+        
+        let direction = match self.sort_order.as_deref().map(str::to_ascii_lowercase).as_deref() {
+            None | Some("desc") => "DESC",
+            Some("asc") => "ASC",
+            Some(other) => return Err(format!("Invalid sort order '{}'", other)),
+        };
+        
+        Ok(format!("{} {}", expression, direction))
+    }
 }
 
 impl<T> PaginatedResponse<T> {
@@ -0,0 +1,78 @@
+use chrono::Utc;
+use redis::{AsyncCommands, RedisResult};
+use uuid::Uuid;
+
+/// How long an issued challenge can be signed and redeemed.
+pub const CHALLENGE_TTL_SECS: u64 = 300;
+
+const MESSAGE_PREFIX: &str = "Sign in to DeAI as";
+
+/// Random single-use login challenge.
+pub fn generate_challenge() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// The text the wallet signs: `<prefix> <account>|<challenge>|<issued at>`.
+pub fn challenge_message(account_id: &str, challenge: &str) -> String {
+    format!("{} {}|{}|{}", MESSAGE_PREFIX, account_id, challenge, Utc::now().timestamp())
+}
+
+/// Split a signed login message into its challenge and timestamp, or `None`
+/// if it isn't in the `challenge_message` format.
+pub fn parse_challenge_message(message: &str) -> Option<(&str, i64)> {
+    let mut parts = message.split('|');
+    let (_, challenge, timestamp) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() || challenge.is_empty() {
+        return None;
+    }
+
+    Some((challenge, timestamp.parse().ok()?))
+}
+
+// Bound to the account it was issued for, so it can't be spent on another
+fn challenge_key(account_id: &str, challenge: &str) -> String {
+    format!("near_challenge:{}:{}", account_id, challenge)
+}
+
+pub async fn store_challenge(redis_client: &redis::Client, account_id: &str, challenge: &str) -> RedisResult<()> {
+    let mut conn = redis_client.get_multiplexed_async_connection().await?;
+    conn.set_ex(challenge_key(account_id, challenge), 1, CHALLENGE_TTL_SECS).await
+}
+
+/// Consume a challenge issued to `account_id`. Returns `false` if it is
+/// unknown, expired or already used. The delete is atomic, so two logins
+/// racing with the same signed message can't both succeed.
+pub async fn redeem_challenge(redis_client: &redis::Client, account_id: &str, challenge: &str) -> RedisResult<bool> {
+    let mut conn = redis_client.get_multiplexed_async_connection().await?;
+    let deleted: u32 = conn.del(challenge_key(account_id, challenge)).await?;
+    Ok(deleted == 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_challenge_message_round_trips() {
+        let challenge = generate_challenge();
+        let message = challenge_message("alice.testnet", &challenge);
+
+        let (parsed, timestamp) = parse_challenge_message(&message).unwrap();
+        assert_eq!(parsed, challenge);
+        assert!((Utc::now().timestamp() - timestamp).abs() <= 1);
+    }
+
+    #[test]
+    fn test_message_without_challenge_is_rejected() {
+        // The format accepted before challenges were required
+        assert!(parse_challenge_message("Sign in to DeAI|1700000000").is_none());
+        assert!(parse_challenge_message("Sign in|abc|1700000000|extra").is_none());
+        assert!(parse_challenge_message("Sign in||1700000000").is_none());
+        assert!(parse_challenge_message("Sign in|abc|soon").is_none());
+    }
+
+    #[test]
+    fn test_challenge_key_is_bound_to_account() {
+        assert_ne!(challenge_key("alice.testnet", "abc"), challenge_key("bob.testnet", "abc"));
+    }
+}
@@ -0,0 +1,685 @@
+use anyhow::{Context, Result};
+use chrono::{TimeZone, Utc};
+use near_crypto::{InMemorySigner, PublicKey, SecretKey};
+use near_jsonrpc_client::{methods, JsonRpcClient};
+use near_primitives::{
+    hash::CryptoHash,
+    transaction::{Action, FunctionCallAction, SignedTransaction, Transaction},
+    types::{AccountId, Balance, BlockId, BlockReference, Finality, Gas},
+    views::{AccessKeyView, FinalExecutionOutcomeView, FinalExecutionStatus},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::str::FromStr;
+use tracing::{debug, error, info};
+
+use crate::{
+    circuit_breaker::{BreakerState, CircuitBreaker},
+    config::AppConfig,
+    models::NodeInfo,
+    rpc_retry::RetryPolicy,
+};
+
+// Storage fee the contract charges on top of the compute cost for each task
+const TASK_STORAGE_COST: Balance = 1_000_000_000_000_000_000_000; // 0.001 NEAR
+const SUBMIT_TASK_GAS: Gas = 100_000_000_000_000; // 100 TGas
+const SUBMIT_TASKS_BATCH_GAS: Gas = 300_000_000_000_000; // 300 TGas
+const TIMEOUT_TASK_GAS: Gas = 50_000_000_000_000; // 50 TGas
+const ADMIN_CALL_GAS: Gas = 30_000_000_000_000; // 30 TGas
+const ONE_YOCTO: Balance = 1;
+
+/// Most tasks the contract accepts in one `submit_tasks_batch` call.
+pub const MAX_TASKS_BATCH: usize = 20;
+
+pub struct NearClient {
+    client: JsonRpcClient,
+    signer: InMemorySigner,
+    contract_id: AccountId,
+    retry: RetryPolicy,
+    breaker: CircuitBreaker,
+}
+
+// Node record as returned by the contract's view methods
+#[derive(Deserialize, Debug)]
+pub struct ContractNodeInfo {
+    pub account_id: String,
+    pub stake: Value,
+    pub public_ip: String,
+    pub gpu_specs: String,
+    pub cpu_specs: String,
+    pub api_endpoint: String,
+    pub is_active: bool,
+    pub last_heartbeat: u64,
+    pub total_tasks_completed: u64,
+    pub reputation_score: u32,
+    #[serde(default)]
+    pub slashed_amount: Value,
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+}
+
+// Subset of the contract's task record needed by the gateway
+#[derive(Deserialize, Debug, Default)]
+pub struct ContractTask {
+    pub id: u64,
+    pub status: String,
+    pub assignee: Option<String>,
+    #[serde(default)]
+    pub assignees: Vec<String>,
+    pub output: Option<String>,
+    pub proof_hash: Option<String>,
+    pub assigned_at: Option<u64>, // Nanoseconds
+    pub completed_at: Option<u64>,
+}
+
+impl ContractTask {
+    /// Whether `node_id` is one of the nodes the task was given to.
+    pub fn is_assigned_to(&self, node_id: &str) -> bool {
+        self.assignee.as_deref() == Some(node_id) || self.assignees.iter().any(|a| a == node_id)
+    }
+}
+
+/// Counters from the contract's `get_contract_stats` view.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ContractStats {
+    pub active_nodes: u64,
+    pub total_nodes: u64,
+    pub active_tasks: u64,
+    pub paused: bool,
+    #[serde(default)]
+    pub max_tasks_per_node: u32,
+    // Assignment to accepted result, from `get_timing_stats`
+    #[serde(default)]
+    pub average_task_time_ms: Option<f64>,
+}
+
+/// Owner-tunable contract settings.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ContractParams {
+    pub min_stake: String, // yoctoNEAR
+    pub max_tasks_per_node: u32,
+    pub task_timeout_seconds: u64,
+    pub paused: bool,
+}
+
+// Nanosecond averages from the contract's `get_timing_stats` view
+#[derive(Deserialize, Debug)]
+struct ContractTimingStats {
+    average_time_to_complete: Option<u64>,
+}
+
+/// A log line the contract emitted while executing a receipt.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractLog {
+    pub receipt_id: String,
+    pub log_index: u32, // Position among the receipt's logs
+    pub log: String,
+}
+
+/// Contract logs from the transactions included in one block.
+#[derive(Debug)]
+pub struct BlockLogs {
+    pub height: u64,
+    pub timestamp_nanos: u64,
+    pub logs: Vec<ContractLog>,
+}
+
+/// Read a yoctoNEAR amount the contract serialized either as a string or a number.
+pub fn parse_yocto(value: &Value) -> u128 {
+    match value {
+        Value::String(amount) => amount.parse().unwrap_or(0),
+        Value::Number(amount) => amount.to_string().parse().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+impl From<ContractNodeInfo> for NodeInfo {
+    fn from(node: ContractNodeInfo) -> Self {
+        Self {
+            account_id: node.account_id,
+            public_ip: node.public_ip,
+            gpu_specs: node.gpu_specs,
+            cpu_specs: node.cpu_specs,
+            api_endpoint: node.api_endpoint,
+            is_active: node.is_active,
+            last_heartbeat: Utc.timestamp_nanos(node.last_heartbeat as i64),
+            total_tasks_completed: node.total_tasks_completed,
+            reputation_score: node.reputation_score,
+            stake_amount: match node.stake {
+                Value::String(stake) => stake,
+                other => other.to_string(),
+            },
+            capabilities: node.capabilities,
+        }
+    }
+}
+
+impl NearClient {
+    pub async fn new(config: &AppConfig) -> Result<Self> {
+        let client = JsonRpcClient::connect(&config.near.rpc_url);
+
+        let secret_key = SecretKey::from_str(&config.near.signer_private_key)
+            .context("Invalid signer private key format")?;
+
+        let account_id = AccountId::from_str(&config.near.signer_account_id)
+            .context("Invalid signer account ID format")?;
+
+        let signer = InMemorySigner::from_secret_key(account_id, secret_key);
+
+        let contract_id = AccountId::from_str(&config.near.contract_account_id)
+            .context("Invalid contract account ID")?;
+
+        Ok(Self {
+            client,
+            signer,
+            contract_id,
+            retry: RetryPolicy::from_config(&config.near),
+            breaker: CircuitBreaker::from_config(&config.near),
+        })
+    }
+
+    /// Submit a task to the contract and return its on-chain task id.
+    pub async fn submit_task(&self, description: String, compute_cost: Balance) -> Result<u64> {
+        info!("Submitting task with compute cost: {} yoctoNEAR", compute_cost);
+
+        let args = json!({
+            "description": description,
+            "estimated_compute_cost": compute_cost.to_string(),
+        });
+
+        let outcome = self.call_contract_method(
+            "submit_task",
+            args,
+            SUBMIT_TASK_GAS,
+            compute_cost + TASK_STORAGE_COST,
+        ).await?;
+
+        parse_task_id_from_outcome(&outcome)
+    }
+
+    /// Submit tasks sharing one compute cost in a single transaction and
+    /// return their on-chain task ids in input order.
+    pub async fn submit_tasks_batch(&self, descriptions: Vec<String>, compute_cost_each: Balance) -> Result<Vec<u64>> {
+        anyhow::ensure!(
+            !descriptions.is_empty() && descriptions.len() <= MAX_TASKS_BATCH,
+            "Batch must contain between 1 and {} tasks",
+            MAX_TASKS_BATCH
+        );
+        
+        info!("Submitting batch of {} tasks with compute cost: {} yoctoNEAR each", descriptions.len(), compute_cost_each);
+        
+        let count = descriptions.len();
+        let args = json!({
+            "tasks": descriptions.into_iter()
+                .map(|description| json!({ "description": description }))
+                .collect::<Vec<_>>(),
+            "estimated_compute_cost_each": compute_cost_each.to_string(),
+        });
+        
+        let outcome = self.call_contract_method(
+            "submit_tasks_batch",
+            args,
+            SUBMIT_TASKS_BATCH_GAS,
+            (compute_cost_each + TASK_STORAGE_COST) * count as Balance,
+        ).await?;
+        
+        let task_ids: Vec<u64> = match outcome.status {
+            FinalExecutionStatus::SuccessValue(value) => serde_json::from_slice(&value)
+                .context("Failed to parse submitted task ids")?,
+            status => anyhow::bail!("Unexpected batch submission status: {:?}", status),
+        };
+        
+        anyhow::ensure!(task_ids.len() == count, "Contract returned {} task ids for {} tasks", task_ids.len(), count);
+        Ok(task_ids)
+    }
+
+    /// Time out an assigned task whose deadline has passed, slashing its node
+    /// and refunding the requester.
+    pub async fn timeout_task(&self, task_id: u64) -> Result<()> {
+        info!("Timing out task {}", task_id);
+
+        self.call_contract_method(
+            "timeout_task",
+            json!({ "task_id": task_id }),
+            TIMEOUT_TASK_GAS,
+            ONE_YOCTO,
+        ).await?;
+
+        Ok(())
+    }
+
+    /// Look a task up among active tasks, then finished ones. `None` if the
+    /// contract has no such task.
+    pub async fn get_task(&self, task_id: u64) -> Result<Option<ContractTask>> {
+        debug!("Fetching task {}", task_id);
+
+        for method_name in ["get_active_task", "get_task_result"] {
+            let result = self.view_contract_method(method_name, json!({ "task_id": task_id })).await?;
+            let task: Option<ContractTask> = serde_json::from_value(result)
+                .with_context(|| format!("Failed to parse {} response", method_name))?;
+            if task.is_some() {
+                return Ok(task);
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub async fn get_node_info(&self, node_id: &str) -> Result<Option<ContractNodeInfo>> {
+        debug!("Fetching node info for {}", node_id);
+        
+        let result = self.view_contract_method("get_node_info", json!({ "node_id": node_id })).await?;
+        
+        serde_json::from_value(result).context("Failed to parse node info response")
+    }
+
+    pub async fn get_assigned_tasks(&self, node_id: &str) -> Result<Vec<ContractTask>> {
+        debug!("Fetching assigned tasks for {}", node_id);
+        
+        let result = self.view_contract_method("get_assigned_tasks", json!({ "node_id": node_id })).await?;
+        
+        serde_json::from_value(result).context("Failed to parse assigned tasks response")
+    }
+
+    pub async fn get_active_nodes(&self) -> Result<Vec<NodeInfo>> {
+        debug!("Fetching active nodes");
+
+        let result = self.view_contract_method("get_active_nodes", json!({})).await?;
+
+        let nodes: Vec<ContractNodeInfo> = serde_json::from_value(result)
+            .context("Failed to parse active nodes response")?;
+
+        Ok(nodes.into_iter().map(NodeInfo::from).collect())
+    }
+
+    pub async fn get_contract_stats(&self) -> Result<ContractStats> {
+        debug!("Fetching contract stats");
+
+        let result = self.view_contract_method("get_contract_stats", json!({})).await?;
+
+        // Returned as a tuple; the completed task count is always zero on-chain
+        let (active_nodes, total_nodes, active_tasks, _completed_tasks, paused): (u64, u64, u64, u64, bool) =
+            serde_json::from_value(result).context("Failed to parse contract stats response")?;
+
+        let max_tasks_per_node = self.view_contract_method("get_max_tasks_per_node", json!({})).await?;
+        let max_tasks_per_node: u32 = serde_json::from_value(max_tasks_per_node)
+            .context("Failed to parse max tasks per node response")?;
+
+        let timing = self.view_contract_method("get_timing_stats", json!({})).await?;
+        let timing: ContractTimingStats = serde_json::from_value(timing)
+            .context("Failed to parse timing stats response")?;
+        let average_task_time_ms = timing.average_time_to_complete.map(|nanos| nanos as f64 / 1_000_000.0);
+
+        Ok(ContractStats { active_nodes, total_nodes, active_tasks, paused, max_tasks_per_node, average_task_time_ms })
+    }
+
+    pub async fn get_contract_params(&self) -> Result<ContractParams> {
+        let min_stake = self.view_contract_method("get_min_stake", json!({})).await?;
+        let min_stake: String = serde_json::from_value(min_stake).context("Failed to parse min stake response")?;
+
+        let max_tasks_per_node = self.view_contract_method("get_max_tasks_per_node", json!({})).await?;
+        let max_tasks_per_node: u32 = serde_json::from_value(max_tasks_per_node)
+            .context("Failed to parse max tasks per node response")?;
+
+        let task_timeout = self.view_contract_method("get_task_timeout", json!({})).await?;
+        let task_timeout: u64 = serde_json::from_value(task_timeout).context("Failed to parse task timeout response")?;
+
+        let stats = self.view_contract_method("get_contract_stats", json!({})).await?;
+        let (_, _, _, _, paused): (u64, u64, u64, u64, bool) =
+            serde_json::from_value(stats).context("Failed to parse contract stats response")?;
+
+        Ok(ContractParams {
+            min_stake,
+            max_tasks_per_node,
+            task_timeout_seconds: task_timeout / 1_000_000_000,
+            paused,
+        })
+    }
+
+    // The owner-only setters below only succeed when the gateway signer is the contract owner
+
+    pub async fn update_min_stake(&self, min_stake: Balance) -> Result<()> {
+        self.call_contract_method(
+            "update_min_stake",
+            json!({ "new_min_stake": min_stake.to_string() }),
+            ADMIN_CALL_GAS,
+            ONE_YOCTO,
+        ).await?;
+
+        Ok(())
+    }
+
+    pub async fn update_max_tasks_per_node(&self, max_tasks: u32) -> Result<()> {
+        self.call_contract_method(
+            "update_max_tasks_per_node",
+            json!({ "max_tasks": max_tasks }),
+            ADMIN_CALL_GAS,
+            ONE_YOCTO,
+        ).await?;
+
+        Ok(())
+    }
+
+    pub async fn update_task_timeout(&self, timeout_seconds: u64) -> Result<()> {
+        self.call_contract_method(
+            "update_task_timeout",
+            json!({ "timeout_duration": timeout_seconds * 1_000_000_000 }),
+            ADMIN_CALL_GAS,
+            ONE_YOCTO,
+        ).await?;
+
+        Ok(())
+    }
+
+    pub async fn set_paused(&self, paused: bool) -> Result<()> {
+        let method = if paused { "pause_contract" } else { "unpause_contract" };
+        self.call_contract_method(method, json!({}), ADMIN_CALL_GAS, ONE_YOCTO).await?;
+
+        Ok(())
+    }
+
+    // Runs inside the caller's request span, so the transaction hash logged
+    // here can be traced back to the request id
+    #[tracing::instrument(skip(self, args), fields(contract = %self.contract_id))]
+    async fn call_contract_method(
+        &self,
+        method_name: &str,
+        args: Value,
+        gas: Gas,
+        deposit: Balance,
+    ) -> Result<FinalExecutionOutcomeView> {
+        let access_key = self.get_access_key().await?;
+
+        let transaction = Transaction {
+            signer_id: self.signer.account_id.clone(),
+            public_key: self.signer.public_key(),
+            nonce: access_key.nonce + 1,
+            receiver_id: self.contract_id.clone(),
+            block_hash: self.get_latest_block_hash().await?,
+            actions: vec![Action::FunctionCall(Box::new(FunctionCallAction {
+                method_name: method_name.to_string(),
+                args: args.to_string().into_bytes(),
+                gas,
+                deposit,
+            }))],
+        };
+
+        let signed_transaction = SignedTransaction::new(
+            self.signer.sign(&transaction.get_hash_and_size().0),
+            transaction,
+        );
+
+        // The same signed transaction is resent, so one that did land the
+        // first time is never executed twice
+        let response = self.breaker.run(self.retry.run(method_name, || {
+            self.client.call(methods::send_tx::RpcSendTransactionRequest {
+                signed_transaction: signed_transaction.clone(),
+                wait_until: near_primitives::views::TxExecutionStatus::Final,
+            })
+        })).await
+            .context("Failed to send transaction")?;
+
+        let transaction_hash = response.transaction.hash;
+
+        if let Some(failure) = &response.status.as_failure() {
+            error!("Transaction {} failed: {:?}", transaction_hash, failure);
+            anyhow::bail!("Transaction {} failed: {:?}", transaction_hash, failure);
+        }
+
+        info!("Transaction {} for {} finalized", transaction_hash, method_name);
+
+        Ok(response)
+    }
+
+    async fn view_contract_method(
+        &self,
+        method_name: &str,
+        args: Value,
+    ) -> Result<Value> {
+        let args = args.to_string();
+        let response = self.breaker.run(self.retry.run(method_name, || {
+            self.client.call(methods::query::RpcQueryRequest {
+                block_reference: BlockReference::latest(),
+                request: near_primitives::views::QueryRequest::CallFunction {
+                    account_id: self.contract_id.clone(),
+                    method_name: method_name.to_string(),
+                    args: args.clone().into_bytes().into(),
+                },
+            })
+        })).await
+            .context("Failed to query contract")?;
+
+        if let near_primitives::views::QueryResponseKind::CallResult(result) = response.kind {
+            let value: Value = serde_json::from_slice(&result.result)
+                .context("Failed to parse view result")?;
+            Ok(value)
+        } else {
+            anyhow::bail!("Unexpected query response type");
+        }
+    }
+
+    async fn get_access_key(&self) -> Result<AccessKeyView> {
+        let response = self.breaker.run(self.retry.run("view_access_key", || {
+            self.client.call(methods::query::RpcQueryRequest {
+                block_reference: BlockReference::latest(),
+                request: near_primitives::views::QueryRequest::ViewAccessKey {
+                    account_id: self.signer.account_id.clone(),
+                    public_key: self.signer.public_key(),
+                },
+            })
+        })).await
+            .context("Failed to get access key")?;
+
+        if let near_primitives::views::QueryResponseKind::AccessKey(access_key) = response.kind {
+            Ok(access_key)
+        } else {
+            anyhow::bail!("Unexpected access key response type");
+        }
+    }
+
+    /// Whether `public_key` is a full-access or function-call key on
+    /// `account_id`. An unknown account or key is `Ok(false)`.
+    pub async fn has_access_key(&self, account_id: &AccountId, public_key: &PublicKey) -> Result<bool> {
+        let response = self.breaker.run(self.retry.run("view_access_key", || {
+            self.client.call(methods::query::RpcQueryRequest {
+                block_reference: BlockReference::latest(),
+                request: near_primitives::views::QueryRequest::ViewAccessKey {
+                    account_id: account_id.clone(),
+                    public_key: public_key.clone(),
+                },
+            })
+        })).await;
+
+        match response {
+            Ok(response) => Ok(matches!(response.kind, near_primitives::views::QueryResponseKind::AccessKey(_))),
+            Err(e) if is_missing_access_key(&format!("{:?}", e)) => Ok(false),
+            Err(e) => Err(e).context("Failed to look up access key"),
+        }
+    }
+
+    pub fn circuit_state(&self) -> BreakerState {
+        self.breaker.state()
+    }
+
+    /// Ask the RPC node for its status, without retrying: readiness wants to
+    /// know whether it answers right now.
+    pub async fn check_rpc(&self) -> Result<()> {
+        self.breaker.run(self.client.call(methods::status::RpcStatusRequest))
+            .await
+            .context("NEAR RPC status request failed")?;
+
+        Ok(())
+    }
+
+    /// Height of the latest final block, which can no longer be reorganized.
+    pub async fn get_final_block_height(&self) -> Result<u64> {
+        let response = self.breaker.run(self.retry.run("block", || {
+            self.client.call(methods::block::RpcBlockRequest {
+                block_reference: BlockReference::Finality(Finality::Final),
+            })
+        })).await
+            .context("Failed to get final block")?;
+
+        Ok(response.header.height)
+    }
+
+    /// Logs of the contract from transactions sent to it in block `height`,
+    /// including those of receipts executed in later blocks. `None` when no
+    /// block was produced at that height.
+    pub async fn get_block_logs(&self, height: u64) -> Result<Option<BlockLogs>> {
+        let block = match self.breaker.run(self.retry.run("block", || {
+            self.client.call(methods::block::RpcBlockRequest {
+                block_reference: BlockReference::BlockId(BlockId::Height(height)),
+            })
+        })).await {
+            Ok(block) => block,
+            Err(e) if is_missing_block(&format!("{:?}", e)) => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("Failed to get block {}", height)),
+        };
+
+        let mut logs = Vec::new();
+        // Chunks not produced in this block repeat an earlier block's
+        for chunk_header in block.chunks.iter().filter(|chunk| chunk.height_included == height) {
+            let chunk = self.breaker.run(self.retry.run("chunk", || {
+                self.client.call(methods::chunk::RpcChunkRequest {
+                    chunk_reference: methods::chunk::ChunkReference::ChunkHash {
+                        chunk_id: chunk_header.chunk_hash,
+                    },
+                })
+            })).await
+                .with_context(|| format!("Failed to get chunk {}", chunk_header.chunk_hash))?;
+
+            for transaction in chunk.transactions.iter().filter(|tx| tx.receiver_id == self.contract_id) {
+                let outcome = self.breaker.run(self.retry.run("tx", || {
+                    self.client.call(methods::tx::RpcTransactionStatusRequest {
+                        transaction_info: methods::tx::TransactionInfo::TransactionId {
+                            tx_hash: transaction.hash,
+                            sender_account_id: transaction.signer_id.clone(),
+                        },
+                        wait_until: near_primitives::views::TxExecutionStatus::Final,
+                    })
+                })).await
+                    .with_context(|| format!("Failed to get outcome of transaction {}", transaction.hash))?;
+
+                logs.extend(contract_logs(&outcome, &self.contract_id));
+            }
+        }
+
+        Ok(Some(BlockLogs {
+            height,
+            timestamp_nanos: block.header.timestamp_nanosec,
+            logs,
+        }))
+    }
+
+    async fn get_latest_block_hash(&self) -> Result<CryptoHash> {
+        let response = self.breaker.run(self.retry.run("block", || {
+            self.client.call(methods::block::RpcBlockRequest {
+                block_reference: BlockReference::latest(),
+            })
+        })).await
+            .context("Failed to get latest block")?;
+
+        Ok(response.header.hash)
+    }
+}
+
+// Query errors meaning the account or key doesn't exist, as opposed to the
+// lookup itself failing
+fn is_missing_access_key(error: &str) -> bool {
+    error.contains("UnknownAccessKey") || error.contains("UnknownAccount")
+}
+
+// Heights skipped by block production, as opposed to the lookup failing
+fn is_missing_block(error: &str) -> bool {
+    error.contains("UnknownBlock")
+}
+
+// Logs from receipts the contract executed, in execution order
+fn contract_logs(outcome: &FinalExecutionOutcomeView, contract_id: &AccountId) -> Vec<ContractLog> {
+    outcome.receipts_outcome.iter()
+        .filter(|receipt| &receipt.outcome.executor_id == contract_id)
+        .flat_map(|receipt| {
+            receipt.outcome.logs.iter().enumerate().map(|(log_index, log)| ContractLog {
+                receipt_id: receipt.id.to_string(),
+                log_index: log_index as u32,
+                log: log.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Extract the id of the task created by a `submit_task` transaction.
+pub fn parse_task_id_from_outcome(outcome: &FinalExecutionOutcomeView) -> Result<u64> {
+    let logs: Vec<&str> = outcome.transaction_outcome.outcome.logs.iter()
+        .chain(outcome.receipts_outcome.iter().flat_map(|receipt| receipt.outcome.logs.iter()))
+        .map(String::as_str)
+        .collect();
+
+    parse_submitted_task_id(&logs)
+}
+
+// Prefers the NEP-297 `task_submitted` event, falling back to the plain
+// "Task submitted: <id>, ..." log line
+fn parse_submitted_task_id(logs: &[&str]) -> Result<u64> {
+    let from_event = logs.iter()
+        .filter_map(|log| log.strip_prefix("EVENT_JSON:"))
+        .filter_map(|event| serde_json::from_str::<Value>(event).ok())
+        .find(|event| event["standard"] == "deai" && event["event"] == "task_submitted")
+        .and_then(|event| event["data"][0]["task_id"].as_u64());
+
+    if let Some(task_id) = from_event {
+        return Ok(task_id);
+    }
+
+    logs.iter()
+        .filter_map(|log| log.strip_prefix("Task submitted: "))
+        .find_map(|rest| rest.split(',').next()?.trim().parse().ok())
+        .context("No submitted task id found in transaction logs")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_submitted_task_id() {
+        let logs = vec![
+            r#"EVENT_JSON:{"standard":"deai-audit","version":"1.0.0","event":"call","data":[{"method":"submit_task"}]}"#.to_string(),
+            "Task submitted: 42, requester: gateway.testnet, amount: 100, fee: 0".to_string(),
+            r#"EVENT_JSON:{"standard":"deai","version":"1.0.0","event":"task_submitted","data":[{"task_id":42,"requester":"gateway.testnet","reward_amount":"100","priority":"Normal"}]}"#.to_string(),
+        ];
+
+        let logs: Vec<&str> = logs.iter().map(String::as_str).collect();
+
+        assert_eq!(parse_submitted_task_id(&logs).unwrap(), 42);
+    }
+
+    #[test]
+    fn test_parse_submitted_task_id_from_log_line() {
+        let logs = ["Task submitted: 7, requester: gateway.testnet, amount: 100, fee: 0"];
+
+        assert_eq!(parse_submitted_task_id(&logs).unwrap(), 7);
+    }
+
+    #[test]
+    fn test_parse_submitted_task_id_missing() {
+        let logs = ["Task assigned: 7 to node: node.testnet"];
+
+        assert!(parse_submitted_task_id(&logs).is_err());
+        assert!(parse_submitted_task_id(&[]).is_err());
+    }
+
+    #[test]
+    fn test_is_missing_block() {
+        assert!(is_missing_block("ServerError(HandlerError(UnknownBlock { error_message: \"DB Not Found Error: BLOCK HEIGHT: 42\" }))"));
+        assert!(!is_missing_block("TransportError(SendError(Server returned 502))"));
+    }
+
+    #[test]
+    fn test_is_missing_access_key() {
+        assert!(is_missing_access_key("ServerError(HandlerError(UnknownAccessKey { public_key: ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp, block_height: 1, block_hash: 11111111111111111111111111111111 }))"));
+        assert!(is_missing_access_key("ServerError(HandlerError(UnknownAccount { requested_account_id: AccountId(\"ghost.testnet\"), block_height: 1, block_hash: 11111111111111111111111111111111 }))"));
+        assert!(!is_missing_access_key("TransportError(SendError(Server returned 502))"));
+    }
+}
@@ -0,0 +1,116 @@
+use axum::http::HeaderMap;
+use chrono::Utc;
+use near_crypto::{PublicKey, Signature};
+use near_primitives::types::AccountId;
+use uuid::Uuid;
+
+use crate::{
+    errors::{ApiError, ApiResult},
+    handlers::{auth::verify_key_ownership, AppState},
+};
+
+// Nodes call some endpoints directly, signing with their NEAR account key
+// instead of holding a user token
+pub const ACCOUNT_HEADER: &str = "x-node-account-id";
+pub const PUBLIC_KEY_HEADER: &str = "x-node-public-key";
+pub const TIMESTAMP_HEADER: &str = "x-node-timestamp";
+pub const SIGNATURE_HEADER: &str = "x-node-signature";
+
+// How far a signed request's timestamp may be from the gateway's clock
+const MAX_CLOCK_SKEW_SECS: i64 = 300;
+
+/// What a node signs to fetch the off-chain input of `task_id`.
+pub fn task_input_message(task_id: Uuid, timestamp: i64) -> String {
+    format!("deai-task-input|{}|{}", task_id, timestamp)
+}
+
+/// Authenticate a node request: the signature over `message(timestamp)` must
+/// verify and the key must be an access key of the claimed account. Returns
+/// the node's account.
+pub async fn verify_node_request<F>(state: &AppState, headers: &HeaderMap, message: F) -> ApiResult<AccountId>
+where
+    F: Fn(i64) -> String,
+{
+    let (account_id, public_key) = verify_signature(headers, message, Utc::now().timestamp())?;
+    verify_key_ownership(state, &account_id, &public_key.to_string()).await?;
+
+    Ok(account_id)
+}
+
+fn verify_signature<F>(headers: &HeaderMap, message: F, now: i64) -> ApiResult<(AccountId, PublicKey)>
+where
+    F: Fn(i64) -> String,
+{
+    let header = |name: &str| {
+        headers.get(name)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| ApiError::Unauthorized(format!("Missing {} header", name)))
+    };
+
+    let account_id: AccountId = header(ACCOUNT_HEADER)?.parse()
+        .map_err(|_| ApiError::BadRequest("Invalid node account ID".to_string()))?;
+    let public_key: PublicKey = header(PUBLIC_KEY_HEADER)?.parse()
+        .map_err(|_| ApiError::BadRequest("Invalid public key format".to_string()))?;
+    let timestamp: i64 = header(TIMESTAMP_HEADER)?.parse()
+        .map_err(|_| ApiError::BadRequest("Invalid timestamp".to_string()))?;
+    let signature: Signature = header(SIGNATURE_HEADER)?.parse()
+        .map_err(|_| ApiError::BadRequest("Invalid signature format".to_string()))?;
+
+    if (now - timestamp).abs() > MAX_CLOCK_SKEW_SECS {
+        return Err(ApiError::Unauthorized("Request timestamp too old".to_string()));
+    }
+
+    if !signature.verify(message(timestamp).as_bytes(), &public_key) {
+        return Err(ApiError::Unauthorized("Invalid node signature".to_string()));
+    }
+
+    Ok((account_id, public_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_crypto::{KeyType, SecretKey};
+
+    fn signed_headers(secret_key: &SecretKey, task_id: Uuid, timestamp: i64) -> HeaderMap {
+        let signature = secret_key.sign(task_input_message(task_id, timestamp).as_bytes());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCOUNT_HEADER, "node.testnet".parse().unwrap());
+        headers.insert(PUBLIC_KEY_HEADER, secret_key.public_key().to_string().parse().unwrap());
+        headers.insert(TIMESTAMP_HEADER, timestamp.to_string().parse().unwrap());
+        headers.insert(SIGNATURE_HEADER, signature.to_string().parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_signed_request_is_accepted() {
+        let secret_key = SecretKey::from_random(KeyType::ED25519);
+        let task_id = Uuid::new_v4();
+        let headers = signed_headers(&secret_key, task_id, 1_700_000_000);
+
+        let (account_id, public_key) =
+            verify_signature(&headers, |ts| task_input_message(task_id, ts), 1_700_000_010).unwrap();
+        assert_eq!(account_id.as_str(), "node.testnet");
+        assert_eq!(public_key, secret_key.public_key());
+    }
+
+    #[test]
+    fn test_signature_is_bound_to_task_and_time() {
+        let secret_key = SecretKey::from_random(KeyType::ED25519);
+        let task_id = Uuid::new_v4();
+        let headers = signed_headers(&secret_key, task_id, 1_700_000_000);
+
+        // Reused for another task
+        let other_task = Uuid::new_v4();
+        assert!(verify_signature(&headers, |ts| task_input_message(other_task, ts), 1_700_000_000).is_err());
+
+        // Replayed after the window
+        assert!(verify_signature(&headers, |ts| task_input_message(task_id, ts), 1_700_000_301).is_err());
+    }
+
+    #[test]
+    fn test_missing_headers_are_rejected() {
+        assert!(verify_signature(&HeaderMap::new(), |ts| task_input_message(Uuid::nil(), ts), 0).is_err());
+    }
+}
@@ -0,0 +1,122 @@
+use chrono::{DateTime, Duration, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// Random single-use reset token. Only its hash is stored.
+pub fn generate_reset_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+// Tokens are high-entropy, so a plain digest is enough and keeps lookups by hash possible
+fn hash_reset_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+pub async fn store_reset_token(
+    pool: &SqlitePool,
+    user_id: Uuid,
+    token: &str,
+    valid_for: Duration,
+) -> Result<(), sqlx::Error> {
+    let now = Utc::now();
+
+    sqlx::query(
+        "INSERT INTO password_reset_tokens (id, user_id, token_hash, expires_at, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(hash_reset_token(token))
+    .bind(now + valid_for)
+    .bind(now)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Consume a reset token and return the user it belongs to, or `None` if it
+/// is unknown, expired or already used. Marking it used in the same statement
+/// that checks it means two concurrent resets can't both succeed.
+pub async fn redeem_reset_token(
+    pool: &SqlitePool,
+    token: &str,
+    now: DateTime<Utc>,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let user_id: Option<Uuid> = sqlx::query_scalar(
+        "UPDATE password_reset_tokens SET used_at = ?1
+         WHERE token_hash = ?2 AND used_at IS NULL AND expires_at > ?1
+         RETURNING user_id",
+    )
+    .bind(now)
+    .bind(hash_reset_token(token))
+    .fetch_optional(pool)
+    .await?;
+
+    // Any other outstanding tokens for the account are void once one is used
+    if let Some(user_id) = user_id {
+        sqlx::query("UPDATE password_reset_tokens SET used_at = ?1 WHERE user_id = ?2 AND used_at IS NULL")
+            .bind(now)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+    }
+
+    Ok(user_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn create_test_pool() -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::query(
+            r#"
+            CREATE TABLE password_reset_tokens (
+                id BLOB PRIMARY KEY, user_id BLOB NOT NULL, token_hash TEXT NOT NULL UNIQUE,
+                expires_at TEXT NOT NULL, used_at TEXT, created_at TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_reset_token_is_single_use() {
+        let pool = create_test_pool().await;
+        let user_id = Uuid::new_v4();
+        let token = generate_reset_token();
+        store_reset_token(&pool, user_id, &token, Duration::minutes(30)).await.unwrap();
+
+        assert_eq!(redeem_reset_token(&pool, &token, Utc::now()).await.unwrap(), Some(user_id));
+        assert_eq!(redeem_reset_token(&pool, &token, Utc::now()).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_expired_reset_token_is_rejected() {
+        let pool = create_test_pool().await;
+        let token = generate_reset_token();
+        store_reset_token(&pool, Uuid::new_v4(), &token, Duration::minutes(30)).await.unwrap();
+
+        let later = Utc::now() + Duration::minutes(31);
+        assert_eq!(redeem_reset_token(&pool, &token, later).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_using_a_token_voids_older_ones() {
+        let pool = create_test_pool().await;
+        let user_id = Uuid::new_v4();
+        let first = generate_reset_token();
+        let second = generate_reset_token();
+        store_reset_token(&pool, user_id, &first, Duration::minutes(30)).await.unwrap();
+        store_reset_token(&pool, user_id, &second, Duration::minutes(30)).await.unwrap();
+
+        assert_eq!(redeem_reset_token(&pool, &second, Utc::now()).await.unwrap(), Some(user_id));
+        assert_eq!(redeem_reset_token(&pool, &first, Utc::now()).await.unwrap(), None);
+        assert_eq!(redeem_reset_token(&pool, "unknown-token", Utc::now()).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,69 @@
+use tracing::warn;
+
+use crate::{
+    chain_cache,
+    config::PricingConfig,
+    handlers::AppState,
+    near_client::ContractStats,
+};
+
+/// Price multiplier for the current network load, read from the cached
+/// contract stats. Falls back to no surge when the stats are unavailable.
+pub async fn current_surge_multiplier(state: &AppState) -> f64 {
+    match chain_cache::contract_stats(state, false).await {
+        Ok(stats) => surge_multiplier(&stats.data, &state.config.pricing),
+        Err(e) => {
+            warn!("Failed to fetch contract stats for pricing: {}", e);
+            1.0
+        }
+    }
+}
+
+// 1.0 up to `surge_threshold` active tasks per active node, then growing with
+// utilization until `max_surge_multiplier`
+pub fn surge_multiplier(stats: &ContractStats, config: &PricingConfig) -> f64 {
+    if stats.active_nodes == 0 {
+        return config.max_surge_multiplier;
+    }
+
+    let utilization = stats.active_tasks as f64 / stats.active_nodes as f64;
+    (utilization / config.surge_threshold).clamp(1.0, config.max_surge_multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> PricingConfig {
+        PricingConfig { surge_threshold: 1.0, max_surge_multiplier: 3.0 }
+    }
+
+    fn stats(active_nodes: u64, active_tasks: u64) -> ContractStats {
+        ContractStats {
+            active_nodes,
+            total_nodes: active_nodes,
+            active_tasks,
+            paused: false,
+            max_tasks_per_node: 5,
+            average_task_time_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_low_utilization_has_no_surge() {
+        assert_eq!(surge_multiplier(&stats(10, 0), &config()), 1.0);
+        assert_eq!(surge_multiplier(&stats(10, 5), &config()), 1.0);
+        assert_eq!(surge_multiplier(&stats(10, 10), &config()), 1.0);
+    }
+
+    #[test]
+    fn test_surge_grows_with_utilization() {
+        assert_eq!(surge_multiplier(&stats(10, 15), &config()), 1.5);
+    }
+
+    #[test]
+    fn test_saturated_network_is_capped() {
+        assert_eq!(surge_multiplier(&stats(2, 100), &config()), 3.0);
+        assert_eq!(surge_multiplier(&stats(0, 4), &config()), 3.0);
+    }
+}
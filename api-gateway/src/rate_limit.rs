@@ -24,6 +24,22 @@ impl Default for RateLimitConfig {
     }
 }
 
+impl RateLimitConfig {
+    /// Limits for an API key with a `rate_limit_override`, interpreted as
+    /// requests per minute. Longer windows scale from it and the burst limit
+    /// matches it, so the override is the effective per-minute cap.
+    pub fn from_requests_per_minute(requests_per_minute: u32) -> Self {
+        let requests_per_minute = requests_per_minute.max(1);
+        
+        Self {
+            requests_per_minute,
+            requests_per_hour: requests_per_minute.saturating_mul(60),
+            requests_per_day: requests_per_minute.saturating_mul(60 * 24),
+            burst_limit: requests_per_minute,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserTier {
     pub name: String,
@@ -293,4 +309,45 @@ impl RateLimiter {
             burst_limit: 20,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_limiter() -> RateLimiter {
+        // Opening a client does not connect, so only the in-memory store is used
+        RateLimiter::new(redis::Client::open("redis://127.0.0.1:1/").unwrap())
+    }
+
+    fn is_limited(info: &RateLimitInfo) -> bool {
+        info.remaining == 0 && info.retry_after.is_some()
+    }
+
+    #[test]
+    fn test_api_key_override_limits_before_tier() {
+        let limiter = create_test_limiter();
+        let key_config = RateLimitConfig::from_requests_per_minute(2);
+        let tier_config = limiter.get_user_tier_config("free");
+
+        for _ in 0..2 {
+            assert!(!is_limited(&limiter.check_memory_rate_limit("api_key:1", &key_config).unwrap()));
+        }
+        assert!(is_limited(&limiter.check_memory_rate_limit("api_key:1", &key_config).unwrap()));
+
+        // The same number of requests is well inside the free tier
+        for _ in 0..3 {
+            assert!(!is_limited(&limiter.check_memory_rate_limit("user:1", &tier_config).unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_api_keys_have_independent_buckets() {
+        let limiter = create_test_limiter();
+        let config = RateLimitConfig::from_requests_per_minute(1);
+
+        limiter.check_memory_rate_limit("api_key:1", &config).unwrap();
+        assert!(is_limited(&limiter.check_memory_rate_limit("api_key:1", &config).unwrap()));
+        assert!(!is_limited(&limiter.check_memory_rate_limit("api_key:2", &config).unwrap()));
+    }
+}
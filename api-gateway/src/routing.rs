@@ -0,0 +1,132 @@
+use dashmap::DashMap;
+use std::time::{Duration, Instant};
+use crate::{config::RoutingConfig, models::NodeInfo};
+
+// Tracks a model that is currently being warmed on a single node
+#[derive(Debug, Clone)]
+struct WarmingEntry {
+    node_id: String,
+    assigned: u32,
+    started_at: Instant,
+}
+
+/// Routing recommendations for task submission.
+///
+/// When a burst of tasks arrives for a model that no node has cached yet,
+/// the first `coalesce_tasks` of them are steered toward one node so only
+/// that node downloads the model. Once the batch is used up, tasks fan out
+/// normally until the coalescing window expires.
+#[derive(Debug)]
+pub struct ModelRouter {
+    warming: DashMap<String, WarmingEntry>,
+    coalesce_tasks: u32,
+    coalesce_window: Duration,
+}
+
+impl ModelRouter {
+    pub fn new(config: &RoutingConfig) -> Self {
+        Self {
+            warming: DashMap::new(),
+            coalesce_tasks: config.coalesce_tasks,
+            coalesce_window: Duration::from_secs(config.coalesce_window_seconds),
+        }
+    }
+
+    /// Recommend a node for a task using `model_name`, or `None` to let the
+    /// contract choose.
+    pub fn recommend_node(&self, model_name: &str, candidates: &[NodeInfo]) -> Option<String> {
+        if self.coalesce_tasks == 0 {
+            return None;
+        }
+
+        let now = Instant::now();
+
+        if let Some(mut entry) = self.warming.get_mut(model_name) {
+            let expired = now.duration_since(entry.started_at) > self.coalesce_window;
+            let node_available = candidates
+                .iter()
+                .any(|node| node.is_active && node.account_id == entry.node_id);
+
+            if !expired && node_available {
+                if entry.assigned >= self.coalesce_tasks {
+                    // Batch exhausted - the cache is warm, fan out
+                    return None;
+                }
+
+                entry.assigned += 1;
+                return Some(entry.node_id.clone());
+            }
+        }
+
+        // Start warming on the most reputable active node
+        let node = candidates
+            .iter()
+            .filter(|node| node.is_active)
+            .max_by_key(|node| node.reputation_score)?;
+
+        self.warming.insert(
+            model_name.to_string(),
+            WarmingEntry {
+                node_id: node.account_id.clone(),
+                assigned: 1,
+                started_at: now,
+            },
+        );
+
+        Some(node.account_id.clone())
+    }
+
+    /// Drop coalescing state for models whose window has elapsed.
+    pub fn prune_expired(&self) {
+        let now = Instant::now();
+        self.warming
+            .retain(|_, entry| now.duration_since(entry.started_at) <= self.coalesce_window);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn node(account_id: &str, reputation_score: u32) -> NodeInfo {
+        NodeInfo {
+            account_id: account_id.to_string(),
+            public_ip: "127.0.0.1".to_string(),
+            gpu_specs: "RTX 4090".to_string(),
+            cpu_specs: "Intel i9".to_string(),
+            api_endpoint: "http://127.0.0.1:8080".to_string(),
+            is_active: true,
+            last_heartbeat: Utc::now(),
+            total_tasks_completed: 0,
+            reputation_score,
+            stake_amount: "1000000000000000000000000".to_string(),
+            capabilities: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_coalesces_first_tasks_for_model() {
+        let router = ModelRouter::new(&RoutingConfig {
+            coalesce_tasks: 3,
+            coalesce_window_seconds: 300,
+        });
+        let nodes = vec![node("a.testnet", 100), node("b.testnet", 200)];
+
+        for _ in 0..3 {
+            assert_eq!(
+                router.recommend_node("gpt2", &nodes),
+                Some("b.testnet".to_string())
+            );
+        }
+
+        // Batch exhausted, tasks fan out
+        assert_eq!(router.recommend_node("gpt2", &nodes), None);
+
+        // Other models warm independently
+        assert_eq!(
+            router.recommend_node("bert-base-uncased", &nodes),
+            Some("b.testnet".to_string())
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::hash_map::RandomState;
+use std::fmt::Debug;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+use tracing::warn;
+
+use crate::config::NearConfig;
+
+const MAX_DELAY: Duration = Duration::from_secs(10);
+
+// Markers in the Debug output of RPC client errors for failures worth
+// retrying: the request never got a proper answer or the node was overloaded.
+// Anything else, such as a contract panic, would fail the same way again.
+const RETRYABLE_MARKERS: &[&str] = &[
+    "TransportError",
+    "TooManyRequests",
+    "TimeoutError",
+    "ServiceUnavailable",
+    "InternalError",
+    "status: 502",
+    "status: 503",
+    "status: 504",
+];
+
+pub fn is_retryable(error: &str) -> bool {
+    RETRYABLE_MARKERS.iter().any(|marker| error.contains(marker))
+}
+
+/// Retries transient RPC failures with jittered exponential backoff.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32, base_delay: Duration) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            base_delay,
+        }
+    }
+
+    pub fn from_config(config: &NearConfig) -> Self {
+        Self::new(config.rpc_max_attempts, Duration::from_millis(config.rpc_retry_base_delay_ms))
+    }
+
+    /// Run `call` until it succeeds, fails with a non-retryable error or
+    /// runs out of attempts. The last error is returned.
+    pub async fn run<T, E, F, Fut>(&self, operation: &str, mut call: F) -> Result<T, E>
+    where
+        E: Debug,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match call().await {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    let error = format!("{:?}", e);
+                    if attempt >= self.max_attempts || !is_retryable(&error) {
+                        return Err(e);
+                    }
+
+                    let delay = self.delay(attempt);
+                    warn!("{} failed (attempt {}/{}), retrying in {}ms: {}",
+                          operation, attempt, self.max_attempts, delay.as_millis(), error);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    // Between half and all of `base_delay * 2^(attempt - 1)`, so clients
+    // that failed together don't retry in lockstep
+    fn delay(&self, attempt: u32) -> Duration {
+        let ceiling = self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(MAX_DELAY);
+        let half = ceiling / 2;
+
+        half + half.mul_f64(jitter())
+    }
+}
+
+// Uniform in [0, 1); the std hasher is randomly keyed per instance
+fn jitter() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    // Stands in for the RPC transport: fails with `error` `failures` times,
+    // then answers
+    struct MockTransport {
+        calls: AtomicU32,
+        failures: u32,
+        error: &'static str,
+    }
+
+    impl MockTransport {
+        async fn call(&self) -> Result<u64, String> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            if call <= self.failures {
+                Err(self.error.to_string())
+            } else {
+                Ok(42)
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_are_retried() {
+        let transport = MockTransport {
+            calls: AtomicU32::new(0),
+            failures: 2,
+            error: "TransportError(SendError(Server returned 502))",
+        };
+        let policy = RetryPolicy::new(3, Duration::from_millis(1));
+
+        assert_eq!(policy.run("view", || transport.call()).await, Ok(42));
+        assert_eq!(transport.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_contract_panics_are_not_retried() {
+        let transport = MockTransport {
+            calls: AtomicU32::new(0),
+            failures: 2,
+            error: "ServerError(HandlerError(ContractExecutionError { vm_error: \"Smart contract panicked: Task not found\" }))",
+        };
+        let policy = RetryPolicy::new(3, Duration::from_millis(1));
+
+        assert!(policy.run("view", || transport.call()).await.is_err());
+        assert_eq!(transport.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let transport = MockTransport {
+            calls: AtomicU32::new(0),
+            failures: 5,
+            error: "ServerError(ResponseStatusError(TooManyRequests))",
+        };
+        let policy = RetryPolicy::new(3, Duration::from_millis(1));
+
+        assert!(policy.run("view", || transport.call()).await.is_err());
+        assert_eq!(transport.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_delay_grows_with_jitter_and_is_capped() {
+        let policy = RetryPolicy::new(5, Duration::from_millis(200));
+
+        for _ in 0..20 {
+            let first = policy.delay(1);
+            assert!(first >= Duration::from_millis(100) && first <= Duration::from_millis(200));
+
+            let third = policy.delay(3);
+            assert!(third >= Duration::from_millis(400) && third <= Duration::from_millis(800));
+        }
+        assert!(policy.delay(20) <= MAX_DELAY);
+    }
+}
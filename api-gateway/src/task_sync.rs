@@ -0,0 +1,266 @@
+use anyhow::Result;
+use chrono::{DateTime, TimeZone, Utc};
+use sqlx::SqlitePool;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::{
+    handlers::AppState,
+    models::{Task, TaskStatus},
+    near_client::ContractTask,
+    webhooks,
+};
+
+// Spacing between view calls within a pass, to stay polite to the RPC node
+const REQUEST_SPACING: Duration = Duration::from_millis(100);
+
+/// What the contract says about a task, in database terms.
+#[derive(Debug, PartialEq)]
+struct ChainUpdate {
+    status: TaskStatus,
+    assigned_node_id: Option<String>,
+    result_data: Option<String>,
+    proof_hash: Option<String>,
+    started_at: Option<DateTime<Utc>>,
+    completed_at: Option<DateTime<Utc>>,
+    error_message: Option<&'static str>,
+}
+
+/// Periodically copy the on-chain state of every submitted but unfinished
+/// task into the database. Waits longer after each failed pass, up to
+/// `max_backoff_seconds`, and drops back to the interval once RPC recovers.
+pub fn spawn_task_sync(state: AppState) -> tokio::task::JoinHandle<()> {
+    let config = state.config.task_sync.clone();
+    let interval = Duration::from_secs(config.interval_seconds);
+    let max_backoff = Duration::from_secs(config.max_backoff_seconds).max(interval);
+
+    tokio::spawn(async move {
+        let mut delay = interval;
+        loop {
+            tokio::time::sleep(delay).await;
+
+            delay = match sync_tasks(&state, config.batch_size).await {
+                Ok(updated) => {
+                    if updated > 0 {
+                        debug!("Synced {} tasks from the contract", updated);
+                    }
+                    interval
+                }
+                Err(e) => {
+                    let next = backoff(delay, max_backoff);
+                    warn!("Task status sync failed, retrying in {}s: {:#}", next.as_secs(), e);
+                    next
+                }
+            };
+        }
+    })
+}
+
+fn backoff(delay: Duration, max: Duration) -> Duration {
+    (delay * 2).min(max)
+}
+
+// Stops at the first RPC error so a struggling node isn't hit with the rest
+// of the batch. Returns how many tasks changed.
+async fn sync_tasks(state: &AppState, batch_size: u32) -> Result<usize> {
+    let tasks = unfinished_tasks(&state.db_pool, batch_size).await?;
+    let mut updated = 0;
+
+    for task in tasks {
+        let Some(contract_task_id) = task.contract_task_id else {
+            continue;
+        };
+
+        match state.near_client.get_task(contract_task_id as u64).await? {
+            Some(chain_task) => {
+                if let Some(update) = chain_update(&chain_task) {
+                    if apply_update(&state.db_pool, &task, &update).await? {
+                        updated += 1;
+                        if is_terminal(&update.status) {
+                            tokio::spawn(webhooks::deliver_task_result(state.clone(), task.id));
+                        }
+                    }
+                }
+            }
+            None => warn!("Task {} (contract task {}) not found on-chain", task.id, contract_task_id),
+        }
+
+        tokio::time::sleep(REQUEST_SPACING).await;
+    }
+
+    Ok(updated)
+}
+
+// Oldest first, so a backlog is worked through in order
+async fn unfinished_tasks(pool: &SqlitePool, limit: u32) -> Result<Vec<Task>> {
+    let tasks = sqlx::query_as::<_, Task>(
+        "SELECT * FROM tasks
+         WHERE contract_task_id IS NOT NULL AND status IN ('submitted', 'assigned', 'inprogress')
+         ORDER BY created_at
+         LIMIT ?1",
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(tasks)
+}
+
+fn chain_update(chain_task: &ContractTask) -> Option<ChainUpdate> {
+    let (status, error_message) = match chain_task.status.as_str() {
+        "Pending" => (TaskStatus::Submitted, None),
+        "Assigned" => (TaskStatus::Assigned, None),
+        // Awaiting the requester's approval still counts as running
+        "InProgress" | "PendingApproval" => (TaskStatus::InProgress, None),
+        "Completed" => (TaskStatus::Completed, None),
+        "Failed" => (TaskStatus::Failed, Some("Nodes did not agree on a result")),
+        "Disputed" => (TaskStatus::Failed, Some("Result disputed by the requester")),
+        "TimedOut" => (TaskStatus::Expired, Some("Timed out on-chain")),
+        "Cancelled" => (TaskStatus::Cancelled, None),
+        other => {
+            warn!("Unknown on-chain status {} for contract task {}", other, chain_task.id);
+            return None;
+        }
+    };
+
+    let timestamp = |nanos: Option<u64>| nanos.map(|nanos| Utc.timestamp_nanos(nanos as i64));
+
+    Some(ChainUpdate {
+        status,
+        assigned_node_id: chain_task.assignee.clone(),
+        result_data: chain_task.output.clone(),
+        proof_hash: chain_task.proof_hash.clone(),
+        started_at: timestamp(chain_task.assigned_at),
+        completed_at: timestamp(chain_task.completed_at),
+        error_message,
+    })
+}
+
+fn is_terminal(status: &TaskStatus) -> bool {
+    matches!(status, TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled | TaskStatus::Expired)
+}
+
+// Only writes over the status the task was read with, so a user cancelling
+// it meanwhile wins. Returns whether the row changed.
+async fn apply_update(pool: &SqlitePool, task: &Task, update: &ChainUpdate) -> Result<bool> {
+    if update.status == task.status && update.assigned_node_id == task.assigned_node_id {
+        return Ok(false);
+    }
+
+    let result = sqlx::query(
+        "UPDATE tasks SET
+            status = ?1,
+            assigned_node_id = COALESCE(?2, assigned_node_id),
+            result_data = COALESCE(?3, result_data),
+            proof_hash = COALESCE(?4, proof_hash),
+            started_at = COALESCE(started_at, ?5),
+            completed_at = COALESCE(?6, completed_at),
+            error_message = COALESCE(?7, error_message)
+         WHERE id = ?8 AND status = ?9",
+    )
+    .bind(&update.status)
+    .bind(&update.assigned_node_id)
+    .bind(&update.result_data)
+    .bind(&update.proof_hash)
+    .bind(update.started_at)
+    .bind(update.completed_at)
+    .bind(update.error_message)
+    .bind(task.id)
+    .bind(&task.status)
+    .execute(pool)
+    .await?;
+
+    let changed = result.rows_affected() > 0;
+    if changed {
+        info!("Task {} is now {:?} on-chain", task.id, update.status);
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+    use crate::{config::DatabaseConfig, database::{create_user, init_database}};
+
+    async fn load_task(pool: &SqlitePool, task_id: Uuid) -> Task {
+        sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE id = ?1")
+            .bind(task_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    fn chain_task(status: &str) -> ContractTask {
+        ContractTask {
+            id: 7,
+            status: status.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_chain_status_mapping() {
+        assert_eq!(chain_update(&chain_task("Pending")).unwrap().status, TaskStatus::Submitted);
+        assert_eq!(chain_update(&chain_task("PendingApproval")).unwrap().status, TaskStatus::InProgress);
+
+        let timed_out = chain_update(&chain_task("TimedOut")).unwrap();
+        assert_eq!(timed_out.status, TaskStatus::Expired);
+        assert_eq!(timed_out.error_message, Some("Timed out on-chain"));
+
+        assert!(chain_update(&chain_task("Exploded")).is_none());
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let max = Duration::from_secs(60);
+
+        assert_eq!(backoff(Duration::from_secs(10), max), Duration::from_secs(20));
+        assert_eq!(backoff(Duration::from_secs(40), max), max);
+    }
+
+    #[tokio::test]
+    async fn test_completed_task_is_written_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = init_database(&format!("sqlite:{}", dir.path().join("gateway.db").display()), &DatabaseConfig::default()).await.unwrap();
+        let user = create_user(&pool, "alice", &None, "hash", None).await.unwrap();
+
+        let task_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO tasks (id, user_id, contract_task_id, task_type, model_name, input_data, status, estimated_cost, created_at, expires_at)
+             VALUES (?1, ?2, 7, 'inference', 'gpt2', 'hello', 'submitted', '1000', ?3, ?3)",
+        )
+        .bind(task_id)
+        .bind(user.id)
+        .bind(Utc::now())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let tasks = unfinished_tasks(&pool, 10).await.unwrap();
+        assert_eq!(tasks.len(), 1);
+
+        let update = chain_update(&ContractTask {
+            id: 7,
+            status: "Completed".to_string(),
+            assignee: Some("node.testnet".to_string()),
+            output: Some("{\"result\":\"hi\"}".to_string()),
+            proof_hash: Some("abc".to_string()),
+            assigned_at: Some(1_700_000_000_000_000_000),
+            completed_at: Some(1_700_000_060_000_000_000),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(apply_update(&pool, &tasks[0], &update).await.unwrap());
+
+        let task = load_task(&pool, task_id).await;
+        assert_eq!(task.status, TaskStatus::Completed);
+        assert_eq!(task.assigned_node_id.as_deref(), Some("node.testnet"));
+        assert_eq!(task.proof_hash.as_deref(), Some("abc"));
+        assert_eq!(task.completed_at, Some(Utc.timestamp_nanos(1_700_000_060_000_000_000)));
+        assert!(unfinished_tasks(&pool, 10).await.unwrap().is_empty());
+
+        // A stale read doesn't overwrite the newer state
+        assert!(!apply_update(&pool, &tasks[0], &chain_update(&chain_task("Assigned")).unwrap()).await.unwrap());
+    }
+}
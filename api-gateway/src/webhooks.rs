@@ -0,0 +1,239 @@
+use hmac::{Hmac, Mac};
+use reqwest::Url;
+use sha2::Sha256;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    handlers::AppState,
+    models::{Task, TaskResultResponse},
+};
+
+pub const SIGNATURE_HEADER: &str = "X-DeAI-Signature";
+
+const MAX_ATTEMPTS: u32 = 3;
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Check that a callback URL is https and only resolves to public addresses,
+/// so webhooks cannot be pointed at the gateway's internal network.
+pub async fn validate_callback_url(url: &str) -> Result<(), String> {
+    resolve_callback_url(url).await.map(|_| ())
+}
+
+// Resolves the callback host, rejecting it unless every address is public.
+// Returns the host with the addresses that were checked.
+async fn resolve_callback_url(url: &str) -> Result<(String, Vec<SocketAddr>), String> {
+    let parsed = Url::parse(url).map_err(|_| "Invalid callback URL".to_string())?;
+
+    if parsed.scheme() != "https" {
+        return Err("Callback URL must use https".to_string());
+    }
+
+    let host = parsed.host_str().ok_or("Callback URL must include a host")?;
+    let port = parsed.port_or_known_default().unwrap_or(443);
+
+    let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|_| "Callback URL host could not be resolved".to_string())?
+        .collect();
+
+    if addresses.is_empty() || !addresses.iter().all(|addr| is_public_ip(addr.ip())) {
+        return Err("Callback URL must not point to a private or loopback address".to_string());
+    }
+
+    Ok((host.to_string(), addresses))
+}
+
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let octets = ip.octets();
+            let shared = octets[0] == 100 && (octets[1] & 0xc0) == 64; // 100.64.0.0/10
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || shared)
+        }
+        IpAddr::V6(ip) => {
+            if let Some(mapped) = ip.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(mapped));
+            }
+            let segments = ip.segments();
+            let unique_local = (segments[0] & 0xfe00) == 0xfc00; // fc00::/7
+            let link_local = (segments[0] & 0xffc0) == 0xfe80; // fe80::/10
+            !(ip.is_loopback() || ip.is_unspecified() || ip.is_multicast() || unique_local || link_local)
+        }
+    }
+}
+
+/// Random per-task secret handed back to the submitter for verifying signatures.
+pub fn generate_secret() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Hex-encoded HMAC-SHA256 of the request body.
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// POST the task's result to its callback URL, if it has one, and record the
+/// delivery status. Call this whenever a task reaches `Completed` or `Failed`.
+pub async fn deliver_task_result(state: AppState, task_id: Uuid) {
+    let task = match sqlx::query_as!(Task, "SELECT * FROM tasks WHERE id = ?1", task_id)
+        .fetch_optional(&state.db_pool)
+        .await
+    {
+        Ok(Some(task)) => task,
+        Ok(None) => return,
+        Err(e) => {
+            warn!("Failed to load task {} for webhook delivery: {}", task_id, e);
+            return;
+        }
+    };
+
+    let (Some(url), Some(secret)) = (task.webhook_url.clone(), task.webhook_secret.clone()) else {
+        return;
+    };
+
+    let delivered = match send_with_retries(&url, &secret, &result_payload(task)).await {
+        Ok(()) => {
+            info!("Delivered webhook for task {}", task_id);
+            true
+        }
+        Err(e) => {
+            warn!("Webhook delivery for task {} failed: {}", task_id, e);
+            false
+        }
+    };
+
+    let webhook_status = if delivered { "delivered" } else { "failed" };
+    if let Err(e) = sqlx::query!(
+        "UPDATE tasks SET webhook_status = ?1 WHERE id = ?2",
+        webhook_status,
+        task_id
+    )
+    .execute(&state.db_pool)
+    .await
+    {
+        warn!("Failed to record webhook status for task {}: {}", task_id, e);
+    }
+}
+
+fn result_payload(task: Task) -> TaskResultResponse {
+    let execution_time_ms = match (task.started_at, task.completed_at) {
+        (Some(started), Some(completed)) => Some((completed - started).num_milliseconds()),
+        _ => None,
+    };
+
+    TaskResultResponse {
+        task_id: task.id,
+        status: task.status,
+        result_data: task.result_data.as_deref().and_then(|data| serde_json::from_str(data).ok()),
+        proof_hash: task.proof_hash,
+        error_message: task.error_message,
+        execution_time_ms,
+        completed_at: task.completed_at,
+    }
+}
+
+async fn send_with_retries(url: &str, secret: &str, payload: &TaskResultResponse) -> Result<(), String> {
+    // Re-check the destination in case DNS changed since submission, then pin
+    // the checked addresses so the client cannot resolve the host again
+    let (host, addresses) = resolve_callback_url(url).await?;
+
+    let body = serde_json::to_vec(payload).map_err(|e| e.to_string())?;
+    let signature = sign_payload(secret, &body);
+    let client = reqwest::Client::builder()
+        .resolve_to_addrs(&host, &addresses)
+        .timeout(REQUEST_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let mut backoff = INITIAL_BACKOFF;
+    let mut last_error = String::new();
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        let response = client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .header(SIGNATURE_HEADER, format!("sha256={}", signature))
+            .body(body.clone())
+            .send()
+            .await;
+
+        match response {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => last_error = format!("HTTP {}", response.status()),
+            Err(e) => last_error = e.to_string(),
+        }
+
+        if attempt < MAX_ATTEMPTS {
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+
+    Err(format!("{} attempts failed, last error: {}", MAX_ATTEMPTS, last_error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_public_ip() {
+        assert!(is_public_ip("93.184.216.34".parse().unwrap()));
+        assert!(is_public_ip("2606:2800:220:1::248".parse().unwrap()));
+
+        for ip in ["127.0.0.1", "10.0.0.5", "192.168.1.1", "169.254.169.254", "100.64.0.1", "::1", "fd00::1", "::ffff:127.0.0.1"] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{} should be rejected", ip);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rejects_non_https_and_loopback_urls() {
+        assert!(validate_callback_url("http://example.com/hook").await.is_err());
+        assert!(validate_callback_url("https://127.0.0.1/hook").await.is_err());
+        assert!(validate_callback_url("not a url").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolved_addresses_are_pinned_for_delivery() {
+        let (host, addresses) = resolve_callback_url("https://93.184.216.34:8443/hook").await.unwrap();
+        assert_eq!(host, "93.184.216.34");
+        assert_eq!(addresses, vec!["93.184.216.34:8443".parse::<SocketAddr>().unwrap()]);
+
+        // Delivery refuses a destination that no longer resolves publicly
+        let payload = TaskResultResponse {
+            task_id: Uuid::new_v4(),
+            status: crate::models::TaskStatus::Completed,
+            result_data: None,
+            proof_hash: None,
+            error_message: None,
+            execution_time_ms: None,
+            completed_at: None,
+        };
+        let error = send_with_retries("https://127.0.0.1/hook", "secret", &payload).await.unwrap_err();
+        assert!(error.contains("private or loopback"));
+    }
+
+    #[test]
+    fn test_sign_payload() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign_payload("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use near_sdk::json_types::U128;
+use near_sdk::serde::Serialize;
+use near_sdk::serde_json;
+use near_sdk::{log, AccountId};
+
+use crate::{NodeResources, TaskPriority};
+
+pub const EVENT_STANDARD: &str = "deai";
+pub const EVENT_VERSION: &str = "1.0.0";
+
+// NEP-297 event envelope
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct EventLog<'a> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event: &'a DeAIEvent,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum DeAIEvent {
+    NodeRegistered(Vec<NodeRegisteredData>),
+    TaskSubmitted(Vec<TaskSubmittedData>),
+    TaskAssigned(Vec<TaskAssignedData>),
+    TaskCompleted(Vec<TaskCompletedData>),
+    TaskTimedOut(Vec<TaskTimedOutData>),
+    TaskCancelled(Vec<TaskCancelledData>),
+    TaskDeclined(Vec<TaskDeclinedData>),
+    TaskConsensusFailed(Vec<TaskConsensusFailedData>),
+    TaskAwaitingApproval(Vec<TaskAwaitingApprovalData>),
+    TaskDisputed(Vec<TaskDisputedData>),
+    TaskDisputeResolved(Vec<TaskDisputeResolvedData>),
+    NodeSlashed(Vec<NodeSlashedData>),
+    NodeBlacklisted(Vec<NodeBlacklistedData>),
+    StakeToppedUp(Vec<StakeToppedUpData>),
+    NodeHeartbeat(Vec<NodeHeartbeatData>),
+    RewardsClaimed(Vec<RewardsClaimedData>),
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NodeRegisteredData {
+    pub account_id: AccountId,
+    pub stake: U128,
+    pub public_ip: String,
+    pub api_endpoint: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TaskSubmittedData {
+    pub task_id: u64,
+    pub requester: AccountId,
+    pub reward_amount: U128,
+    pub priority: TaskPriority,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TaskAssignedData {
+    pub task_id: u64,
+    pub assignee: AccountId,
+    pub timeout_at: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TaskCompletedData {
+    pub task_id: u64,
+    pub assignee: AccountId,
+    pub reward_amount: U128,
+    pub proof_hash: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TaskTimedOutData {
+    pub task_id: u64,
+    pub assignee: Option<AccountId>,
+    pub refund_amount: U128,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TaskCancelledData {
+    pub task_id: u64,
+    pub requester: AccountId,
+    pub refund_amount: U128,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TaskDeclinedData {
+    pub task_id: u64,
+    pub node_id: AccountId,
+    pub reason: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TaskConsensusFailedData {
+    pub task_id: u64,
+    pub refund_amount: U128,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TaskAwaitingApprovalData {
+    pub task_id: u64,
+    pub requester: AccountId,
+    pub approval_deadline: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TaskDisputedData {
+    pub task_id: u64,
+    pub requester: AccountId,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TaskDisputeResolvedData {
+    pub task_id: u64,
+    pub paid_node: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NodeSlashedData {
+    pub account_id: AccountId,
+    pub amount: U128,
+    pub reputation_score: u32,
+    pub reason: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NodeBlacklistedData {
+    pub account_id: AccountId,
+    pub reason: String,
+    pub forfeited_stake: U128,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StakeToppedUpData {
+    pub account_id: AccountId,
+    pub amount: U128,
+    pub effective_stake: U128,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NodeHeartbeatData {
+    pub account_id: AccountId,
+    pub resources: NodeResources,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RewardsClaimedData {
+    pub account_id: AccountId,
+    pub amount: U128,
+}
+
+impl DeAIEvent {
+    pub fn to_event_json(&self) -> String {
+        let event = EventLog {
+            standard: EVENT_STANDARD,
+            version: EVENT_VERSION,
+            event: self,
+        };
+        format!("EVENT_JSON:{}", serde_json::to_string(&event).unwrap())
+    }
+
+    pub fn emit(&self) {
+        log!("{}", self.to_event_json());
+    }
+}
@@ -1,20 +1,73 @@
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize, BorshSchema};
-use near_sdk::collections::{UnorderedMap, Vector, LookupMap};
-use near_sdk::{near, AccountId, env, Promise, json_types::U128, PanicOnDefault, NearToken, log, require, Gas};
+use near_sdk::collections::{UnorderedMap, Vector, LookupMap, LazyOption};
+use near_sdk::{near, AccountId, env, Promise, PromiseError, PromiseOrValue, json_types::U128, PanicOnDefault, NearToken, log, require, Gas};
+use near_sdk::serde_json::{json, Value};
 use schemars::JsonSchema;
 use near_contract_standards::fungible_token::{FungibleToken, FungibleTokenCore, Balance};
+use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+use near_contract_standards::storage_management::{
+    StorageBalance, StorageBalanceBounds, StorageManagement,
+};
+use near_contract_standards::fungible_token::metadata::{
+    FungibleTokenMetadata, FungibleTokenMetadataProvider, FT_METADATA_SPEC,
+};
 use serde::{Deserialize, Serialize};
 
+pub mod events;
+pub mod ref_finance_integration;
+
+use ref_finance_integration::{fungible_token, GAS_FOR_FT_TRANSFER};
+
+use events::{
+    DeAIEvent, NodeBlacklistedData, NodeHeartbeatData, NodeRegisteredData, NodeSlashedData, TaskAssignedData, TaskCompletedData,
+    RewardsClaimedData, StakeToppedUpData, TaskAwaitingApprovalData, TaskCancelledData, TaskConsensusFailedData, TaskDeclinedData,
+    TaskDisputedData, TaskDisputeResolvedData, TaskSubmittedData, TaskTimedOutData,
+};
+
 
 pub const MIN_STAKE_YOCTO: u128 = 1_000_000_000_000_000_000_000_000; // 1 NEAR
 pub const STORAGE_COST: Balance = 1_000_000_000_000_000_000_000; // 0.001 NEAR
 pub const ONE_YOCTO: u128 = 1;
-pub const HEARTBEAT_TIMEOUT: u64 = 300_000_000_000; // 5 minutes in nanoseconds
+pub const HEARTBEAT_TIMEOUT: u64 = 300_000_000_000; // 5 minutes in nanoseconds, for standard nodes
+pub const ENTERPRISE_HEARTBEAT_TIMEOUT: u64 = 120_000_000_000; // 2 minutes
+pub const HOBBY_HEARTBEAT_TIMEOUT: u64 = 900_000_000_000; // 15 minutes
+pub const MIN_HEARTBEAT_TIMEOUT: u64 = 60_000_000_000; // 1 minute
+pub const MAX_HEARTBEAT_TIMEOUT: u64 = 3600_000_000_000; // 1 hour
+pub const TOKEN_NAME: &str = "DeAI Compute Token";
+pub const TOKEN_SYMBOL: &str = "DEAI";
 pub const MAX_REPUTATION: u32 = 1000;
+pub const BASE_REPUTATION: u32 = 100; // New nodes start here
+pub const MAX_REPUTATION_BONUS_BPS: u32 = 1000; // +10% reward at max reputation
 pub const REPUTATION_GAIN: u32 = 10;
 pub const REPUTATION_LOSS: u32 = 50;
 pub const CALLBACK_GAS: Gas = Gas::from_tgas(5); // 5 TGas for callbacks
 pub const MAX_TASK_TIMEOUT: u64 = 3600_000_000_000; // 1 hour in nanoseconds
+pub const MAX_RESULTS_BATCH: usize = 20;
+pub const MAX_TASKS_BATCH: usize = 20;
+pub const AUDIT_EVENT_STANDARD: &str = "deai-audit";
+pub const AUDIT_EVENT_VERSION: &str = "1.0.0";
+pub const DEFAULT_MIN_REPUTATION_FOR_ASSIGNMENT: u32 = 50;
+pub const MAX_PAGE_LIMIT: u64 = 100;
+pub const REPUTATION_DECAY_FLOOR: u32 = 50;
+pub const DEFAULT_REPUTATION_DECAY_PER_DAY: u32 = 5;
+pub const NANOS_PER_DAY: u64 = 86_400_000_000_000;
+pub const DEFAULT_UNBONDING_PERIOD: u64 = 3 * NANOS_PER_DAY;
+pub const DEFAULT_SLASH_COMPENSATION_BPS: u32 = 5000; // 50% of a slash goes to the requester
+pub const BPS_DENOMINATOR: u32 = 10_000;
+pub const MAX_PLATFORM_FEE_BPS: u32 = 2000; // 20%
+pub const MAX_REDUNDANCY: u8 = 5;
+pub const DEFAULT_APPROVAL_WINDOW: u64 = NANOS_PER_DAY;
+pub const MAX_LEADERBOARD_NODES: u64 = 500;
+pub const TIMEOUT_SLASH_BPS: u32 = 1000; // 10% of stake
+pub const MAX_FORCE_SLASH_BPS: u16 = 5000; // 50% of stake
+pub const MAX_ALLOWED_NODES: usize = 20;
+pub const MAX_NODE_CAPABILITIES: usize = 16;
+pub const MAX_CAPABILITY_LEN: usize = 64;
+pub const DEFAULT_MIN_COMPUTE_COST: Balance = 1;
+pub const DEFAULT_MAX_COMPUTE_COST: Balance = 100_000_000_000_000_000_000_000_000; // 100 NEAR
+pub const MAX_OVERPAYMENT: Balance = 10_000_000_000_000_000_000_000; // 0.01 NEAR
+pub const MAX_RESULT_OUTPUT_LEN: usize = 10_000; // Bytes in a single-shot result
+pub const MAX_CHUNKED_OUTPUT_LEN: usize = 100_000; // Bytes across all chunks of a result
 
 #[derive(BorshDeserialize, BorshSerialize, BorshSchema, Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(crate = "near_sdk::serde")]
@@ -31,6 +84,11 @@ pub struct NodeInfo {
     pub reputation_score: u32,
     pub slashed_amount: u128,
     pub registration_time: u64,
+    pub last_reputation_update: u64,
+    pub unbond_available_at: Option<u64>,
+    pub capabilities: Vec<String>, // Task types the node runs; empty means it takes any
+    pub auto_claim_rewards: bool, // Mint DEAI rewards per task instead of accruing them for `claim_rewards`
+    pub tier: NodeTier, // Sets how long the node may miss heartbeats
 }
 
 #[derive(BorshDeserialize, BorshSerialize, BorshSchema, Serialize, Deserialize, Clone, JsonSchema)]
@@ -38,7 +96,10 @@ pub struct NodeInfo {
 pub struct Task {
     pub id: u64,
     pub description: String,  // JSON: {model: "url", input: "data", task_type: "inference"}
-    pub assignee: Option<String>,
+    pub assignee: Option<String>, // First of `assignees`
+    pub assignees: Vec<String>,
+    pub redundancy: u8, // Nodes that independently run the task
+    pub submissions: Vec<ResultSubmission>,
     pub status: TaskStatus,
     pub output: Option<String>,
     pub proof_hash: Option<String>,
@@ -47,8 +108,105 @@ pub struct Task {
     pub assigned_at: Option<u64>,
     pub timeout_at: Option<u64>,
     pub reward_amount: Balance,
+    pub platform_fee: Balance, // Credited to the treasury once the task completes
     pub requester: String,
     pub priority: TaskPriority,
+    pub min_vram_gb: Option<u32>,
+    pub declined_by: Vec<String>, // Nodes that declined the task; it is never reassigned to them
+    pub requires_approval: bool, // Reward is held until the requester approves the result
+    pub approval_deadline: Option<u64>, // Auto-approved after this
+    pub reward_token: Option<String>, // NEP-141 token the reward is paid in; NEAR when unset
+    pub allowed_nodes: Option<Vec<String>>, // Only these nodes may run the task
+    pub task_type: Option<String>, // From the description; routes to nodes advertising it
+    pub preferred_node: Option<String>, // From the description; wins assignment whenever eligible
+}
+
+impl Task {
+    /// The full compute cost paid by the requester, platform fee included.
+    pub fn refund_amount(&self) -> Balance {
+        self.reward_amount + self.platform_fee
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, BorshSchema, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ResultSubmission {
+    pub node_id: String,
+    pub proof_hash: String,
+    pub output: String,
+}
+
+/// A result a node is streaming in chunks, sealed by `finalize_result`.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct PartialResult {
+    pub next_seq: u32,
+    pub output: String,
+    pub storage_deposit: Balance, // Attached to the chunks, returned once they are dropped
+}
+
+/// One slash of a node's stake, kept so the node can see what caused it.
+#[derive(BorshDeserialize, BorshSerialize, BorshSchema, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SlashEvent {
+    pub timestamp: u64,
+    pub amount: U128,
+    pub reason: String, // "timeout", "result_mismatch", or the owner's reason
+    pub task_id: Option<u64>, // The task that caused it, if any
+}
+
+/// Averages over every task whose result was accepted, in nanoseconds.
+/// `None` until a task has completed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TimingStats {
+    pub completed_tasks: u64,
+    pub average_time_to_assign: Option<u64>,
+    pub average_time_to_complete: Option<u64>,
+}
+
+/// Registered nodes against the owner-set registration caps. `None` caps
+/// are unlimited.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NetworkCapacity {
+    pub total_nodes: u64,
+    pub max_network_nodes: Option<u64>,
+    pub max_nodes_per_owner: Option<u32>,
+}
+
+// How a new task is run and paid for
+struct TaskTerms {
+    redundancy: u8,
+    requires_approval: bool,
+    reward_token: Option<AccountId>,
+    allowed_nodes: Option<Vec<AccountId>>,
+}
+
+impl Default for TaskTerms {
+    fn default() -> Self {
+        Self {
+            redundancy: 1,
+            requires_approval: false,
+            reward_token: None,
+            allowed_nodes: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TaskInput {
+    pub description: String,
+}
+
+/// Live load a node may attach to its heartbeat. Emitted as an event, not stored.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NodeResources {
+    pub gpu_utilization_percent: Option<u32>,
+    pub gpu_memory_used_mb: Option<u64>,
+    pub gpu_memory_total_mb: Option<u64>,
+    pub active_tasks: u32,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, BorshSchema, Serialize, Deserialize, Clone, PartialEq, Debug, JsonSchema)]
@@ -61,6 +219,8 @@ pub enum TaskStatus {
     Failed,
     TimedOut,
     Disputed,
+    Cancelled,
+    PendingApproval,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, BorshSchema, Serialize, Deserialize, Clone, PartialEq, Debug, JsonSchema)]
@@ -72,21 +232,117 @@ pub enum TaskPriority {
     Urgent,
 }
 
+/// Service level of a node, assigned by the owner. Stricter tiers stop
+/// counting as live sooner after a missed heartbeat.
+#[derive(BorshDeserialize, BorshSerialize, BorshSchema, Serialize, Deserialize, Clone, PartialEq, Debug, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub enum NodeTier {
+    Enterprise,
+    Standard,
+    Hobby,
+}
+
+/// Nanoseconds a node of each tier may go without a heartbeat and still be live.
+#[derive(BorshDeserialize, BorshSerialize, BorshSchema, Serialize, Deserialize, Clone, PartialEq, Debug, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HeartbeatTimeouts {
+    pub enterprise: u64,
+    pub standard: u64,
+    pub hobby: u64,
+}
+
+impl Default for HeartbeatTimeouts {
+    fn default() -> Self {
+        Self {
+            enterprise: ENTERPRISE_HEARTBEAT_TIMEOUT,
+            standard: HEARTBEAT_TIMEOUT,
+            hobby: HOBBY_HEARTBEAT_TIMEOUT,
+        }
+    }
+}
+
+impl HeartbeatTimeouts {
+    pub fn for_tier(&self, tier: &NodeTier) -> u64 {
+        match tier {
+            NodeTier::Enterprise => self.enterprise,
+            NodeTier::Standard => self.standard,
+            NodeTier::Hobby => self.hobby,
+        }
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, BorshSchema, Serialize, Deserialize, Clone, PartialEq, Debug, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub enum AssignmentStrategy {
+    HighestReputation,
+    LeastLoaded,
+}
+
 #[near(contract_state)]
 #[derive(PanicOnDefault)]
 pub struct DeAICompute {
     pub nodes: UnorderedMap<AccountId, NodeInfo>,
+    pub registered_ips: LookupMap<String, AccountId>,
     pub active_tasks: UnorderedMap<u64, Task>,
     pub completed_tasks: LookupMap<u64, Task>,
     pub pending_tasks: Vector<u64>,
+    pub node_active_tasks: LookupMap<AccountId, u32>,
     pub task_counter: u64,
     pub token: FungibleToken,
+    pub metadata: LazyOption<FungibleTokenMetadata>,
     pub min_stake: u128,
     pub total_rewards_distributed: Balance,
     pub owner_id: AccountId,
     pub paused: bool,
     pub max_tasks_per_node: u32,
     pub task_timeout_duration: u64,
+    pub audit_enabled: bool,
+    pub assignment_strategy: AssignmentStrategy,
+    pub reputation_decay_per_day: u32,
+    pub min_reputation_for_assignment: u32,
+    pub unbonding_period: u64,
+    pub slash_compensation_bps: u32,
+    pub total_slashed: Balance,
+    pub stale_sweep_cursor: u64,
+    pub platform_fee_bps: u32,
+    pub treasury_id: AccountId,
+    pub treasury_balance: Balance,
+    pub ref_pool_config: Option<ref_finance_integration::LiquidityPoolConfig>,
+    pub cached_price: Option<(U128, u64)>,
+    pub liquidity_positions: LookupMap<u64, ref_finance_integration::LiquidityPosition>,
+    pub slippage_bps: u32,
+    pub approval_window: u64,
+    pub blacklist: LookupMap<AccountId, String>, // Banned node accounts and why
+    // Sums over completed tasks of creation-to-assignment and
+    // assignment-to-result nanoseconds, for `get_timing_stats`
+    pub total_assign_time: u128,
+    pub total_execution_time: u128,
+    pub timed_task_count: u64,
+    // NEP-141 balances held for accounts, keyed by (account, token): requester
+    // escrow for token-paid tasks, refunds, fees and rewards that failed to send
+    pub token_deposits: LookupMap<(AccountId, AccountId), Balance>,
+    // DEAI still available to mint as reputation bonuses
+    pub reputation_bonus_budget: Balance,
+    // Ids of each requester's tasks in submission order, keyed by
+    // (requester, position), with the number submitted so far
+    pub requester_tasks: LookupMap<(AccountId, u64), u64>,
+    pub requester_task_count: LookupMap<AccountId, u64>,
+    // DEAI earned by nodes with auto-claim off, minted by `claim_rewards`
+    pub pending_rewards: LookupMap<AccountId, Balance>,
+    pub heartbeat_timeouts: HeartbeatTimeouts,
+    // Sybil limits on registration, and registered nodes per controlling
+    // account (see `registration_owner`)
+    pub max_network_nodes: Option<u64>,
+    pub max_nodes_per_owner: Option<u32>,
+    pub owner_node_counts: LookupMap<String, u32>,
+    // Accepted range of a NEAR-paid task's compute cost
+    pub min_compute_cost: Balance,
+    pub max_compute_cost: Balance,
+    // Each node's slashes in order, by (node, position)
+    pub slash_events: LookupMap<(AccountId, u64), SlashEvent>,
+    pub slash_event_count: LookupMap<AccountId, u64>,
+    // Outputs being streamed in chunks, by (task, node)
+    pub partial_results: LookupMap<(u64, AccountId), PartialResult>,
 }
 
 #[near]
@@ -98,19 +354,69 @@ impl DeAICompute {
         let mut token = FungibleToken::new(b"t".to_vec());
         token.internal_register_account(&owner_id);
         
+        let metadata = FungibleTokenMetadata {
+            spec: FT_METADATA_SPEC.to_string(),
+            name: TOKEN_NAME.to_string(),
+            symbol: TOKEN_SYMBOL.to_string(),
+            icon: None,
+            reference: None,
+            reference_hash: None,
+            decimals: ref_finance_integration::DEAI_TOKEN_DECIMALS,
+        };
+        
+        // Platform fees accrue to the owner until a treasury is configured
+        let treasury_id = owner_id.clone();
+        
         Self {
             nodes: UnorderedMap::new(b"n".to_vec()),
+            registered_ips: LookupMap::new(b"ip".to_vec()),
             active_tasks: UnorderedMap::new(b"at".to_vec()),
             completed_tasks: LookupMap::new(b"ct".to_vec()),
             pending_tasks: Vector::new(b"pt".to_vec()),
+            node_active_tasks: LookupMap::new(b"nat".to_vec()),
             task_counter: 0,
             token,
+            metadata: LazyOption::new(b"m".to_vec(), Some(&metadata)),
             min_stake: MIN_STAKE_YOCTO,
             total_rewards_distributed: 0,
             owner_id,
             paused: false,
             max_tasks_per_node: 5,
             task_timeout_duration: MAX_TASK_TIMEOUT,
+            audit_enabled: true,
+            assignment_strategy: AssignmentStrategy::HighestReputation,
+            reputation_decay_per_day: DEFAULT_REPUTATION_DECAY_PER_DAY,
+            min_reputation_for_assignment: DEFAULT_MIN_REPUTATION_FOR_ASSIGNMENT,
+            unbonding_period: DEFAULT_UNBONDING_PERIOD,
+            slash_compensation_bps: DEFAULT_SLASH_COMPENSATION_BPS,
+            total_slashed: 0,
+            stale_sweep_cursor: 0,
+            platform_fee_bps: 0,
+            treasury_id,
+            treasury_balance: 0,
+            ref_pool_config: None,
+            cached_price: None,
+            liquidity_positions: LookupMap::new(b"lp".to_vec()),
+            slippage_bps: ref_finance_integration::SLIPPAGE_TOLERANCE,
+            approval_window: DEFAULT_APPROVAL_WINDOW,
+            blacklist: LookupMap::new(b"bl".to_vec()),
+            total_assign_time: 0,
+            total_execution_time: 0,
+            timed_task_count: 0,
+            token_deposits: LookupMap::new(b"td".to_vec()),
+            reputation_bonus_budget: 0,
+            requester_tasks: LookupMap::new(b"rt".to_vec()),
+            requester_task_count: LookupMap::new(b"rtc".to_vec()),
+            pending_rewards: LookupMap::new(b"pr".to_vec()),
+            heartbeat_timeouts: HeartbeatTimeouts::default(),
+            max_network_nodes: None,
+            max_nodes_per_owner: None,
+            owner_node_counts: LookupMap::new(b"onc".to_vec()),
+            min_compute_cost: DEFAULT_MIN_COMPUTE_COST,
+            max_compute_cost: DEFAULT_MAX_COMPUTE_COST,
+            slash_events: LookupMap::new(b"se".to_vec()),
+            slash_event_count: LookupMap::new(b"sec".to_vec()),
+            partial_results: LookupMap::new(b"prs".to_vec()),
         }
     }
 
@@ -123,9 +429,40 @@ impl DeAICompute {
         require!(env::predecessor_account_id() == self.owner_id, "Only owner can call this method");
     }
     
+    fn assert_compute_cost_in_bounds(&self, compute_cost: Balance) {
+        require!(
+            compute_cost >= self.min_compute_cost,
+            format!("Compute cost is below the minimum of {} yoctoNEAR", self.min_compute_cost)
+        );
+        require!(
+            compute_cost <= self.max_compute_cost,
+            format!("Compute cost exceeds the maximum of {} yoctoNEAR", self.max_compute_cost)
+        );
+    }
+    
     fn assert_one_yocto(&self) {
         require!(env::attached_deposit().as_yoctonear() == ONE_YOCTO, "Exactly 1 yoctoNEAR required for security");
     }
+    
+    // Audit log for state-changing calls (log-only, nothing is stored)
+    fn audit(&self, method: &str, data: Value) {
+        if !self.audit_enabled {
+            return;
+        }
+        
+        let event = json!({
+            "standard": AUDIT_EVENT_STANDARD,
+            "version": AUDIT_EVENT_VERSION,
+            "event": "call",
+            "data": [{
+                "method": method,
+                "caller": env::predecessor_account_id(),
+                "args": data,
+                "timestamp": env::block_timestamp(),
+            }],
+        });
+        log!("EVENT_JSON:{}", event);
+    }
 
     // Node Registry Functions
     #[payable]
@@ -135,23 +472,49 @@ impl DeAICompute {
         gpu_specs: String,
         cpu_specs: String,
         api_endpoint: String,
+        capabilities: Option<Vec<String>>,
     ) {
+        self.audit("register_node", json!({ "public_ip": public_ip, "api_endpoint": api_endpoint, "capabilities": capabilities }));
         self.assert_not_paused();
         let account_id = env::predecessor_account_id();
         let stake = env::attached_deposit();
         
+        require!(self.blacklist.get(&account_id).is_none(), "Node is blacklisted");
+
+        // A retry of a registration that already landed, e.g. after the RPC
+        // timed out on the first attempt. Hand back the duplicate stake.
+        if self.nodes.get(&account_id).is_some() {
+            log!("Node already registered: {}", account_id);
+            if !stake.is_zero() {
+                Promise::new(account_id).transfer(stake);
+            }
+            return;
+        }
+
         require!(stake.as_yoctonear() >= self.min_stake, "Insufficient stake. Minimum: {} yoctoNEAR");
-        require!(self.nodes.get(&account_id).is_none(), "Node already registered");
         require!(!public_ip.is_empty(), "Public IP cannot be empty");
         require!(!api_endpoint.is_empty(), "API endpoint cannot be empty");
         require!(gpu_specs.len() <= 500, "GPU specs too long");
         require!(cpu_specs.len() <= 500, "CPU specs too long");
         require!(api_endpoint.len() <= 200, "API endpoint too long");
+        let capabilities = normalize_capabilities(capabilities.unwrap_or_default());
+        require!(
+            self.token.accounts.contains_key(&account_id),
+            "Account not registered for storage. Call storage_deposit first"
+        );
 
-        // Validate IP is unique - use iterator for efficiency
-        for node in self.nodes.values() {
-            require!(node.public_ip != public_ip, "IP address already registered");
+        // Validate IP is unique
+        require!(self.registered_ips.get(&public_ip).is_none(), "IP address already registered");
+
+        if let Some(max_nodes) = self.max_network_nodes {
+            require!(self.nodes.len() < max_nodes, "Network node limit reached");
+        }
+        let owner = registration_owner(&account_id);
+        let owner_nodes = self.owner_node_counts.get(&owner).unwrap_or(0);
+        if let Some(max_per_owner) = self.max_nodes_per_owner {
+            require!(owner_nodes < max_per_owner, format!("Node limit reached for {}", owner));
         }
+        self.owner_node_counts.insert(&owner, &(owner_nodes + 1));
 
         let node_info = NodeInfo {
             account_id: account_id.to_string(),
@@ -163,45 +526,176 @@ impl DeAICompute {
             is_active: true,
             last_heartbeat: env::block_timestamp(),
             total_tasks_completed: 0,
-            reputation_score: 100, // Start with base reputation
+            reputation_score: BASE_REPUTATION,
             slashed_amount: 0,
             registration_time: env::block_timestamp(),
+            last_reputation_update: env::block_timestamp(),
+            unbond_available_at: None,
+            capabilities,
+            auto_claim_rewards: true,
+            tier: NodeTier::Standard,
         };
 
+        self.registered_ips.insert(&node_info.public_ip, &account_id);
         self.nodes.insert(&account_id, &node_info);
         
-        // Register account for token rewards
-        if !self.token.accounts.contains_key(&account_id) {
-            self.token.internal_register_account(&account_id);
-        }
-        
         log!("Node registered: {}", account_id);
+        DeAIEvent::NodeRegistered(vec![NodeRegisteredData {
+            account_id: account_id.clone(),
+            stake: U128(node_info.stake),
+            public_ip: node_info.public_ip.clone(),
+            api_endpoint: node_info.api_endpoint.clone(),
+        }]).emit();
+    }
+
+    /// Add the attached deposit to the caller's stake, e.g. to restore
+    /// effective stake (`stake - slashed_amount`) after being slashed.
+    #[payable]
+    pub fn top_up_stake(&mut self) {
+        self.audit("top_up_stake", json!({}));
+        self.assert_not_paused();
+        let account_id = env::predecessor_account_id();
+        let amount = env::attached_deposit().as_yoctonear();
+        let mut node = self.nodes.get(&account_id).expect("Node not registered");
+        
+        require!(amount > 0, "Top-up amount must be positive");
+        require!(self.blacklist.get(&account_id).is_none(), "Node is blacklisted");
+        require!(node.unbond_available_at.is_none(), "Node is unbonding");
+        
+        node.stake += amount;
+        self.nodes.insert(&account_id, &node);
+        
+        log!("Stake topped up: {}, amount: {}, stake: {}", account_id, amount, node.stake);
+        DeAIEvent::StakeToppedUp(vec![StakeToppedUpData {
+            account_id,
+            amount: U128(amount),
+            effective_stake: U128(node.stake.saturating_sub(node.slashed_amount)),
+        }]).emit();
     }
 
-    pub fn heartbeat(&mut self) {
+    pub fn heartbeat(&mut self, resources: Option<NodeResources>) {
+        self.audit("heartbeat", json!({ "resources": resources }));
         self.assert_not_paused();
         let account_id = env::predecessor_account_id();
         let mut node = self.nodes.get(&account_id).expect("Node not registered").clone();
+        require!(self.blacklist.get(&account_id).is_none(), "Node is blacklisted");
+        require!(node.unbond_available_at.is_none(), "Node is unbonding");
+        // A node deactivated for being slashed below the minimum stays so until topped up
+        require!(
+            node.is_active || node.stake.saturating_sub(node.slashed_amount) >= self.min_stake,
+            "Stake below minimum, top up to reactivate"
+        );
         
+        // Inactive nodes have released their IP; reactivating takes it back
+        if !node.is_active {
+            self.claim_node_ip(&account_id, &node.public_ip);
+        }
         node.last_heartbeat = env::block_timestamp();
         node.is_active = true;
         
         self.nodes.insert(&account_id, &node);
         log!("Heartbeat from node: {}", account_id);
+        
+        if let Some(resources) = resources {
+            DeAIEvent::NodeHeartbeat(vec![NodeHeartbeatData {
+                account_id,
+                resources,
+            }])
+            .emit();
+        }
+    }
+
+    #[payable]
+    pub fn update_node_info(
+        &mut self,
+        public_ip: Option<String>,
+        gpu_specs: Option<String>,
+        cpu_specs: Option<String>,
+        api_endpoint: Option<String>,
+        capabilities: Option<Vec<String>>,
+    ) {
+        self.audit("update_node_info", json!({ "public_ip": public_ip, "api_endpoint": api_endpoint, "capabilities": capabilities }));
+        self.assert_not_paused();
+        self.assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let mut node = self.nodes.get(&account_id).expect("Node not registered");
+        
+        if let Some(public_ip) = public_ip {
+            require!(!public_ip.is_empty(), "Public IP cannot be empty");
+            if public_ip != node.public_ip {
+                require!(self.registered_ips.get(&public_ip).is_none(), "IP address already registered");
+                
+                // Inactive nodes have already released their IP
+                if node.is_active {
+                    self.registered_ips.remove(&node.public_ip);
+                    self.registered_ips.insert(&public_ip, &account_id);
+                }
+                node.public_ip = public_ip;
+            }
+        }
+        
+        if let Some(gpu_specs) = gpu_specs {
+            require!(gpu_specs.len() <= 500, "GPU specs too long");
+            node.gpu_specs = gpu_specs;
+        }
+        
+        if let Some(cpu_specs) = cpu_specs {
+            require!(cpu_specs.len() <= 500, "CPU specs too long");
+            node.cpu_specs = cpu_specs;
+        }
+        
+        if let Some(api_endpoint) = api_endpoint {
+            require!(!api_endpoint.is_empty(), "API endpoint cannot be empty");
+            require!(api_endpoint.len() <= 200, "API endpoint too long");
+            node.api_endpoint = api_endpoint;
+        }
+        
+        if let Some(capabilities) = capabilities {
+            node.capabilities = normalize_capabilities(capabilities);
+        }
+        
+        self.nodes.insert(&account_id, &node);
+        log!("Node info updated: {}", account_id);
     }
 
     #[payable]
     pub fn deactivate_node(&mut self) {
+        self.audit("deactivate_node", json!({}));
         self.assert_one_yocto();
         let account_id = env::predecessor_account_id();
         let mut node = self.nodes.get(&account_id).expect("Node not registered").clone();
         
-        require!(node.is_active, "Node already inactive");
+        // Nodes deactivated by a slash or a sweep still hold stake to unbond
+        require!(
+            node.is_active || (node.stake > 0 && node.unbond_available_at.is_none()),
+            "Node already inactive"
+        );
         
         // Check if node has pending tasks
         let has_active_tasks = self.node_has_active_task(&account_id);
         require!(!has_active_tasks, "Cannot deactivate node with active tasks");
         
+        // Stake stays slashable until the unbonding period ends
+        let unbond_available_at = env::block_timestamp() + self.unbonding_period;
+        
+        node.is_active = false;
+        node.unbond_available_at = Some(unbond_available_at);
+        self.registered_ips.remove(&node.public_ip);
+        self.nodes.insert(&account_id, &node);
+        
+        log!("Node deactivated: {}, stake unbonds at {}", account_id, unbond_available_at);
+    }
+
+    #[payable]
+    pub fn claim_unbonded_stake(&mut self) {
+        self.audit("claim_unbonded_stake", json!({}));
+        self.assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let mut node = self.nodes.get(&account_id).expect("Node not registered");
+        
+        let unbond_available_at = node.unbond_available_at.expect("Node is not unbonding");
+        require!(env::block_timestamp() >= unbond_available_at, "Unbonding period has not ended");
+        
         // Calculate amount to return (stake minus any slashing)
         let return_amount = node.stake.saturating_sub(node.slashed_amount);
         
@@ -209,34 +703,191 @@ impl DeAICompute {
             Promise::new(account_id.clone()).transfer(NearToken::from_yoctonear(return_amount));
         }
         
-        node.is_active = false;
+        node.stake = 0;
+        node.unbond_available_at = None;
+        self.nodes.insert(&account_id, &node);
+        
+        log!("Unbonded stake claimed: {}, returned: {} yoctoNEAR", account_id, return_amount);
+    }
+
+    /// Choose whether DEAI rewards are minted as each task completes, or
+    /// accrue until `claim_rewards`. Rewards already accrued stay claimable.
+    #[payable]
+    pub fn set_auto_claim_rewards(&mut self, enabled: bool) {
+        self.audit("set_auto_claim_rewards", json!({ "enabled": enabled }));
+        self.assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let mut node = self.nodes.get(&account_id).expect("Node not registered");
+        
+        node.auto_claim_rewards = enabled;
         self.nodes.insert(&account_id, &node);
         
-        log!("Node deactivated: {}, returned: {} yoctoNEAR", account_id, return_amount);
+        log!("Auto-claim rewards {} for {}", if enabled { "enabled" } else { "disabled" }, account_id);
+    }
+
+    /// Mint all DEAI rewards accrued to the caller in one transfer.
+    #[payable]
+    pub fn claim_rewards(&mut self) -> U128 {
+        self.audit("claim_rewards", json!({}));
+        self.assert_not_paused();
+        self.assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        
+        let amount = self.pending_rewards.get(&account_id).unwrap_or(0);
+        require!(amount > 0, "No rewards to claim");
+        require!(
+            self.token.accounts.contains_key(&account_id),
+            "Account not registered for storage. Call storage_deposit first"
+        );
+        
+        self.pending_rewards.remove(&account_id);
+        self.token.internal_deposit(&account_id, amount);
+        self.total_rewards_distributed += amount;
+        
+        log!("Rewards claimed: {}, amount: {}", account_id, amount);
+        DeAIEvent::RewardsClaimed(vec![RewardsClaimedData {
+            account_id,
+            amount: U128(amount),
+        }]).emit();
+        
+        U128(amount)
     }
 
     // Task Management Functions
     #[payable]
-    pub fn submit_task(&mut self, description: String, estimated_compute_cost: U128, priority: Option<TaskPriority>) {
+    /// Submit a task. With `redundancy` above 1 the task runs on that many
+    /// distinct nodes and the output a majority agrees on is accepted. With
+    /// `require_approval` the reward is held until the requester approves the
+    /// result, or the approval window passes. With `allowed_nodes` only those
+    /// nodes run the task, which stays pending until enough are available.
+    #[allow(clippy::too_many_arguments)]
+    pub fn submit_task(
+        &mut self,
+        description: String,
+        estimated_compute_cost: U128,
+        priority: Option<TaskPriority>,
+        redundancy: Option<u8>,
+        require_approval: Option<bool>,
+        reward_token: Option<AccountId>,
+        allowed_nodes: Option<Vec<AccountId>>,
+    ) {
+        self.audit("submit_task", json!({
+            "estimated_compute_cost": estimated_compute_cost,
+            "priority": priority,
+            "redundancy": redundancy,
+            "require_approval": require_approval,
+            "reward_token": reward_token,
+            "allowed_nodes": allowed_nodes,
+        }));
         self.assert_not_paused();
         let requester = env::predecessor_account_id();
         let fee = env::attached_deposit();
         let compute_cost: Balance = estimated_compute_cost.into();
+        let redundancy = redundancy.unwrap_or(1);
+        
+        // Every node's result is stored until consensus. A token-paid task
+        // takes its cost from the requester's deposit of that token instead.
+        let near_cost = if reward_token.is_some() { 0 } else { compute_cost };
+        if reward_token.is_none() {
+            self.assert_compute_cost_in_bounds(compute_cost);
+        }
+        assert_payment(fee.as_yoctonear(), near_cost + STORAGE_COST * redundancy as u128);
+        if let Some(token_id) = &reward_token {
+            require!(
+                self.internal_token_deposit(&requester, token_id) >= compute_cost,
+                "Insufficient token deposit for compute cost"
+            );
+            self.internal_withdraw_token(&requester, token_id, compute_cost);
+        }
+        
+        self.internal_create_task(
+            &requester,
+            description,
+            compute_cost,
+            priority.unwrap_or(TaskPriority::Normal),
+            TaskTerms {
+                redundancy,
+                requires_approval: require_approval.unwrap_or(false),
+                reward_token,
+                allowed_nodes,
+            },
+        );
+        
+        // Try to assign to available node
+        self.try_assign_next_task();
+    }
+
+    #[payable]
+    pub fn submit_tasks_batch(
+        &mut self,
+        tasks: Vec<TaskInput>,
+        estimated_compute_cost_each: U128,
+        priority: Option<TaskPriority>,
+    ) -> Vec<u64> {
+        self.audit("submit_tasks_batch", json!({
+            "count": tasks.len(),
+            "estimated_compute_cost_each": estimated_compute_cost_each,
+            "priority": priority,
+        }));
+        self.assert_not_paused();
+        require!(!tasks.is_empty(), "Tasks batch cannot be empty");
+        require!(tasks.len() <= MAX_TASKS_BATCH, "Tasks batch too large");
+        
+        let requester = env::predecessor_account_id();
+        let compute_cost: Balance = estimated_compute_cost_each.into();
+        self.assert_compute_cost_in_bounds(compute_cost);
+        assert_payment(env::attached_deposit().as_yoctonear(), (compute_cost + STORAGE_COST) * tasks.len() as u128);
+        
+        let priority = priority.unwrap_or(TaskPriority::Normal);
+        let mut task_ids = Vec::with_capacity(tasks.len());
+        
+        for input in tasks {
+            task_ids.push(self.internal_create_task(&requester, input.description, compute_cost, priority.clone(), TaskTerms::default()));
+        }
         
-        require!(fee.as_yoctonear() >= compute_cost + STORAGE_COST, "Insufficient payment for compute cost and storage");
+        for _ in 0..task_ids.len() {
+            self.try_assign_next_task();
+        }
+        
+        task_ids
+    }
+
+    fn internal_create_task(
+        &mut self,
+        requester: &AccountId,
+        description: String,
+        compute_cost: Balance,
+        priority: TaskPriority,
+        terms: TaskTerms,
+    ) -> u64 {
+        let TaskTerms { redundancy, requires_approval, reward_token, allowed_nodes } = terms;
         require!(!description.is_empty(), "Task description cannot be empty");
         require!(description.len() <= 1000, "Task description too long");
         require!(compute_cost > 0, "Compute cost must be positive");
-
-        // Register requester for token operations if needed
-        if !self.token.accounts.contains_key(&requester) {
-            self.token.internal_register_account(&requester);
+        require!((1..=MAX_REDUNDANCY).contains(&redundancy), "Redundancy must be between 1 and 5");
+        if let Some(allowed) = &allowed_nodes {
+            require!(allowed.len() <= MAX_ALLOWED_NODES, "Too many allowed nodes");
+            require!(allowed.len() >= redundancy as usize, "Fewer allowed nodes than the task's redundancy");
+            for node_id in allowed {
+                require!(self.nodes.get(node_id).is_some(), format!("Allowed node {} is not registered", node_id));
+            }
         }
 
+        // The platform keeps its fee, the node is rewarded with the rest. Both
+        // are held until the task completes so a failed task is refunded in full
+        let platform_fee = compute_cost * self.platform_fee_bps as u128 / BPS_DENOMINATOR as u128;
+        let reward_amount = compute_cost - platform_fee;
+
+        let min_vram_gb = parse_min_vram_gb(&description);
+        let task_type = parse_task_type(&description);
+        let preferred_node = parse_preferred_node(&description);
         let task = Task {
             id: self.task_counter,
             description,
             assignee: None,
+            assignees: Vec::new(),
+            redundancy,
+            submissions: Vec::new(),
             status: TaskStatus::Pending,
             output: None,
             proof_hash: None,
@@ -244,148 +895,764 @@ impl DeAICompute {
             completed_at: None,
             assigned_at: None,
             timeout_at: None,
-            reward_amount: compute_cost,
+            reward_amount,
+            platform_fee,
             requester: requester.to_string(),
-            priority: priority.unwrap_or(TaskPriority::Normal),
+            priority,
+            min_vram_gb,
+            declined_by: Vec::new(),
+            requires_approval,
+            approval_deadline: None,
+            reward_token: reward_token.map(String::from),
+            allowed_nodes: allowed_nodes.map(|nodes| nodes.iter().map(|n| n.to_string()).collect()),
+            task_type,
+            preferred_node,
         };
 
         self.active_tasks.insert(&self.task_counter, &task);
         self.pending_tasks.push(&self.task_counter);
+        let position = self.requester_task_count.get(requester).unwrap_or(0);
+        self.requester_tasks.insert(&(requester.clone(), position), &self.task_counter);
+        self.requester_task_count.insert(requester, &(position + 1));
         self.task_counter += 1;
         
-        log!("Task submitted: {}, requester: {}, amount: {}", self.task_counter - 1, requester, compute_cost);
+        log!("Task submitted: {}, requester: {}, amount: {}, fee: {}", task.id, requester, compute_cost, platform_fee);
+        DeAIEvent::TaskSubmitted(vec![TaskSubmittedData {
+            task_id: task.id,
+            requester: requester.clone(),
+            reward_amount: U128(reward_amount),
+            priority: task.priority.clone(),
+        }]).emit();
         
-        // Try to assign to available node
-        self.try_assign_next_task();
+        task.id
     }
 
     #[payable]
     pub fn submit_result(&mut self, task_id: u64, proof_hash: String, output: String) {
+        self.audit("submit_result", json!({ "task_id": task_id, "proof_hash": proof_hash }));
+        self.assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        require!(output.len() <= MAX_RESULT_OUTPUT_LEN, "Output too long");
+        
+        self.internal_complete_task(&account_id, task_id, proof_hash, output);
+
+        // Try to assign next task
+        self.try_assign_next_task();
+    }
+
+    /// Add the next piece of an output too long for `submit_result`. Chunks
+    /// must arrive in order from `seq` 0; `finalize_result` submits them.
+    /// The node attaches the storage the chunk takes up, which is returned
+    /// when the chunks are submitted or dropped.
+    #[payable]
+    pub fn append_result_chunk(&mut self, task_id: u64, seq: u32, chunk: String) {
+        self.audit("append_result_chunk", json!({ "task_id": task_id, "seq": seq, "len": chunk.len() }));
+        let attached = env::attached_deposit().as_yoctonear();
+        let account_id = env::predecessor_account_id();
+        let task = self.active_tasks.get(&task_id).expect("Task not found");
+        
+        require!(task.assignees.iter().any(|a| a == account_id.as_str()), "Not assigned to this node");
+        require!(matches!(task.status, TaskStatus::Assigned | TaskStatus::InProgress), "Task not in assignable state");
+        require!(is_awaiting_result(&task, account_id.as_str()), "Result already submitted");
+        require!(!chunk.is_empty(), "Chunk cannot be empty");
+        if let Some(timeout) = task.timeout_at {
+            require!(env::block_timestamp() <= timeout, "Task has timed out");
+        }
+        
+        let key = (task_id, account_id);
+        let mut partial = self.partial_results.get(&key).unwrap_or(PartialResult {
+            next_seq: 0,
+            output: String::new(),
+            storage_deposit: 0,
+        });
+        require!(seq == partial.next_seq, format!("Expected chunk {}", partial.next_seq));
+        require!(
+            partial.output.len() + chunk.len() <= MAX_CHUNKED_OUTPUT_LEN,
+            format!("Output exceeds {} bytes", MAX_CHUNKED_OUTPUT_LEN)
+        );
+        
+        let initial_storage = env::storage_usage();
+        partial.output.push_str(&chunk);
+        partial.next_seq += 1;
+        partial.storage_deposit += attached;
+        self.partial_results.insert(&key, &partial);
+        
+        let storage_cost = env::storage_usage().saturating_sub(initial_storage) as u128
+            * env::storage_byte_cost().as_yoctonear();
+        require!(
+            attached >= storage_cost,
+            format!("Attach at least {} yoctoNEAR to cover chunk storage", storage_cost)
+        );
+    }
+
+    /// Submit the chunks appended so far as this node's result. The storage
+    /// deposit comes back less whatever the accepted output keeps using.
+    #[payable]
+    pub fn finalize_result(&mut self, task_id: u64, proof_hash: String) {
+        self.audit("finalize_result", json!({ "task_id": task_id, "proof_hash": proof_hash }));
+        self.assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let partial = self.partial_results.get(&(task_id, account_id.clone())).expect("No result chunks submitted");
+        
+        let initial_storage = env::storage_usage();
+        // Takes the chunks out of `partial_results` without refunding them
+        self.partial_results.remove(&(task_id, account_id.clone()));
+        self.internal_complete_task(&account_id, task_id, proof_hash, partial.output);
+        
+        let storage_cost = env::storage_usage().saturating_sub(initial_storage) as u128
+            * env::storage_byte_cost().as_yoctonear();
+        let refund = partial.storage_deposit.saturating_sub(storage_cost);
+        if refund > 0 {
+            Promise::new(account_id).transfer(NearToken::from_yoctonear(refund));
+        }
+        
+        self.try_assign_next_task();
+    }
+
+    #[payable]
+    pub fn submit_results_batch(&mut self, results: Vec<(u64, String, String)>) {
+        self.audit("submit_results_batch", json!({ "task_ids": results.iter().map(|r| r.0).collect::<Vec<u64>>() }));
         self.assert_one_yocto();
         let account_id = env::predecessor_account_id();
         
+        require!(!results.is_empty(), "Results batch cannot be empty");
+        require!(results.len() <= MAX_RESULTS_BATCH, "Results batch too large");
+        
+        let count = results.len();
+        for (task_id, proof_hash, output) in results {
+            require!(output.len() <= MAX_RESULT_OUTPUT_LEN, "Output too long");
+            self.internal_complete_task(&account_id, task_id, proof_hash, output);
+        }
+        
+        log!("Results batch submitted: {} tasks, node: {}", count, account_id);
+
+        // Fill the slots freed by the batch
+        for _ in 0..count {
+            self.try_assign_next_task();
+        }
+    }
+
+    fn internal_complete_task(&mut self, account_id: &AccountId, task_id: u64, proof_hash: String, output: String) {
         // Get task from active tasks
         let mut task = self.active_tasks.get(&task_id).expect("Task not found").clone();
         
-        require!(task.assignee.as_ref() == Some(&account_id.to_string()), "Not assigned to this node");
+        require!(task.assignees.iter().any(|a| a == account_id.as_str()), "Not assigned to this node");
         require!(matches!(task.status, TaskStatus::Assigned | TaskStatus::InProgress), "Task not in assignable state");
+        require!(!task.submissions.iter().any(|s| s.node_id == account_id.as_str()), "Result already submitted");
         require!(!proof_hash.is_empty(), "Proof hash cannot be empty");
         require!(!output.is_empty(), "Output cannot be empty");
         require!(proof_hash.len() <= 64, "Proof hash too long");
-        require!(output.len() <= 10000, "Output too long");
 
         // Check if task has timed out
         if let Some(timeout) = task.timeout_at {
             require!(env::block_timestamp() <= timeout, "Task has timed out");
         }
 
-        // Update task
-        task.status = TaskStatus::Completed;
-        task.output = Some(output);
-        task.proof_hash = Some(proof_hash);
-        task.completed_at = Some(env::block_timestamp());
-
-        // Update node stats
-        let mut node = self.nodes.get(&account_id).unwrap().clone();
-        node.total_tasks_completed += 1;
-        node.reputation_score = std::cmp::min(MAX_REPUTATION, node.reputation_score + REPUTATION_GAIN);
-        self.nodes.insert(&account_id, &node);
-
-        // Mint reward tokens
-        self.token.internal_deposit(&account_id, task.reward_amount);
-        self.total_rewards_distributed += task.reward_amount;
+        // This node's part is done whatever the others answer
+        self.decrement_node_active_tasks(account_id);
+        self.drop_partial_result(task_id, account_id);
+        task.submissions.push(ResultSubmission {
+            node_id: account_id.to_string(),
+            proof_hash,
+            output: output.clone(),
+        });
 
-        // Move task to completed
-        self.active_tasks.remove(&task_id);
-        self.completed_tasks.insert(&task_id, &task);
-        
-        log!("Task completed: {}, node: {}, reward: {}", task_id, account_id, task.reward_amount);
+        // Proof hashes embed the node and time, so agreement is on the output
+        let agreeing: Vec<ResultSubmission> = task.submissions.iter()
+            .filter(|submission| submission.output == output)
+            .cloned()
+            .collect();
 
-        // Try to assign next task
-        self.try_assign_next_task();
+        if agreeing.len() >= quorum_size(task.redundancy) {
+            self.finalize_task(task, agreeing);
+        } else if task.submissions.len() == task.assignees.len() {
+            self.fail_task_without_consensus(task);
+        } else {
+            log!("Result recorded: {}, node: {}, {}/{} results", task_id, account_id, task.submissions.len(), task.assignees.len());
+            self.active_tasks.insert(&task_id, &task);
+        }
     }
 
-    fn try_assign_next_task(&mut self) {
-        if let Some(available_node) = self.get_available_node() {
-            // Find highest priority pending task
-            let mut best_task_index = None;
-            let mut best_priority = TaskPriority::Low;
-            
-            for i in 0..self.pending_tasks.len() {
-                let task_id = self.pending_tasks.get(i).unwrap();
-                if let Some(task) = self.active_tasks.get(&task_id) {
-                    if task.status == TaskStatus::Pending {
-                        if best_task_index.is_none() || self.priority_value(&task.priority) > self.priority_value(&best_priority) {
-                            best_task_index = Some(i);
-                            best_priority = task.priority.clone();
-                        }
-                    }
+    // Accepts the output a quorum agreed on and slashes the nodes that
+    // submitted anything else. The reward is released now, or held for the
+    // requester's approval when the task asks for it.
+    fn finalize_task(&mut self, mut task: Task, agreeing: Vec<ResultSubmission>) {
+        let task_id = task.id;
+        let accepted = agreeing[0].clone();
+        
+        task.output = Some(accepted.output.clone());
+        task.proof_hash = Some(accepted.proof_hash.clone());
+        
+        // Timed when the result is accepted, not when an approval releases the reward
+        if let Some(assigned_at) = task.assigned_at {
+            self.total_assign_time += assigned_at.saturating_sub(task.created_at) as u128;
+            self.total_execution_time += env::block_timestamp().saturating_sub(assigned_at) as u128;
+            self.timed_task_count += 1;
+        }
+        
+        // Nodes still working are released without reward or penalty
+        for assignee in &task.assignees {
+            if is_awaiting_result(&task, assignee) {
+                if let Ok(assignee_id) = assignee.parse::<AccountId>() {
+                    self.decrement_node_active_tasks(&assignee_id);
+                    self.drop_partial_result(task_id, &assignee_id);
                 }
             }
-            
-            if let Some(index) = best_task_index {
-                let task_id = self.pending_tasks.get(index).unwrap();
-                self.pending_tasks.swap_remove(index);
-                if let Some(task) = self.active_tasks.get(&task_id) {
-                    let mut updated_task = task.clone();
-                    updated_task.assignee = Some(available_node.to_string());
-                    updated_task.status = TaskStatus::Assigned;
-                    updated_task.assigned_at = Some(env::block_timestamp());
-                    updated_task.timeout_at = Some(env::block_timestamp() + self.task_timeout_duration);
-                    
-                    self.active_tasks.insert(&task_id, &updated_task);
-                    log!("Task assigned: {} to node: {}", task_id, available_node);
+        }
+        
+        // A node that disagreed with the quorum returned a wrong result
+        for submission in &task.submissions {
+            if submission.output != accepted.output {
+                if let Ok(node_id) = submission.node_id.parse::<AccountId>() {
+                    let slashed = self.internal_slash_node(&node_id, TIMEOUT_SLASH_BPS, "result_mismatch", Some(task.id));
+                    self.treasury_balance += slashed;
                 }
             }
         }
-    }
-    
-    fn priority_value(&self, priority: &TaskPriority) -> u8 {
-        match priority {
-            TaskPriority::Low => 1,
-            TaskPriority::Normal => 2,
-            TaskPriority::High => 3,
-            TaskPriority::Urgent => 4,
+        
+        if task.requires_approval {
+            let approval_deadline = env::block_timestamp() + self.approval_window;
+            task.status = TaskStatus::PendingApproval;
+            task.approval_deadline = Some(approval_deadline);
+            self.active_tasks.insert(&task_id, &task);
+            
+            log!("Task awaiting approval: {}, deadline: {}", task_id, approval_deadline);
+            DeAIEvent::TaskAwaitingApproval(vec![TaskAwaitingApprovalData {
+                task_id,
+                requester: task.requester.parse().unwrap(),
+                approval_deadline,
+            }]).emit();
+        } else {
+            self.release_reward(task);
         }
     }
 
-    fn get_available_node(&self) -> Option<AccountId> {
-        let current_time = env::block_timestamp();
+    // Pays the nodes behind the accepted output, splitting the reward, and
+    // completes the task
+    fn release_reward(&mut self, mut task: Task) {
+        let task_id = task.id;
+        let agreeing: Vec<ResultSubmission> = task.submissions.iter()
+            .filter(|submission| Some(&submission.output) == task.output.as_ref())
+            .cloned()
+            .collect();
         
-        // Find node with highest reputation that's available
-        let mut best_node = None;
-        let mut best_reputation = 0;
+        task.status = TaskStatus::Completed;
+        task.completed_at = Some(env::block_timestamp());
         
-        for (account_id, node) in self.nodes.iter() {
-            if node.is_active 
-                && current_time - node.last_heartbeat < HEARTBEAT_TIMEOUT
-                && node.reputation_score > best_reputation
-                && self.get_node_active_task_count(&account_id) < self.max_tasks_per_node {
-                best_node = Some(account_id.clone());
-                best_reputation = node.reputation_score;
+        match task.reward_token.as_ref().and_then(|t| t.parse::<AccountId>().ok()) {
+            Some(token_id) => {
+                let treasury_id = self.treasury_id.clone();
+                self.internal_deposit_token(&treasury_id, &token_id, task.platform_fee);
             }
+            None => self.treasury_balance += task.platform_fee,
         }
-        best_node
+        
+        let share = task.reward_amount / agreeing.len() as u128;
+        let remainder = task.reward_amount - share * agreeing.len() as u128;
+        let mut completed_events = Vec::with_capacity(agreeing.len());
+        
+        for (i, submission) in agreeing.iter().enumerate() {
+            let node_id: AccountId = submission.node_id.parse().unwrap();
+            let reward = if i == 0 { share + remainder } else { share };
+            
+            let mut node = self.nodes.get(&node_id).unwrap().clone();
+            let reputation = self.decayed_reputation(&node);
+            node.total_tasks_completed += 1;
+            node.reputation_score = std::cmp::min(MAX_REPUTATION, reputation + REPUTATION_GAIN);
+            node.last_reputation_update = env::block_timestamp();
+            self.nodes.insert(&node_id, &node);
+            
+            let reward = match task.reward_token.as_ref().and_then(|t| t.parse::<AccountId>().ok()) {
+                Some(token_id) => {
+                    self.transfer_token(&node_id, token_id, reward);
+                    reward
+                }
+                None => {
+                    // Mint reward tokens, plus a bonus from the inflation budget
+                    // earned on the reputation held before this task
+                    let bonus = self.reputation_bonus(reputation, reward);
+                    self.reputation_bonus_budget -= bonus;
+                    if node.auto_claim_rewards {
+                        self.token.internal_deposit(&node_id, reward + bonus);
+                        self.total_rewards_distributed += reward + bonus;
+                    } else {
+                        let pending = self.pending_rewards.get(&node_id).unwrap_or(0);
+                        self.pending_rewards.insert(&node_id, &(pending + reward + bonus));
+                    }
+                    reward + bonus
+                }
+            };
+            
+            log!("Task completed: {}, node: {}, reward: {}", task_id, node_id, reward);
+            completed_events.push(TaskCompletedData {
+                task_id,
+                assignee: node_id,
+                reward_amount: U128(reward),
+                proof_hash: submission.proof_hash.clone(),
+            });
+        }
+        
+        // Move task to completed
+        self.active_tasks.remove(&task_id);
+        self.completed_tasks.insert(&task_id, &task);
+        
+        DeAIEvent::TaskCompleted(completed_events).emit();
     }
 
-    fn node_has_active_task(&self, node_id: &AccountId) -> bool {
-        self.get_node_active_task_count(node_id) > 0
+    /// Accept the result of a task awaiting approval and release the reward.
+    #[payable]
+    pub fn approve_result(&mut self, task_id: u64) {
+        self.audit("approve_result", json!({ "task_id": task_id }));
+        self.assert_one_yocto();
+        let task = self.active_tasks.get(&task_id).expect("Task not found");
+        
+        require!(task.requester == env::predecessor_account_id().as_str(), "Only the requester can approve this task");
+        require!(task.status == TaskStatus::PendingApproval, "Task not awaiting approval");
+        
+        log!("Task result approved: {}", task_id);
+        self.release_reward(task);
+    }
+
+    /// Refuse the result of a task awaiting approval. The reward stays held
+    /// and the task is disputed.
+    #[payable]
+    pub fn reject_result(&mut self, task_id: u64) {
+        self.audit("reject_result", json!({ "task_id": task_id }));
+        self.assert_one_yocto();
+        let mut task = self.active_tasks.get(&task_id).expect("Task not found");
+        let requester = env::predecessor_account_id();
+        
+        require!(task.requester == requester.as_str(), "Only the requester can reject this task");
+        require!(task.status == TaskStatus::PendingApproval, "Task not awaiting approval");
+        require!(
+            env::block_timestamp() <= task.approval_deadline.unwrap_or_default(),
+            "Approval window has passed"
+        );
+        
+        task.status = TaskStatus::Disputed;
+        self.active_tasks.insert(&task_id, &task);
+        
+        log!("Task result rejected: {}, requester: {}", task_id, requester);
+        DeAIEvent::TaskDisputed(vec![TaskDisputedData { task_id, requester }]).emit();
+    }
+
+    /// Settle a disputed task: pay its nodes as if the requester had approved,
+    /// or refund the requester. Either way the task is finished.
+    #[payable]
+    pub fn resolve_dispute(&mut self, task_id: u64, pay_node: bool) {
+        self.audit("resolve_dispute", json!({ "task_id": task_id, "pay_node": pay_node }));
+        self.assert_owner();
+        self.assert_one_yocto();
+        let mut task = self.active_tasks.get(&task_id).expect("Task not found");
+        
+        require!(task.status == TaskStatus::Disputed, "Task not disputed");
+        
+        if pay_node {
+            self.release_reward(task);
+        } else {
+            task.status = TaskStatus::Failed;
+            task.completed_at = Some(env::block_timestamp());
+            self.refund_requester(&task);
+            
+            self.active_tasks.remove(&task_id);
+            self.completed_tasks.insert(&task_id, &task);
+        }
+        
+        log!("Dispute resolved: {}, paid node: {}", task_id, pay_node);
+        DeAIEvent::TaskDisputeResolved(vec![TaskDisputeResolvedData { task_id, paid_node: pay_node }]).emit();
+    }
+
+    /// Release the reward of a task whose requester let the approval window
+    /// pass. Callable by anyone, so nodes aren't left waiting on the requester.
+    pub fn auto_approve_result(&mut self, task_id: u64) {
+        self.audit("auto_approve_result", json!({ "task_id": task_id }));
+        let task = self.active_tasks.get(&task_id).expect("Task not found");
+        
+        require!(task.status == TaskStatus::PendingApproval, "Task not awaiting approval");
+        require!(
+            env::block_timestamp() > task.approval_deadline.unwrap_or_default(),
+            "Approval window has not passed yet"
+        );
+        
+        log!("Task result auto-approved: {}", task_id);
+        self.release_reward(task);
+    }
+
+    // Every node answered but no output reached a quorum, so there is no way
+    // to tell which is right: refund the requester and slash nobody
+    fn fail_task_without_consensus(&mut self, mut task: Task) {
+        let task_id = task.id;
+        
+        task.status = TaskStatus::Failed;
+        task.completed_at = Some(env::block_timestamp());
+        
+        self.refund_requester(&task);
+        
+        self.active_tasks.remove(&task_id);
+        self.completed_tasks.insert(&task_id, &task);
+        
+        log!("Task failed without consensus: {}, refund: {}", task_id, task.refund_amount());
+        DeAIEvent::TaskConsensusFailed(vec![TaskConsensusFailedData {
+            task_id,
+            refund_amount: U128(task.refund_amount()),
+        }]).emit();
+    }
+
+    // Cuts reputation and `bps` of the stake, never more than what is left.
+    // Returns the amount slashed.
+    fn internal_slash_node(&mut self, node_id: &AccountId, bps: u32, reason: &str, task_id: Option<u64>) -> Balance {
+        let Some(node) = self.nodes.get(node_id) else {
+            return 0;
+        };
+        
+        let mut updated_node = node.clone();
+        updated_node.reputation_score = self.decayed_reputation(&node).saturating_sub(REPUTATION_LOSS);
+        updated_node.last_reputation_update = env::block_timestamp();
+        
+        let slash_amount = std::cmp::min(
+            updated_node.stake * bps as u128 / BPS_DENOMINATOR as u128,
+            updated_node.stake.saturating_sub(updated_node.slashed_amount),
+        );
+        updated_node.slashed_amount += slash_amount;
+        self.total_slashed += slash_amount;
+        
+        self.nodes.insert(node_id, &updated_node);
+        self.record_slash(node_id, slash_amount, reason, task_id);
+        log!("Node slashed for {}: {}, amount: {}", reason, node_id, slash_amount);
+        DeAIEvent::NodeSlashed(vec![NodeSlashedData {
+            account_id: node_id.clone(),
+            amount: U128(slash_amount),
+            reputation_score: updated_node.reputation_score,
+            reason: reason.to_string(),
+        }]).emit();
+        
+        slash_amount
+    }
+
+    fn claim_node_ip(&mut self, account_id: &AccountId, public_ip: &String) {
+        require!(
+            self.registered_ips.get(public_ip).map_or(true, |holder| holder == *account_id),
+            "IP address already registered"
+        );
+        self.registered_ips.insert(public_ip, account_id);
+    }
+
+    // Discards a node's unfinished chunked result and returns its storage deposit
+    fn drop_partial_result(&mut self, task_id: u64, node_id: &AccountId) {
+        if let Some(partial) = self.partial_results.remove(&(task_id, node_id.clone())) {
+            if partial.storage_deposit > 0 {
+                Promise::new(node_id.clone()).transfer(NearToken::from_yoctonear(partial.storage_deposit));
+            }
+        }
+    }
+
+    fn record_slash(&mut self, node_id: &AccountId, amount: Balance, reason: &str, task_id: Option<u64>) {
+        let position = self.slash_event_count.get(node_id).unwrap_or(0);
+        self.slash_events.insert(&(node_id.clone(), position), &SlashEvent {
+            timestamp: env::block_timestamp(),
+            amount: U128(amount),
+            reason: reason.to_string(),
+            task_id,
+        });
+        self.slash_event_count.insert(node_id, &(position + 1));
+    }
+
+    fn try_assign_next_task(&mut self) {
+        // Collect pending tasks, highest priority first
+        let mut candidates: Vec<(u64, Task)> = Vec::new();
+        for i in 0..self.pending_tasks.len() {
+            let task_id = self.pending_tasks.get(i).unwrap();
+            if let Some(task) = self.active_tasks.get(&task_id) {
+                if task.status == TaskStatus::Pending {
+                    candidates.push((i, task));
+                }
+            }
+        }
+        candidates.sort_by(|a, b| self.priority_value(&b.1.priority).cmp(&self.priority_value(&a.1.priority)));
+        
+        // Assign the first task that enough available nodes can serve
+        for (index, task) in candidates {
+            let mut excluded = task.declined_by.clone();
+            let mut assignees: Vec<AccountId> = Vec::new();
+            while assignees.len() < task.redundancy as usize {
+                match self.get_available_node(&task, &excluded) {
+                    Some(node) => {
+                        excluded.push(node.to_string());
+                        assignees.push(node);
+                    }
+                    None => break,
+                }
+            }
+            
+            // Fewer nodes than the quorum could never agree on a result
+            if assignees.len() < quorum_size(task.redundancy) {
+                continue;
+            }
+            
+            let task_id = task.id;
+            self.pending_tasks.swap_remove(index);
+            
+            let mut updated_task = task;
+            updated_task.assignee = Some(assignees[0].to_string());
+            updated_task.assignees = assignees.iter().map(|a| a.to_string()).collect();
+            updated_task.status = TaskStatus::Assigned;
+            updated_task.assigned_at = Some(env::block_timestamp());
+            updated_task.timeout_at = Some(env::block_timestamp() + self.task_timeout_duration);
+            
+            self.active_tasks.insert(&task_id, &updated_task);
+            let mut assigned_events = Vec::with_capacity(assignees.len());
+            for node in assignees {
+                self.increment_node_active_tasks(&node);
+                log!("Task assigned: {} to node: {}", task_id, node);
+                assigned_events.push(TaskAssignedData {
+                    task_id,
+                    assignee: node,
+                    timeout_at: updated_task.timeout_at.unwrap_or_default(),
+                });
+            }
+            DeAIEvent::TaskAssigned(assigned_events).emit();
+            return;
+        }
+    }
+    
+    fn priority_value(&self, priority: &TaskPriority) -> u8 {
+        match priority {
+            TaskPriority::Low => 1,
+            TaskPriority::Normal => 2,
+            TaskPriority::High => 3,
+            TaskPriority::Urgent => 4,
+        }
+    }
+
+    fn get_available_node(&self, task: &Task, excluded: &[String]) -> Option<AccountId> {
+        let current_time = env::block_timestamp();
+        
+        // Candidates must be live, reputable, able to serve the task, allowed
+        // by the requester and below capacity
+        let mut best_node: Option<(AccountId, u32, u32)> = None; // (account, active tasks, reputation)
+        
+        for (account_id, node) in self.nodes.iter() {
+            if !node.is_active 
+                || !self.is_within_heartbeat_timeout(&node, current_time)
+                || self.decayed_reputation(&node) < self.min_reputation_for_assignment
+                || !node_meets_vram(&node, task.min_vram_gb)
+                || !node_supports_task_type(&node, task.task_type.as_deref())
+                || !node_allowed(task, &account_id)
+                || excluded.iter().any(|declined| declined == account_id.as_str()) {
+                continue;
+            }
+            
+            let active_tasks = self.get_node_active_task_count(&account_id);
+            if active_tasks >= self.max_tasks_per_node {
+                continue;
+            }
+            
+            // Gateways steer bursts for one model to the same node to reuse its cache
+            if task.preferred_node.as_deref() == Some(account_id.as_str()) {
+                return Some(account_id);
+            }
+            
+            let reputation = self.decayed_reputation(&node);
+            let is_better = match &best_node {
+                None => true,
+                Some((_, best_active, best_reputation)) => match self.assignment_strategy {
+                    AssignmentStrategy::HighestReputation => reputation > *best_reputation,
+                    // Fewest active tasks wins, reputation breaks ties
+                    AssignmentStrategy::LeastLoaded => {
+                        active_tasks < *best_active
+                            || (active_tasks == *best_active && reputation > *best_reputation)
+                    }
+                },
+            };
+            
+            if is_better {
+                best_node = Some((account_id, active_tasks, reputation));
+            }
+        }
+        best_node.map(|(account_id, _, _)| account_id)
+    }
+
+    fn is_within_heartbeat_timeout(&self, node: &NodeInfo, current_time: u64) -> bool {
+        current_time.saturating_sub(node.last_heartbeat) < self.heartbeat_timeouts.for_tier(&node.tier)
+    }
+
+    // Reputation after applying decay for each full day without task activity
+    fn decayed_reputation(&self, node: &NodeInfo) -> u32 {
+        let idle_days = env::block_timestamp().saturating_sub(node.last_reputation_update) / NANOS_PER_DAY;
+        let decay = (idle_days as u32).saturating_mul(self.reputation_decay_per_day);
+        
+        // Decay never pushes a node below the floor, nor lifts one already under it
+        let floor = REPUTATION_DECAY_FLOOR.min(node.reputation_score);
+        node.reputation_score.saturating_sub(decay).max(floor)
+    }
+
+    // Scales linearly from nothing at base reputation to
+    // MAX_REPUTATION_BONUS_BPS at max, limited by what is left of the budget
+    fn reputation_bonus(&self, reputation: u32, reward: Balance) -> Balance {
+        let earned = reputation.saturating_sub(BASE_REPUTATION) as u128;
+        let bonus = reward * MAX_REPUTATION_BONUS_BPS as u128 * earned
+            / (BPS_DENOMINATOR as u128 * (MAX_REPUTATION - BASE_REPUTATION) as u128);
+        bonus.min(self.reputation_bonus_budget)
+    }
+
+    fn node_has_active_task(&self, node_id: &AccountId) -> bool {
+        self.get_node_active_task_count(node_id) > 0
     }
     
     fn get_node_active_task_count(&self, node_id: &AccountId) -> u32 {
-        let mut count = 0;
-        for task in self.active_tasks.values() {
-            if task.assignee.as_ref() == Some(&node_id.to_string()) && 
-               matches!(task.status, TaskStatus::Assigned | TaskStatus::InProgress) {
-                count += 1;
+        self.node_active_tasks.get(node_id).unwrap_or(0)
+    }
+    
+    fn increment_node_active_tasks(&mut self, node_id: &AccountId) {
+        let count = self.get_node_active_task_count(node_id);
+        self.node_active_tasks.insert(node_id, &(count + 1));
+    }
+    
+    fn decrement_node_active_tasks(&mut self, node_id: &AccountId) {
+        let count = self.get_node_active_task_count(node_id);
+        if count <= 1 {
+            self.node_active_tasks.remove(node_id);
+        } else {
+            self.node_active_tasks.insert(node_id, &(count - 1));
+        }
+    }
+
+    // Return an assigned task to the queue, dropping any results so far
+    fn release_task(&mut self, mut task: Task) {
+        for assignee in &task.assignees {
+            if is_awaiting_result(&task, assignee) {
+                if let Ok(assignee_id) = assignee.parse::<AccountId>() {
+                    self.decrement_node_active_tasks(&assignee_id);
+                    self.drop_partial_result(task.id, &assignee_id);
+                }
+            }
+        }
+        
+        task.assignee = None;
+        task.assignees.clear();
+        task.submissions.clear();
+        task.status = TaskStatus::Pending;
+        task.assigned_at = None;
+        task.timeout_at = None;
+        
+        self.active_tasks.insert(&task.id, &task);
+        self.pending_tasks.push(&task.id);
+    }
+
+    /// Hand an assigned task back to the queue when this node can't run it,
+    /// e.g. an unsupported task type or a model too large for it. The task is
+    /// reassigned to another node; declining isn't penalised. For a redundant
+    /// task every node's assignment is released and it is assigned afresh.
+    pub fn decline_task(&mut self, task_id: u64, reason: String) {
+        self.audit("decline_task", json!({ "task_id": task_id, "reason": reason }));
+        self.assert_not_paused();
+        let account_id = env::predecessor_account_id();
+        let mut task = self.active_tasks.get(&task_id).expect("Task not found");
+        
+        require!(task.assignees.iter().any(|a| a == account_id.as_str()), "Not assigned to this node");
+        require!(task.status == TaskStatus::Assigned, "Task not in assignable state");
+        require!(!task.submissions.iter().any(|s| s.node_id == account_id.as_str()), "Result already submitted");
+        require!(reason.len() <= 256, "Decline reason too long");
+        
+        task.declined_by.push(account_id.to_string());
+        self.release_task(task);
+        
+        log!("Task declined: {} by node: {}, reason: {}", task_id, account_id, reason);
+        DeAIEvent::TaskDeclined(vec![TaskDeclinedData {
+            task_id,
+            node_id: account_id,
+            reason,
+        }]).emit();
+        
+        self.try_assign_next_task();
+    }
+
+    /// Penalize a node caught misbehaving off-chain, e.g. returning fraudulent
+    /// results. Slashes `bps` of its stake to the treasury and cuts its
+    /// reputation; a node left below the minimum stake is deactivated.
+    #[payable]
+    pub fn slash_node(&mut self, node_id: AccountId, bps: u16, reason: String) {
+        self.audit("slash_node", json!({ "node_id": node_id, "bps": bps, "reason": reason }));
+        self.assert_owner();
+        self.assert_one_yocto();
+        require!(bps > 0 && bps <= MAX_FORCE_SLASH_BPS, "Slash must be between 1 and 5000 bps");
+        require!(!reason.is_empty() && reason.len() <= 256, "Slash reason must be 1-256 characters");
+        require!(self.nodes.get(&node_id).is_some(), "Node not registered");
+        
+        let slashed = self.internal_slash_node(&node_id, bps as u32, &reason, None);
+        self.treasury_balance += slashed;
+        
+        let mut node = self.nodes.get(&node_id).unwrap();
+        if node.is_active && node.stake.saturating_sub(node.slashed_amount) < self.min_stake {
+            node.is_active = false;
+            self.registered_ips.remove(&node.public_ip);
+            self.nodes.insert(&node_id, &node);
+            log!("Node deactivated after slash: {}", node_id);
+        }
+    }
+
+    /// Ban a node account from registering. A registered node is deactivated,
+    /// its unfinished tasks are reassigned and its remaining stake is forfeited
+    /// to the treasury.
+    #[payable]
+    pub fn blacklist_node(&mut self, node_id: AccountId, reason: String) {
+        self.audit("blacklist_node", json!({ "node_id": node_id, "reason": reason }));
+        self.assert_owner();
+        self.assert_one_yocto();
+        require!(!reason.is_empty() && reason.len() <= 256, "Blacklist reason must be 1-256 characters");
+        require!(self.blacklist.get(&node_id).is_none(), "Node already blacklisted");
+        
+        self.blacklist.insert(&node_id, &reason);
+        
+        let mut forfeited: Balance = 0;
+        if let Some(mut node) = self.nodes.get(&node_id) {
+            forfeited = node.stake.saturating_sub(node.slashed_amount);
+            node.slashed_amount = node.stake;
+            node.is_active = false;
+            self.total_slashed += forfeited;
+            self.treasury_balance += forfeited;
+            self.registered_ips.remove(&node.public_ip);
+            self.nodes.insert(&node_id, &node);
+            self.record_slash(&node_id, forfeited, &format!("blacklisted: {}", reason), None);
+            
+            let unfinished: Vec<Task> = self.active_tasks.values()
+                .filter(|task| is_awaiting_result(task, node_id.as_str())
+                    && matches!(task.status, TaskStatus::Assigned | TaskStatus::InProgress))
+                .collect();
+            let released = unfinished.len();
+            for mut task in unfinished {
+                task.declined_by.push(node_id.to_string());
+                self.release_task(task);
+            }
+            for _ in 0..released {
+                self.try_assign_next_task();
             }
         }
-        count
+        
+        log!("Node blacklisted: {}, reason: {}, forfeited: {}", node_id, reason, forfeited);
+        DeAIEvent::NodeBlacklisted(vec![NodeBlacklistedData {
+            account_id: node_id,
+            reason,
+            forfeited_stake: U128(forfeited),
+        }]).emit();
+    }
+
+    /// Lift a ban. Forfeited stake is not returned; the node must top up its
+    /// stake before it can become active again.
+    #[payable]
+    pub fn unblacklist_node(&mut self, node_id: AccountId) {
+        self.audit("unblacklist_node", json!({ "node_id": node_id }));
+        self.assert_owner();
+        self.assert_one_yocto();
+        require!(self.blacklist.remove(&node_id).is_some(), "Node not blacklisted");
+        
+        log!("Node removed from blacklist: {}", node_id);
     }
 
     // Timeout and slashing functions
     #[payable] 
     pub fn timeout_task(&mut self, task_id: u64) {
+        self.audit("timeout_task", json!({ "task_id": task_id }));
         self.assert_one_yocto();
         let mut task = self.active_tasks.get(&task_id).expect("Task not found").clone();
         
@@ -395,35 +1662,141 @@ impl DeAICompute {
             require!(env::block_timestamp() > timeout, "Task has not timed out yet");
         }
         
-        // Slash node reputation and stake
-        if let Some(assignee_str) = &task.assignee {
-            if let Ok(assignee_id) = assignee_str.parse::<AccountId>() {
-                if let Some(mut node) = self.nodes.get(&assignee_id) {
-                    let mut updated_node = node.clone();
-                    updated_node.reputation_score = updated_node.reputation_score.saturating_sub(REPUTATION_LOSS);
-                    
-                    // Slash 10% of stake
-                    let slash_amount = updated_node.stake / 10;
-                    updated_node.slashed_amount += slash_amount;
-                    
-                    self.nodes.insert(&assignee_id, &updated_node);
-                    log!("Node slashed for timeout: {}, amount: {}", assignee_id, slash_amount);
-                }
-            }
+        // Slash every node that never delivered a result
+        let mut slash_amount: Balance = 0;
+        let late_nodes: Vec<AccountId> = task.assignees.iter()
+            .filter(|assignee| is_awaiting_result(&task, assignee))
+            .filter_map(|assignee| assignee.parse().ok())
+            .collect();
+        for assignee_id in late_nodes {
+            self.decrement_node_active_tasks(&assignee_id);
+            self.drop_partial_result(task_id, &assignee_id);
+            slash_amount += self.internal_slash_node(&assignee_id, TIMEOUT_SLASH_BPS, "timeout", Some(task_id));
         }
         
         task.status = TaskStatus::TimedOut;
         task.completed_at = Some(env::block_timestamp());
         
+        // Requester is compensated from the slash, the rest goes to the treasury
+        let compensation = slash_amount * self.slash_compensation_bps as u128 / BPS_DENOMINATOR as u128;
+        let treasury_amount = slash_amount - compensation;
+        
         // Return funds to requester
-        if let Ok(requester_id) = task.requester.parse::<AccountId>() {
-            Promise::new(requester_id).transfer(NearToken::from_yoctonear(task.reward_amount));
+        self.refund_requester(&task);
+        if compensation > 0 {
+            if let Ok(requester_id) = task.requester.parse::<AccountId>() {
+                Promise::new(requester_id).transfer(NearToken::from_yoctonear(compensation));
+            }
         }
+        self.treasury_balance += treasury_amount;
         
         self.active_tasks.remove(&task_id);
         self.completed_tasks.insert(&task_id, &task);
         
-        log!("Task timed out: {}", task_id);
+        log!("Task timed out: {}, refund: {}, compensation: {}", task_id, task.refund_amount(), compensation);
+        DeAIEvent::TaskTimedOut(vec![TaskTimedOutData {
+            task_id,
+            assignee: task.assignee.as_ref().and_then(|a| a.parse().ok()),
+            refund_amount: U128(task.refund_amount()),
+        }]).emit();
+    }
+
+    pub fn sweep_stale_nodes(&mut self, limit: u64) -> u64 {
+        self.audit("sweep_stale_nodes", json!({ "limit": limit }));
+        let current_time = env::block_timestamp();
+        let node_ids = self.nodes.keys_as_vector();
+        let total_nodes = node_ids.len();
+        if total_nodes == 0 {
+            return 0;
+        }
+        
+        // Resume where the previous sweep stopped so every node gets checked
+        let scan_count = limit.min(total_nodes);
+        let mut stale_nodes = Vec::new();
+        for offset in 0..scan_count {
+            let node_id = node_ids.get((self.stale_sweep_cursor + offset) % total_nodes).unwrap();
+            let node = self.nodes.get(&node_id).unwrap();
+            if node.is_active && !self.is_within_heartbeat_timeout(&node, current_time) {
+                stale_nodes.push((node_id, node));
+            }
+        }
+        self.stale_sweep_cursor = (self.stale_sweep_cursor + scan_count) % total_nodes;
+        
+        let swept = stale_nodes.len() as u64;
+        let mut released_tasks = 0;
+        for (node_id, mut node) in stale_nodes {
+            node.is_active = false;
+            self.registered_ips.remove(&node.public_ip);
+            self.nodes.insert(&node_id, &node);
+            
+            // Return the node's unfinished tasks to the queue
+            let stuck_tasks: Vec<Task> = self.active_tasks.values()
+                .filter(|task| is_awaiting_result(task, node_id.as_str())
+                    && matches!(task.status, TaskStatus::Assigned | TaskStatus::InProgress))
+                .collect();
+            
+            for task in stuck_tasks {
+                self.release_task(task);
+                released_tasks += 1;
+            }
+            
+            log!("Stale node deactivated: {}", node_id);
+        }
+        
+        for _ in 0..released_tasks {
+            self.try_assign_next_task();
+        }
+        
+        swept
+    }
+
+    #[payable]
+    pub fn cancel_task(&mut self, task_id: u64) {
+        self.audit("cancel_task", json!({ "task_id": task_id }));
+        self.assert_one_yocto();
+        
+        require!(self.completed_tasks.get(&task_id).is_none(), "Task already completed");
+        let mut task = self.active_tasks.get(&task_id).expect("Task not found");
+        
+        let requester = env::predecessor_account_id();
+        require!(task.requester == requester.as_str(), "Only the requester can cancel this task");
+        require!(task.status == TaskStatus::Pending, "Task already assigned");
+        
+        // Remove from the pending queue
+        if let Some(index) = self.pending_tasks.iter().position(|id| id == task_id) {
+            self.pending_tasks.swap_remove(index as u64);
+        }
+        
+        task.status = TaskStatus::Cancelled;
+        task.completed_at = Some(env::block_timestamp());
+        
+        self.active_tasks.remove(&task_id);
+        self.completed_tasks.insert(&task_id, &task);
+        
+        self.refund_requester(&task);
+        
+        log!("Task cancelled: {}, refund: {}", task_id, task.refund_amount());
+        DeAIEvent::TaskCancelled(vec![TaskCancelledData {
+            task_id,
+            requester,
+            refund_amount: U128(task.refund_amount()),
+        }]).emit();
+    }
+
+    pub fn decay_reputation(&mut self, node_id: AccountId) {
+        self.audit("decay_reputation", json!({ "node_id": node_id }));
+        let mut node = self.nodes.get(&node_id).expect("Node not registered");
+        
+        let idle_days = env::block_timestamp().saturating_sub(node.last_reputation_update) / NANOS_PER_DAY;
+        require!(idle_days > 0, "No reputation decay due");
+        
+        let old_score = node.reputation_score;
+        node.reputation_score = self.decayed_reputation(&node);
+        // Keep the partial day so repeated calls don't lose decay
+        node.last_reputation_update += idle_days * NANOS_PER_DAY;
+        self.nodes.insert(&node_id, &node);
+        
+        log!("Reputation decayed for {}: {} -> {}", node_id, old_score, node.reputation_score);
     }
 
     // View Functions
@@ -437,36 +1810,165 @@ impl DeAICompute {
 
     pub fn get_assigned_tasks(&self, node_id: AccountId) -> Vec<Task> {
         self.active_tasks.values()
-            .filter(|task| task.assignee.as_ref() == Some(&node_id.to_string()))
+            .filter(|task| is_awaiting_result(task, node_id.as_str()))
             .map(|task| task.clone())
             .collect()
     }
 
+    /// Tasks submitted by `requester`, active or finished, in submission order.
+    /// `from_index` counts the requester's own tasks, not global task ids.
+    pub fn get_requester_tasks(&self, requester: AccountId, from_index: u64, limit: u64) -> Vec<Task> {
+        let end = from_index
+            .saturating_add(limit.min(MAX_PAGE_LIMIT))
+            .min(self.get_requester_task_count(requester.clone()));
+        
+        (from_index..end)
+            .filter_map(|position| self.requester_tasks.get(&(requester.clone(), position)))
+            .filter_map(|task_id| self.active_tasks.get(&task_id).or_else(|| self.completed_tasks.get(&task_id)))
+            .collect()
+    }
+
+    pub fn get_requester_task_count(&self, requester: AccountId) -> u64 {
+        self.requester_task_count.get(&requester).unwrap_or(0)
+    }
+
+    /// A node's slashes, oldest first.
+    pub fn get_slash_history(&self, node_id: AccountId, from_index: u64, limit: u64) -> Vec<SlashEvent> {
+        let end = from_index
+            .saturating_add(limit.min(MAX_PAGE_LIMIT))
+            .min(self.get_slash_count(node_id.clone()));
+        
+        (from_index..end)
+            .filter_map(|position| self.slash_events.get(&(node_id.clone(), position)))
+            .collect()
+    }
+
+    pub fn get_slash_count(&self, node_id: AccountId) -> u64 {
+        self.slash_event_count.get(&node_id).unwrap_or(0)
+    }
+
+    /// The `seq` of the next chunk `append_result_chunk` expects from a node,
+    /// so a restarted node can resume streaming a result.
+    pub fn get_next_result_chunk(&self, task_id: u64, node_id: AccountId) -> u32 {
+        self.partial_results.get(&(task_id, node_id)).map_or(0, |partial| partial.next_seq)
+    }
+
+    pub fn is_blacklisted(&self, node_id: AccountId) -> bool {
+        self.blacklist.get(&node_id).is_some()
+    }
+
     pub fn get_node_info(&self, node_id: AccountId) -> Option<NodeInfo> {
         self.nodes.get(&node_id).map(|n| n.clone())
     }
 
+    /// The first page of active nodes; see `get_active_nodes_paged` for the
+    /// rest and `get_active_node_count` for how many there are.
     pub fn get_active_nodes(&self) -> Vec<NodeInfo> {
+        self.get_active_nodes_paged(0, MAX_PAGE_LIMIT)
+    }
+
+    pub fn get_active_node_count(&self) -> u64 {
         let current_time = env::block_timestamp();
         
         self.nodes.values()
-            .filter(|node| node.is_active && current_time - node.last_heartbeat < HEARTBEAT_TIMEOUT)
-            .map(|node| node.clone())
+            .filter(|node| node.is_active && self.is_within_heartbeat_timeout(node, current_time))
+            .count() as u64
+    }
+
+    pub fn get_active_nodes_paged(&self, from_index: u64, limit: u64) -> Vec<NodeInfo> {
+        let current_time = env::block_timestamp();
+        
+        self.nodes.values()
+            .filter(|node| node.is_active && self.is_within_heartbeat_timeout(node, current_time))
+            .skip(from_index as usize)
+            .take(limit.min(MAX_PAGE_LIMIT) as usize)
+            .collect()
+    }
+
+    /// Registered, non-deactivated nodes ranked by reputation, then completed
+    /// tasks. Sorting happens per call, so only the first
+    /// `MAX_LEADERBOARD_NODES` nodes in storage order are ranked.
+    pub fn get_node_leaderboard(&self, from_index: u64, limit: u64) -> Vec<NodeInfo> {
+        let mut nodes: Vec<NodeInfo> = self.nodes.values()
+            .take(MAX_LEADERBOARD_NODES as usize)
+            .filter(|node| node.is_active)
+            .collect();
+        nodes.sort_by(|a, b| {
+            b.reputation_score.cmp(&a.reputation_score)
+                .then(b.total_tasks_completed.cmp(&a.total_tasks_completed))
+        });
+        
+        nodes.into_iter()
+            .skip(from_index as usize)
+            .take(limit.min(MAX_PAGE_LIMIT) as usize)
             .collect()
     }
 
     pub fn get_pending_tasks(&self) -> Vec<Task> {
-        let mut pending_tasks = Vec::new();
-        for task_id in self.pending_tasks.iter() {
-            if let Some(task) = self.active_tasks.get(&task_id) {
-                if task.status == TaskStatus::Pending {
-                    pending_tasks.push(task.clone());
-                }
+        self.get_pending_tasks_paged(0, MAX_PAGE_LIMIT)
+    }
+
+    pub fn get_pending_tasks_paged(&self, from_index: u64, limit: u64) -> Vec<Task> {
+        self.pending_tasks.iter()
+            .skip(from_index as usize)
+            .take(limit.min(MAX_PAGE_LIMIT) as usize)
+            .filter_map(|task_id| self.active_tasks.get(&task_id))
+            .filter(|task| task.status == TaskStatus::Pending)
+            .collect()
+    }
+
+    /// Tasks with the given status. Live statuses page through active tasks by
+    /// match index; finished statuses scan task ids `from_index..from_index + limit`
+    /// in completed storage so the lookup cost stays bounded.
+    pub fn get_tasks_by_status(&self, status: TaskStatus, from_index: u64, limit: u64) -> Vec<Task> {
+        let limit = limit.min(MAX_PAGE_LIMIT);
+        
+        match status {
+            TaskStatus::Pending | TaskStatus::Assigned | TaskStatus::InProgress | TaskStatus::Disputed
+            | TaskStatus::PendingApproval => {
+                self.active_tasks.values()
+                    .filter(|task| task.status == status)
+                    .skip(from_index as usize)
+                    .take(limit as usize)
+                    .collect()
+            }
+            _ => {
+                let end = from_index.saturating_add(limit).min(self.task_counter);
+                (from_index..end)
+                    .filter_map(|task_id| self.completed_tasks.get(&task_id))
+                    .filter(|task| task.status == status)
+                    .collect()
             }
         }
-        pending_tasks
     }
 
+    /// Whether the node is active and has sent a heartbeat within its tier's timeout.
+    pub fn get_node_liveness(&self, node_id: AccountId) -> bool {
+        self.nodes.get(&node_id)
+            .map_or(false, |node| node.is_active && self.is_within_heartbeat_timeout(&node, env::block_timestamp()))
+    }
+
+    pub fn get_network_capacity(&self) -> NetworkCapacity {
+        NetworkCapacity {
+            total_nodes: self.nodes.len(),
+            max_network_nodes: self.max_network_nodes,
+            max_nodes_per_owner: self.max_nodes_per_owner,
+        }
+    }
+
+    /// Nodes registered under the account controlling `account_id`.
+    pub fn get_owner_node_count(&self, account_id: AccountId) -> u32 {
+        self.owner_node_counts.get(&registration_owner(&account_id)).unwrap_or(0)
+    }
+
+    pub fn get_heartbeat_timeouts(&self) -> HeartbeatTimeouts {
+        self.heartbeat_timeouts.clone()
+    }
+
+    pub fn get_assignment_strategy(&self) -> AssignmentStrategy {
+        self.assignment_strategy.clone()
+    }
+    
     pub fn get_task_count(&self) -> u64 {
         self.task_counter
     }
@@ -475,8 +1977,66 @@ impl DeAICompute {
         self.total_rewards_distributed.into()
     }
     
+    pub fn get_treasury_balance(&self) -> U128 {
+        self.treasury_balance.into()
+    }
+    
+    pub fn get_total_slashed(&self) -> U128 {
+        self.total_slashed.into()
+    }
+    
+    pub fn get_reputation_bonus_budget(&self) -> U128 {
+        self.reputation_bonus_budget.into()
+    }
+    
+    /// DEAI accrued to a node and not claimed yet.
+    pub fn get_pending_rewards(&self, node_id: AccountId) -> U128 {
+        U128(self.pending_rewards.get(&node_id).unwrap_or(0))
+    }
+    
+    /// DEAI a node would be minted for a task paying `base`, including its
+    /// reputation bonus.
+    pub fn get_effective_reward(&self, node_id: AccountId, base: U128) -> U128 {
+        let bonus = self.nodes.get(&node_id)
+            .map_or(0, |node| self.reputation_bonus(self.decayed_reputation(&node), base.0));
+        U128(base.0 + bonus)
+    }
+    
+    pub fn get_max_tasks_per_node(&self) -> u32 {
+        self.max_tasks_per_node
+    }
+
+    pub fn get_min_stake(&self) -> U128 {
+        self.min_stake.into()
+    }
+
+    pub fn get_min_compute_cost(&self) -> U128 {
+        self.min_compute_cost.into()
+    }
+
+    pub fn get_max_compute_cost(&self) -> U128 {
+        self.max_compute_cost.into()
+    }
+    
+    /// Nanoseconds an assigned task may run before it can be timed out.
+    pub fn get_task_timeout(&self) -> u64 {
+        self.task_timeout_duration
+    }
+
+    pub fn get_timing_stats(&self) -> TimingStats {
+        let average = |total: u128| {
+            (self.timed_task_count > 0).then(|| (total / self.timed_task_count as u128) as u64)
+        };
+        
+        TimingStats {
+            completed_tasks: self.timed_task_count,
+            average_time_to_assign: average(self.total_assign_time),
+            average_time_to_complete: average(self.total_execution_time),
+        }
+    }
+
     pub fn get_contract_stats(&self) -> (u64, u64, u64, u64, bool) {
-        let active_nodes = self.get_active_nodes().len() as u64;
+        let active_nodes = self.get_active_node_count();
         let total_nodes = self.nodes.len() as u64;
         let active_tasks = self.active_tasks.len() as u64;
         let completed_tasks = 0u64; // LookupMap doesn't have len()
@@ -487,6 +2047,7 @@ impl DeAICompute {
     // Token Functions
     #[payable]
     pub fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>) {
+        self.audit("ft_transfer", json!({ "receiver_id": receiver_id, "amount": amount }));
         self.assert_one_yocto();
         self.token.ft_transfer(receiver_id, amount, memo)
     }
@@ -502,6 +2063,7 @@ impl DeAICompute {
     // Admin Functions
     #[payable]
     pub fn update_min_stake(&mut self, new_min_stake: U128) {
+        self.audit("update_min_stake", json!({ "new_min_stake": new_min_stake }));
         self.assert_owner();
         self.assert_one_yocto();
         require!(new_min_stake.0 > 0, "Min stake must be positive");
@@ -512,8 +2074,28 @@ impl DeAICompute {
         log!("Min stake updated from {} to {}", old_stake, self.min_stake);
     }
     
+    /// Set the range of compute costs accepted for NEAR-paid tasks, in
+    /// yoctoNEAR. Token-paid tasks are priced in their token and not checked.
+    #[payable]
+    pub fn update_compute_cost_bounds(&mut self, min_compute_cost: U128, max_compute_cost: U128) {
+        self.audit("update_compute_cost_bounds", json!({
+            "min_compute_cost": min_compute_cost,
+            "max_compute_cost": max_compute_cost,
+        }));
+        self.assert_owner();
+        self.assert_one_yocto();
+        require!(min_compute_cost.0 > 0, "Min compute cost must be positive");
+        require!(min_compute_cost.0 <= max_compute_cost.0, "Min compute cost cannot exceed max compute cost");
+        
+        self.min_compute_cost = min_compute_cost.into();
+        self.max_compute_cost = max_compute_cost.into();
+        
+        log!("Compute cost bounds updated to {}..={}", self.min_compute_cost, self.max_compute_cost);
+    }
+    
     #[payable]
     pub fn pause_contract(&mut self) {
+        self.audit("pause_contract", json!({}));
         self.assert_owner();
         self.assert_one_yocto();
         require!(!self.paused, "Contract already paused");
@@ -524,6 +2106,7 @@ impl DeAICompute {
     
     #[payable]
     pub fn unpause_contract(&mut self) {
+        self.audit("unpause_contract", json!({}));
         self.assert_owner();
         self.assert_one_yocto();
         require!(self.paused, "Contract not paused");
@@ -534,6 +2117,7 @@ impl DeAICompute {
     
     #[payable]
     pub fn update_max_tasks_per_node(&mut self, max_tasks: u32) {
+        self.audit("update_max_tasks_per_node", json!({ "max_tasks": max_tasks }));
         self.assert_owner();
         self.assert_one_yocto();
         require!(max_tasks > 0 && max_tasks <= 100, "Invalid max tasks per node");
@@ -542,8 +2126,27 @@ impl DeAICompute {
         log!("Max tasks per node updated to {}", max_tasks);
     }
     
+    /// Cap the number of registered nodes, in total and per controlling
+    /// account. Existing nodes are kept when a cap is lowered below them.
+    #[payable]
+    pub fn set_registration_caps(&mut self, max_network_nodes: Option<u64>, max_nodes_per_owner: Option<u32>) {
+        self.audit("set_registration_caps", json!({
+            "max_network_nodes": max_network_nodes,
+            "max_nodes_per_owner": max_nodes_per_owner,
+        }));
+        self.assert_owner();
+        self.assert_one_yocto();
+        require!(max_network_nodes != Some(0), "Network node limit must be positive");
+        require!(max_nodes_per_owner != Some(0), "Per-owner node limit must be positive");
+        
+        self.max_network_nodes = max_network_nodes;
+        self.max_nodes_per_owner = max_nodes_per_owner;
+        log!("Registration caps updated: network {:?}, per owner {:?}", max_network_nodes, max_nodes_per_owner);
+    }
+    
     #[payable]
     pub fn update_task_timeout(&mut self, timeout_duration: u64) {
+        self.audit("update_task_timeout", json!({ "timeout_duration": timeout_duration }));
         self.assert_owner();
         self.assert_one_yocto();
         require!(timeout_duration >= 300_000_000_000, "Timeout too short (min 5 minutes)"); // 5 minutes minimum
@@ -555,6 +2158,7 @@ impl DeAICompute {
     
     #[payable]
     pub fn emergency_withdraw(&mut self, amount: U128) {
+        self.audit("emergency_withdraw", json!({ "amount": amount }));
         self.assert_owner();
         self.assert_one_yocto();
         require!(self.paused, "Contract must be paused for emergency withdrawal");
@@ -566,4 +2170,460 @@ impl DeAICompute {
         Promise::new(self.owner_id.clone()).transfer(NearToken::from_yoctonear(withdraw_amount));
         log!("Emergency withdrawal: {} yoctoNEAR", withdraw_amount);
     }
-}
\ No newline at end of file
+    
+    #[payable]
+    pub fn set_audit_enabled(&mut self, enabled: bool) {
+        self.audit("set_audit_enabled", json!({ "enabled": enabled }));
+        self.assert_owner();
+        self.assert_one_yocto();
+        
+        self.audit_enabled = enabled;
+        log!("Audit logging {}", if enabled { "enabled" } else { "disabled" });
+    }
+    
+    /// Update NEP-148 token metadata shown by wallets and exchanges.
+    #[payable]
+    pub fn update_token_metadata(&mut self, metadata: FungibleTokenMetadata) {
+        self.audit("update_token_metadata", json!({ "name": metadata.name, "symbol": metadata.symbol }));
+        self.assert_owner();
+        self.assert_one_yocto();
+        require!(metadata.spec == FT_METADATA_SPEC, "Unsupported metadata spec");
+        require!(!metadata.name.is_empty() && !metadata.symbol.is_empty(), "Token name and symbol are required");
+        // Balances are already denominated in the current decimals
+        require!(metadata.decimals == ref_finance_integration::DEAI_TOKEN_DECIMALS, "Token decimals cannot change");
+        metadata.assert_valid();
+        
+        self.metadata.set(&metadata);
+        log!("Token metadata updated: {} ({})", metadata.name, metadata.symbol);
+    }
+    
+    #[payable]
+    pub fn update_reputation_decay(&mut self, decay_per_day: u32) {
+        self.audit("update_reputation_decay", json!({ "decay_per_day": decay_per_day }));
+        self.assert_owner();
+        self.assert_one_yocto();
+        require!(decay_per_day <= MAX_REPUTATION, "Invalid reputation decay");
+        
+        self.reputation_decay_per_day = decay_per_day;
+        log!("Reputation decay updated to {} per day", decay_per_day);
+    }
+    
+    #[payable]
+    pub fn update_min_reputation_for_assignment(&mut self, min_reputation: u32) {
+        self.audit("update_min_reputation_for_assignment", json!({ "min_reputation": min_reputation }));
+        self.assert_owner();
+        self.assert_one_yocto();
+        require!(min_reputation <= MAX_REPUTATION, "Invalid minimum reputation");
+        
+        self.min_reputation_for_assignment = min_reputation;
+        log!("Min reputation for assignment updated to {}", min_reputation);
+    }
+    
+    #[payable]
+    pub fn update_approval_window(&mut self, approval_window: u64) {
+        self.audit("update_approval_window", json!({ "approval_window": approval_window }));
+        self.assert_owner();
+        self.assert_one_yocto();
+        require!(approval_window > 0, "Approval window must be positive");
+        require!(approval_window <= 30 * NANOS_PER_DAY, "Approval window too long (max 30 days)");
+        
+        self.approval_window = approval_window;
+        log!("Approval window updated to {} nanoseconds", approval_window);
+    }
+    
+    /// Set how much DEAI may still be minted as reputation bonuses.
+    /// Zero turns the bonus off.
+    #[payable]
+    pub fn update_reputation_bonus_budget(&mut self, budget: U128) {
+        self.audit("update_reputation_bonus_budget", json!({ "budget": budget }));
+        self.assert_owner();
+        self.assert_one_yocto();
+        
+        self.reputation_bonus_budget = budget.into();
+        log!("Reputation bonus budget updated to {}", budget.0);
+    }
+    
+    #[payable]
+    pub fn update_unbonding_period(&mut self, unbonding_period: u64) {
+        self.audit("update_unbonding_period", json!({ "unbonding_period": unbonding_period }));
+        self.assert_owner();
+        self.assert_one_yocto();
+        require!(unbonding_period <= 30 * NANOS_PER_DAY, "Unbonding period too long (max 30 days)");
+        
+        self.unbonding_period = unbonding_period;
+        log!("Unbonding period updated to {} nanoseconds", unbonding_period);
+    }
+    
+    #[payable]
+    pub fn update_slash_compensation(&mut self, compensation_bps: u32) {
+        self.audit("update_slash_compensation", json!({ "compensation_bps": compensation_bps }));
+        self.assert_owner();
+        self.assert_one_yocto();
+        require!(compensation_bps <= BPS_DENOMINATOR, "Compensation cannot exceed 100%");
+        
+        self.slash_compensation_bps = compensation_bps;
+        log!("Slash compensation updated to {} bps", compensation_bps);
+    }
+    
+    #[payable]
+    pub fn update_platform_fee(&mut self, fee_bps: u32) {
+        self.audit("update_platform_fee", json!({ "fee_bps": fee_bps }));
+        self.assert_owner();
+        self.assert_one_yocto();
+        require!(fee_bps <= MAX_PLATFORM_FEE_BPS, "Platform fee too high (max 20%)");
+        
+        self.platform_fee_bps = fee_bps;
+        log!("Platform fee updated to {} bps", fee_bps);
+    }
+    
+    #[payable]
+    pub fn set_treasury(&mut self, treasury_id: AccountId) {
+        self.audit("set_treasury", json!({ "treasury_id": treasury_id }));
+        self.assert_owner();
+        self.assert_one_yocto();
+        
+        log!("Treasury updated from {} to {}", self.treasury_id, treasury_id);
+        self.treasury_id = treasury_id;
+    }
+    
+    #[payable]
+    pub fn withdraw_treasury(&mut self, amount: U128) {
+        self.audit("withdraw_treasury", json!({ "amount": amount }));
+        self.assert_one_yocto();
+        require!(env::predecessor_account_id() == self.treasury_id, "Only treasury can withdraw fees");
+        
+        let withdraw_amount: u128 = amount.into();
+        require!(withdraw_amount <= self.treasury_balance, "Insufficient treasury balance");
+        
+        self.treasury_balance -= withdraw_amount;
+        Promise::new(self.treasury_id.clone()).transfer(NearToken::from_yoctonear(withdraw_amount));
+        log!("Treasury withdrawal: {} yoctoNEAR", withdraw_amount);
+    }
+    
+    /// Send back part of the caller's deposit of a NEP-141 token.
+    #[payable]
+    pub fn withdraw_token_deposit(&mut self, token_id: AccountId, amount: U128) -> Promise {
+        self.audit("withdraw_token_deposit", json!({ "token_id": token_id, "amount": amount }));
+        self.assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let amount: Balance = amount.into();
+        
+        require!(amount > 0, "Amount must be positive");
+        require!(self.internal_token_deposit(&account_id, &token_id) >= amount, "Insufficient token deposit");
+        
+        self.internal_withdraw_token(&account_id, &token_id, amount);
+        log!("Token withdrawal: {} {} to {}", amount, token_id, account_id);
+        self.transfer_token(&account_id, token_id, amount)
+    }
+    
+    pub fn get_token_deposit(&self, account_id: AccountId, token_id: AccountId) -> U128 {
+        self.internal_token_deposit(&account_id, &token_id).into()
+    }
+    
+    /// Credits back a token transfer the token contract rejected, e.g. because
+    /// the receiver has no storage registered there.
+    #[private]
+    pub fn on_token_transfer(
+        &mut self,
+        token_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) -> bool {
+        if result.is_ok() {
+            return true;
+        }
+        
+        self.internal_deposit_token(&receiver_id, &token_id, amount.into());
+        log!("Transfer of {} {} to {} failed, kept as deposit", amount.0, token_id, receiver_id);
+        false
+    }
+    
+    fn internal_token_deposit(&self, account_id: &AccountId, token_id: &AccountId) -> Balance {
+        self.token_deposits.get(&(account_id.clone(), token_id.clone())).unwrap_or(0)
+    }
+    
+    fn internal_deposit_token(&mut self, account_id: &AccountId, token_id: &AccountId, amount: Balance) {
+        if amount == 0 {
+            return;
+        }
+        let key = (account_id.clone(), token_id.clone());
+        let balance = self.token_deposits.get(&key).unwrap_or(0);
+        self.token_deposits.insert(&key, &(balance + amount));
+    }
+    
+    fn internal_withdraw_token(&mut self, account_id: &AccountId, token_id: &AccountId, amount: Balance) {
+        let key = (account_id.clone(), token_id.clone());
+        let balance = self.token_deposits.get(&key).unwrap_or(0);
+        require!(balance >= amount, "Insufficient token deposit");
+        if balance == amount {
+            self.token_deposits.remove(&key);
+        } else {
+            self.token_deposits.insert(&key, &(balance - amount));
+        }
+    }
+    
+    // Pays out over `ft_transfer`; if the token contract refuses, the amount
+    // is credited back to the receiver's deposit by `on_token_transfer`
+    fn transfer_token(&mut self, receiver_id: &AccountId, token_id: AccountId, amount: Balance) -> Promise {
+        fungible_token::ext(token_id.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(ONE_YOCTO))
+            .with_static_gas(GAS_FOR_FT_TRANSFER)
+            .ft_transfer(receiver_id.clone(), U128(amount), None)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(CALLBACK_GAS)
+                    .on_token_transfer(token_id, receiver_id.clone(), U128(amount))
+            )
+    }
+    
+    // Returns a task's held reward and fee in the currency it was paid in.
+    // Token refunds go to the requester's deposit, ready for the next task.
+    fn refund_requester(&mut self, task: &Task) {
+        let Ok(requester_id) = task.requester.parse::<AccountId>() else {
+            return;
+        };
+        match task.reward_token.as_ref().and_then(|t| t.parse::<AccountId>().ok()) {
+            Some(token_id) => self.internal_deposit_token(&requester_id, &token_id, task.refund_amount()),
+            None => {
+                Promise::new(requester_id).transfer(NearToken::from_yoctonear(task.refund_amount()));
+            }
+        }
+    }
+    
+    #[payable]
+    pub fn set_assignment_strategy(&mut self, strategy: AssignmentStrategy) {
+        self.audit("set_assignment_strategy", json!({ "strategy": strategy }));
+        self.assert_owner();
+        self.assert_one_yocto();
+        
+        log!("Assignment strategy updated to {:?}", strategy);
+        self.assignment_strategy = strategy;
+    }
+    
+    #[payable]
+    pub fn set_node_tier(&mut self, node_id: AccountId, tier: NodeTier) {
+        self.audit("set_node_tier", json!({ "node_id": node_id, "tier": tier }));
+        self.assert_owner();
+        self.assert_one_yocto();
+        let mut node = self.nodes.get(&node_id).expect("Node not registered");
+        
+        log!("Node tier updated: {}, {:?} -> {:?}", node_id, node.tier, tier);
+        node.tier = tier;
+        self.nodes.insert(&node_id, &node);
+    }
+    
+    /// Set how long nodes of `tier` may go without a heartbeat, in nanoseconds.
+    #[payable]
+    pub fn set_heartbeat_timeout(&mut self, tier: NodeTier, timeout: u64) {
+        self.audit("set_heartbeat_timeout", json!({ "tier": tier, "timeout": timeout }));
+        self.assert_owner();
+        self.assert_one_yocto();
+        require!(
+            (MIN_HEARTBEAT_TIMEOUT..=MAX_HEARTBEAT_TIMEOUT).contains(&timeout),
+            "Heartbeat timeout must be between 1 minute and 1 hour"
+        );
+        
+        match tier {
+            NodeTier::Enterprise => self.heartbeat_timeouts.enterprise = timeout,
+            NodeTier::Standard => self.heartbeat_timeouts.standard = timeout,
+            NodeTier::Hobby => self.heartbeat_timeouts.hobby = timeout,
+        }
+        log!("Heartbeat timeout for {:?} nodes updated to {}", tier, timeout);
+    }
+}
+
+// Matching results needed to accept a task: a strict majority of its nodes
+fn quorum_size(redundancy: u8) -> usize {
+    redundancy as usize / 2 + 1
+}
+
+// Assigned to the node, which has not submitted a result yet
+fn is_awaiting_result(task: &Task, node_id: &str) -> bool {
+    task.assignees.iter().any(|a| a == node_id)
+        && !task.submissions.iter().any(|s| s.node_id == node_id)
+}
+
+// Reads the optional `min_vram_gb` requirement from a JSON task description
+fn parse_min_vram_gb(description: &str) -> Option<u32> {
+    near_sdk::serde_json::from_str::<Value>(description)
+        .ok()?
+        .get("min_vram_gb")?
+        .as_u64()
+        .map(|gb| gb.min(u32::MAX as u64) as u32)
+}
+
+fn parse_task_type(description: &str) -> Option<String> {
+    near_sdk::serde_json::from_str::<Value>(description)
+        .ok()?
+        .get("task_type")?
+        .as_str()
+        .map(str::to_string)
+}
+
+fn parse_preferred_node(description: &str) -> Option<String> {
+    near_sdk::serde_json::from_str::<Value>(description)
+        .ok()?
+        .get("preferred_node")?
+        .as_str()
+        .map(str::to_string)
+}
+
+// Sorted and deduplicated, so equal sets compare equal
+fn normalize_capabilities(mut capabilities: Vec<String>) -> Vec<String> {
+    require!(capabilities.len() <= MAX_NODE_CAPABILITIES, "Too many capabilities");
+    for capability in &capabilities {
+        require!(!capability.is_empty(), "Capability cannot be empty");
+        require!(capability.len() <= MAX_CAPABILITY_LEN, "Capability too long");
+    }
+    capabilities.sort();
+    capabilities.dedup();
+    capabilities
+}
+
+// Extracts the largest "<n>GB" figure from free-form GPU specs, e.g. "RTX 4090 24GB"
+fn parse_vram_gb(gpu_specs: &str) -> Option<u32> {
+    let specs = gpu_specs.to_uppercase();
+    let bytes = specs.as_bytes();
+    let mut vram = None;
+    
+    for (pos, _) in specs.match_indices("GB") {
+        let mut end = pos;
+        while end > 0 && bytes[end - 1] == b' ' {
+            end -= 1;
+        }
+        let mut start = end;
+        while start > 0 && bytes[start - 1].is_ascii_digit() {
+            start -= 1;
+        }
+        if let Ok(gb) = specs[start..end].parse::<u32>() {
+            vram = vram.max(Some(gb));
+        }
+    }
+    vram
+}
+
+// The account that controls a node for the per-owner cap: the name registered
+// under a top-level account, since only it can create sub-accounts below it.
+// `node1.alice.near` and `node2.alice.near` both belong to `alice.near`.
+fn registration_owner(account_id: &AccountId) -> String {
+    let labels: Vec<&str> = account_id.as_str().rsplitn(3, '.').collect();
+    match labels.as_slice() {
+        [top_level, name, _] => format!("{}.{}", name, top_level),
+        _ => account_id.to_string(),
+    }
+}
+
+// The deposit must cover `required`. Anything past it is kept, so more than
+// a small margin is rejected as a likely mistake rather than trapped.
+fn assert_payment(attached: Balance, required: Balance) {
+    require!(attached >= required, "Insufficient payment for compute cost and storage");
+    require!(
+        attached - required <= MAX_OVERPAYMENT,
+        format!("Attached deposit exceeds the required {} yoctoNEAR by more than {}", required, MAX_OVERPAYMENT)
+    );
+}
+
+fn node_allowed(task: &Task, node_id: &AccountId) -> bool {
+    task.allowed_nodes.as_ref().map_or(true, |allowed| allowed.iter().any(|n| n == node_id.as_str()))
+}
+
+// Nodes that advertise no capabilities take any task, as before they existed
+fn node_supports_task_type(node: &NodeInfo, task_type: Option<&str>) -> bool {
+    match task_type {
+        Some(task_type) if !node.capabilities.is_empty() => node.capabilities.iter().any(|c| c == task_type),
+        _ => true,
+    }
+}
+
+// Nodes that don't state their VRAM can only serve tasks without a requirement
+fn node_meets_vram(node: &NodeInfo, min_vram_gb: Option<u32>) -> bool {
+    match min_vram_gb {
+        Some(required) => parse_vram_gb(&node.gpu_specs).map_or(false, |vram| vram >= required),
+        None => true,
+    }
+}
+
+#[near]
+impl FungibleTokenMetadataProvider for DeAICompute {
+    fn ft_metadata(&self) -> FungibleTokenMetadata {
+        self.metadata.get().unwrap()
+    }
+}
+
+/// Escrow for token-paid tasks: `ft_transfer_call` a NEP-141 token to this
+/// contract to credit the sender's deposit of that token.
+#[near]
+impl FungibleTokenReceiver for DeAICompute {
+    fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128> {
+        let token_id = env::predecessor_account_id();
+        self.audit("ft_on_transfer", json!({ "sender_id": sender_id, "amount": amount, "msg": msg }));
+        
+        // DEAI rewards are minted, never escrowed; return it all
+        if token_id == env::current_account_id() || self.paused {
+            return PromiseOrValue::Value(amount);
+        }
+        
+        self.internal_deposit_token(&sender_id, &token_id, amount.into());
+        log!("Token deposit: {} {} from {}", amount.0, token_id, sender_id);
+        PromiseOrValue::Value(U128(0))
+    }
+}
+
+#[near]
+impl StorageManagement for DeAICompute {
+    #[payable]
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        self.audit("storage_deposit", json!({ "account_id": account_id, "registration_only": registration_only }));
+        self.token.storage_deposit(account_id, registration_only)
+    }
+
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<NearToken>) -> StorageBalance {
+        self.audit("storage_withdraw", json!({ "amount": amount }));
+        self.token.storage_withdraw(amount)
+    }
+
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        self.audit("storage_unregister", json!({ "force": force }));
+        let account_id = env::predecessor_account_id();
+        
+        // Nodes must deactivate and claim their stake before reclaiming storage
+        if let Some(node) = self.nodes.get(&account_id) {
+            require!(!node.is_active, "Deactivate node before unregistering storage");
+            require!(node.unbond_available_at.is_none(), "Claim unbonded stake before unregistering storage");
+            require!(node.stake == 0, "Unbond and claim stake before unregistering storage");
+            require!(!self.node_has_active_task(&account_id), "Cannot unregister a node with assigned tasks");
+            // Rewards held for approval are paid to the node record
+            require!(
+                !self.active_tasks.values().any(|task| task.submissions.iter().any(|s| s.node_id == account_id.as_str())),
+                "Cannot unregister a node with results awaiting approval"
+            );
+        }
+        require!(self.pending_rewards.get(&account_id).is_none(), "Claim pending rewards before unregistering storage");
+        
+        let unregistered = self.token.internal_storage_unregister(force).is_some();
+        if unregistered && self.nodes.remove(&account_id).is_some() {
+            let owner = registration_owner(&account_id);
+            match self.owner_node_counts.get(&owner).unwrap_or(0) {
+                0 | 1 => { self.owner_node_counts.remove(&owner); }
+                count => { self.owner_node_counts.insert(&owner, &(count - 1)); }
+            }
+            log!("Node record removed on storage unregister: {}", account_id);
+        }
+        unregistered
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        self.token.storage_balance_bounds()
+    }
+
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.token.storage_balance_of(account_id)
+    }
+}
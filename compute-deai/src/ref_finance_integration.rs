@@ -1,5 +1,6 @@
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
-use near_sdk::{near, AccountId, Promise, json_types::U128, ext_contract, Gas, NearToken};
+use near_sdk::{near, require, AccountId, Promise, PromiseError, json_types::U128, ext_contract, Gas, NearToken};
+use near_sdk::serde_json::json;
 use serde::{Deserialize, Serialize};
 
 // Ref Finance contract interface
@@ -90,11 +91,13 @@ pub struct LiquidityPosition {
 pub const REF_FINANCE_CONTRACT: &str = "v2.ref-finance.near";
 pub const DEAI_TOKEN_DECIMALS: u8 = 18;
 pub const MIN_LIQUIDITY_AMOUNT: u128 = 1_000_000_000_000_000_000_000; // 1000 DEAI
-pub const SLIPPAGE_TOLERANCE: u32 = 300; // 3% in basis points
-pub const GAS_FOR_FT_TRANSFER: Gas = Gas(Gas::ONE_TERA.0 * 15);
-pub const GAS_FOR_SWAP: Gas = Gas(Gas::ONE_TERA.0 * 50);
-pub const GAS_FOR_ADD_LIQUIDITY: Gas = Gas(Gas::ONE_TERA.0 * 100);
+pub const SLIPPAGE_TOLERANCE: u32 = 300; // 3% in basis points, default for slippage_bps
+pub const MAX_SLIPPAGE_BPS: u32 = 1000; // 10%
+pub const GAS_FOR_FT_TRANSFER: Gas = Gas::from_tgas(15);
+pub const GAS_FOR_SWAP: Gas = Gas::from_tgas(50);
+pub const GAS_FOR_ADD_LIQUIDITY: Gas = Gas::from_tgas(100);
 
+#[near]
 impl crate::DeAICompute {
     /// Initialize Ref Finance integration
     pub fn init_ref_finance_integration(&mut self, pool_id: u64) {
@@ -109,12 +112,23 @@ impl crate::DeAICompute {
             is_active: true,
         };
         
-        // Store pool configuration
-        // self.ref_pool_config = Some(pool_config);
+        self.ref_pool_config = Some(pool_config);
         
         near_sdk::log!("Ref Finance integration initialized with pool ID: {}", pool_id);
     }
     
+    /// Get the stored DEAI/wNEAR pool configuration
+    pub fn get_ref_pool_config(&self) -> Option<LiquidityPoolConfig> {
+        self.ref_pool_config.clone()
+    }
+    
+    fn ref_pool_id(&self) -> u64 {
+        self.ref_pool_config
+            .as_ref()
+            .expect("Ref Finance integration not initialized. Call init_ref_finance_integration first")
+            .pool_id
+    }
+    
     /// Add liquidity to the DEAI/wNEAR pool on Ref Finance
     #[payable]
     pub fn add_liquidity_to_ref(
@@ -129,11 +143,12 @@ impl crate::DeAICompute {
         
         assert!(deai_amount_val >= MIN_LIQUIDITY_AMOUNT, "DEAI amount too small");
         assert!(attached_near.as_yoctonear() > 0, "Must attach NEAR for liquidity");
+        let pool_id = self.ref_pool_id();
         
         // First, transfer DEAI tokens to Ref Finance
         let transfer_msg = serde_json::json!({
             "AddLiquidity": {
-                "pool_id": 1, // Assuming DEAI/wNEAR pool ID is 1
+                "pool_id": pool_id,
                 "amounts": [deai_amount, U128(attached_near.as_yoctonear())],
                 "min_amounts": [deai_amount, min_wnear_amount]
             }
@@ -147,6 +162,58 @@ impl crate::DeAICompute {
                 Some("Adding liquidity to DEAI/wNEAR pool".to_string()),
                 transfer_msg,
             )
+            .then(
+                ext_self::ext(near_sdk::env::current_account_id())
+                    .with_static_gas(crate::CALLBACK_GAS)
+                    .on_add_liquidity_callback(pool_id, deai_amount, U128(attached_near.as_yoctonear()))
+            )
+    }
+    
+    /// Record the LP position once Ref Finance accepted the liquidity
+    #[private]
+    pub fn on_add_liquidity_callback(
+        &mut self,
+        pool_id: u64,
+        deai_amount: U128,
+        wnear_amount: U128,
+        #[callback_result] shares_added: Result<U128, PromiseError>,
+    ) -> bool {
+        let shares_added = match shares_added {
+            Ok(shares) if shares.0 > 0 => shares,
+            _ => {
+                near_sdk::log!("Adding liquidity to pool {} failed, no position recorded", pool_id);
+                return false;
+            }
+        };
+        
+        let now = near_sdk::env::block_timestamp();
+        let position = match self.liquidity_positions.get(&pool_id) {
+            Some(existing) => LiquidityPosition {
+                pool_id,
+                shares: U128(existing.shares.0 + shares_added.0),
+                token_a_amount: U128(existing.token_a_amount.0 + deai_amount.0),
+                token_b_amount: U128(existing.token_b_amount.0 + wnear_amount.0),
+                created_at: existing.created_at,
+                last_updated: now,
+            },
+            None => LiquidityPosition {
+                pool_id,
+                shares: shares_added,
+                token_a_amount: deai_amount,
+                token_b_amount: wnear_amount,
+                created_at: now,
+                last_updated: now,
+            },
+        };
+        
+        self.liquidity_positions.insert(&pool_id, &position);
+        near_sdk::log!("Liquidity position updated for pool {}: {} shares", pool_id, position.shares.0);
+        true
+    }
+    
+    /// Get the contract's LP position in a Ref Finance pool
+    pub fn get_liquidity_position(&self, pool_id: u64) -> Option<LiquidityPosition> {
+        self.liquidity_positions.get(&pool_id)
     }
     
     /// Remove liquidity from the DEAI/wNEAR pool
@@ -157,13 +224,17 @@ impl crate::DeAICompute {
         min_wnear_amount: U128,
     ) -> Promise {
         self.assert_owner();
+        let pool_id = self.ref_pool_id();
+        
+        let position = self.liquidity_positions.get(&pool_id).expect("No liquidity position for pool");
+        assert!(shares.0 > 0 && shares.0 <= position.shares.0, "Invalid share amount");
         
         // Call Ref Finance to remove liquidity
-        ref_finance::ext(REF_FINANCE_CONTRACT.parse().unwrap())
+        ref_finance_extended::ext(REF_FINANCE_CONTRACT.parse().unwrap())
             .with_static_gas(GAS_FOR_SWAP)
             .with_attached_deposit(NearToken::from_yoctonear(1))
             .remove_liquidity(
-                1, // pool_id
+                pool_id,
                 shares,
                 vec![min_deai_amount, min_wnear_amount],
             )
@@ -173,11 +244,13 @@ impl crate::DeAICompute {
     pub fn swap_deai_for_wnear(
         &mut self,
         deai_amount: U128,
-        min_wnear_amount: U128,
+        min_wnear_amount: Option<U128>,
     ) -> Promise {
         let deai_amount_val: u128 = deai_amount.into();
         
         assert!(deai_amount_val > 0, "Amount must be positive");
+        let pool_id = self.ref_pool_id();
+        let min_wnear_amount = min_wnear_amount.unwrap_or_else(|| self.calculate_min_amount_out(deai_amount, true));
         assert!(
             self.token.accounts.get(&near_sdk::env::predecessor_account_id()).unwrap_or(0) >= deai_amount_val,
             "Insufficient DEAI balance"
@@ -188,7 +261,7 @@ impl crate::DeAICompute {
         
         // Prepare swap action
         let swap_action = SwapAction {
-            pool_id: 1, // DEAI/wNEAR pool
+            pool_id,
             token_in: near_sdk::env::current_account_id(),
             amount_in: Some(deai_amount),
             token_out: "wrap.near".parse().unwrap(),
@@ -209,15 +282,18 @@ impl crate::DeAICompute {
     #[payable]
     pub fn swap_wnear_for_deai(
         &mut self,
-        min_deai_amount: U128,
+        min_deai_amount: Option<U128>,
     ) -> Promise {
         let wnear_amount = near_sdk::env::attached_deposit();
         
         assert!(wnear_amount.as_yoctonear() > 0, "Must attach wNEAR for swap");
+        let pool_id = self.ref_pool_id();
+        let min_deai_amount = min_deai_amount
+            .unwrap_or_else(|| self.calculate_min_amount_out(U128(wnear_amount.as_yoctonear()), false));
         
         // Prepare swap action
         let swap_action = SwapAction {
-            pool_id: 1, // DEAI/wNEAR pool
+            pool_id,
             token_in: "wrap.near".parse().unwrap(),
             amount_in: Some(U128(wnear_amount.as_yoctonear())),
             token_out: near_sdk::env::current_account_id(),
@@ -234,16 +310,78 @@ impl crate::DeAICompute {
             )
     }
     
+    /// Minimum acceptable swap output for `amount_in` at the cached price less the slippage tolerance
+    pub fn calculate_min_amount_out(&self, amount_in: U128, deai_in: bool) -> U128 {
+        let (price, _) = self.cached_price.expect("No cached DEAI price. Call get_deai_price or pass a minimum amount out");
+        let one_deai = 10u128.pow(DEAI_TOKEN_DECIMALS as u32);
+        
+        let expected_out = if deai_in {
+            mul_div(amount_in.0, price.0, one_deai)
+        } else {
+            mul_div(amount_in.0, one_deai, price.0)
+        }
+        .expect("Swap amount too large");
+        
+        U128(expected_out * (10_000 - self.slippage_bps) as u128 / 10_000)
+    }
+    
+    /// Update the slippage tolerance applied to swaps without an explicit minimum
+    #[payable]
+    pub fn set_slippage_tolerance(&mut self, slippage_bps: u32) {
+        self.audit("set_slippage_tolerance", json!({ "slippage_bps": slippage_bps }));
+        self.assert_owner();
+        self.assert_one_yocto();
+        require!(slippage_bps <= MAX_SLIPPAGE_BPS, "Slippage tolerance too high (max 10%)");
+        
+        self.slippage_bps = slippage_bps;
+        near_sdk::log!("Slippage tolerance updated to {} bps", slippage_bps);
+    }
+    
     /// Get current pool information from Ref Finance
     pub fn get_ref_pool_info(&self, pool_id: u64) -> Promise {
         ref_finance::ext(REF_FINANCE_CONTRACT.parse().unwrap())
-            .with_static_gas(Gas::ONE_TERA)
+            .with_static_gas(Gas::from_tgas(1))
             .get_pool_info(pool_id)
     }
     
-    /// Get DEAI token price in wNEAR from Ref Finance
+    /// Refresh the cached DEAI price in wNEAR from Ref Finance
     pub fn get_deai_price(&self) -> Promise {
-        self.get_ref_pool_info(1) // Assuming DEAI/wNEAR pool ID is 1
+        self.get_ref_pool_info(self.ref_pool_id()).then(
+            ext_self::ext(near_sdk::env::current_account_id())
+                .with_static_gas(crate::CALLBACK_GAS)
+                .on_pool_info_callback()
+        )
+    }
+    
+    /// Cache the DEAI price (yoctoNEAR per whole DEAI) computed from pool reserves
+    #[private]
+    pub fn on_pool_info_callback(&mut self, #[callback_result] result: Result<PoolInfo, PromiseError>) -> Option<U128> {
+        let pool_info = match result {
+            Ok(pool_info) => pool_info,
+            Err(_) => {
+                near_sdk::log!("Failed to fetch Ref Finance pool info");
+                return None;
+            }
+        };
+        
+        let price = match calculate_deai_price(&pool_info, &near_sdk::env::current_account_id()) {
+            Some(price) => price,
+            None => {
+                near_sdk::log!("Pool has no DEAI liquidity, price not updated");
+                return None;
+            }
+        };
+        
+        self.cached_price = Some((price, near_sdk::env::block_timestamp()));
+        near_sdk::log!("DEAI price updated: {} yoctoNEAR", price.0);
+        Some(price)
+    }
+    
+    /// Get the last cached DEAI price and its age in nanoseconds
+    pub fn get_cached_deai_price(&self) -> Option<(U128, u64)> {
+        self.cached_price.map(|(price, updated_at)| {
+            (price, near_sdk::env::block_timestamp().saturating_sub(updated_at))
+        })
     }
     
     /// Enable automated liquidity management
@@ -313,7 +451,7 @@ impl crate::DeAICompute {
             total_supply: U128(total_supply),
             circulating_supply: U128(circulation_supply),
             total_rewards_distributed: U128(total_rewards_distributed),
-            active_nodes: self.get_active_nodes().len() as u32,
+            active_nodes: self.get_active_node_count() as u32,
             total_tasks_completed: self.task_counter,
             avg_reward_per_task: if self.task_counter > 0 {
                 U128(total_rewards_distributed / self.task_counter)
@@ -323,19 +461,12 @@ impl crate::DeAICompute {
         }
     }
     
-    /// Update token metadata for NEP-141 compliance
-    pub fn update_token_metadata(&mut self, metadata: FungibleTokenMetadata) {
-        self.assert_owner();
-        // Update token metadata for better Ref Finance integration
-        // self.token_metadata = metadata;
-    }
-    
     /// Emergency functions for liquidity management
     pub fn emergency_withdraw_liquidity(&mut self, pool_id: u64) -> Promise {
         self.assert_owner();
         
         // Emergency withdrawal from Ref Finance pool
-        ref_finance::ext(REF_FINANCE_CONTRACT.parse().unwrap())
+        ref_finance_extended::ext(REF_FINANCE_CONTRACT.parse().unwrap())
             .with_static_gas(GAS_FOR_SWAP)
             .with_attached_deposit(NearToken::from_yoctonear(1))
             .emergency_withdraw(pool_id)
@@ -366,15 +497,58 @@ impl crate::DeAICompute {
             }
         }
     }
+}
+
+// Price of one whole DEAI in the pool's other token, from the reserve ratio
+fn calculate_deai_price(pool_info: &PoolInfo, deai_token: &AccountId) -> Option<U128> {
+    if pool_info.token_account_ids.len() != 2 || pool_info.amounts.len() != 2 {
+        return None;
+    }
     
-    /// Helper function to assert owner-only access
-    fn assert_owner(&self) {
-        assert_eq!(
-            near_sdk::env::predecessor_account_id(),
-            self.owner_id,
-            "Only owner can call this method"
-        );
+    let deai_index = pool_info.token_account_ids.iter().position(|id| id == deai_token)?;
+    let deai_reserve = pool_info.amounts[deai_index].0;
+    let wnear_reserve = pool_info.amounts[1 - deai_index].0;
+    if deai_reserve == 0 {
+        return None;
+    }
+    
+    mul_div(wnear_reserve, 10u128.pow(DEAI_TOKEN_DECIMALS as u32), deai_reserve).map(U128)
+}
+
+// a * b / denominator with a 256-bit intermediate; None if the result overflows u128
+fn mul_div(a: u128, b: u128, denominator: u128) -> Option<u128> {
+    if denominator == 0 {
+        return None;
+    }
+    
+    const MASK: u128 = u64::MAX as u128;
+    let (a_hi, a_lo) = (a >> 64, a & MASK);
+    let (b_hi, b_lo) = (b >> 64, b & MASK);
+    
+    let lo_lo = a_lo * b_lo;
+    let hi_lo = a_hi * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let cross = (lo_lo >> 64) + (hi_lo & MASK) + (lo_hi & MASK);
+    
+    let low = (cross << 64) | (lo_lo & MASK);
+    let high = a_hi * b_hi + (hi_lo >> 64) + (lo_hi >> 64) + (cross >> 64);
+    if high >= denominator {
+        return None;
+    }
+    
+    // Binary long division of (high, low) by denominator
+    let mut remainder = high;
+    let mut quotient = 0u128;
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= denominator {
+            remainder = remainder.wrapping_sub(denominator);
+            quotient |= 1;
+        }
     }
+    Some(quotient)
 }
 
 // Token economics statistics structure
@@ -389,19 +563,6 @@ pub struct TokenEconomicsStats {
     pub avg_reward_per_task: U128,
 }
 
-// Token metadata structure for NEP-141 compliance
-#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
-#[serde(crate = "near_sdk::serde")]
-pub struct FungibleTokenMetadata {
-    pub spec: String,
-    pub name: String,
-    pub symbol: String,
-    pub icon: Option<String>,
-    pub reference: Option<String>,
-    pub reference_hash: Option<String>,
-    pub decimals: u8,
-}
-
 // External contract interfaces for callbacks
 #[ext_contract(ext_self)]
 trait ExtSelf {
@@ -411,6 +572,10 @@ trait ExtSelf {
         amount_out: U128,
         token_out: AccountId,
     );
+    
+    fn on_pool_info_callback(&mut self) -> Option<U128>;
+    
+    fn on_add_liquidity_callback(&mut self, pool_id: u64, deai_amount: U128, wnear_amount: U128) -> bool;
 }
 
 #[ext_contract(ref_finance_extended)]
@@ -459,4 +624,253 @@ mod tests {
         assert_eq!(stats.total_supply, U128(0));
         assert_eq!(stats.total_tasks_completed, 0);
     }
+    
+    #[test]
+    fn test_ref_pool_config_persisted() {
+        let context = VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .build();
+        testing_env!(context);
+        
+        let mut contract = crate::DeAICompute::new(accounts(0));
+        assert!(contract.get_ref_pool_config().is_none());
+        
+        contract.init_ref_finance_integration(42);
+        
+        let config = contract.get_ref_pool_config().unwrap();
+        assert_eq!(config.pool_id, 42);
+        assert_eq!(config.token_a, accounts(0));
+        assert!(config.is_active);
+    }
+    
+    // Arguments of the first scheduled call to `method`
+    fn scheduled_call_args(method: &str) -> serde_json::Value {
+        near_sdk::test_utils::get_created_receipts()
+            .into_iter()
+            .flat_map(|receipt| receipt.actions)
+            .find_map(|action| match action {
+                near_sdk::mock::MockAction::FunctionCallWeight { method_name, args, .. }
+                    if method_name == method.as_bytes() => Some(serde_json::from_slice(&args).unwrap()),
+                _ => None,
+            })
+            .unwrap_or_else(|| panic!("No call to {} scheduled", method))
+    }
+    
+    #[test]
+    fn test_swaps_and_liquidity_use_configured_pool() {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0));
+        testing_env!(builder.build());
+        
+        let mut contract = crate::DeAICompute::new(accounts(0));
+        contract.init_ref_finance_integration(42);
+        contract.token.internal_deposit(&accounts(0), 1000);
+        
+        contract.swap_deai_for_wnear(U128(1000), Some(U128(1)));
+        assert_eq!(scheduled_call_args("swap")["actions"][0]["pool_id"], 42);
+        
+        builder.attached_deposit(NearToken::from_near(1));
+        testing_env!(builder.build());
+        contract.swap_wnear_for_deai(Some(U128(1)));
+        assert_eq!(scheduled_call_args("swap")["actions"][0]["pool_id"], 42);
+        
+        testing_env!(builder.build());
+        contract.add_liquidity_to_ref(U128(MIN_LIQUIDITY_AMOUNT), U128(1));
+        let transfer = scheduled_call_args("ft_transfer_call");
+        let msg: serde_json::Value = serde_json::from_str(transfer["msg"].as_str().unwrap()).unwrap();
+        assert_eq!(msg["AddLiquidity"]["pool_id"], 42);
+        assert_eq!(scheduled_call_args("on_add_liquidity_callback")["pool_id"], 42);
+    }
+    
+    #[test]
+    fn test_pool_info_callback_caches_price() {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .block_timestamp(1_000);
+        testing_env!(builder.build());
+        
+        let mut contract = crate::DeAICompute::new(accounts(0));
+        assert!(contract.get_cached_deai_price().is_none());
+        
+        // 2000 DEAI against 1 wNEAR
+        let pool_info = PoolInfo {
+            token_account_ids: vec!["wrap.near".parse().unwrap(), accounts(0)],
+            amounts: vec![
+                U128(1_000_000_000_000_000_000_000_000),
+                U128(2_000_000_000_000_000_000_000),
+            ],
+            total_fee: 25,
+            shares_total_supply: U128(0),
+        };
+        let price = contract.on_pool_info_callback(Ok(pool_info)).unwrap();
+        assert_eq!(price, U128(500_000_000_000_000_000_000));
+        
+        builder.block_timestamp(5_000);
+        testing_env!(builder.build());
+        assert_eq!(contract.get_cached_deai_price(), Some((price, 4_000)));
+    }
+    
+    #[test]
+    fn test_failed_pool_info_callback_keeps_cached_price() {
+        let context = VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .build();
+        testing_env!(context);
+        
+        let mut contract = crate::DeAICompute::new(accounts(0));
+        contract.cached_price = Some((U128(500), 0));
+        
+        assert!(contract.on_pool_info_callback(Err(PromiseError::Failed)).is_none());
+        
+        // A pool without DEAI reserves has no price
+        let empty_pool = PoolInfo {
+            token_account_ids: vec!["wrap.near".parse().unwrap(), accounts(0)],
+            amounts: vec![U128(1_000), U128(0)],
+            total_fee: 25,
+            shares_total_supply: U128(0),
+        };
+        assert!(contract.on_pool_info_callback(Ok(empty_pool)).is_none());
+        
+        assert_eq!(contract.get_cached_deai_price(), Some((U128(500), 0)));
+    }
+    
+    #[test]
+    fn test_add_liquidity_callback_records_position() {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .block_timestamp(1_000);
+        testing_env!(builder.build());
+        
+        let mut contract = crate::DeAICompute::new(accounts(0));
+        assert!(contract.on_add_liquidity_callback(7, U128(100), U128(50), Ok(U128(10))));
+        
+        builder.block_timestamp(2_000);
+        testing_env!(builder.build());
+        assert!(contract.on_add_liquidity_callback(7, U128(40), U128(20), Ok(U128(4))));
+        
+        let position = contract.get_liquidity_position(7).unwrap();
+        assert_eq!(position.shares, U128(14));
+        assert_eq!(position.token_a_amount, U128(140));
+        assert_eq!(position.token_b_amount, U128(70));
+        assert_eq!(position.created_at, 1_000);
+        assert_eq!(position.last_updated, 2_000);
+    }
+    
+    #[test]
+    fn test_failed_add_liquidity_records_no_position() {
+        let context = VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .build();
+        testing_env!(context);
+        
+        let mut contract = crate::DeAICompute::new(accounts(0));
+        
+        assert!(!contract.on_add_liquidity_callback(7, U128(100), U128(50), Err(PromiseError::Failed)));
+        assert!(!contract.on_add_liquidity_callback(7, U128(100), U128(50), Ok(U128(0))));
+        assert!(contract.get_liquidity_position(7).is_none());
+        
+        // A later failure leaves an existing position untouched
+        assert!(contract.on_add_liquidity_callback(7, U128(100), U128(50), Ok(U128(10))));
+        assert!(!contract.on_add_liquidity_callback(7, U128(40), U128(20), Err(PromiseError::Failed)));
+        let position = contract.get_liquidity_position(7).unwrap();
+        assert_eq!(position.shares, U128(10));
+        assert_eq!(position.token_a_amount, U128(100));
+    }
+    
+    #[test]
+    fn test_min_amount_out_uses_slippage_tolerance() {
+        let context = VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .build();
+        testing_env!(context);
+        
+        let mut contract = crate::DeAICompute::new(accounts(0));
+        assert_eq!(contract.slippage_bps, SLIPPAGE_TOLERANCE);
+        
+        // 1 DEAI = 0.0005 NEAR
+        contract.cached_price = Some((U128(500_000_000_000_000_000_000), 0));
+        contract.set_slippage_tolerance(500);
+        
+        // 1000 DEAI is expected to return 0.5 NEAR, 5% slippage leaves 0.475 NEAR
+        let min_out = contract.calculate_min_amount_out(U128(1_000_000_000_000_000_000_000), true);
+        assert_eq!(min_out, U128(475_000_000_000_000_000_000_000));
+        
+        // 0.5 NEAR is expected to return 1000 DEAI, 5% slippage leaves 950 DEAI
+        let min_out = contract.calculate_min_amount_out(U128(500_000_000_000_000_000_000_000), false);
+        assert_eq!(min_out, U128(950_000_000_000_000_000_000));
+    }
+    
+    #[test]
+    fn test_swap_without_minimum_applies_slippage_tolerance() {
+        let context = VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .build();
+        testing_env!(context);
+        
+        let mut contract = crate::DeAICompute::new(accounts(0));
+        contract.init_ref_finance_integration(42);
+        contract.set_slippage_tolerance(500);
+        contract.cached_price = Some((U128(500_000_000_000_000_000_000), 0));
+        contract.token.internal_deposit(&accounts(0), 1_000_000_000_000_000_000_000);
+        
+        contract.swap_deai_for_wnear(U128(1_000_000_000_000_000_000_000), None);
+        assert_eq!(
+            scheduled_call_args("swap")["actions"][0]["min_amount_out"],
+            "475000000000000000000000"
+        );
+    }
+    
+    #[test]
+    #[should_panic(expected = "Slippage tolerance too high")]
+    fn test_slippage_tolerance_capped() {
+        let context = VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .build();
+        testing_env!(context);
+        
+        let mut contract = crate::DeAICompute::new(accounts(0));
+        contract.set_slippage_tolerance(MAX_SLIPPAGE_BPS + 1);
+    }
+    
+    #[test]
+    #[should_panic(expected = "Exactly 1 yoctoNEAR required for security")]
+    fn test_slippage_tolerance_requires_one_yocto() {
+        let context = VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .build();
+        testing_env!(context);
+        
+        let mut contract = crate::DeAICompute::new(accounts(0));
+        contract.set_slippage_tolerance(500);
+    }
+    
+    #[test]
+    #[should_panic(expected = "Ref Finance integration not initialized")]
+    fn test_swap_requires_ref_integration() {
+        let context = VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .build();
+        testing_env!(context);
+        
+        let mut contract = crate::DeAICompute::new(accounts(0));
+        contract.token.internal_deposit(&accounts(0), 1000);
+        contract.swap_deai_for_wnear(U128(1000), Some(U128(1)));
+    }
 }
\ No newline at end of file
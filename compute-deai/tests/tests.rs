@@ -3,11 +3,15 @@ mod tests {
     use compute_deai::*;
     use near_sdk::test_utils::{accounts, VMContextBuilder};
     use near_sdk::{testing_env, NearToken, AccountId};
+    use near_sdk::json_types::U128;
     use near_contract_standards::fungible_token::Balance;
+    use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+    use near_contract_standards::storage_management::StorageManagement;
 
     const MIN_STAKE: Balance = 1_000_000_000_000_000_000_000_000; // 1 NEAR
     const STORAGE_COST: Balance = 1_000_000_000_000_000_000_000; // 0.001 NEAR
     const ONE_YOCTO: Balance = 1;
+    const STORAGE_DEPOSIT: Balance = 10_000_000_000_000_000_000_000; // 0.01 NEAR
 
     fn get_context(predecessor_account_id: AccountId, attached_deposit: Balance) -> VMContextBuilder {
         let mut builder = VMContextBuilder::new();
@@ -19,6 +23,41 @@ mod tests {
         builder
     }
 
+    fn register_storage(contract: &mut DeAICompute, account_id: AccountId) {
+        let context = get_context(account_id.clone(), STORAGE_DEPOSIT);
+        testing_env!(context.build());
+        contract.storage_deposit(Some(account_id), Some(true));
+    }
+
+    fn register_node_at(contract: &mut DeAICompute, account_id: AccountId, public_ip: &str, stake: Balance) {
+        register_storage(contract, account_id.clone());
+        let context = get_context(account_id, stake);
+        testing_env!(context.build());
+        contract.register_node(
+            public_ip.to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            format!("http://{}:8080", public_ip),
+            None,
+        );
+    }
+
+    // Registers each node with the given stake on 192.168.1.100, .101, ...
+    fn register_nodes(contract: &mut DeAICompute, nodes: &[AccountId], stake: Balance) {
+        for (i, node) in nodes.iter().enumerate() {
+            register_node_at(contract, node.clone(), &format!("192.168.1.{}", 100 + i), stake);
+        }
+    }
+
+    fn setup_nodes(nodes: &[AccountId], stake: Balance) -> DeAICompute {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        register_nodes(&mut contract, nodes, stake);
+        contract
+    }
+
     #[test]
     fn test_new_contract() {
         let mut context = get_context(accounts(1), 0);
@@ -37,6 +76,7 @@ mod tests {
         let mut contract = DeAICompute::new(accounts(1));
         
         // Test successful node registration
+        register_storage(&mut contract, accounts(2));
         let mut context = get_context(accounts(2), MIN_STAKE);
         testing_env!(context.build());
         
@@ -45,6 +85,7 @@ mod tests {
             "RTX 4090".to_string(),
             "Intel i9".to_string(),
             "http://192.168.1.100:8080".to_string(),
+            None,
         );
         
         let node_info = contract.get_node_info(accounts(2)).unwrap();
@@ -72,6 +113,7 @@ mod tests {
             "RTX 4090".to_string(),
             "Intel i9".to_string(),
             "http://192.168.1.100:8080".to_string(),
+            None,
         );
     }
 
@@ -84,6 +126,7 @@ mod tests {
         let mut contract = DeAICompute::new(accounts(1));
         
         // Register first node
+        register_storage(&mut contract, accounts(2));
         let mut context = get_context(accounts(2), MIN_STAKE);
         testing_env!(context.build());
         
@@ -92,9 +135,11 @@ mod tests {
             "RTX 4090".to_string(),
             "Intel i9".to_string(),
             "http://192.168.1.100:8080".to_string(),
+            None,
         );
         
         // Try to register another node with same IP
+        register_storage(&mut contract, accounts(3));
         let mut context = get_context(accounts(3), MIN_STAKE);
         testing_env!(context.build());
         
@@ -103,6 +148,7 @@ mod tests {
             "RTX 3080".to_string(),
             "Intel i7".to_string(),
             "http://192.168.1.100:8081".to_string(),
+            None,
         );
     }
 
@@ -114,6 +160,7 @@ mod tests {
         let mut contract = DeAICompute::new(accounts(1));
         
         // Register a node first
+        register_storage(&mut contract, accounts(2));
         let mut context = get_context(accounts(2), MIN_STAKE);
         testing_env!(context.build());
         
@@ -122,6 +169,7 @@ mod tests {
             "RTX 4090".to_string(),
             "Intel i9".to_string(),
             "http://192.168.1.100:8080".to_string(),
+            None,
         );
         
         // Submit a task
@@ -133,6 +181,10 @@ mod tests {
             r#"{"model": "gpt2", "input": "Hello world", "task_type": "inference"}"#.to_string(),
             task_cost.into(),
             Some(TaskPriority::Normal),
+            None,
+            None,
+            None,
+            None,
         );
         
         assert_eq!(contract.get_task_count(), 1);
@@ -151,6 +203,7 @@ mod tests {
         let mut contract = DeAICompute::new(accounts(1));
         
         // Register a node
+        register_storage(&mut contract, accounts(2));
         let mut context = get_context(accounts(2), MIN_STAKE);
         testing_env!(context.build());
         
@@ -159,6 +212,7 @@ mod tests {
             "RTX 4090".to_string(),
             "Intel i9".to_string(),
             "http://192.168.1.100:8080".to_string(),
+            None,
         );
         
         // Submit a task
@@ -170,6 +224,10 @@ mod tests {
             r#"{"model": "gpt2", "input": "Hello world", "task_type": "inference"}"#.to_string(),
             task_cost.into(),
             Some(TaskPriority::Normal),
+            None,
+            None,
+            None,
+            None,
         );
         
         // Submit result as node
@@ -205,6 +263,7 @@ mod tests {
         let mut contract = DeAICompute::new(accounts(1));
         
         // Register a node
+        register_storage(&mut contract, accounts(2));
         let mut context = get_context(accounts(2), MIN_STAKE);
         testing_env!(context.build());
         
@@ -213,6 +272,7 @@ mod tests {
             "RTX 4090".to_string(),
             "Intel i9".to_string(),
             "http://192.168.1.100:8080".to_string(),
+            None,
         );
         
         let initial_heartbeat = contract.get_node_info(accounts(2)).unwrap().last_heartbeat;
@@ -222,7 +282,7 @@ mod tests {
         context.block_timestamp(initial_heartbeat + 60_000_000_000); // +1 minute
         testing_env!(context.build());
         
-        contract.heartbeat();
+        contract.heartbeat(None);
         
         let updated_heartbeat = contract.get_node_info(accounts(2)).unwrap().last_heartbeat;
         assert!(updated_heartbeat > initial_heartbeat);
@@ -236,6 +296,7 @@ mod tests {
         let mut contract = DeAICompute::new(accounts(1));
         
         // Register two nodes
+        register_storage(&mut contract, accounts(2));
         let mut context = get_context(accounts(2), MIN_STAKE);
         testing_env!(context.build());
         
@@ -244,8 +305,10 @@ mod tests {
             "RTX 4090".to_string(),
             "Intel i9".to_string(),
             "http://192.168.1.100:8080".to_string(),
+            None,
         );
         
+        register_storage(&mut contract, accounts(3));
         let mut context = get_context(accounts(3), MIN_STAKE);
         testing_env!(context.build());
         
@@ -254,6 +317,7 @@ mod tests {
             "RTX 3080".to_string(),
             "Intel i7".to_string(),
             "http://192.168.1.101:8080".to_string(),
+            None,
         );
         
         // Submit two tasks
@@ -261,11 +325,11 @@ mod tests {
         
         let mut context = get_context(accounts(4), task_cost + STORAGE_COST);
         testing_env!(context.build());
-        contract.submit_task("Task 1".to_string(), task_cost.into(), Some(TaskPriority::Normal));
+        contract.submit_task("Task 1".to_string(), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
         
         let mut context = get_context(accounts(4), task_cost + STORAGE_COST);
         testing_env!(context.build());
-        contract.submit_task("Task 2".to_string(), task_cost.into(), Some(TaskPriority::High));
+        contract.submit_task("Task 2".to_string(), task_cost.into(), Some(TaskPriority::High), None, None, None, None);
         
         // Check both tasks were assigned
         assert_eq!(contract.get_task_count(), 2);
@@ -290,6 +354,7 @@ mod tests {
         assert_eq!(contract.ft_total_supply().0, 0);
         
         // Register a node and complete a task to mint tokens
+        register_storage(&mut contract, accounts(2));
         let mut context = get_context(accounts(2), MIN_STAKE);
         testing_env!(context.build());
         
@@ -298,6 +363,7 @@ mod tests {
             "RTX 4090".to_string(),
             "Intel i9".to_string(),
             "http://192.168.1.100:8080".to_string(),
+            None,
         );
         
         // Submit and complete a task
@@ -305,7 +371,7 @@ mod tests {
         let mut context = get_context(accounts(3), task_cost + STORAGE_COST);
         testing_env!(context.build());
         
-        contract.submit_task("Test task".to_string(), task_cost.into(), Some(TaskPriority::Normal));
+        contract.submit_task("Test task".to_string(), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
         
         let mut context = get_context(accounts(2), ONE_YOCTO);
         testing_env!(context.build());
@@ -333,6 +399,7 @@ mod tests {
         contract.pause_contract();
         
         // Try to register node - should panic
+        register_storage(&mut contract, accounts(2));
         let context = get_context(accounts(2), MIN_STAKE);
         testing_env!(context.build());
         
@@ -341,6 +408,7 @@ mod tests {
             "RTX 4090".to_string(),
             "Intel i9".to_string(),
             "http://192.168.1.100:8080".to_string(),
+            None,
         );
     }
     
@@ -367,6 +435,7 @@ mod tests {
         let mut contract = DeAICompute::new(accounts(1));
         
         // Register a node first
+        register_storage(&mut contract, accounts(2));
         let context = get_context(accounts(2), MIN_STAKE);
         testing_env!(context.build());
         
@@ -375,6 +444,7 @@ mod tests {
             "RTX 4090".to_string(),
             "Intel i9".to_string(),
             "http://192.168.1.100:8080".to_string(),
+            None,
         );
         
         // Try to deactivate without 1 yoctoNEAR
@@ -398,6 +468,7 @@ mod tests {
         assert_eq!(paused, false);
         
         // Register a node
+        register_storage(&mut contract, accounts(2));
         let context = get_context(accounts(2), MIN_STAKE);
         testing_env!(context.build());
         
@@ -406,6 +477,7 @@ mod tests {
             "RTX 4090".to_string(),
             "Intel i9".to_string(),
             "http://192.168.1.100:8080".to_string(),
+            None,
         );
         
         let (active_nodes, total_nodes, active_tasks, completed_tasks, paused) = contract.get_contract_stats();
@@ -421,6 +493,7 @@ mod tests {
         let mut contract = DeAICompute::new(accounts(1));
         
         // Register a node
+        register_storage(&mut contract, accounts(2));
         let context = get_context(accounts(2), MIN_STAKE);
         testing_env!(context.build());
         
@@ -429,6 +502,7 @@ mod tests {
             "RTX 4090".to_string(),
             "Intel i9".to_string(),
             "http://192.168.1.100:8080".to_string(),
+            None,
         );
         
         let task_cost = 100_000_000_000_000_000_000_000;
@@ -436,12 +510,12 @@ mod tests {
         // Submit low priority task
         let context = get_context(accounts(3), task_cost + STORAGE_COST);
         testing_env!(context.build());
-        contract.submit_task("Low priority task".to_string(), task_cost.into(), Some(TaskPriority::Low));
+        contract.submit_task("Low priority task".to_string(), task_cost.into(), Some(TaskPriority::Low), None, None, None, None);
         
         // Submit urgent priority task
         let context = get_context(accounts(3), task_cost + STORAGE_COST);
         testing_env!(context.build());
-        contract.submit_task("Urgent task".to_string(), task_cost.into(), Some(TaskPriority::Urgent));
+        contract.submit_task("Urgent task".to_string(), task_cost.into(), Some(TaskPriority::Urgent), None, None, None, None);
         
         // Both tasks should be assigned since max_tasks_per_node is 5
         let assigned_tasks = contract.get_assigned_tasks(accounts(2));
@@ -481,6 +555,10 @@ mod tests {
         testing_env!(context.build());
         contract.update_task_timeout(new_timeout);
         
+        assert_eq!(contract.get_min_stake(), U128(new_stake));
+        assert_eq!(contract.get_max_tasks_per_node(), 10);
+        assert_eq!(contract.get_task_timeout(), new_timeout);
+        
         // Test pause/unpause
         let context = get_context(accounts(1), ONE_YOCTO);
         testing_env!(context.build());
@@ -499,6 +577,7 @@ mod tests {
         let mut contract = DeAICompute::new(accounts(1));
         
         // Register a node
+        register_storage(&mut contract, accounts(2));
         let context = get_context(accounts(2), MIN_STAKE);
         testing_env!(context.build());
         
@@ -507,6 +586,7 @@ mod tests {
             "RTX 4090".to_string(),
             "Intel i9".to_string(),
             "http://192.168.1.100:8080".to_string(),
+            None,
         );
         
         // Submit a task
@@ -514,7 +594,7 @@ mod tests {
         let context = get_context(accounts(3), task_cost + STORAGE_COST);
         testing_env!(context.build());
         
-        contract.submit_task("Test task".to_string(), task_cost.into(), Some(TaskPriority::Normal));
+        contract.submit_task("Test task".to_string(), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
         
         // Try to deactivate node with active task - should panic
         let context = get_context(accounts(2), ONE_YOCTO);
@@ -539,12 +619,13 @@ mod tests {
         testing_env!(context.build());
         
         let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-            contract.submit_task("".to_string(), 1000u128.into(), Some(TaskPriority::Normal));
+            contract.submit_task("".to_string(), 1000u128.into(), Some(TaskPriority::Normal), None, None, None, None);
         }));
         
         assert!(result.is_err());
         
         // Test empty IP registration
+        register_storage(&mut contract, accounts(2));
         let context = get_context(accounts(2), MIN_STAKE);
         testing_env!(context.build());
         
@@ -554,6 +635,7 @@ mod tests {
                 "RTX 4090".to_string(),
                 "Intel i9".to_string(),
                 "http://192.168.1.100:8080".to_string(),
+                None,
             );
         }));
         
@@ -592,6 +674,7 @@ mod tests {
         let mut contract = DeAICompute::new(accounts(1));
         
         // Register a node
+        register_storage(&mut contract, accounts(2));
         let context = get_context(accounts(2), MIN_STAKE);
         testing_env!(context.build());
         
@@ -600,6 +683,7 @@ mod tests {
             "RTX 4090".to_string(),
             "Intel i9".to_string(),
             "http://192.168.1.100:8080".to_string(),
+            None,
         );
         
         // Submit a task
@@ -607,7 +691,7 @@ mod tests {
         let context = get_context(accounts(3), task_cost + STORAGE_COST);
         testing_env!(context.build());
         
-        contract.submit_task("Test task".to_string(), task_cost.into(), Some(TaskPriority::Normal));
+        contract.submit_task("Test task".to_string(), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
         
         // Get active task
         let active_task = contract.get_active_task(0);
@@ -627,6 +711,7 @@ mod tests {
         let mut contract = DeAICompute::new(accounts(1));
         
         // Register a node
+        register_storage(&mut contract, accounts(2));
         let mut context = get_context(accounts(2), MIN_STAKE);
         testing_env!(context.build());
         
@@ -635,6 +720,7 @@ mod tests {
             "RTX 4090".to_string(),
             "Intel i9".to_string(),
             "http://192.168.1.100:8080".to_string(),
+            None,
         );
         
         // Submit a task
@@ -642,7 +728,7 @@ mod tests {
         let mut context = get_context(accounts(3), task_cost + STORAGE_COST);
         testing_env!(context.build());
         
-        contract.submit_task("Test task".to_string(), task_cost.into(), Some(TaskPriority::Normal));
+        contract.submit_task("Test task".to_string(), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
         
         // Get initial node reputation
         let initial_reputation = contract.get_node_info(accounts(2)).unwrap().reputation_score;
@@ -676,6 +762,7 @@ mod tests {
         let mut contract = DeAICompute::new(accounts(1));
         
         // Register a node
+        register_storage(&mut contract, accounts(2));
         let mut context = get_context(accounts(2), MIN_STAKE);
         testing_env!(context.build());
         
@@ -684,6 +771,7 @@ mod tests {
             "RTX 4090".to_string(),
             "Intel i9".to_string(),
             "http://192.168.1.100:8080".to_string(),
+            None,
         );
         
         // Submit a task
@@ -691,7 +779,7 @@ mod tests {
         let mut context = get_context(accounts(3), task_cost + STORAGE_COST);
         testing_env!(context.build());
         
-        contract.submit_task("Test task".to_string(), task_cost.into(), Some(TaskPriority::Normal));
+        contract.submit_task("Test task".to_string(), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
         
         // Try to timeout immediately (should fail)
         let mut context = get_context(accounts(4), ONE_YOCTO);
@@ -708,6 +796,7 @@ mod tests {
         let mut contract = DeAICompute::new(accounts(1));
         
         // Register a node
+        register_storage(&mut contract, accounts(2));
         let mut context = get_context(accounts(2), MIN_STAKE);
         testing_env!(context.build());
         
@@ -716,6 +805,7 @@ mod tests {
             "RTX 4090".to_string(),
             "Intel i9".to_string(),
             "http://192.168.1.100:8080".to_string(),
+            None,
         );
         
         // Submit and complete multiple tasks to test reputation gain
@@ -724,7 +814,7 @@ mod tests {
             let mut context = get_context(accounts(3), task_cost + STORAGE_COST);
             testing_env!(context.build());
             
-            contract.submit_task(format!("Task {}", i), task_cost.into(), Some(TaskPriority::Normal));
+            contract.submit_task(format!("Task {}", i), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
             
             let mut context = get_context(accounts(2), ONE_YOCTO);
             testing_env!(context.build());
@@ -746,6 +836,7 @@ mod tests {
         let mut contract = DeAICompute::new(accounts(1));
         
         // Register a node
+        register_storage(&mut contract, accounts(2));
         let mut context = get_context(accounts(2), MIN_STAKE);
         testing_env!(context.build());
         
@@ -754,6 +845,7 @@ mod tests {
             "RTX 4090".to_string(),
             "Intel i9".to_string(),
             "http://192.168.1.100:8080".to_string(),
+            None,
         );
         
         // Complete many tasks to test reputation cap (MAX_REPUTATION = 1000)
@@ -762,7 +854,7 @@ mod tests {
             let mut context = get_context(accounts(3), task_cost + STORAGE_COST);
             testing_env!(context.build());
             
-            contract.submit_task(format!("Task {}", i), task_cost.into(), Some(TaskPriority::Normal));
+            contract.submit_task(format!("Task {}", i), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
             
             let mut context = get_context(accounts(2), ONE_YOCTO);
             testing_env!(context.build());
@@ -784,6 +876,7 @@ mod tests {
         let mut contract = DeAICompute::new(accounts(1));
         
         // Register a node and complete a task to mint tokens
+        register_storage(&mut contract, accounts(2));
         let mut context = get_context(accounts(2), MIN_STAKE);
         testing_env!(context.build());
         
@@ -792,19 +885,23 @@ mod tests {
             "RTX 4090".to_string(),
             "Intel i9".to_string(),
             "http://192.168.1.100:8080".to_string(),
+            None,
         );
         
         let task_cost = 100_000_000_000_000_000_000_000;
         let mut context = get_context(accounts(3), task_cost + STORAGE_COST);
         testing_env!(context.build());
         
-        contract.submit_task("Test task".to_string(), task_cost.into(), Some(TaskPriority::Normal));
+        contract.submit_task("Test task".to_string(), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
         
         let mut context = get_context(accounts(2), ONE_YOCTO);
         testing_env!(context.build());
         
         contract.submit_result(0, "proof_hash".to_string(), "result".to_string());
         
+        // Receiver must be registered for storage
+        register_storage(&mut contract, accounts(3));
+        
         // Test token transfer with 1 yoctoNEAR security
         let mut context = get_context(accounts(2), ONE_YOCTO);
         testing_env!(context.build());
@@ -830,4 +927,2875 @@ mod tests {
         
         contract.ft_transfer(accounts(3), 1000u128.into(), None);
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_register_many_nodes_gas_bounded() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        let mut first_gas = 0;
+        let mut last_gas = 0;
+        
+        for i in 0..100 {
+            let node_id: AccountId = format!("node{}.testnet", i).parse().unwrap();
+            register_storage(&mut contract, node_id.clone());
+            let context = get_context(node_id, MIN_STAKE);
+            testing_env!(context.build());
+            
+            contract.register_node(
+                format!("10.0.{}.{}", i / 256, i % 256),
+                "RTX 4090".to_string(),
+                "Intel i9".to_string(),
+                format!("http://10.0.{}.{}:8080", i / 256, i % 256),
+                None,
+            );
+            
+            let used_gas = near_sdk::env::used_gas().as_gas();
+            if i == 0 {
+                first_gas = used_gas;
+            }
+            last_gas = used_gas;
+        }
+        
+        assert_eq!(contract.get_active_nodes().len(), 100);
+        // IP uniqueness check no longer scales with the number of registered nodes:
+        // a linear scan over 99 nodes would cost several times the first registration
+        assert!(last_gas <= first_gas + first_gas / 10, "gas grew from {} to {}", first_gas, last_gas);
+    }
+    
+    #[test]
+    fn test_deactivated_node_releases_ip() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.deactivate_node();
+        
+        // Another node can now claim the released IP
+        register_storage(&mut contract, accounts(3));
+        let context = get_context(accounts(3), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 3080".to_string(),
+            "Intel i7".to_string(),
+            "http://192.168.1.100:8081".to_string(),
+            None,
+        );
+        
+        assert!(contract.get_node_info(accounts(3)).unwrap().is_active);
+    }
+    
+    #[test]
+    fn test_node_active_task_counter() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.update_max_tasks_per_node(1);
+        
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        for i in 0..2 {
+            let context = get_context(accounts(3), task_cost + STORAGE_COST);
+            testing_env!(context.build());
+            contract.submit_task(format!("Task {}", i), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
+        }
+        
+        // Node is at capacity, second task stays pending
+        assert_eq!(contract.get_assigned_tasks(accounts(2)).len(), 1);
+        assert_eq!(contract.get_pending_tasks().len(), 1);
+        
+        // Completing the first task frees a slot for the second
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.submit_result(0, "proof_0".to_string(), "result_0".to_string());
+        
+        let assigned = contract.get_assigned_tasks(accounts(2));
+        assert_eq!(assigned.len(), 1);
+        assert_eq!(assigned[0].id, 1);
+        assert_eq!(contract.get_pending_tasks().len(), 0);
+        
+        // With both results in, the counter is back to zero and the node can deactivate
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.submit_result(1, "proof_1".to_string(), "result_1".to_string());
+        contract.deactivate_node();
+        assert!(!contract.get_node_info(accounts(2)).unwrap().is_active);
+    }
+    
+    #[test]
+    #[should_panic(expected = "Cannot deactivate node with active tasks")]
+    fn test_deactivate_node_with_assigned_task() {
+        let mut contract = setup_nodes(&[accounts(2)], MIN_STAKE);
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(3), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Task 0".to_string(), task_cost.into(), None, None, None, None, None);
+        assert_eq!(contract.get_assigned_tasks(accounts(2)).len(), 1);
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.deactivate_node();
+    }
+    
+    #[test]
+    fn test_mutating_call_emits_audit_event() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        let logs = near_sdk::test_utils::get_logs();
+        let audit_log = logs.iter()
+            .find(|log| log.starts_with("EVENT_JSON:") && log.contains("\"standard\":\"deai-audit\""))
+            .expect("Audit event not emitted");
+        assert!(audit_log.contains("\"method\":\"register_node\""));
+        assert!(audit_log.contains(&format!("\"caller\":\"{}\"", accounts(2))));
+        
+        // Disabling audit stops further events
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.set_audit_enabled(false);
+        
+        let context = get_context(accounts(2), 0);
+        testing_env!(context.build());
+        contract.heartbeat(None);
+        
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(!logs.iter().any(|log| log.contains("deai-audit")));
+    }
+    
+    #[test]
+    fn test_submit_results_batch() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        for i in 0..3 {
+            let context = get_context(accounts(3), task_cost + STORAGE_COST);
+            testing_env!(context.build());
+            contract.submit_task(format!("Task {}", i), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
+        }
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        
+        contract.submit_results_batch(vec![
+            (0, "proof_0".to_string(), "result_0".to_string()),
+            (1, "proof_1".to_string(), "result_1".to_string()),
+            (2, "proof_2".to_string(), "result_2".to_string()),
+        ]);
+        
+        for i in 0..3 {
+            let result = contract.get_task_result(i).unwrap();
+            assert_eq!(result.status, TaskStatus::Completed);
+            assert_eq!(result.output.unwrap(), format!("result_{}", i));
+        }
+        
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, task_cost * 3);
+        let node_info = contract.get_node_info(accounts(2)).unwrap();
+        assert_eq!(node_info.total_tasks_completed, 3);
+        assert_eq!(node_info.reputation_score, 130);
+        assert!(contract.get_assigned_tasks(accounts(2)).is_empty());
+    }
+    
+    #[test]
+    #[should_panic(expected = "Not assigned to this node")]
+    fn test_submit_results_batch_rejects_foreign_task() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(3), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Task".to_string(), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
+        
+        let context = get_context(accounts(4), ONE_YOCTO);
+        testing_env!(context.build());
+        
+        contract.submit_results_batch(vec![(0, "proof".to_string(), "result".to_string())]);
+    }
+    
+    #[test]
+    fn test_lifecycle_events_emitted() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs.iter().any(|log| log.starts_with("EVENT_JSON:{\"standard\":\"deai\",\"version\":\"1.0.0\",\"event\":\"node_registered\"")));
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(3), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Test task".to_string(), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
+        
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs.iter().any(|log| log.contains("\"standard\":\"deai\"") && log.contains("\"event\":\"task_submitted\"")));
+        assert!(logs.iter().any(|log| log.contains("\"standard\":\"deai\"") && log.contains("\"event\":\"task_assigned\"")));
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.submit_result(0, "proof_hash".to_string(), "result".to_string());
+        
+        let logs = near_sdk::test_utils::get_logs();
+        let completed = logs.iter()
+            .find(|log| log.contains("\"standard\":\"deai\"") && log.contains("\"event\":\"task_completed\""))
+            .expect("task_completed event not emitted");
+        assert!(completed.contains("\"task_id\":0"));
+        assert!(completed.contains("\"proof_hash\":\"proof_hash\""));
+    }
+    
+    #[test]
+    fn test_ft_metadata() {
+        use near_contract_standards::fungible_token::metadata::FungibleTokenMetadataProvider;
+        
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        let metadata = contract.ft_metadata();
+        assert_eq!(metadata.spec, "ft-1.0.0");
+        assert_eq!(metadata.name, "DeAI Compute Token");
+        assert_eq!(metadata.symbol, "DEAI");
+        assert_eq!(metadata.decimals, 18);
+        assert!(metadata.icon.is_none());
+        
+        // Owner can update the metadata
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        let mut updated = metadata.clone();
+        updated.icon = Some("data:image/svg+xml,<svg></svg>".to_string());
+        contract.update_token_metadata(updated);
+        
+        assert_eq!(contract.ft_metadata().icon.unwrap(), "data:image/svg+xml,<svg></svg>");
+    }
+    
+    #[test]
+    #[should_panic(expected = "Token decimals cannot change")]
+    fn test_update_token_metadata_keeps_decimals() {
+        use near_contract_standards::fungible_token::metadata::FungibleTokenMetadataProvider;
+        
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        let mut metadata = contract.ft_metadata();
+        metadata.decimals = 24;
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.update_token_metadata(metadata);
+    }
+    
+    #[test]
+    #[should_panic(expected = "Only owner can call this method")]
+    fn test_update_token_metadata_requires_owner() {
+        use near_contract_standards::fungible_token::metadata::FungibleTokenMetadataProvider;
+        
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        let metadata = contract.ft_metadata();
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.update_token_metadata(metadata);
+    }
+    
+    #[test]
+    #[should_panic(expected = "Account not registered for storage")]
+    fn test_register_node_requires_storage_deposit() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+    }
+    
+    #[test]
+    fn test_storage_deposit_and_balance() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        assert!(contract.storage_balance_of(accounts(2)).is_none());
+        
+        register_storage(&mut contract, accounts(2));
+        
+        let balance = contract.storage_balance_of(accounts(2)).unwrap();
+        assert_eq!(balance.total, contract.storage_balance_bounds().min);
+    }
+    
+    #[test]
+    fn test_deactivated_node_reclaims_storage() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.deactivate_node();
+        
+        let mut context = get_context(accounts(2), ONE_YOCTO);
+        context.block_timestamp(DEFAULT_UNBONDING_PERIOD);
+        testing_env!(context.build());
+        contract.claim_unbonded_stake();
+        
+        assert!(contract.storage_unregister(None));
+        assert!(contract.storage_balance_of(accounts(2)).is_none());
+        assert!(contract.get_node_info(accounts(2)).is_none());
+    }
+    
+    #[test]
+    #[should_panic(expected = "Deactivate node before unregistering storage")]
+    fn test_active_node_cannot_unregister_storage() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.storage_unregister(None);
+    }
+    
+    #[test]
+    fn test_task_matched_by_gpu_capability() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        // The smaller GPU registers first and would win a reputation tie
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "NVIDIA RTX 3080 10GB".to_string(),
+            "Intel i7".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        register_storage(&mut contract, accounts(3));
+        let context = get_context(accounts(3), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.101".to_string(),
+            "NVIDIA RTX 4090 24 GB".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.101:8080".to_string(),
+            None,
+        );
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(4), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        
+        contract.submit_task(
+            r#"{"model": "llama-13b", "input": "Hello", "task_type": "text_generation", "min_vram_gb": 24}"#.to_string(),
+            task_cost.into(),
+            Some(TaskPriority::Normal),
+            None,
+            None,
+            None,
+            None,
+        );
+        
+        assert!(contract.get_assigned_tasks(accounts(2)).is_empty());
+        let assigned = contract.get_assigned_tasks(accounts(3));
+        assert_eq!(assigned.len(), 1);
+        assert_eq!(assigned[0].min_vram_gb, Some(24));
+        
+        // No node can serve 80GB, so the task stays pending
+        let context = get_context(accounts(4), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        
+        contract.submit_task(
+            r#"{"model": "llama-70b", "input": "Hello", "task_type": "text_generation", "min_vram_gb": 80}"#.to_string(),
+            task_cost.into(),
+            Some(TaskPriority::Normal),
+            None,
+            None,
+            None,
+            None,
+        );
+        
+        assert_eq!(contract.get_pending_tasks().len(), 1);
+        assert_eq!(contract.get_pending_tasks()[0].id, 1);
+    }
+    
+    fn use_least_loaded_assignment(contract: &mut DeAICompute) {
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.set_assignment_strategy(AssignmentStrategy::LeastLoaded);
+    }
+    
+    fn submit_tasks(contract: &mut DeAICompute, count: u64) {
+        let task_cost = 100_000_000_000_000_000_000_000;
+        for i in 0..count {
+            let context = get_context(accounts(4), task_cost + STORAGE_COST);
+            testing_env!(context.build());
+            contract.submit_task(format!("Task {}", i), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
+        }
+    }
+    
+    #[test]
+    fn test_least_loaded_assignment_splits_tasks() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        assert_eq!(contract.get_assignment_strategy(), AssignmentStrategy::HighestReputation);
+        
+        use_least_loaded_assignment(&mut contract);
+        register_nodes(&mut contract, &[accounts(2), accounts(3)], MIN_STAKE);
+        submit_tasks(&mut contract, 5);
+        
+        assert_eq!(contract.get_assigned_tasks(accounts(2)).len(), 3);
+        assert_eq!(contract.get_assigned_tasks(accounts(3)).len(), 2);
+    }
+    
+    #[test]
+    fn test_highest_reputation_assignment_strategy() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        register_nodes(&mut contract, &[accounts(2), accounts(3)], MIN_STAKE);
+        submit_tasks(&mut contract, 5);
+        
+        // The first node fills up to max_tasks_per_node before the second gets anything
+        assert_eq!(contract.get_assigned_tasks(accounts(2)).len(), 5);
+        assert!(contract.get_assigned_tasks(accounts(3)).is_empty());
+    }
+    
+    #[test]
+    #[should_panic(expected = "Only owner can call this method")]
+    fn test_set_assignment_strategy_owner_only() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.set_assignment_strategy(AssignmentStrategy::HighestReputation);
+    }
+    
+    #[test]
+    fn test_cancel_pending_task() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        // No nodes registered, so the task stays pending
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(3), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Test task".to_string(), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
+        assert_eq!(contract.get_pending_tasks().len(), 1);
+        
+        let context = get_context(accounts(3), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.cancel_task(0);
+        
+        assert!(contract.get_pending_tasks().is_empty());
+        assert!(contract.get_active_task(0).is_none());
+        let task = contract.get_task_result(0).unwrap();
+        assert_eq!(task.status, TaskStatus::Cancelled);
+        
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs.iter().any(|log| log.contains(r#""event":"task_cancelled""#)));
+    }
+    
+    #[test]
+    #[should_panic(expected = "Only the requester can cancel this task")]
+    fn test_cancel_task_not_requester() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(3), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Test task".to_string(), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
+        
+        let context = get_context(accounts(4), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.cancel_task(0);
+    }
+    
+    #[test]
+    #[should_panic(expected = "Task already assigned")]
+    fn test_cancel_assigned_task() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(3), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Test task".to_string(), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
+        
+        let context = get_context(accounts(3), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.cancel_task(0);
+    }
+    
+    #[test]
+    fn test_paginated_views() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        for i in 0..5u64 {
+            let node_id: AccountId = format!("node{}.testnet", i).parse().unwrap();
+            register_storage(&mut contract, node_id.clone());
+            let context = get_context(node_id, MIN_STAKE);
+            testing_env!(context.build());
+            
+            contract.register_node(
+                format!("10.0.0.{}", i),
+                "RTX 4090".to_string(),
+                "Intel i9".to_string(),
+                format!("http://10.0.0.{}:8080", i),
+                None,
+            );
+        }
+        
+        assert_eq!(contract.get_active_nodes_paged(0, 2).len(), 2);
+        assert_eq!(contract.get_active_nodes_paged(4, 2).len(), 1);
+        assert!(contract.get_active_nodes_paged(5, 2).is_empty());
+        assert_eq!(contract.get_active_nodes_paged(1, 1)[0].public_ip, "10.0.0.1");
+        assert_eq!(contract.get_active_nodes().len(), 5);
+    }
+    
+    #[test]
+    fn test_paginated_pending_tasks() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        // No nodes registered, so every task stays pending
+        submit_tasks(&mut contract, 5);
+        
+        let page = contract.get_pending_tasks_paged(2, 2);
+        assert_eq!(page.iter().map(|t| t.id).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(contract.get_pending_tasks_paged(4, 10).len(), 1);
+        assert!(contract.get_pending_tasks_paged(5, 10).is_empty());
+        assert_eq!(contract.get_pending_tasks().len(), 5);
+    }
+    
+    #[test]
+    fn test_reputation_decay_after_inactivity() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        assert_eq!(contract.get_node_info(accounts(2)).unwrap().reputation_score, 100);
+        
+        // Anyone can apply decay once a week has passed
+        let mut context = get_context(accounts(3), 0);
+        context.block_timestamp(7 * NANOS_PER_DAY);
+        testing_env!(context.build());
+        
+        contract.decay_reputation(accounts(2));
+        
+        let node_info = contract.get_node_info(accounts(2)).unwrap();
+        assert_eq!(node_info.reputation_score, 100 - 7 * DEFAULT_REPUTATION_DECAY_PER_DAY);
+        assert_eq!(node_info.last_reputation_update, 7 * NANOS_PER_DAY);
+        
+        // A long absence stops at the floor
+        let mut context = get_context(accounts(3), 0);
+        context.block_timestamp(60 * NANOS_PER_DAY);
+        testing_env!(context.build());
+        
+        contract.decay_reputation(accounts(2));
+        assert_eq!(contract.get_node_info(accounts(2)).unwrap().reputation_score, REPUTATION_DECAY_FLOOR);
+    }
+    
+    #[test]
+    #[should_panic(expected = "No reputation decay due")]
+    fn test_reputation_decay_not_due() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        contract.decay_reputation(accounts(2));
+    }
+    
+    #[test]
+    fn test_get_tasks_by_status() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        // Tasks 0-4 fill the node, tasks 5-6 stay pending
+        submit_tasks(&mut contract, 7);
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.submit_result(0, "a".repeat(64), "done".to_string());
+        
+        // Completing task 0 frees a slot for task 5
+        assert_eq!(contract.get_tasks_by_status(TaskStatus::Assigned, 0, 10).len(), 5);
+        assert_eq!(contract.get_tasks_by_status(TaskStatus::Assigned, 3, 10).len(), 2);
+        assert_eq!(contract.get_tasks_by_status(TaskStatus::Assigned, 0, 2).len(), 2);
+        
+        let pending = contract.get_tasks_by_status(TaskStatus::Pending, 0, 10);
+        assert_eq!(pending.iter().map(|t| t.id).collect::<Vec<_>>(), vec![6]);
+        
+        let completed = contract.get_tasks_by_status(TaskStatus::Completed, 0, 10);
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed[0].id, 0);
+        assert!(contract.get_tasks_by_status(TaskStatus::Completed, 1, 10).is_empty());
+        assert!(contract.get_tasks_by_status(TaskStatus::InProgress, 0, 10).is_empty());
+    }
+    
+    #[test]
+    fn test_low_reputation_node_not_assigned() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        assert_eq!(contract.min_reputation_for_assignment, DEFAULT_MIN_REPUTATION_FOR_ASSIGNMENT);
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.update_min_reputation_for_assignment(60);
+        
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        submit_tasks(&mut contract, 1);
+        assert_eq!(contract.get_assigned_tasks(accounts(2)).len(), 1);
+        
+        // Time the task out, slashing the node from 100 to 50
+        let mut context = get_context(accounts(4), ONE_YOCTO);
+        context.block_timestamp(3700_000_000_000);
+        testing_env!(context.build());
+        contract.timeout_task(0);
+        assert_eq!(contract.get_node_info(accounts(2)).unwrap().reputation_score, 50);
+        
+        // Node is live but below the threshold, so the next task waits
+        let mut context = get_context(accounts(2), 0);
+        context.block_timestamp(3700_000_000_000);
+        testing_env!(context.build());
+        contract.heartbeat(None);
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let mut context = get_context(accounts(4), task_cost + STORAGE_COST);
+        context.block_timestamp(3700_000_000_000);
+        testing_env!(context.build());
+        contract.submit_task("Task 1".to_string(), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
+        
+        assert!(contract.get_assigned_tasks(accounts(2)).is_empty());
+        assert_eq!(contract.get_tasks_by_status(TaskStatus::Pending, 0, 10).len(), 1);
+    }
+    
+    fn register_and_deactivate_node(contract: &mut DeAICompute) {
+        register_nodes(contract, &[accounts(2)], MIN_STAKE);
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.deactivate_node();
+    }
+    
+    #[test]
+    #[should_panic(expected = "Unbonding period has not ended")]
+    fn test_claim_unbonded_stake_too_early() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        register_and_deactivate_node(&mut contract);
+        
+        let node_info = contract.get_node_info(accounts(2)).unwrap();
+        assert!(!node_info.is_active);
+        assert_eq!(node_info.unbond_available_at, Some(DEFAULT_UNBONDING_PERIOD));
+        
+        let mut context = get_context(accounts(2), ONE_YOCTO);
+        context.block_timestamp(DEFAULT_UNBONDING_PERIOD - 1);
+        testing_env!(context.build());
+        contract.claim_unbonded_stake();
+    }
+    
+    #[test]
+    fn test_claim_unbonded_stake_after_window() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        register_and_deactivate_node(&mut contract);
+        
+        let mut context = get_context(accounts(2), ONE_YOCTO);
+        context.block_timestamp(DEFAULT_UNBONDING_PERIOD);
+        testing_env!(context.build());
+        contract.claim_unbonded_stake();
+        
+        let node_info = contract.get_node_info(accounts(2)).unwrap();
+        assert_eq!(node_info.stake, 0);
+        assert!(node_info.unbond_available_at.is_none());
+        
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs.iter().any(|log| log.contains(&format!("returned: {} yoctoNEAR", MIN_STAKE))));
+    }
+    
+    #[test]
+    fn test_update_node_info() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.update_node_info(
+            Some("10.0.0.5".to_string()),
+            Some("RTX 5090 32GB".to_string()),
+            None,
+            Some("http://10.0.0.5:8080".to_string()),
+            None,
+        );
+        
+        let node_info = contract.get_node_info(accounts(2)).unwrap();
+        assert_eq!(node_info.public_ip, "10.0.0.5");
+        assert_eq!(node_info.gpu_specs, "RTX 5090 32GB");
+        assert_eq!(node_info.cpu_specs, "Intel i9");
+        assert_eq!(node_info.api_endpoint, "http://10.0.0.5:8080");
+        assert!(node_info.is_active);
+        assert_eq!(node_info.stake, MIN_STAKE);
+        assert_eq!(node_info.reputation_score, 100);
+        
+        // The old IP is free for another node
+        register_storage(&mut contract, accounts(3));
+        let context = get_context(accounts(3), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 3080".to_string(),
+            "Intel i7".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        assert_eq!(contract.get_active_nodes().len(), 2);
+    }
+    
+    #[test]
+    #[should_panic(expected = "IP address already registered")]
+    fn test_update_node_info_duplicate_ip() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        register_nodes(&mut contract, &[accounts(2), accounts(3)], MIN_STAKE);
+        
+        let context = get_context(accounts(3), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.update_node_info(Some("192.168.1.100".to_string()), None, None, None, None);
+    }
+    
+    #[test]
+    fn test_submit_tasks_batch() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        use_least_loaded_assignment(&mut contract);
+        register_nodes(&mut contract, &[accounts(2), accounts(3)], MIN_STAKE);
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let tasks: Vec<TaskInput> = (0..4)
+            .map(|i| TaskInput { description: format!("Task {}", i) })
+            .collect();
+        
+        let context = get_context(accounts(4), 4 * (task_cost + STORAGE_COST));
+        testing_env!(context.build());
+        let task_ids = contract.submit_tasks_batch(tasks, task_cost.into(), Some(TaskPriority::High));
+        
+        assert_eq!(task_ids, vec![0, 1, 2, 3]);
+        assert_eq!(contract.get_task_count(), 4);
+        assert!(contract.get_pending_tasks().is_empty());
+        assert_eq!(contract.get_assigned_tasks(accounts(2)).len(), 2);
+        assert_eq!(contract.get_assigned_tasks(accounts(3)).len(), 2);
+        assert_eq!(contract.get_active_task(3).unwrap().priority, TaskPriority::High);
+    }
+    
+    #[test]
+    #[should_panic(expected = "Insufficient payment for compute cost and storage")]
+    fn test_submit_tasks_batch_insufficient_deposit() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let tasks: Vec<TaskInput> = (0..3)
+            .map(|i| TaskInput { description: format!("Task {}", i) })
+            .collect();
+        
+        // Covers only two of the three tasks
+        let context = get_context(accounts(4), 2 * (task_cost + STORAGE_COST));
+        testing_env!(context.build());
+        contract.submit_tasks_batch(tasks, task_cost.into(), None);
+    }
+    
+    #[test]
+    #[should_panic(expected = "Tasks batch too large")]
+    fn test_submit_tasks_batch_too_large() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let tasks: Vec<TaskInput> = (0..MAX_TASKS_BATCH + 1)
+            .map(|i| TaskInput { description: format!("Task {}", i) })
+            .collect();
+        
+        let context = get_context(accounts(4), 100 * (task_cost + STORAGE_COST));
+        testing_env!(context.build());
+        contract.submit_tasks_batch(tasks, task_cost.into(), None);
+    }
+    
+    #[test]
+    fn test_timeout_compensates_requester_from_slash() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(3), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Test task".to_string(), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
+        
+        let mut context = get_context(accounts(4), ONE_YOCTO);
+        context.block_timestamp(3700_000_000_000);
+        testing_env!(context.build());
+        contract.timeout_task(0);
+        
+        // 10% of the stake is slashed, half of it compensates the requester
+        let slash_amount = MIN_STAKE / 10;
+        let compensation = slash_amount / 2;
+        assert_eq!(contract.get_total_slashed().0, slash_amount);
+        // The rest accrues to the treasury
+        assert_eq!(contract.get_treasury_balance().0, slash_amount - compensation);
+        
+        let logs = near_sdk::test_utils::get_logs();
+        let expected = format!("Task timed out: 0, refund: {}, compensation: {}", task_cost, compensation);
+        assert!(logs.iter().any(|log| log == &expected));
+    }
+    
+    #[test]
+    fn test_sweep_stale_nodes_releases_tasks() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        use_least_loaded_assignment(&mut contract);
+        register_nodes(&mut contract, &[accounts(2), accounts(3)], MIN_STAKE);
+        submit_tasks(&mut contract, 2);
+        assert_eq!(contract.get_assigned_tasks(accounts(2)).len(), 1);
+        assert_eq!(contract.get_assigned_tasks(accounts(3)).len(), 1);
+        
+        // Only the second node keeps sending heartbeats
+        let stale_time = 400_000_000_000; // past the 5 minute heartbeat timeout
+        let mut context = get_context(accounts(3), 0);
+        context.block_timestamp(stale_time);
+        testing_env!(context.build());
+        contract.heartbeat(None);
+        
+        let mut context = get_context(accounts(4), 0);
+        context.block_timestamp(stale_time);
+        testing_env!(context.build());
+        assert_eq!(contract.sweep_stale_nodes(10), 1);
+        
+        assert!(!contract.get_node_info(accounts(2)).unwrap().is_active);
+        assert!(contract.get_assigned_tasks(accounts(2)).is_empty());
+        
+        // The released task is reassigned to the live node
+        assert_eq!(contract.get_assigned_tasks(accounts(3)).len(), 2);
+        assert!(contract.get_pending_tasks().is_empty());
+        
+        // Nothing left to sweep
+        assert_eq!(contract.sweep_stale_nodes(10), 0);
+    }
+
+    #[test]
+    fn test_swept_node_releases_and_reclaims_ip() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        register_nodes(&mut contract, &[accounts(2), accounts(3)], MIN_STAKE);
+        
+        let stale_time = 400_000_000_000;
+        let mut context = get_context(accounts(4), 0);
+        context.block_timestamp(stale_time);
+        testing_env!(context.build());
+        assert_eq!(contract.sweep_stale_nodes(10), 2);
+        
+        // The swept node moves to a new IP and comes back
+        let mut context = get_context(accounts(2), ONE_YOCTO);
+        context.block_timestamp(stale_time);
+        testing_env!(context.build());
+        contract.update_node_info(Some("10.0.0.9".to_string()), None, None, None, None);
+        contract.heartbeat(None);
+        assert!(contract.get_node_info(accounts(2)).unwrap().is_active);
+        
+        // Its old IP is free, the new one is taken
+        register_node_at(&mut contract, accounts(4), "192.168.1.100", MIN_STAKE);
+        assert!(contract.get_node_info(accounts(4)).is_some());
+        
+        let mut context = get_context(accounts(3), ONE_YOCTO);
+        context.block_timestamp(stale_time);
+        testing_env!(context.build());
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.update_node_info(Some("10.0.0.9".to_string()), None, None, None, None);
+        }));
+        assert!(result.is_err());
+    }
+    
+    #[test]
+    fn test_platform_fee_split() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.update_platform_fee(500); // 5%
+        contract.set_treasury(accounts(5));
+        
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let fee = task_cost / 20;
+        let context = get_context(accounts(3), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Test task".to_string(), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
+        
+        // The fee is held with the reward until the task completes
+        assert_eq!(contract.get_treasury_balance().0, 0);
+        assert_eq!(contract.get_active_task(0).unwrap().reward_amount, task_cost - fee);
+        assert_eq!(contract.get_active_task(0).unwrap().platform_fee, fee);
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.submit_result(0, "abc123hash".to_string(), "done".to_string());
+        
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, task_cost - fee);
+        assert_eq!(contract.get_treasury_balance().0, fee);
+        
+        // Treasury can withdraw the accrued fee
+        let context = get_context(accounts(5), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.withdraw_treasury(fee.into());
+        assert_eq!(contract.get_treasury_balance().0, 0);
+    }
+    
+    #[test]
+    fn test_heartbeat_with_resources_emits_event() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        let context = get_context(accounts(2), 0);
+        testing_env!(context.build());
+        contract.heartbeat(None);
+        
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(!logs.iter().any(|log| log.contains("\"event\":\"node_heartbeat\"")));
+        
+        let context = get_context(accounts(2), 0);
+        testing_env!(context.build());
+        contract.heartbeat(Some(NodeResources {
+            gpu_utilization_percent: Some(85),
+            gpu_memory_used_mb: Some(18_432),
+            gpu_memory_total_mb: Some(24_576),
+            active_tasks: 2,
+        }));
+        
+        let logs = near_sdk::test_utils::get_logs();
+        let event = logs.iter()
+            .find(|log| log.contains("\"event\":\"node_heartbeat\""))
+            .expect("node_heartbeat event not emitted");
+        assert!(event.contains("\"gpu_utilization_percent\":85"));
+        assert!(event.contains("\"active_tasks\":2"));
+    }
+    
+    #[test]
+    fn test_decline_task_reassigns_to_another_node() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(4), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Task 1".to_string(), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
+        assert_eq!(contract.get_assigned_tasks(accounts(2)).len(), 1);
+        
+        register_storage(&mut contract, accounts(3));
+        let context = get_context(accounts(3), MIN_STAKE);
+        testing_env!(context.build());
+        contract.register_node(
+            "192.168.1.101".to_string(),
+            "RTX 3080".to_string(),
+            "Intel i7".to_string(),
+            "http://192.168.1.101:8080".to_string(),
+            None,
+        );
+        
+        let context = get_context(accounts(2), 0);
+        testing_env!(context.build());
+        contract.decline_task(0, "Unsupported task type: training".to_string());
+        
+        assert!(contract.get_assigned_tasks(accounts(2)).is_empty());
+        let reassigned = contract.get_assigned_tasks(accounts(3));
+        assert_eq!(reassigned.len(), 1);
+        assert_eq!(reassigned[0].declined_by, vec![accounts(2).to_string()]);
+        
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs.iter().any(|log| log.contains(r#""event":"task_declined""#)));
+    }
+    
+    #[test]
+    fn test_declined_task_is_not_reassigned_to_same_node() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(4), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Task 1".to_string(), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
+        
+        let context = get_context(accounts(2), 0);
+        testing_env!(context.build());
+        contract.decline_task(0, "Model too large".to_string());
+        
+        // The only node declined, so the task waits for another one
+        assert!(contract.get_assigned_tasks(accounts(2)).is_empty());
+        assert_eq!(contract.get_pending_tasks().len(), 1);
+        assert_eq!(contract.get_active_task(0).unwrap().status, TaskStatus::Pending);
+    }
+    
+    #[test]
+    #[should_panic(expected = "Not assigned to this node")]
+    fn test_decline_task_not_assignee() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(4), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Task 1".to_string(), task_cost.into(), Some(TaskPriority::Normal), None, None, None, None);
+        
+        let context = get_context(accounts(3), 0);
+        testing_env!(context.build());
+        contract.decline_task(0, "Not mine".to_string());
+    }
+    
+    #[test]
+    fn test_top_up_stake() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        let context = get_context(accounts(2), MIN_STAKE / 2);
+        testing_env!(context.build());
+        contract.top_up_stake();
+        
+        assert_eq!(contract.get_node_info(accounts(2)).unwrap().stake, MIN_STAKE + MIN_STAKE / 2);
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs.iter().any(|log| log.contains(r#""event":"stake_topped_up""#)));
+    }
+    
+    #[test]
+    #[should_panic(expected = "Node not registered")]
+    fn test_top_up_stake_unregistered() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        contract.top_up_stake();
+    }
+
+    #[test]
+    fn test_redundant_task_accepts_majority_result() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        register_nodes(&mut contract, &[accounts(2), accounts(3), accounts(4)], MIN_STAKE);
+        
+        let task_cost = 90_000_000_000_000_000_000_000;
+        let context = get_context(accounts(5), task_cost + 3 * STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Task 1".to_string(), task_cost.into(), Some(TaskPriority::High), Some(3), None, None, None);
+        
+        let task = contract.get_active_task(0).unwrap();
+        assert_eq!(task.status, TaskStatus::Assigned);
+        assert_eq!(task.assignees.len(), 3);
+        for node in [accounts(2), accounts(3), accounts(4)] {
+            assert_eq!(contract.get_assigned_tasks(node).len(), 1);
+        }
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.submit_result(0, "hash-a".to_string(), "Paris".to_string());
+        
+        let context = get_context(accounts(3), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.submit_result(0, "hash-b".to_string(), "Lyon".to_string());
+        
+        // No quorum yet, and nodes that answered are no longer polled for it
+        assert_eq!(contract.get_active_task(0).unwrap().submissions.len(), 2);
+        assert!(contract.get_assigned_tasks(accounts(2)).is_empty());
+        
+        let context = get_context(accounts(4), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.submit_result(0, "hash-c".to_string(), "Paris".to_string());
+        
+        let result = contract.get_task_result(0).unwrap();
+        assert_eq!(result.status, TaskStatus::Completed);
+        assert_eq!(result.output.unwrap(), "Paris");
+        
+        // Agreeing nodes split the reward, the dissenting node is slashed
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, task_cost / 2);
+        assert_eq!(contract.ft_balance_of(accounts(4)).0, task_cost / 2);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 0);
+        
+        let dissenter = contract.get_node_info(accounts(3)).unwrap();
+        assert_eq!(dissenter.slashed_amount, MIN_STAKE / 10);
+        assert_eq!(dissenter.total_tasks_completed, 0);
+        assert_eq!(contract.get_node_info(accounts(4)).unwrap().total_tasks_completed, 1);
+    }
+
+    #[test]
+    fn test_redundant_task_without_consensus_fails() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        register_nodes(&mut contract, &[accounts(2), accounts(3), accounts(4)], MIN_STAKE);
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(5), task_cost + 2 * STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Task 1".to_string(), task_cost.into(), None, Some(2), None, None, None);
+        
+        let assignees = contract.get_active_task(0).unwrap().assignees;
+        assert_eq!(assignees.len(), 2);
+        
+        let context = get_context(assignees[0].parse().unwrap(), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.submit_result(0, "hash-a".to_string(), "Paris".to_string());
+        
+        let context = get_context(assignees[1].parse().unwrap(), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.submit_result(0, "hash-b".to_string(), "Lyon".to_string());
+        
+        let result = contract.get_task_result(0).unwrap();
+        assert_eq!(result.status, TaskStatus::Failed);
+        assert_eq!(result.output, None);
+        for node in assignees {
+            let node: AccountId = node.parse().unwrap();
+            assert_eq!(contract.ft_balance_of(node.clone()).0, 0);
+            assert_eq!(contract.get_node_info(node).unwrap().slashed_amount, 0);
+        }
+    }
+
+    #[test]
+    fn test_redundant_task_waits_for_enough_nodes() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+        
+        // One node can't form a majority of three
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(5), task_cost + 3 * STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Task 1".to_string(), task_cost.into(), None, Some(3), None, None, None);
+        
+        assert_eq!(contract.get_pending_tasks().len(), 1);
+        assert!(contract.get_assigned_tasks(accounts(2)).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Redundancy must be between 1 and 5")]
+    fn test_submit_task_invalid_redundancy() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(5), task_cost + 6 * STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Task 1".to_string(), task_cost.into(), None, Some(6), None, None, None);
+    }
+
+    // Registers accounts(2) as a node and has it complete a task from
+    // accounts(3) that requires approval
+    fn setup_task_awaiting_approval(task_cost: Balance) -> DeAICompute {
+        let mut contract = setup_nodes(&[accounts(2)], MIN_STAKE);
+        
+        let context = get_context(accounts(3), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Task 1".to_string(), task_cost.into(), None, None, Some(true), None, None);
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.submit_result(0, "abc123hash".to_string(), "Hello world response".to_string());
+        
+        contract
+    }
+
+    #[test]
+    fn test_approve_result_releases_reward() {
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let mut contract = setup_task_awaiting_approval(task_cost);
+        
+        // The reward is held until the requester approves
+        let task = contract.get_active_task(0).unwrap();
+        assert_eq!(task.status, TaskStatus::PendingApproval);
+        assert_eq!(task.output.unwrap(), "Hello world response");
+        assert_eq!(task.approval_deadline, Some(DEFAULT_APPROVAL_WINDOW));
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, 0);
+        assert!(contract.get_assigned_tasks(accounts(2)).is_empty());
+        
+        let context = get_context(accounts(3), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.approve_result(0);
+        
+        assert_eq!(contract.get_task_result(0).unwrap().status, TaskStatus::Completed);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, task_cost);
+        assert_eq!(contract.get_node_info(accounts(2)).unwrap().total_tasks_completed, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the requester can approve this task")]
+    fn test_approve_result_not_requester() {
+        let mut contract = setup_task_awaiting_approval(100_000_000_000_000_000_000_000);
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.approve_result(0);
+    }
+
+    #[test]
+    fn test_reject_result_opens_dispute() {
+        let mut contract = setup_task_awaiting_approval(100_000_000_000_000_000_000_000);
+        
+        let context = get_context(accounts(3), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.reject_result(0);
+        
+        let task = contract.get_active_task(0).unwrap();
+        assert_eq!(task.status, TaskStatus::Disputed);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, 0);
+        assert_eq!(contract.get_tasks_by_status(TaskStatus::Disputed, 0, 10).len(), 1);
+    }
+
+    #[test]
+    fn test_resolve_dispute_pays_node() {
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let mut contract = setup_task_awaiting_approval(task_cost);
+        
+        let context = get_context(accounts(3), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.reject_result(0);
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.resolve_dispute(0, true);
+        
+        assert!(contract.get_active_task(0).is_none());
+        assert_eq!(contract.get_task_result(0).unwrap().status, TaskStatus::Completed);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, task_cost);
+    }
+
+    #[test]
+    fn test_resolve_dispute_refunds_requester() {
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let mut contract = setup_task_awaiting_approval(task_cost);
+        
+        let context = get_context(accounts(3), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.reject_result(0);
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.resolve_dispute(0, false);
+        
+        assert!(contract.get_active_task(0).is_none());
+        assert_eq!(contract.get_task_result(0).unwrap().status, TaskStatus::Failed);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, 0);
+        assert!(contract.get_tasks_by_status(TaskStatus::Disputed, 0, 10).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Task not disputed")]
+    fn test_resolve_dispute_requires_dispute() {
+        let mut contract = setup_task_awaiting_approval(100_000_000_000_000_000_000_000);
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.resolve_dispute(0, true);
+    }
+
+    #[test]
+    fn test_auto_approve_after_window() {
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let mut contract = setup_task_awaiting_approval(task_cost);
+        
+        let mut context = get_context(accounts(4), 0);
+        context.block_timestamp(DEFAULT_APPROVAL_WINDOW + 1);
+        testing_env!(context.build());
+        contract.auto_approve_result(0);
+        
+        assert_eq!(contract.get_task_result(0).unwrap().status, TaskStatus::Completed);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, task_cost);
+    }
+
+    #[test]
+    #[should_panic(expected = "Approval window has not passed yet")]
+    fn test_auto_approve_before_window() {
+        let mut contract = setup_task_awaiting_approval(100_000_000_000_000_000_000_000);
+        
+        let context = get_context(accounts(4), 0);
+        testing_env!(context.build());
+        contract.auto_approve_result(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Approval window has passed")]
+    fn test_reject_result_after_window() {
+        let mut contract = setup_task_awaiting_approval(100_000_000_000_000_000_000_000);
+        
+        let mut context = get_context(accounts(3), ONE_YOCTO);
+        context.block_timestamp(DEFAULT_APPROVAL_WINDOW + 1);
+        testing_env!(context.build());
+        contract.reject_result(0);
+    }
+
+    #[test]
+    fn test_get_node_leaderboard() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        register_nodes(&mut contract, &[accounts(2), accounts(3), accounts(4)], MIN_STAKE);
+        
+        // accounts(3) completes two tasks, accounts(4) one
+        let task_cost = 100_000_000_000_000_000_000_000;
+        for node in [accounts(3), accounts(3), accounts(4)] {
+            let context = get_context(accounts(5), task_cost + STORAGE_COST);
+            testing_env!(context.build());
+            contract.submit_task("Task".to_string(), task_cost.into(), None, None, None, None, None);
+            let task_id = contract.get_task_count() - 1;
+            
+            // Steer the task to the intended node by having the others decline it
+            let mut task = contract.get_active_task(task_id).unwrap();
+            while task.assignee.as_deref() != Some(node.as_str()) {
+                let context = get_context(task.assignee.unwrap().parse().unwrap(), 0);
+                testing_env!(context.build());
+                contract.decline_task(task_id, "busy".to_string());
+                task = contract.get_active_task(task_id).unwrap();
+            }
+            
+            let context = get_context(node, ONE_YOCTO);
+            testing_env!(context.build());
+            contract.submit_result(task_id, "hash".to_string(), "output".to_string());
+        }
+        
+        let leaderboard = contract.get_node_leaderboard(0, 10);
+        let ranked: Vec<&str> = leaderboard.iter().map(|node| node.account_id.as_str()).collect();
+        assert_eq!(ranked, vec![accounts(3).as_str(), accounts(4).as_str(), accounts(2).as_str()]);
+        
+        let page = contract.get_node_leaderboard(1, 1);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].account_id, accounts(4).to_string());
+    }
+
+    #[test]
+    fn test_force_slash_node() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.slash_node(accounts(2), 2500, "Fraudulent results".to_string());
+        
+        let node = contract.get_node_info(accounts(2)).unwrap();
+        assert_eq!(node.slashed_amount, MIN_STAKE / 2); // 25% of 2 NEAR
+        assert_eq!(node.reputation_score, 50);
+        assert!(node.is_active);
+        assert_eq!(contract.get_treasury_balance().0, MIN_STAKE / 2);
+        
+        // A second slash leaves less than the minimum stake
+        contract.slash_node(accounts(2), 5000, "Fraudulent results".to_string());
+        
+        let node = contract.get_node_info(accounts(2)).unwrap();
+        assert_eq!(node.slashed_amount, MIN_STAKE * 3 / 2);
+        assert_eq!(node.reputation_score, 0);
+        assert!(!node.is_active);
+    }
+
+    #[test]
+    #[should_panic(expected = "Stake below minimum, top up to reactivate")]
+    fn test_force_slashed_node_cannot_heartbeat() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.slash_node(accounts(2), 5000, "Fraudulent results".to_string());
+        contract.slash_node(accounts(2), 5000, "Fraudulent results".to_string());
+        
+        let context = get_context(accounts(2), 0);
+        testing_env!(context.build());
+        contract.heartbeat(None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can call this method")]
+    fn test_force_slash_node_not_owner() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        
+        let context = get_context(accounts(3), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.slash_node(accounts(2), 1000, "Fraudulent results".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Slash must be between 1 and 5000 bps")]
+    fn test_force_slash_node_bps_too_high() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.slash_node(accounts(2), 5001, "Fraudulent results".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Node not registered")]
+    fn test_force_slash_unknown_node() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.slash_node(accounts(3), 1000, "Fraudulent results".to_string());
+    }
+
+    #[test]
+    fn test_blacklist_node_forfeits_stake_and_releases_tasks() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(4), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Task 1".to_string(), task_cost.into(), None, None, None, None, None);
+        assert_eq!(contract.get_assigned_tasks(accounts(2)).len(), 1);
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.blacklist_node(accounts(2), "Repeated fraudulent results".to_string());
+        
+        assert!(contract.is_blacklisted(accounts(2)));
+        let node = contract.get_node_info(accounts(2)).unwrap();
+        assert!(!node.is_active);
+        assert_eq!(node.slashed_amount, node.stake);
+        assert_eq!(contract.get_treasury_balance().0, 2 * MIN_STAKE);
+        
+        // The task waits for another node
+        assert!(contract.get_assigned_tasks(accounts(2)).is_empty());
+        assert_eq!(contract.get_pending_tasks().len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Node is blacklisted")]
+    fn test_blacklisted_account_cannot_register() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.blacklist_node(accounts(3), "Sybil operator".to_string());
+        
+        register_storage(&mut contract, accounts(3));
+        let context = get_context(accounts(3), MIN_STAKE);
+        testing_env!(context.build());
+        contract.register_node(
+            "192.168.1.101".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.101:8080".to_string(),
+            None,
+        );
+    }
+
+    #[test]
+    fn test_unblacklisted_account_can_register() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.blacklist_node(accounts(3), "Sybil operator".to_string());
+        contract.unblacklist_node(accounts(3));
+        assert!(!contract.is_blacklisted(accounts(3)));
+        
+        register_storage(&mut contract, accounts(3));
+        let context = get_context(accounts(3), MIN_STAKE);
+        testing_env!(context.build());
+        contract.register_node(
+            "192.168.1.101".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.101:8080".to_string(),
+            None,
+        );
+        assert!(contract.get_node_info(accounts(3)).unwrap().is_active);
+    }
+
+    #[test]
+    #[should_panic(expected = "Node is blacklisted")]
+    fn test_blacklisted_node_cannot_heartbeat() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.blacklist_node(accounts(2), "Repeated fraudulent results".to_string());
+        
+        let context = get_context(accounts(2), 0);
+        testing_env!(context.build());
+        contract.heartbeat(None);
+    }
+
+    #[test]
+    fn test_get_timing_stats() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        
+        // Nothing has completed yet
+        let stats = contract.get_timing_stats();
+        assert_eq!(stats.completed_tasks, 0);
+        assert_eq!(stats.average_time_to_assign, None);
+        assert_eq!(stats.average_time_to_complete, None);
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        for (submitted_at, completed_at) in [(1_000_000_000, 3_000_000_000), (10_000_000_000, 16_000_000_000)] {
+            let mut context = get_context(accounts(4), task_cost + STORAGE_COST);
+            context.block_timestamp(submitted_at);
+            testing_env!(context.build());
+            contract.submit_task("Task".to_string(), task_cost.into(), None, None, None, None, None);
+            
+            let mut context = get_context(accounts(2), ONE_YOCTO);
+            context.block_timestamp(completed_at);
+            testing_env!(context.build());
+            contract.submit_result(contract.get_task_count() - 1, "hash".to_string(), "output".to_string());
+        }
+        
+        // Tasks are assigned on submission, then take 2s and 6s
+        let stats = contract.get_timing_stats();
+        assert_eq!(stats.completed_tasks, 2);
+        assert_eq!(stats.average_time_to_assign, Some(0));
+        assert_eq!(stats.average_time_to_complete, Some(4_000_000_000));
+    }
+
+    fn deposit_reward_token(contract: &mut DeAICompute, sender: AccountId, amount: Balance) {
+        let context = get_context(accounts(5), 0);
+        testing_env!(context.build());
+        contract.ft_on_transfer(sender, amount.into(), String::new());
+    }
+
+    #[test]
+    fn test_ft_on_transfer_credits_token_deposit() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        deposit_reward_token(&mut contract, accounts(3), 500);
+        deposit_reward_token(&mut contract, accounts(3), 250);
+        assert_eq!(contract.get_token_deposit(accounts(3), accounts(5)).0, 750);
+        
+        // DEAI itself can't be escrowed
+        let context = get_context(accounts(0), 0);
+        testing_env!(context.build());
+        match contract.ft_on_transfer(accounts(3), U128(100), String::new()) {
+            near_sdk::PromiseOrValue::Value(unused) => assert_eq!(unused.0, 100),
+            _ => panic!("Expected the transfer to be returned"),
+        }
+        assert_eq!(contract.get_token_deposit(accounts(3), accounts(0)).0, 0);
+    }
+
+    #[test]
+    fn test_token_task_pays_reward_without_minting() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        let task_cost = 1_000_000;
+        deposit_reward_token(&mut contract, accounts(3), task_cost);
+        
+        let context = get_context(accounts(3), STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Token task".to_string(), task_cost.into(), None, None, None, Some(accounts(5)), None);
+        assert_eq!(contract.get_token_deposit(accounts(3), accounts(5)).0, 0);
+        
+        let task = contract.get_active_task(0).unwrap();
+        assert_eq!(task.reward_token, Some(accounts(5).to_string()));
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.submit_result(0, "proof_hash".to_string(), "result".to_string());
+        
+        assert_eq!(contract.get_task_result(0).unwrap().status, TaskStatus::Completed);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient token deposit for compute cost")]
+    fn test_token_task_requires_deposit() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        let mut contract = DeAICompute::new(accounts(1));
+        deposit_reward_token(&mut contract, accounts(3), 100);
+        
+        let context = get_context(accounts(3), STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Token task".to_string(), U128(101), None, None, None, Some(accounts(5)), None);
+    }
+
+    #[test]
+    fn test_cancelled_token_task_refunds_deposit() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        let mut contract = DeAICompute::new(accounts(1));
+        deposit_reward_token(&mut contract, accounts(3), 1_000);
+        
+        let context = get_context(accounts(3), STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Token task".to_string(), U128(600), None, None, None, Some(accounts(5)), None);
+        assert_eq!(contract.get_token_deposit(accounts(3), accounts(5)).0, 400);
+        
+        let context = get_context(accounts(3), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.cancel_task(0);
+        assert_eq!(contract.get_token_deposit(accounts(3), accounts(5)).0, 1_000);
+    }
+
+    fn complete_task(contract: &mut DeAICompute, task_id: u64, task_cost: Balance) {
+        let context = get_context(accounts(3), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task(format!("Task {}", task_id), task_cost.into(), None, None, None, None, None);
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.submit_result(task_id, format!("proof_{}", task_id), format!("result_{}", task_id));
+    }
+
+    #[test]
+    fn test_max_reputation_node_earns_full_bonus() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        let task_cost = 1_000_000;
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.update_reputation_bonus_budget(U128(100_000_000));
+        
+        // 90 completions take a node from base to max reputation
+        for i in 0..90 {
+            complete_task(&mut contract, i, task_cost);
+        }
+        assert_eq!(contract.get_node_info(accounts(2)).unwrap().reputation_score, MAX_REPUTATION);
+        assert_eq!(contract.get_effective_reward(accounts(2), U128(task_cost)).0, task_cost * 11 / 10);
+        
+        let before = contract.ft_balance_of(accounts(2)).0;
+        let budget_before = contract.get_reputation_bonus_budget().0;
+        complete_task(&mut contract, 90, task_cost);
+        
+        assert_eq!(contract.ft_balance_of(accounts(2)).0 - before, task_cost * 11 / 10);
+        assert_eq!(contract.get_reputation_bonus_budget().0, budget_before - task_cost / 10);
+    }
+
+    #[test]
+    fn test_base_reputation_node_earns_no_bonus() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        let task_cost = 1_000_000;
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.update_reputation_bonus_budget(U128(1_000_000));
+        
+        assert_eq!(contract.get_effective_reward(accounts(2), U128(task_cost)).0, task_cost);
+        complete_task(&mut contract, 0, task_cost);
+        
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, task_cost);
+        assert_eq!(contract.get_reputation_bonus_budget().0, 1_000_000);
+    }
+
+    #[test]
+    fn test_reputation_bonus_limited_by_budget() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        let task_cost = 1_000_000;
+        for i in 0..90 {
+            complete_task(&mut contract, i, task_cost);
+        }
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.update_reputation_bonus_budget(U128(30_000));
+        
+        let before = contract.ft_balance_of(accounts(2)).0;
+        complete_task(&mut contract, 90, task_cost);
+        
+        assert_eq!(contract.ft_balance_of(accounts(2)).0 - before, task_cost + 30_000);
+        assert_eq!(contract.get_reputation_bonus_budget().0, 0);
+    }
+
+    #[test]
+    fn test_allowlisted_task_only_runs_on_allowed_node() {
+        let mut contract = setup_nodes(&[accounts(2), accounts(4)], MIN_STAKE);
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(3), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task(
+            "Vetted task".to_string(),
+            task_cost.into(),
+            None,
+            None,
+            None,
+            None,
+            Some(vec![accounts(4)]),
+        );
+        
+        let task = contract.get_active_task(0).unwrap();
+        assert_eq!(task.assignee, Some(accounts(4).to_string()));
+        assert_eq!(task.allowed_nodes, Some(vec![accounts(4).to_string()]));
+        
+        // Once the allowed node declines, the other node is not a fallback
+        let context = get_context(accounts(4), 0);
+        testing_env!(context.build());
+        contract.decline_task(0, "Busy".to_string());
+        
+        let task = contract.get_active_task(0).unwrap();
+        assert_eq!(task.status, TaskStatus::Pending);
+        assert!(task.assignee.is_none());
+        assert!(contract.get_assigned_tasks(accounts(2)).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "is not registered")]
+    fn test_allowlist_requires_registered_nodes() {
+        let mut contract = setup_nodes(&[accounts(2), accounts(4)], MIN_STAKE);
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(3), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task(
+            "Vetted task".to_string(),
+            task_cost.into(),
+            None,
+            None,
+            None,
+            None,
+            Some(vec![accounts(5)]),
+        );
+    }
+
+    #[test]
+    fn test_register_node_twice_is_a_no_op() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+
+        // The first registration landed but the daemon saw an RPC timeout and retried
+        let context = get_context(accounts(2), 2 * MIN_STAKE);
+        testing_env!(context.build());
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            None,
+        );
+
+        let node = contract.get_node_info(accounts(2)).unwrap();
+        assert_eq!(node.stake, 2 * MIN_STAKE);
+        assert_eq!(contract.get_active_nodes().len(), 1);
+        assert!(near_sdk::test_utils::get_logs().iter().any(|log| log.contains("Node already registered")));
+    }
+    
+    #[test]
+    fn test_tasks_are_routed_by_capability() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        register_storage(&mut contract, accounts(2));
+        let context = get_context(accounts(2), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.100".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.100:8080".to_string(),
+            Some(vec!["embedding".to_string(), "embedding".to_string()]),
+        );
+        assert_eq!(contract.get_node_info(accounts(2)).unwrap().capabilities, vec!["embedding"]);
+        
+        register_storage(&mut contract, accounts(3));
+        let context = get_context(accounts(3), MIN_STAKE);
+        testing_env!(context.build());
+        
+        contract.register_node(
+            "192.168.1.101".to_string(),
+            "RTX 4090".to_string(),
+            "Intel i9".to_string(),
+            "http://192.168.1.101:8080".to_string(),
+            Some(vec!["text_generation".to_string(), "inference".to_string()]),
+        );
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(4), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        
+        contract.submit_task(
+            r#"{"model": "gpt2", "input": "Hello", "task_type": "text_generation"}"#.to_string(),
+            task_cost.into(),
+            Some(TaskPriority::Normal),
+            None,
+            None,
+            None,
+            None,
+        );
+        
+        assert!(contract.get_assigned_tasks(accounts(2)).is_empty());
+        let assigned = contract.get_assigned_tasks(accounts(3));
+        assert_eq!(assigned.len(), 1);
+        assert_eq!(assigned[0].task_type.as_deref(), Some("text_generation"));
+        
+        // Neither node advertises classification, so the task waits
+        let context = get_context(accounts(4), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        
+        contract.submit_task(
+            r#"{"model": "bert", "input": "Hello", "task_type": "classification"}"#.to_string(),
+            task_cost.into(),
+            Some(TaskPriority::Normal),
+            None,
+            None,
+            None,
+            None,
+        );
+        
+        assert_eq!(contract.get_pending_tasks().len(), 1);
+        assert_eq!(contract.get_pending_tasks()[0].id, 1);
+    }
+    
+    #[test]
+    fn test_node_without_capabilities_takes_any_task_type() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(4), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        
+        contract.submit_task(
+            r#"{"model": "bert", "input": "Hello", "task_type": "classification"}"#.to_string(),
+            task_cost.into(),
+            Some(TaskPriority::Normal),
+            None,
+            None,
+            None,
+            None,
+        );
+        
+        assert_eq!(contract.get_assigned_tasks(accounts(2)).len(), 1);
+    }
+    
+    #[test]
+    fn test_update_node_capabilities() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.update_node_info(None, None, None, None, Some(vec!["inference".to_string(), "embedding".to_string()]));
+        assert_eq!(contract.get_node_info(accounts(2)).unwrap().capabilities, vec!["embedding", "inference"]);
+        
+        // Leaving capabilities out keeps them
+        contract.update_node_info(None, Some("RTX 5090".to_string()), None, None, None);
+        assert_eq!(contract.get_node_info(accounts(2)).unwrap().capabilities, vec!["embedding", "inference"]);
+    }
+    
+    #[test]
+    #[should_panic(expected = "Capability cannot be empty")]
+    fn test_empty_capability_rejected() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.update_node_info(None, None, None, None, Some(vec![String::new()]));
+    }
+    
+    #[test]
+    fn test_get_requester_tasks() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        let task_cost = 1_000_000;
+        
+        complete_task(&mut contract, 0, task_cost);
+        complete_task(&mut contract, 1, task_cost);
+        
+        let context = get_context(accounts(4), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Task 2".to_string(), task_cost.into(), None, None, None, None, None);
+        
+        let context = get_context(accounts(3), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Task 3".to_string(), task_cost.into(), None, None, None, None, None);
+        
+        // Finished and active tasks together, in submission order
+        let tasks = contract.get_requester_tasks(accounts(3), 0, 10);
+        assert_eq!(tasks.iter().map(|t| t.id).collect::<Vec<_>>(), vec![0, 1, 3]);
+        assert_eq!(tasks[0].status, TaskStatus::Completed);
+        assert_eq!(tasks[2].status, TaskStatus::Assigned);
+        assert_eq!(contract.get_requester_task_count(accounts(3)), 3);
+        
+        let page = contract.get_requester_tasks(accounts(3), 1, 1);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].id, 1);
+        
+        assert_eq!(contract.get_requester_tasks(accounts(4), 0, 10)[0].id, 2);
+        assert!(contract.get_requester_tasks(accounts(5), 0, 10).is_empty());
+    }
+    
+    #[test]
+    fn test_rewards_accrue_until_claimed() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        let task_cost = 1_000_000;
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.set_auto_claim_rewards(false);
+        assert!(!contract.get_node_info(accounts(2)).unwrap().auto_claim_rewards);
+        
+        complete_task(&mut contract, 0, task_cost);
+        complete_task(&mut contract, 1, task_cost);
+        
+        // Nothing minted yet, both rewards waiting
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, 0);
+        assert_eq!(contract.get_pending_rewards(accounts(2)).0, 2 * task_cost);
+        assert_eq!(contract.get_total_rewards_distributed().0, 0);
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        assert_eq!(contract.claim_rewards().0, 2 * task_cost);
+        
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, 2 * task_cost);
+        assert_eq!(contract.get_pending_rewards(accounts(2)).0, 0);
+        assert_eq!(contract.get_total_rewards_distributed().0, 2 * task_cost);
+    }
+    
+    #[test]
+    fn test_auto_claim_is_the_default() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        let task_cost = 1_000_000;
+        
+        complete_task(&mut contract, 0, task_cost);
+        
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, task_cost);
+        assert_eq!(contract.get_pending_rewards(accounts(2)).0, 0);
+    }
+    
+    #[test]
+    #[should_panic(expected = "No rewards to claim")]
+    fn test_claim_without_rewards() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.claim_rewards();
+    }
+    
+    #[test]
+    fn test_liveness_follows_node_tier() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        register_nodes(&mut contract, &[accounts(2), accounts(3)], MIN_STAKE);
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.set_node_tier(accounts(2), NodeTier::Enterprise);
+        contract.set_node_tier(accounts(3), NodeTier::Hobby);
+        assert_eq!(contract.get_node_info(accounts(2)).unwrap().tier, NodeTier::Enterprise);
+        
+        // Three minutes without a heartbeat: too long for enterprise only
+        let mut context = get_context(accounts(4), 0);
+        context.block_timestamp(180_000_000_000);
+        testing_env!(context.build());
+        assert!(!contract.get_node_liveness(accounts(2)));
+        assert!(contract.get_node_liveness(accounts(3)));
+        assert_eq!(contract.get_active_nodes().len(), 1);
+        
+        // Ten minutes: still within the hobby tolerance
+        let mut context = get_context(accounts(4), 0);
+        context.block_timestamp(600_000_000_000);
+        testing_env!(context.build());
+        assert!(contract.get_node_liveness(accounts(3)));
+        assert_eq!(contract.sweep_stale_nodes(10), 1);
+        assert!(!contract.get_node_info(accounts(2)).unwrap().is_active);
+        
+        assert!(!contract.get_node_liveness(accounts(5)));
+    }
+    
+    #[test]
+    fn test_set_heartbeat_timeout() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        register_nodes(&mut contract, &[accounts(2), accounts(3)], MIN_STAKE);
+        assert_eq!(contract.get_heartbeat_timeouts().standard, 300_000_000_000);
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.set_heartbeat_timeout(NodeTier::Standard, 600_000_000_000);
+        assert_eq!(contract.get_heartbeat_timeouts().standard, 600_000_000_000);
+        
+        // Standard nodes now survive past the old 5 minutes
+        let mut context = get_context(accounts(4), 0);
+        context.block_timestamp(400_000_000_000);
+        testing_env!(context.build());
+        assert!(contract.get_node_liveness(accounts(2)));
+        assert_eq!(contract.sweep_stale_nodes(10), 0);
+    }
+    
+    #[test]
+    #[should_panic(expected = "Heartbeat timeout must be between 1 minute and 1 hour")]
+    fn test_heartbeat_timeout_bounds() {
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        contract.set_heartbeat_timeout(NodeTier::Hobby, 1_000_000_000);
+    }
+    
+    #[test]
+    #[should_panic(expected = "Only owner can call this method")]
+    fn test_only_owner_sets_node_tier() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.set_node_tier(accounts(2), NodeTier::Hobby);
+    }
+
+    #[test]
+    fn test_per_owner_registration_cap() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.set_registration_caps(None, Some(2));
+        
+        register_node_at(&mut contract, "n1.alice.near".parse().unwrap(), "10.0.0.1", 2 * MIN_STAKE);
+        register_node_at(&mut contract, "n2.alice.near".parse().unwrap(), "10.0.0.2", 2 * MIN_STAKE);
+        // Another owner is counted separately
+        register_node_at(&mut contract, "n1.bob.near".parse().unwrap(), "10.0.0.3", 2 * MIN_STAKE);
+        
+        assert_eq!(contract.get_owner_node_count("n9.alice.near".parse().unwrap()), 2);
+        assert_eq!(contract.get_owner_node_count("bob.near".parse().unwrap()), 1);
+        assert_eq!(contract.get_network_capacity().total_nodes, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Node limit reached for alice.near")]
+    fn test_per_owner_cap_rejects_extra_node() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.set_registration_caps(None, Some(1));
+        
+        register_node_at(&mut contract, "n1.alice.near".parse().unwrap(), "10.0.0.1", 2 * MIN_STAKE);
+        register_node_at(&mut contract, "n2.alice.near".parse().unwrap(), "10.0.0.2", 2 * MIN_STAKE);
+    }
+
+    #[test]
+    #[should_panic(expected = "Network node limit reached")]
+    fn test_network_registration_cap() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.set_registration_caps(Some(2), None);
+        
+        register_node_at(&mut contract, "n1.alice.near".parse().unwrap(), "10.0.0.1", 2 * MIN_STAKE);
+        register_node_at(&mut contract, "n1.bob.near".parse().unwrap(), "10.0.0.2", 2 * MIN_STAKE);
+        
+        let capacity = contract.get_network_capacity();
+        assert_eq!(capacity.total_nodes, 2);
+        assert_eq!(capacity.max_network_nodes, Some(2));
+        assert_eq!(capacity.max_nodes_per_owner, None);
+        
+        register_node_at(&mut contract, "n1.carol.near".parse().unwrap(), "10.0.0.3", 2 * MIN_STAKE);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can call this method")]
+    fn test_only_owner_sets_registration_caps() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.set_registration_caps(Some(10), Some(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Compute cost exceeds the maximum of 1000000000000000000000000 yoctoNEAR")]
+    fn test_submit_task_above_max_compute_cost() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.update_compute_cost_bounds(U128(1_000), U128(MIN_STAKE));
+        assert_eq!(contract.get_max_compute_cost().0, MIN_STAKE);
+        
+        let task_cost = MIN_STAKE + 1;
+        let context = get_context(accounts(3), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Task 1".to_string(), task_cost.into(), None, None, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Compute cost is below the minimum of 1000 yoctoNEAR")]
+    fn test_submit_task_below_min_compute_cost() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.update_compute_cost_bounds(U128(1_000), U128(MIN_STAKE));
+        
+        let task_cost = 999;
+        let context = get_context(accounts(4), 2 * (task_cost + STORAGE_COST));
+        testing_env!(context.build());
+        let tasks = vec![
+            TaskInput { description: "Task 1".to_string() },
+            TaskInput { description: "Task 2".to_string() },
+        ];
+        contract.submit_tasks_batch(tasks, task_cost.into(), None);
+    }
+
+    #[test]
+    fn test_submit_task_within_overpayment_tolerance() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(3), task_cost + STORAGE_COST + MAX_OVERPAYMENT);
+        testing_env!(context.build());
+        contract.submit_task("Task 1".to_string(), task_cost.into(), None, None, None, None, None);
+        
+        assert_eq!(contract.get_task_count(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached deposit exceeds the required")]
+    fn test_submit_task_rejects_overpayment() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(3), task_cost + STORAGE_COST + MAX_OVERPAYMENT + 1);
+        testing_env!(context.build());
+        contract.submit_task("Task 1".to_string(), task_cost.into(), None, None, None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Min compute cost cannot exceed max compute cost")]
+    fn test_compute_cost_bounds_must_be_ordered() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.update_compute_cost_bounds(U128(2_000), U128(1_000));
+    }
+
+    #[test]
+    fn test_slash_history_records_each_slash() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(3), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Test task".to_string(), task_cost.into(), None, None, None, None, None);
+        
+        let mut context = get_context(accounts(4), ONE_YOCTO);
+        context.block_timestamp(3700_000_000_000);
+        testing_env!(context.build());
+        contract.timeout_task(0);
+        
+        let mut context = get_context(accounts(1), ONE_YOCTO);
+        context.block_timestamp(3800_000_000_000);
+        testing_env!(context.build());
+        contract.slash_node(accounts(2), 500, "fraudulent result".to_string());
+        
+        assert_eq!(contract.get_slash_count(accounts(2)), 2);
+        let history = contract.get_slash_history(accounts(2), 0, 10);
+        assert_eq!(history[0], SlashEvent {
+            timestamp: 3700_000_000_000,
+            amount: U128(2 * MIN_STAKE / 10),
+            reason: "timeout".to_string(),
+            task_id: Some(0),
+        });
+        assert_eq!(history[1].reason, "fraudulent result");
+        assert_eq!(history[1].amount.0, 2 * MIN_STAKE / 20);
+        assert_eq!(history[1].task_id, None);
+        
+        // Paged from an offset
+        assert_eq!(contract.get_slash_history(accounts(2), 1, 10), vec![history[1].clone()]);
+        assert!(contract.get_slash_history(accounts(3), 0, 10).is_empty());
+    }
+
+    // Covers a full-size chunk at the default storage byte cost
+    const CHUNK_STORAGE_DEPOSIT: Balance = 200_000_000_000_000_000_000_000; // 0.2 NEAR
+
+    #[test]
+    fn test_chunked_result_exceeds_single_shot_cap() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        let task_cost = 1_000_000;
+        
+        let context = get_context(accounts(3), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Long generation".to_string(), task_cost.into(), None, None, None, None, None);
+        
+        let context = get_context(accounts(2), CHUNK_STORAGE_DEPOSIT);
+        testing_env!(context.build());
+        let chunks: Vec<String> = (0..3).map(|i| i.to_string().repeat(MAX_RESULT_OUTPUT_LEN)).collect();
+        for (seq, chunk) in chunks.iter().enumerate() {
+            contract.append_result_chunk(0, seq as u32, chunk.clone());
+        }
+        assert_eq!(contract.get_next_result_chunk(0, accounts(2)), 3);
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.finalize_result(0, "proof_0".to_string());
+        
+        let result = contract.get_task_result(0).unwrap();
+        assert_eq!(result.output.unwrap(), chunks.concat());
+        assert_eq!(contract.get_next_result_chunk(0, accounts(2)), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected chunk 1")]
+    fn test_result_chunks_must_arrive_in_order() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        let task_cost = 1_000_000;
+        
+        let context = get_context(accounts(3), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Long generation".to_string(), task_cost.into(), None, None, None, None, None);
+        
+        let context = get_context(accounts(2), CHUNK_STORAGE_DEPOSIT);
+        testing_env!(context.build());
+        contract.append_result_chunk(0, 0, "first".to_string());
+        contract.append_result_chunk(0, 2, "third".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Output exceeds 100000 bytes")]
+    fn test_chunked_result_size_is_bounded() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        let task_cost = 1_000_000;
+        
+        let context = get_context(accounts(3), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Long generation".to_string(), task_cost.into(), None, None, None, None, None);
+        
+        // Enough storage for the whole output
+        let context = get_context(accounts(2), 20 * CHUNK_STORAGE_DEPOSIT);
+        testing_env!(context.build());
+        contract.append_result_chunk(0, 0, "a".repeat(MAX_CHUNKED_OUTPUT_LEN));
+        contract.append_result_chunk(0, 1, "a".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "to cover chunk storage")]
+    fn test_result_chunk_must_pay_for_storage() {
+        let mut contract = setup_nodes(&[accounts(2)], 2 * MIN_STAKE);
+        let task_cost = 1_000_000;
+        
+        let context = get_context(accounts(3), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Long generation".to_string(), task_cost.into(), None, None, None, None, None);
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.append_result_chunk(0, 0, "a".repeat(MAX_RESULT_OUTPUT_LEN));
+    }
+
+    #[test]
+    fn test_slashed_below_minimum_releases_ip_and_keeps_stake_bonded() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        let mut contract = DeAICompute::new(accounts(1));
+        register_node_at(&mut contract, accounts(2), "192.168.1.100", MIN_STAKE);
+        
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.slash_node(accounts(2), 5000, "fraudulent result".to_string());
+        assert!(!contract.get_node_info(accounts(2)).unwrap().is_active);
+        
+        // The IP is free for another node
+        register_node_at(&mut contract, accounts(3), "192.168.1.100", MIN_STAKE);
+        
+        // The remaining stake has to be unbonded before the record can go
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.storage_unregister(None);
+        }));
+        assert!(result.is_err());
+        
+        contract.deactivate_node();
+        let mut context = get_context(accounts(2), ONE_YOCTO);
+        context.block_timestamp(DEFAULT_UNBONDING_PERIOD);
+        testing_env!(context.build());
+        contract.claim_unbonded_stake();
+        assert!(contract.storage_unregister(None));
+        assert!(contract.get_node_info(accounts(2)).is_none());
+    }
+
+    fn register_unbond_and_top_up(contract: &mut DeAICompute) {
+        register_node_at(contract, accounts(2), "192.168.1.100", MIN_STAKE);
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.deactivate_node();
+        
+        let mut context = get_context(accounts(2), ONE_YOCTO);
+        context.block_timestamp(DEFAULT_UNBONDING_PERIOD);
+        testing_env!(context.build());
+        contract.claim_unbonded_stake();
+        
+        let mut context = get_context(accounts(2), MIN_STAKE);
+        context.block_timestamp(DEFAULT_UNBONDING_PERIOD);
+        testing_env!(context.build());
+        contract.top_up_stake();
+    }
+
+    #[test]
+    fn test_reactivated_node_reclaims_its_ip() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        let mut contract = DeAICompute::new(accounts(1));
+        register_unbond_and_top_up(&mut contract);
+        
+        contract.heartbeat(None);
+        assert!(contract.get_node_info(accounts(2)).unwrap().is_active);
+        
+        // The IP is indexed again, so it can't be registered twice
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            register_node_at(&mut contract, accounts(3), "192.168.1.100", MIN_STAKE);
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "IP address already registered")]
+    fn test_node_cannot_reactivate_on_a_taken_ip() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        let mut contract = DeAICompute::new(accounts(1));
+        register_unbond_and_top_up(&mut contract);
+        
+        // Another node took the IP while this one was inactive
+        register_node_at(&mut contract, accounts(3), "192.168.1.100", MIN_STAKE);
+        
+        let mut context = get_context(accounts(2), 0);
+        context.block_timestamp(DEFAULT_UNBONDING_PERIOD);
+        testing_env!(context.build());
+        contract.heartbeat(None);
+    }
+
+    #[test]
+    fn test_active_node_count_is_not_paged() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        let mut contract = DeAICompute::new(accounts(1));
+        
+        for i in 0..105 {
+            let node_id: AccountId = format!("node{}.testnet", i).parse().unwrap();
+            register_node_at(&mut contract, node_id, &format!("10.0.0.{}", i), MIN_STAKE);
+        }
+        
+        assert_eq!(contract.get_active_nodes().len(), MAX_PAGE_LIMIT as usize);
+        assert_eq!(contract.get_active_node_count(), 105);
+        assert_eq!(contract.get_contract_stats().0, 105);
+    }
+    
+    fn set_platform_fee(contract: &mut DeAICompute, fee_bps: u32) {
+        let context = get_context(accounts(1), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.update_platform_fee(fee_bps);
+    }
+    
+    fn assert_refunded_in_full(event: &str, task_cost: Balance) {
+        let logs = near_sdk::test_utils::get_logs();
+        let event_log = logs.iter().find(|log| log.contains(&format!(r#""event":"{}""#, event))).unwrap();
+        assert!(event_log.contains(&format!(r#""refund_amount":"{}""#, task_cost)));
+    }
+    
+    #[test]
+    fn test_cancelled_task_refunds_platform_fee() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        let mut contract = DeAICompute::new(accounts(1));
+        set_platform_fee(&mut contract, 500);
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(3), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Test task".to_string(), task_cost.into(), None, None, None, None, None);
+        
+        let context = get_context(accounts(3), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.cancel_task(0);
+        
+        assert_refunded_in_full("task_cancelled", task_cost);
+        assert_eq!(contract.get_treasury_balance().0, 0);
+    }
+    
+    #[test]
+    fn test_timed_out_task_refunds_platform_fee() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        let mut contract = DeAICompute::new(accounts(1));
+        set_platform_fee(&mut contract, 500);
+        register_node_at(&mut contract, accounts(2), "192.168.1.100", MIN_STAKE);
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(3), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Test task".to_string(), task_cost.into(), None, None, None, None, None);
+        
+        let mut context = get_context(accounts(4), ONE_YOCTO);
+        context.block_timestamp(3700_000_000_000);
+        testing_env!(context.build());
+        contract.timeout_task(0);
+        
+        assert_refunded_in_full("task_timed_out", task_cost);
+    }
+    
+    #[test]
+    fn test_task_without_consensus_refunds_platform_fee() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        let mut contract = DeAICompute::new(accounts(1));
+        set_platform_fee(&mut contract, 500);
+        register_nodes(&mut contract, &[accounts(2), accounts(3), accounts(4)], MIN_STAKE);
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        let context = get_context(accounts(5), task_cost + 2 * STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task("Task 1".to_string(), task_cost.into(), None, Some(2), None, None, None);
+        
+        let assignees = contract.get_active_task(0).unwrap().assignees;
+        let context = get_context(assignees[0].parse().unwrap(), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.submit_result(0, "hash-a".to_string(), "Paris".to_string());
+        
+        let context = get_context(assignees[1].parse().unwrap(), ONE_YOCTO);
+        testing_env!(context.build());
+        contract.submit_result(0, "hash-b".to_string(), "Lyon".to_string());
+        
+        assert_eq!(contract.get_task_result(0).unwrap().status, TaskStatus::Failed);
+        assert_refunded_in_full("task_consensus_failed", task_cost);
+        assert_eq!(contract.get_treasury_balance().0, 0);
+    }
+    
+    #[test]
+    fn test_preferred_node_wins_assignment_when_eligible() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        register_node_at(&mut contract, accounts(2), "192.168.1.100", MIN_STAKE);
+        register_node_at(&mut contract, accounts(3), "192.168.1.101", MIN_STAKE);
+        
+        let task_cost = 100_000_000_000_000_000_000_000;
+        for _ in 0..2 {
+            let context = get_context(accounts(4), task_cost + STORAGE_COST);
+            testing_env!(context.build());
+            contract.submit_task(
+                r#"{"model": "gpt2", "input": "Hello", "preferred_node": "danny"}"#.to_string(),
+                task_cost.into(),
+                None,
+                None,
+                None,
+                None,
+                None,
+            );
+        }
+        
+        // Both nodes are equal, so the preference alone moves the tasks off the first
+        assert_eq!(contract.get_assigned_tasks(accounts(3)).len(), 2);
+        assert!(contract.get_assigned_tasks(accounts(2)).is_empty());
+        
+        // A preferred node that cannot serve the task is ignored
+        let context = get_context(accounts(4), task_cost + STORAGE_COST);
+        testing_env!(context.build());
+        contract.submit_task(
+            r#"{"model": "gpt2", "input": "Hello", "preferred_node": "unknown.near"}"#.to_string(),
+            task_cost.into(),
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        assert_eq!(contract.get_assigned_tasks(accounts(2)).len(), 1);
+    }
+    
+    #[test]
+    fn test_storage_calls_emit_audit_events() {
+        let context = get_context(accounts(1), 0);
+        testing_env!(context.build());
+        
+        let mut contract = DeAICompute::new(accounts(1));
+        register_storage(&mut contract, accounts(2));
+        
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs.iter().any(|log| log.contains("deai-audit") && log.contains("\"method\":\"storage_deposit\"")));
+        
+        let context = get_context(accounts(2), ONE_YOCTO);
+        testing_env!(context.build());
+        assert!(contract.storage_unregister(None));
+        
+        let logs = near_sdk::test_utils::get_logs();
+        assert!(logs.iter().any(|log| log.contains("deai-audit") && log.contains("\"method\":\"storage_unregister\"")));
+    }
+}
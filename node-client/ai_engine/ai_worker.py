@@ -39,6 +39,10 @@ class AIWorker:
         self.models_cache = Path(config.get('models_cache_dir', './models_cache'))
         self.models_cache.mkdir(exist_ok=True)
         
+        # Loaded models and pipelines, kept for the life of the process
+        self._models: Dict[str, Tuple[Any, Any]] = {}
+        self._pipelines: Dict[Tuple[str, str], Any] = {}
+        
         # Initialize Hugging Face
         hf_token = config.get('huggingface_token')
         if hf_token:
@@ -156,12 +160,7 @@ class AIWorker:
     
     async def _run_text_generation(self, model_name: str, input_data: str, parameters: Dict) -> str:
         """Run text generation"""
-        generator = pipeline(
-            "text-generation",
-            model=model_name,
-            device=0 if torch.cuda.is_available() else -1,
-            cache_dir=str(self.models_cache)
-        )
+        generator = self._get_pipeline("text-generation", model_name)
         
         max_length = parameters.get('max_length', 100)
         temperature = parameters.get('temperature', 0.7)
@@ -178,12 +177,7 @@ class AIWorker:
     
     async def _run_classification(self, model_name: str, input_data: str, parameters: Dict) -> Dict:
         """Run text classification"""
-        classifier = pipeline(
-            "text-classification",
-            model=model_name,
-            device=0 if torch.cuda.is_available() else -1,
-            cache_dir=str(self.models_cache)
-        )
+        classifier = self._get_pipeline("text-classification", model_name)
         
         result = classifier(input_data)
         return result
@@ -200,8 +194,26 @@ class AIWorker:
         
         return embeddings.squeeze().tolist()
     
+    def _get_pipeline(self, task: str, model_name: str) -> Any:
+        """Build a pipeline once and reuse it for later tasks"""
+        key = (task, model_name)
+        if key not in self._pipelines:
+            self._pipelines[key] = pipeline(
+                task,
+                model=model_name,
+                device=0 if torch.cuda.is_available() else -1,
+                cache_dir=str(self.models_cache)
+            )
+        return self._pipelines[key]
+    
     async def _load_model(self, model_name: str) -> Tuple[Any, Any]:
-        """Load model and tokenizer"""
+        """Load model and tokenizer, reusing ones already in memory"""
+        if model_name not in self._models:
+            self._models[model_name] = self._load_model_uncached(model_name)
+        return self._models[model_name]
+    
+    def _load_model_uncached(self, model_name: str) -> Tuple[Any, Any]:
+        """Load model and tokenizer from the Hugging Face cache"""
         cache_dir = self.models_cache / model_name.replace('/', '_')
         
         try:
@@ -278,10 +290,44 @@ class AIWorker:
         
         return info
 
+def serve():
+    """
+    Run as a persistent worker for the node client.
+    
+    The first line on stdin is the worker config. Every following line is a
+    request {"id": ..., "task": {...}}, answered with one line
+    {"id": ..., "proof_hash": ..., "output": ...} on stdout. Models stay
+    loaded between requests.
+    """
+    import asyncio
+    
+    protocol_out = sys.stdout
+    # Anything libraries print must not corrupt the protocol stream
+    sys.stdout = sys.stderr
+    
+    config = json.loads(sys.stdin.readline())
+    worker = AIWorker(config)
+    loop = asyncio.new_event_loop()
+    
+    for line in sys.stdin:
+        if not line.strip():
+            continue
+        
+        request = json.loads(line)
+        proof_hash, output = loop.run_until_complete(worker.execute_task(request['task']))
+        
+        response = {'id': request['id'], 'proof_hash': proof_hash, 'output': output}
+        protocol_out.write(json.dumps(response) + '\n')
+        protocol_out.flush()
+
 def main():
     """Main entry point for standalone execution"""
+    if len(sys.argv) == 2 and sys.argv[1] == '--serve':
+        serve()
+        return
+    
     if len(sys.argv) != 2:
-        print("Usage: python ai_worker.py <task_json>")
+        print("Usage: python ai_worker.py <task_json> | --serve")
         sys.exit(1)
     
     task_json = sys.argv[1]
@@ -1,11 +1,9 @@
 use anyhow::{Result, Context};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::path::PathBuf;
-use std::process::Stdio;
-use tokio::process::Command;
-use log::{info, warn, error, debug};
-use crate::config::NodeConfig;
+use log::{info, warn, debug};
+use crate::backend::{create_backend, supported_task_types, Backend};
+use crate::config::{BackendKind, NodeConfig};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TaskExecution {
@@ -18,34 +16,23 @@ pub struct TaskDescription {
     pub model: String,
     pub input: String,
     pub task_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub parameters: Option<Value>,
 }
 
 pub struct AiEngine {
     config: NodeConfig,
-    python_path: PathBuf,
-    ai_worker_path: PathBuf,
+    backend: Box<dyn Backend>,
 }
 
 impl AiEngine {
     pub fn new(config: &NodeConfig) -> Result<Self> {
-        let python_path = PathBuf::from(&config.ai.python_path);
-        let ai_worker_path = PathBuf::from("ai_engine/ai_worker.py");
-        
-        // Verify Python exists
-        if !python_path.exists() {
-            anyhow::bail!("Python path does not exist: {}", python_path.display());
-        }
-        
-        // Verify AI worker script exists
-        if !ai_worker_path.exists() {
-            anyhow::bail!("AI worker script not found: {}", ai_worker_path.display());
-        }
+        let backend = create_backend(config)?;
+        info!("Using {} inference backend", backend.name());
         
         Ok(Self {
             config: config.clone(),
-            python_path,
-            ai_worker_path,
+            backend,
         })
     }
     
@@ -61,66 +48,26 @@ impl AiEngine {
         // Validate task
         self.validate_task(&task_desc)?;
         
-        // Prepare task data for Python worker
-        let task_data = serde_json::json!({
-            "description": task_description,
-            "config": {
-                "models_cache_dir": self.config.ai.models_cache_dir,
-                "huggingface_token": self.config.ai.huggingface_token,
-                "node_id": self.config.node.account_id
-            }
-        });
-        
-        // Execute Python AI worker
-        let result = self.run_python_worker(&task_data).await?;
+        let result = self.backend.execute(&task_desc).await?;
         
         info!("AI task completed successfully");
         Ok(result)
     }
     
-    async fn run_python_worker(&self, task_data: &Value) -> Result<TaskExecution> {
-        let task_json = serde_json::to_string(task_data)?;
-        
-        debug!("Running Python worker with task data");
-        
-        let mut cmd = Command::new(&self.python_path);
-        cmd.arg(&self.ai_worker_path)
-            .arg(&task_json)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-        
-        let output = cmd.output().await
-            .context("Failed to execute Python AI worker")?;
-        
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            error!("Python worker failed: {}", stderr);
-            anyhow::bail!("Python worker failed: {}", stderr);
-        }
-        
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        debug!("Python worker output: {}", stdout);
-        
-        let result: TaskExecution = serde_json::from_str(&stdout)
-            .context("Failed to parse Python worker output")?;
-        
-        Ok(result)
-    }
-    
     fn validate_task(&self, task: &TaskDescription) -> Result<()> {
         // Check if model is reasonable size
         if task.model.is_empty() {
             anyhow::bail!("Model name cannot be empty");
         }
         
-        // Check if task type is supported
-        let supported_types = ["inference", "text_generation", "classification", "embedding"];
-        if !supported_types.contains(&task.task_type.as_str()) {
+        // Check if the configured backend can run this task type
+        if !supported_task_types(&self.config.ai.backend).contains(&task.task_type.as_str()) {
             anyhow::bail!("Unsupported task type: {}", task.task_type);
         }
         
         // Check if framework is supported
-        if !self.config.ai.supported_frameworks.contains(&"pytorch".to_string()) &&
+        if self.config.ai.backend == BackendKind::Python &&
+           !self.config.ai.supported_frameworks.contains(&"pytorch".to_string()) &&
            !self.config.ai.supported_frameworks.contains(&"transformers".to_string()) {
             anyhow::bail!("No supported AI frameworks configured");
         }
@@ -136,57 +83,12 @@ impl AiEngine {
     pub async fn check_environment(&self) -> Result<()> {
         info!("Checking AI environment...");
         
-        // Check Python version
-        let mut cmd = Command::new(&self.python_path);
-        cmd.arg("--version")
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-        
-        let output = cmd.output().await
-            .context("Failed to check Python version")?;
-        
-        if !output.status.success() {
-            anyhow::bail!("Python check failed");
-        }
-        
-        let version = String::from_utf8_lossy(&output.stdout);
-        info!("Python version: {}", version.trim());
-        
-        // Check required packages
-        self.check_python_packages().await?;
+        self.backend.check().await?;
         
         info!("AI environment check completed successfully");
         Ok(())
     }
     
-    async fn check_python_packages(&self) -> Result<()> {
-        let required_packages = vec![
-            "torch",
-            "transformers",
-            "huggingface_hub",
-            "numpy",
-        ];
-        
-        for package in required_packages {
-            let mut cmd = Command::new(&self.python_path);
-            cmd.arg("-c")
-                .arg(format!("import {}", package))
-                .stdout(Stdio::null())
-                .stderr(Stdio::piped());
-            
-            let output = cmd.output().await
-                .context(format!("Failed to check package: {}", package))?;
-            
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                anyhow::bail!("Missing required package '{}': {}", package, stderr);
-            }
-        }
-        
-        info!("All required Python packages are available");
-        Ok(())
-    }
-    
     pub async fn test_simple_task(&self) -> Result<()> {
         info!("Running simple AI task test...");
         
@@ -0,0 +1,324 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use log::{debug, info};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::process::Command;
+use crate::ai_engine::{TaskDescription, TaskExecution};
+use crate::config::{BackendKind, NodeConfig};
+use crate::worker_pool::WorkerPool;
+
+const PYTHON_TASK_TYPES: &[&str] = &["inference", "text_generation", "classification", "embedding"];
+// An OpenAI-compatible server has no classification endpoint
+const HTTP_TASK_TYPES: &[&str] = &["inference", "text_generation", "embedding"];
+
+/// Runs AI tasks. Selected by `ai.backend`.
+#[async_trait]
+pub trait Backend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Check the backend is usable before the node starts taking tasks.
+    async fn check(&self) -> Result<()>;
+
+    async fn execute(&self, task: &TaskDescription) -> Result<TaskExecution>;
+}
+
+pub fn create_backend(config: &NodeConfig) -> Result<Box<dyn Backend>> {
+    match config.ai.backend {
+        BackendKind::Python => Ok(Box::new(PythonBackend::new(config)?)),
+        BackendKind::Http => Ok(Box::new(HttpBackend::new(config)?)),
+    }
+}
+
+/// Task types the configured backend can run; others are declined.
+pub fn supported_task_types(kind: &BackendKind) -> &'static [&'static str] {
+    match kind {
+        BackendKind::Python => PYTHON_TASK_TYPES,
+        BackendKind::Http => HTTP_TASK_TYPES,
+    }
+}
+
+/// Runs tasks on the pool of `ai_worker.py` processes with torch/transformers.
+pub struct PythonBackend {
+    python_path: PathBuf,
+    workers: WorkerPool,
+}
+
+impl PythonBackend {
+    pub fn new(config: &NodeConfig) -> Result<Self> {
+        let python_path = PathBuf::from(&config.ai.python_path);
+        let ai_worker_path = PathBuf::from("ai_engine/ai_worker.py");
+
+        // Verify Python exists
+        if !python_path.exists() {
+            anyhow::bail!("Python path does not exist: {}", python_path.display());
+        }
+
+        // Verify AI worker script exists
+        if !ai_worker_path.exists() {
+            anyhow::bail!("AI worker script not found: {}", ai_worker_path.display());
+        }
+
+        let worker_config = json!({
+            "models_cache_dir": config.ai.models_cache_dir,
+            "huggingface_token": config.ai.huggingface_token,
+            "node_id": config.node.account_id
+        });
+
+        // One resident worker per task slot, so loaded models are reused
+        let workers = WorkerPool::new(
+            python_path.clone(),
+            ai_worker_path,
+            worker_config,
+            config.hardware.max_concurrent_tasks as usize,
+            Duration::from_secs(config.ai.task_timeout_seconds),
+        );
+
+        Ok(Self { python_path, workers })
+    }
+
+    async fn check_python_packages(&self) -> Result<()> {
+        let required_packages = vec![
+            "torch",
+            "transformers",
+            "huggingface_hub",
+            "numpy",
+        ];
+
+        for package in required_packages {
+            let mut cmd = Command::new(&self.python_path);
+            cmd.arg("-c")
+                .arg(format!("import {}", package))
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped());
+
+            let output = cmd.output().await
+                .context(format!("Failed to check package: {}", package))?;
+
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                anyhow::bail!("Missing required package '{}': {}", package, stderr);
+            }
+        }
+
+        info!("All required Python packages are available");
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Backend for PythonBackend {
+    fn name(&self) -> &'static str {
+        "python"
+    }
+
+    async fn check(&self) -> Result<()> {
+        // Check Python version
+        let mut cmd = Command::new(&self.python_path);
+        cmd.arg("--version")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let output = cmd.output().await
+            .context("Failed to check Python version")?;
+
+        if !output.status.success() {
+            anyhow::bail!("Python check failed");
+        }
+
+        let version = String::from_utf8_lossy(&output.stdout);
+        info!("Python version: {}", version.trim());
+
+        // Check required packages
+        self.check_python_packages().await
+    }
+
+    async fn execute(&self, task: &TaskDescription) -> Result<TaskExecution> {
+        let task_data = json!({ "description": serde_json::to_string(task)? });
+
+        // Run on a pooled Python worker
+        self.workers.execute(&task_data).await
+            .context("Python AI worker failed")
+    }
+}
+
+/// Forwards tasks to a local OpenAI-compatible inference server such as
+/// vLLM or llama.cpp, so no Python dependencies are needed on the node.
+pub struct HttpBackend {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    node_id: String,
+}
+
+impl HttpBackend {
+    pub fn new(config: &NodeConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.ai.task_timeout_seconds))
+            .build()
+            .context("Failed to build inference server client")?;
+
+        Ok(Self {
+            client,
+            base_url: config.ai.http_backend.base_url.trim_end_matches('/').to_string(),
+            api_key: config.ai.http_backend.api_key.clone(),
+            node_id: config.node.account_id.clone(),
+        })
+    }
+
+    async fn post(&self, path: &str, body: &Value) -> Result<Value> {
+        let mut request = self.client.post(format!("{}{}", self.base_url, path)).json(body);
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let response = request.send().await
+            .with_context(|| format!("Failed to reach inference server at {}", self.base_url))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Inference server returned {}: {}", status, body);
+        }
+
+        response.json().await.context("Invalid response from inference server")
+    }
+}
+
+#[async_trait]
+impl Backend for HttpBackend {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+
+    async fn check(&self) -> Result<()> {
+        let mut request = self.client.get(format!("{}/models", self.base_url));
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let response = request.send().await
+            .with_context(|| format!("Failed to reach inference server at {}", self.base_url))?;
+        if !response.status().is_success() {
+            anyhow::bail!("Inference server health check returned {}", response.status());
+        }
+
+        info!("Inference server reachable at {}", self.base_url);
+        Ok(())
+    }
+
+    async fn execute(&self, task: &TaskDescription) -> Result<TaskExecution> {
+        let start_time = Instant::now();
+        let (path, body) = http_request(task)?;
+
+        debug!("Forwarding {} task to {}{}", task.task_type, self.base_url, path);
+        let response = self.post(path, &body).await?;
+        let result = http_result(&task.task_type, &response)?;
+
+        // Same output shape and proof as the Python worker
+        let timestamp = unix_now();
+        let output = json!({
+            "result": result,
+            "execution_time": start_time.elapsed().as_secs_f64(),
+            "model": task.model,
+            "task_type": task.task_type,
+            "timestamp": timestamp,
+            "backend": self.name(),
+        })
+        .to_string();
+
+        Ok(TaskExecution {
+            proof_hash: proof_hash(&output, timestamp, &self.node_id),
+            output,
+        })
+    }
+}
+
+fn http_request(task: &TaskDescription) -> Result<(&'static str, Value)> {
+    let parameters = task.parameters.clone().unwrap_or_else(|| json!({}));
+
+    match task.task_type.as_str() {
+        "inference" | "text_generation" => Ok(("/completions", json!({
+            "model": task.model,
+            "prompt": task.input,
+            "max_tokens": parameters.get("max_length").and_then(Value::as_u64).unwrap_or(100),
+            "temperature": parameters.get("temperature").and_then(Value::as_f64).unwrap_or(0.7),
+        }))),
+        "embedding" => Ok(("/embeddings", json!({
+            "model": task.model,
+            "input": task.input,
+        }))),
+        other => anyhow::bail!("Task type {} is not supported by the http backend", other),
+    }
+}
+
+fn http_result(task_type: &str, response: &Value) -> Result<Value> {
+    let result = match task_type {
+        "embedding" => response.pointer("/data/0/embedding"),
+        _ => response.pointer("/choices/0/text"),
+    };
+
+    result.cloned().context("Inference server response is missing its result")
+}
+
+fn proof_hash(output: &str, timestamp: u64, node_id: &str) -> String {
+    hex::encode(Sha256::digest(format!("{}:{}:{}", output, timestamp, node_id)))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_task(task_type: &str) -> TaskDescription {
+        TaskDescription {
+            model: "meta-llama/Llama-2-7b-chat-hf".to_string(),
+            input: "Hello".to_string(),
+            task_type: task_type.to_string(),
+            parameters: Some(json!({ "max_length": 32 })),
+        }
+    }
+
+    #[test]
+    fn test_http_request_routes_by_task_type() {
+        let (path, body) = http_request(&create_task("text_generation")).unwrap();
+        assert_eq!(path, "/completions");
+        assert_eq!(body["prompt"], "Hello");
+        assert_eq!(body["max_tokens"], 32);
+
+        let (path, body) = http_request(&create_task("embedding")).unwrap();
+        assert_eq!(path, "/embeddings");
+        assert_eq!(body["input"], "Hello");
+
+        assert!(http_request(&create_task("classification")).is_err());
+    }
+
+    #[test]
+    fn test_http_result_extraction() {
+        let completion = json!({ "choices": [{ "text": " world" }] });
+        assert_eq!(http_result("text_generation", &completion).unwrap(), " world");
+
+        let embedding = json!({ "data": [{ "embedding": [0.1, 0.2] }] });
+        assert_eq!(http_result("embedding", &embedding).unwrap(), json!([0.1, 0.2]));
+
+        assert!(http_result("text_generation", &json!({})).is_err());
+    }
+
+    #[test]
+    fn test_proof_hash_is_hex_sha256() {
+        let hash = proof_hash(r#"{"result":"ok"}"#, 1700000000, "node.testnet");
+
+        assert_eq!(hash.len(), 64);
+        assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(hash, proof_hash(r#"{"result":"ok"}"#, 1700000000, "node.testnet"));
+    }
+}
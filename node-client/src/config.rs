@@ -9,6 +9,8 @@ pub struct NodeConfig {
     pub near: NearConfig,
     pub ai: AiConfig,
     pub hardware: HardwareConfig,
+    #[serde(default)]
+    pub submission: SubmissionConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +20,35 @@ pub struct NodeSettings {
     pub public_ip: String,
     pub api_port: u16,
     pub stake_amount: String, // In NEAR tokens
+    #[serde(default = "default_poll_interval_seconds")]
+    pub poll_interval_seconds: u64, // How often assigned tasks are fetched
+    #[serde(default)]
+    pub auto_topup: AutoTopupConfig,
+}
+
+fn default_poll_interval_seconds() -> u64 {
+    10
+}
+
+/// Top up the stake from the account balance when slashing pushes the
+/// effective stake below `threshold`. Amounts are in NEAR tokens.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoTopupConfig {
+    pub enabled: bool,
+    pub threshold: String,
+    pub amount: String,      // Added per top-up
+    pub max_per_day: String, // Cap over any 24 hours
+}
+
+impl Default for AutoTopupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: "1.0".to_string(),
+            amount: "0.5".to_string(),
+            max_per_day: "1.0".to_string(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +67,39 @@ pub struct AiConfig {
     pub max_model_size_gb: u64,
     pub huggingface_token: Option<String>,
     pub supported_frameworks: Vec<String>,
+    #[serde(default = "default_task_timeout_seconds")]
+    pub task_timeout_seconds: u64,
+    #[serde(default)]
+    pub backend: BackendKind,
+    #[serde(default)]
+    pub http_backend: HttpBackendConfig,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    #[default]
+    Python, // Pool of ai_worker.py processes
+    Http,   // OpenAI-compatible inference server
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpBackendConfig {
+    pub base_url: String, // e.g. a local vLLM or llama.cpp server's /v1
+    pub api_key: Option<String>,
+}
+
+impl Default for HttpBackendConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "http://127.0.0.1:8000/v1".to_string(),
+            api_key: None,
+        }
+    }
+}
+
+fn default_task_timeout_seconds() -> u64 {
+    300
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,18 +109,48 @@ pub struct HardwareConfig {
     pub memory_gb: u64,
     pub storage_gb: u64,
     pub max_concurrent_tasks: u32,
+    #[serde(default)]
+    pub report_load_in_heartbeat: bool, // Attach GPU and task load to heartbeats
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmissionConfig {
+    pub batch_enabled: bool,
+    pub batch_max_size: usize,
+    pub batch_max_wait_ms: u64,
+    #[serde(default = "default_pending_results_path")]
+    pub pending_results_path: String, // Results that failed to submit, replayed on startup
+}
+
+fn default_pending_results_path() -> String {
+    "./pending_results.json".to_string()
+}
+
+impl Default for SubmissionConfig {
+    fn default() -> Self {
+        Self {
+            batch_enabled: false,
+            batch_max_size: 10,
+            batch_max_wait_ms: 5000,
+            pending_results_path: default_pending_results_path(),
+        }
+    }
 }
 
 impl NodeConfig {
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let config = Self::parse(path)?;
+        config.validate()?;
+        Ok(config)
+    }
+    
+    /// Read the config without validating it, for reporting problems step by step.
+    pub fn parse<P: AsRef<Path>>(path: P) -> Result<Self> {
         let content = fs::read_to_string(&path)
             .with_context(|| format!("Failed to read config file: {}", path.as_ref().display()))?;
         
-        let config: NodeConfig = toml::from_str(&content)
-            .with_context(|| "Failed to parse config file")?;
-        
-        config.validate()?;
-        Ok(config)
+        toml::from_str(&content)
+            .with_context(|| "Failed to parse config file")
     }
     
     pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
@@ -75,7 +169,7 @@ impl NodeConfig {
         Ok(config)
     }
     
-    fn validate(&self) -> Result<()> {
+    pub fn validate(&self) -> Result<()> {
         if self.node.account_id.is_empty() {
             anyhow::bail!("Node account_id cannot be empty");
         }
@@ -92,10 +186,38 @@ impl NodeConfig {
             anyhow::bail!("Contract account_id cannot be empty");
         }
         
-        if self.ai.python_path.is_empty() {
+        if self.ai.backend == BackendKind::Http
+            && !(self.ai.http_backend.base_url.starts_with("http://") || self.ai.http_backend.base_url.starts_with("https://")) {
+            anyhow::bail!("AI http_backend base_url must be an http(s) URL");
+        }
+        
+        if self.ai.backend == BackendKind::Python && self.ai.python_path.is_empty() {
             anyhow::bail!("Python path cannot be empty");
         }
         
+        if self.node.poll_interval_seconds == 0 {
+            anyhow::bail!("Node poll_interval_seconds must be greater than zero");
+        }
+        
+        if self.ai.task_timeout_seconds == 0 {
+            anyhow::bail!("AI task_timeout_seconds must be greater than zero");
+        }
+        
+        if self.node.auto_topup.enabled {
+            let topup = &self.node.auto_topup;
+            crate::stake_topup::near_to_yocto(&topup.threshold).context("Invalid auto_topup threshold")?;
+            let amount = crate::stake_topup::near_to_yocto(&topup.amount).context("Invalid auto_topup amount")?;
+            let max_per_day = crate::stake_topup::near_to_yocto(&topup.max_per_day).context("Invalid auto_topup max_per_day")?;
+            
+            if amount == 0 || max_per_day < amount {
+                anyhow::bail!("auto_topup amount must be positive and no larger than max_per_day");
+            }
+        }
+        
+        if self.submission.batch_enabled && (self.submission.batch_max_size == 0 || self.submission.batch_max_size > 20) {
+            anyhow::bail!("Submission batch_max_size must be between 1 and 20");
+        }
+        
         Ok(())
     }
 }
@@ -109,6 +231,8 @@ impl Default for NodeConfig {
                 public_ip: "127.0.0.1".to_string(),
                 api_port: 8080,
                 stake_amount: "1.0".to_string(),
+                poll_interval_seconds: default_poll_interval_seconds(),
+                auto_topup: AutoTopupConfig::default(),
             },
             near: NearConfig {
                 network_id: "testnet".to_string(),
@@ -127,6 +251,9 @@ impl Default for NodeConfig {
                     "tensorflow".to_string(),
                     "transformers".to_string(),
                 ],
+                task_timeout_seconds: default_task_timeout_seconds(),
+                backend: BackendKind::default(),
+                http_backend: HttpBackendConfig::default(),
             },
             hardware: HardwareConfig {
                 gpu_specs: "NVIDIA RTX 4090".to_string(),
@@ -134,7 +261,9 @@ impl Default for NodeConfig {
                 memory_gb: 32,
                 storage_gb: 1000,
                 max_concurrent_tasks: 2,
+                report_load_in_heartbeat: false,
             },
+            submission: SubmissionConfig::default(),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,158 @@
+use anyhow::{Context, Result};
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use log::{info, warn};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use crate::heartbeat::{HealthStatus, HeartbeatManager};
+
+#[derive(Clone)]
+struct HealthState {
+    heartbeat_manager: Arc<HeartbeatManager>,
+    task_permits: Arc<Semaphore>,
+    max_concurrent_tasks: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct ReadinessReport {
+    ready: bool,
+    #[serde(flatten)]
+    health: Option<HealthStatus>,
+    issues: Vec<String>,
+    active_tasks: usize,
+    max_concurrent_tasks: usize,
+}
+
+/// Liveness and readiness probes for systemd, Kubernetes and load balancers,
+/// served on `node.api_port` so the registered `api_endpoint` answers.
+pub struct HealthServer {
+    addr: SocketAddr,
+    state: HealthState,
+}
+
+impl HealthServer {
+    pub fn new(
+        port: u16,
+        heartbeat_manager: Arc<HeartbeatManager>,
+        task_permits: Arc<Semaphore>,
+        max_concurrent_tasks: usize,
+    ) -> Self {
+        Self {
+            addr: SocketAddr::from(([0, 0, 0, 0], port)),
+            state: HealthState {
+                heartbeat_manager,
+                task_permits,
+                max_concurrent_tasks,
+            },
+        }
+    }
+
+    pub async fn start(self) -> Result<()> {
+        let listener = tokio::net::TcpListener::bind(self.addr).await
+            .with_context(|| format!("Failed to bind health server to {}", self.addr))?;
+
+        info!("Health server listening on {}", self.addr);
+
+        axum::serve(listener, router(self.state)).await
+            .context("Health server failed")
+    }
+}
+
+fn router(state: HealthState) -> Router {
+    Router::new()
+        .route("/health", get(liveness))
+        .route("/ready", get(readiness))
+        .with_state(state)
+}
+
+// The process is up and serving; chain state is left to readiness
+async fn liveness() -> Json<Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+async fn readiness(State(state): State<HealthState>) -> (StatusCode, Json<ReadinessReport>) {
+    let active_tasks = state.max_concurrent_tasks.saturating_sub(state.task_permits.available_permits());
+
+    let health = match state.heartbeat_manager.health_check().await {
+        Ok(health) => Some(health),
+        Err(e) => {
+            warn!("Readiness health check failed: {:#}", e);
+            None
+        }
+    };
+
+    let report = readiness_report(health, active_tasks, state.max_concurrent_tasks);
+    let status = if report.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+
+    (status, Json(report))
+}
+
+// Ready means healthy on-chain with a free slot for another task
+fn readiness_report(health: Option<HealthStatus>, active_tasks: usize, max_concurrent_tasks: usize) -> ReadinessReport {
+    let mut issues = match &health {
+        Some(health) => health.get_issues(),
+        None => vec!["Near RPC unreachable".to_string()],
+    };
+
+    let at_capacity = active_tasks >= max_concurrent_tasks;
+    if at_capacity {
+        issues.push(format!("At capacity: {}/{} tasks running", active_tasks, max_concurrent_tasks));
+    }
+
+    ReadinessReport {
+        ready: health.as_ref().map_or(false, HealthStatus::is_healthy) && !at_capacity,
+        health,
+        issues,
+        active_tasks,
+        max_concurrent_tasks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn healthy_status() -> HealthStatus {
+        HealthStatus {
+            is_registered: true,
+            is_active: true,
+            network_latency: Duration::from_millis(120),
+            last_heartbeat_age_seconds: 30,
+            reputation_score: 100,
+            total_tasks_completed: 4,
+            current_stake: "1000000000000000000000000".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_healthy_node_with_capacity_is_ready() {
+        let report = readiness_report(Some(healthy_status()), 1, 2);
+
+        assert!(report.ready);
+        assert!(report.issues.is_empty());
+
+        let body = serde_json::to_value(&report).unwrap();
+        assert_eq!(body["is_registered"], true);
+        assert_eq!(body["network_latency_ms"], 120);
+        assert_eq!(body["active_tasks"], 1);
+    }
+
+    #[test]
+    fn test_node_at_capacity_is_not_ready() {
+        let report = readiness_report(Some(healthy_status()), 2, 2);
+
+        assert!(!report.ready);
+        assert!(report.issues.iter().any(|i| i.contains("At capacity")));
+    }
+
+    #[test]
+    fn test_unreachable_rpc_is_not_ready() {
+        let report = readiness_report(None, 0, 2);
+
+        assert!(!report.ready);
+        assert!(report.issues.iter().any(|i| i.contains("unreachable")));
+    }
+}
@@ -1,13 +1,25 @@
 use anyhow::{Result, Context};
-use tokio::time::{interval, Duration, Instant};
+use tokio::time::{interval, interval_at, Duration, Instant};
 use log::{info, warn, error, debug};
+use serde::{Serialize, Serializer};
 use std::sync::Arc;
 use crate::near_client::NearClient;
+use crate::resource_monitor::ResourceMonitor;
+use crate::stake_topup::StakeTopUp;
+
+// Matches the contract: a node without a heartbeat this long stops getting tasks
+const HEARTBEAT_TIMEOUT_SECONDS: u64 = 300;
+// Consecutive fresh heartbeats before the interval is lengthened
+const HEALTHY_CHECKS_BEFORE_BACKOFF: u32 = 3;
 
 pub struct HeartbeatManager {
     near_client: Arc<NearClient>,
     interval_seconds: u64,
+    min_interval_seconds: u64,
+    max_interval_seconds: u64,
     max_retries: u32,
+    resource_monitor: Option<ResourceMonitor>,
+    stake_topup: Option<StakeTopUp>,
 }
 
 impl HeartbeatManager {
@@ -15,24 +27,53 @@ impl HeartbeatManager {
         Self {
             near_client,
             interval_seconds: 60, // 1 minute intervals
+            min_interval_seconds: 15,
+            max_interval_seconds: 120,
             max_retries: 3,
+            resource_monitor: None,
+            stake_topup: None,
         }
     }
     
+    /// Initial interval; it adapts to the on-chain heartbeat age from there.
     pub fn with_interval(mut self, seconds: u64) -> Self {
         self.interval_seconds = seconds;
         self
     }
     
+    pub fn with_interval_bounds(mut self, min_seconds: u64, max_seconds: u64) -> Self {
+        self.min_interval_seconds = min_seconds;
+        self.max_interval_seconds = max_seconds;
+        self
+    }
+    
     pub fn with_max_retries(mut self, retries: u32) -> Self {
         self.max_retries = retries;
         self
     }
     
+    /// Report live GPU and task load with every heartbeat.
+    pub fn with_resource_monitor(mut self, monitor: ResourceMonitor) -> Self {
+        self.resource_monitor = Some(monitor);
+        self
+    }
+    
+    /// Top up slashed stake whenever a health check finds it below the threshold.
+    pub fn with_stake_topup(mut self, stake_topup: StakeTopUp) -> Self {
+        self.stake_topup = Some(stake_topup);
+        self
+    }
+    
     pub async fn start(&self) {
-        info!("Starting heartbeat manager with {} second intervals", self.interval_seconds);
+        info!("Starting heartbeat manager with {} second intervals ({}-{}s adaptive)",
+              self.interval_seconds, self.min_interval_seconds, self.max_interval_seconds);
         
-        let mut interval = interval(Duration::from_secs(self.interval_seconds));
+        let mut adaptive = AdaptiveInterval::new(
+            self.interval_seconds,
+            self.min_interval_seconds,
+            self.max_interval_seconds,
+        );
+        let mut interval = interval(adaptive.current());
         let mut consecutive_failures = 0u32;
         let mut last_success = Instant::now();
         
@@ -48,6 +89,20 @@ impl HeartbeatManager {
                         debug!("Heartbeat sent successfully");
                     }
                     last_success = Instant::now();
+                    
+                    // The age the contract sees shows whether heartbeats land in time
+                    match self.health_check().await {
+                        Ok(health) => {
+                            let previous = adaptive.current();
+                            let next = adaptive.observe(health.last_heartbeat_age_seconds);
+                            if next != previous {
+                                info!("Heartbeat interval adjusted from {:?} to {:?} (heartbeat age {}s)",
+                                      previous, next, health.last_heartbeat_age_seconds);
+                                interval = interval_at(Instant::now() + next, next);
+                            }
+                        }
+                        Err(e) => debug!("Skipping heartbeat interval adjustment: {}", e),
+                    }
                 }
                 Err(e) => {
                     consecutive_failures += 1;
@@ -74,9 +129,14 @@ impl HeartbeatManager {
     async fn send_heartbeat(&self) -> Result<()> {
         debug!("Sending heartbeat to DeAI network");
         
+        let resources = match &self.resource_monitor {
+            Some(monitor) => Some(monitor.snapshot().await.heartbeat_payload()),
+            None => None,
+        };
+        
         let start_time = Instant::now();
         
-        let result = self.near_client.heartbeat().await
+        let result = self.near_client.heartbeat(resources).await
             .context("Failed to send heartbeat transaction")?;
         
         let duration = start_time.elapsed();
@@ -106,6 +166,12 @@ impl HeartbeatManager {
         
         let network_latency = start_time.elapsed();
         
+        if let (Some(info), Some(stake_topup)) = (&node_info, &self.stake_topup) {
+            if let Err(e) = stake_topup.check(info).await {
+                error!("Automatic stake top-up failed: {:#}", e);
+            }
+        }
+        
         let status = if let Some(info) = node_info {
             let current_time = std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
@@ -140,10 +206,59 @@ impl HeartbeatManager {
     }
 }
 
+/// Heartbeat interval that shortens when heartbeats land late, so the node
+/// isn't dropped on a congested RPC, and lengthens while they land promptly
+/// to save gas. Always kept within `[min, max]`.
 #[derive(Debug)]
+struct AdaptiveInterval {
+    current: Duration,
+    min: Duration,
+    max: Duration,
+    healthy_streak: u32,
+}
+
+impl AdaptiveInterval {
+    fn new(initial_seconds: u64, min_seconds: u64, max_seconds: u64) -> Self {
+        let min = Duration::from_secs(min_seconds.max(1));
+        let max = Duration::from_secs(max_seconds).max(min);
+        
+        Self {
+            current: Duration::from_secs(initial_seconds).clamp(min, max),
+            min,
+            max,
+            healthy_streak: 0,
+        }
+    }
+    
+    fn current(&self) -> Duration {
+        self.current
+    }
+    
+    /// Feed the on-chain heartbeat age measured after a heartbeat was sent.
+    fn observe(&mut self, heartbeat_age_seconds: u64) -> Duration {
+        if heartbeat_age_seconds >= HEARTBEAT_TIMEOUT_SECONDS / 2 {
+            // Halfway to being considered offline
+            self.healthy_streak = 0;
+            self.current = (self.current / 2).max(self.min);
+        } else if heartbeat_age_seconds < HEARTBEAT_TIMEOUT_SECONDS / 4 {
+            self.healthy_streak += 1;
+            if self.healthy_streak >= HEALTHY_CHECKS_BEFORE_BACKOFF {
+                self.healthy_streak = 0;
+                self.current = self.current.mul_f64(1.5).min(self.max);
+            }
+        } else {
+            self.healthy_streak = 0;
+        }
+        
+        self.current
+    }
+}
+
+#[derive(Debug, Serialize)]
 pub struct HealthStatus {
     pub is_registered: bool,
     pub is_active: bool,
+    #[serde(rename = "network_latency_ms", serialize_with = "serialize_millis")]
     pub network_latency: Duration,
     pub last_heartbeat_age_seconds: u64,
     pub reputation_score: u32,
@@ -186,6 +301,10 @@ impl HealthStatus {
     }
 }
 
+fn serialize_millis<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_millis() as u64)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -237,4 +356,38 @@ mod tests {
         // assert_eq!(manager.interval_seconds, 60);
         // assert_eq!(manager.max_retries, 3);
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_interval_shortens_as_heartbeat_age_climbs() {
+        let mut adaptive = AdaptiveInterval::new(60, 15, 120);
+        
+        // Mock chain reporting heartbeats landing later and later
+        let intervals: Vec<u64> = [20, 90, 160, 220, 280]
+            .iter()
+            .map(|&age| adaptive.observe(age).as_secs())
+            .collect();
+        
+        assert_eq!(intervals, vec![60, 60, 30, 15, 15]);
+    }
+    
+    #[test]
+    fn test_interval_lengthens_when_consistently_healthy() {
+        let mut adaptive = AdaptiveInterval::new(60, 15, 120);
+        
+        for _ in 0..2 {
+            assert_eq!(adaptive.observe(5), Duration::from_secs(60));
+        }
+        assert_eq!(adaptive.observe(5), Duration::from_secs(90));
+        
+        for _ in 0..6 {
+            adaptive.observe(5);
+        }
+        assert_eq!(adaptive.current(), Duration::from_secs(120));
+    }
+    
+    #[test]
+    fn test_initial_interval_is_clamped_to_bounds() {
+        assert_eq!(AdaptiveInterval::new(600, 15, 120).current(), Duration::from_secs(120));
+        assert_eq!(AdaptiveInterval::new(5, 15, 120).current(), Duration::from_secs(15));
+    }
+}
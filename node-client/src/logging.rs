@@ -0,0 +1,84 @@
+use clap::ValueEnum;
+use tracing::{Span, Subscriber};
+use tracing_subscriber::{fmt::MakeWriter, util::SubscriberInitExt, EnvFilter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable lines
+    Text,
+    /// One JSON object per line, for log aggregators
+    Json,
+}
+
+/// Install the global logger. Both formats honour `RUST_LOG`.
+pub fn init(format: LogFormat) {
+    match format {
+        LogFormat::Text => env_logger::init(),
+        // Also forwards the crate's `log` records into tracing, so they carry
+        // the fields of the span they were logged in
+        LogFormat::Json => json_subscriber(EnvFilter::from_default_env(), std::io::stdout).init(),
+    }
+}
+
+fn json_subscriber<W>(filter: EnvFilter, make_writer: W) -> impl Subscriber + Send + Sync
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    tracing_subscriber::fmt()
+        .json()
+        .with_env_filter(filter)
+        .with_current_span(true)
+        .with_span_list(false)
+        .with_writer(make_writer)
+        .finish()
+}
+
+/// Span for everything done on behalf of one task, so its log lines share
+/// a `task_id` field. `tx_hash` is recorded once the result is submitted.
+pub fn task_span(task_id: u64) -> Span {
+    tracing::info_span!("task", task_id, tx_hash = tracing::field::Empty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_lines_carry_task_fields() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = json_subscriber(EnvFilter::new("info"), move || writer.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = task_span(42);
+            let _entered = span.enter();
+            span.record("tx_hash", "9xQe");
+            tracing::info!("Task completed");
+        });
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        let line: serde_json::Value = serde_json::from_str(output.lines().next().unwrap()).unwrap();
+
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["fields"]["message"], "Task completed");
+        assert_eq!(line["span"]["task_id"], 42);
+        assert_eq!(line["span"]["tx_hash"], "9xQe");
+        assert!(line["timestamp"].is_string());
+        assert!(line["target"].is_string());
+    }
+}
@@ -1,27 +1,69 @@
 use clap::{Parser, Subcommand};
 use anyhow::Result;
 use log::{info, error};
+use std::path::Path;
 
 mod config;
+mod logging;
 mod near_client;
+mod nonce_manager;
 mod node_daemon;
 mod ai_engine;
+mod backend;
 mod task_processor;
+mod task_input;
+mod task_runner;
 mod heartbeat;
+mod health_server;
+mod result_batcher;
+mod result_queue;
+mod stake_topup;
+mod resource_monitor;
+mod worker_pool;
 
+use ai_engine::AiEngine;
 use config::NodeConfig;
+use logging::LogFormat;
 use node_daemon::NodeDaemon;
+use task_processor::TaskProcessor;
 
 #[derive(Parser)]
 #[command(name = "deai-node")]
 #[command(about = "DeAI Node Client for distributed AI computation")]
 struct Cli {
+    /// Log output format
+    #[arg(long, global = true, value_enum, env = "DEAI_LOG_FORMAT", default_value = "text")]
+    log_format: LogFormat,
     #[command(subcommand)]
     command: Commands,
 }
 
 #[derive(Subcommand)]
 enum Commands {
+    /// Write a default configuration file to fill in
+    Init {
+        /// Node configuration file path
+        #[arg(short, long, default_value = "node_config.toml")]
+        config: String,
+        /// Overwrite an existing file
+        #[arg(long)]
+        force: bool,
+    },
+    /// Check a configuration file and the AI environment it describes
+    Validate {
+        /// Node configuration file path
+        #[arg(short, long, default_value = "node_config.toml")]
+        config: String,
+    },
+    /// Run a sample task locally to check the AI environment, without touching the chain
+    Test {
+        /// Node configuration file path
+        #[arg(short, long, default_value = "node_config.toml")]
+        config: String,
+        /// Model to run the sample task with
+        #[arg(short, long, default_value = "distilbert-base-uncased")]
+        model: String,
+    },
     /// Register node with the DeAI network
     Register {
         /// Node configuration file path
@@ -40,21 +82,61 @@ enum Commands {
         #[arg(short, long, default_value = "node_config.toml")]
         config: String,
     },
-    /// Deactivate and withdraw stake
+    /// Deactivate and start unbonding stake
     Deactivate {
         /// Node configuration file path
         #[arg(short, long, default_value = "node_config.toml")]
         config: String,
+        /// Hand back in-flight tasks instead of finishing them first
+        #[arg(long)]
+        force: bool,
+    },
+    /// Withdraw stake once the unbonding period has ended
+    ClaimStake {
+        /// Node configuration file path
+        #[arg(short, long, default_value = "node_config.toml")]
+        config: String,
+    },
+    /// Mint DEAI rewards accrued while auto-claim is off
+    ClaimRewards {
+        /// Node configuration file path
+        #[arg(short, long, default_value = "node_config.toml")]
+        config: String,
     },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    env_logger::init();
-    
     let cli = Cli::parse();
+    logging::init(cli.log_format);
     
     match cli.command {
+        Commands::Init { config, force } => {
+            if Path::new(&config).exists() && !force {
+                anyhow::bail!("{} already exists. Pass --force to overwrite it.", config);
+            }
+            NodeConfig::create_default(&config)?;
+            println!("Wrote default configuration to {}", config);
+            println!("Set account_id, private_key and public_ip, then run `validate`.");
+        }
+        Commands::Validate { config } => {
+            if !validate_config(&config).await {
+                anyhow::bail!("Configuration check failed");
+            }
+        }
+        Commands::Test { config, model } => {
+            info!("Running sample task with config: {}", config);
+            let node_config = NodeConfig::load(&config)?;
+            let processor = TaskProcessor::new(&node_config).await?;
+            
+            let start_time = std::time::Instant::now();
+            let (proof_hash, output) = processor.test_execution(&model).await?;
+            
+            println!("Sample task completed in {:.2?}", start_time.elapsed());
+            println!("  Model: {}", model);
+            println!("  Proof Hash: {}", proof_hash);
+            println!("  Output: {}", output);
+        }
         Commands::Register { config } => {
             info!("Registering node with config: {}", config);
             let node_config = NodeConfig::load(&config)?;
@@ -74,13 +156,61 @@ async fn main() -> Result<()> {
             let daemon = NodeDaemon::new(node_config).await?;
             daemon.status().await?;
         }
-        Commands::Deactivate { config } => {
+        Commands::Deactivate { config, force } => {
             info!("Deactivating node with config: {}", config);
             let node_config = NodeConfig::load(&config)?;
             let daemon = NodeDaemon::new(node_config).await?;
-            daemon.deactivate().await?;
+            daemon.deactivate(force).await?;
+        }
+        Commands::ClaimStake { config } => {
+            info!("Claiming unbonded stake with config: {}", config);
+            let node_config = NodeConfig::load(&config)?;
+            let daemon = NodeDaemon::new(node_config).await?;
+            daemon.claim_stake().await?;
+        }
+        Commands::ClaimRewards { config } => {
+            info!("Claiming pending rewards with config: {}", config);
+            let node_config = NodeConfig::load(&config)?;
+            let daemon = NodeDaemon::new(node_config).await?;
+            daemon.claim_rewards().await?;
         }
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+// Prints a pass/fail line per check; stops at the first failure since later
+// checks depend on earlier ones
+async fn validate_config(path: &str) -> bool {
+    println!("Checking {}", path);
+    
+    let report = |check: &str, result: &Result<()>| match result {
+        Ok(()) => println!("  [PASS] {}", check),
+        Err(e) => println!("  [FAIL] {}: {:#}", check, e),
+    };
+    
+    let node_config = match NodeConfig::parse(path) {
+        Ok(node_config) => {
+            report("Parse configuration", &Ok(()));
+            node_config
+        }
+        Err(e) => {
+            report("Parse configuration", &Err(e));
+            return false;
+        }
+    };
+    
+    let validation = node_config.validate();
+    report("Validate settings", &validation);
+    if validation.is_err() {
+        return false;
+    }
+    
+    let environment = match AiEngine::new(&node_config) {
+        Ok(engine) => engine.check_environment().await,
+        Err(e) => Err(e),
+    };
+    report("AI environment", &environment);
+    
+    environment.is_ok()
+}
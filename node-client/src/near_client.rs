@@ -12,14 +12,27 @@ use serde_json::{Value, json};
 use log::{info, warn, error, debug};
 use std::str::FromStr;
 use crate::config::NodeConfig;
+use crate::nonce_manager::{is_invalid_nonce, NonceManager};
+use crate::result_batcher::PendingResult;
+
+// Attempts per transaction when the chain rejects its nonce
+const MAX_NONCE_ATTEMPTS: u32 = 3;
+// Longest output `submit_result` takes; longer ones are streamed in chunks
+pub const MAX_RESULT_OUTPUT_LEN: usize = 10_000;
+pub const MAX_CHUNKED_OUTPUT_LEN: usize = 100_000;
+const RESULT_CHUNK_LEN: usize = 10_000;
+// Chunks pay for their storage; the contract refunds what it doesn't use
+const STORAGE_BYTE_COST: Balance = 10_000_000_000_000_000_000; // yoctoNEAR per byte
+const CHUNK_STORAGE_OVERHEAD: usize = 512; // Bytes for the record around the chunk
 
 pub struct NearClient {
     client: JsonRpcClient,
     signer: InMemorySigner,
     contract_id: AccountId,
+    nonces: NonceManager,
 }
 
-#[derive(serde::Deserialize, Debug)]
+#[derive(serde::Deserialize, Debug, Clone)]
 pub struct TaskInfo {
     pub id: u64,
     pub description: String,
@@ -28,6 +41,8 @@ pub struct TaskInfo {
     pub created_at: u64,
     pub reward_amount: String,
     pub requester: AccountId,
+    #[serde(default)]
+    pub priority: Option<String>,
 }
 
 #[derive(serde::Deserialize, Debug)]
@@ -42,6 +57,10 @@ pub struct NodeInfo {
     pub last_heartbeat: u64,
     pub total_tasks_completed: u64,
     pub reputation_score: u32,
+    #[serde(default)]
+    pub slashed_amount: String,
+    #[serde(default)]
+    pub capabilities: Vec<String>,
 }
 
 impl NearClient {
@@ -63,6 +82,7 @@ impl NearClient {
             client,
             signer,
             contract_id,
+            nonces: NonceManager::new(),
         })
     }
     
@@ -72,6 +92,7 @@ impl NearClient {
         gpu_specs: &str,
         cpu_specs: &str,
         api_endpoint: &str,
+        capabilities: &[&str],
         stake_amount: Balance,
     ) -> Result<FinalExecutionOutcomeView> {
         info!("Registering node with stake: {} yoctoNEAR", stake_amount);
@@ -81,6 +102,7 @@ impl NearClient {
             "gpu_specs": gpu_specs,
             "cpu_specs": cpu_specs,
             "api_endpoint": api_endpoint,
+            "capabilities": capabilities,
         });
         
         self.call_contract_method(
@@ -91,17 +113,29 @@ impl NearClient {
         ).await
     }
     
-    pub async fn heartbeat(&self) -> Result<FinalExecutionOutcomeView> {
+    /// Send a heartbeat, optionally reporting live load for the contract to emit.
+    pub async fn heartbeat(&self, resources: Option<Value>) -> Result<FinalExecutionOutcomeView> {
         debug!("Sending heartbeat");
         
         self.call_contract_method(
             "heartbeat",
-            json!({}),
+            json!({ "resources": resources }),
             30_000_000_000_000, // 30 TGas
             0,
         ).await
     }
     
+    pub async fn top_up_stake(&self, amount: Balance) -> Result<FinalExecutionOutcomeView> {
+        info!("Topping up stake by {} yoctoNEAR", amount);
+        
+        self.call_contract_method(
+            "top_up_stake",
+            json!({}),
+            30_000_000_000_000, // 30 TGas
+            amount,
+        ).await
+    }
+    
     pub async fn get_assigned_tasks(&self) -> Result<Vec<TaskInfo>> {
         debug!("Fetching assigned tasks");
         
@@ -122,6 +156,10 @@ impl NearClient {
         proof_hash: &str,
         output: &str,
     ) -> Result<FinalExecutionOutcomeView> {
+        if output.len() > MAX_RESULT_OUTPUT_LEN {
+            return self.submit_result_in_chunks(task_id, proof_hash, output).await;
+        }
+        
         info!("Submitting result for task {}", task_id);
         
         let args = json!({
@@ -134,10 +172,79 @@ impl NearClient {
             "submit_result",
             args,
             100_000_000_000_000, // 100 TGas
+            1, // 1 yoctoNEAR
+        ).await
+    }
+    
+    /// Stream a long output with `append_result_chunk` and seal it with
+    /// `finalize_result`. Chunks the contract already holds are skipped, so a
+    /// retry of the same output resumes where the last attempt stopped.
+    async fn submit_result_in_chunks(
+        &self,
+        task_id: u64,
+        proof_hash: &str,
+        output: &str,
+    ) -> Result<FinalExecutionOutcomeView> {
+        let chunks = split_output(output, RESULT_CHUNK_LEN);
+        let next_seq = self.view_contract_method(
+            "get_next_result_chunk",
+            json!({ "task_id": task_id, "node_id": self.signer.account_id }),
+        ).await?
+            .as_u64()
+            .context("Failed to parse next result chunk")? as usize;
+        
+        info!("Submitting result for task {} in {} chunks", task_id, chunks.len());
+        
+        for (seq, chunk) in chunks.iter().enumerate().skip(next_seq) {
+            debug!("Appending chunk {}/{} for task {}", seq + 1, chunks.len(), task_id);
+            self.call_contract_method(
+                "append_result_chunk",
+                json!({ "task_id": task_id, "seq": seq, "chunk": chunk }),
+                100_000_000_000_000, // 100 TGas
+                chunk_storage_deposit(chunk),
+            ).await
+                .with_context(|| format!("Failed to append chunk {} for task {}", seq, task_id))?;
+        }
+        
+        self.call_contract_method(
+            "finalize_result",
+            json!({ "task_id": task_id, "proof_hash": proof_hash }),
+            100_000_000_000_000, // 100 TGas
+            1, // 1 yoctoNEAR
+        ).await
+    }
+    
+    /// Hand an assigned task back so the contract reassigns it to another node.
+    pub async fn decline_task(&self, task_id: u64, reason: &str) -> Result<FinalExecutionOutcomeView> {
+        info!("Declining task {}: {}", task_id, reason);
+        
+        self.call_contract_method(
+            "decline_task",
+            json!({ "task_id": task_id, "reason": reason }),
+            100_000_000_000_000, // 100 TGas, reassignment runs in the same call
             0,
         ).await
     }
     
+    pub async fn submit_results_batch(
+        &self,
+        results: &[PendingResult],
+    ) -> Result<FinalExecutionOutcomeView> {
+        info!("Submitting results batch for {} tasks", results.len());
+        
+        let batch: Vec<(u64, &str, &str)> = results
+            .iter()
+            .map(|r| (r.task_id, r.proof_hash.as_str(), r.output.as_str()))
+            .collect();
+        
+        self.call_contract_method(
+            "submit_results_batch",
+            json!({ "results": batch }),
+            300_000_000_000_000, // 300 TGas
+            1, // 1 yoctoNEAR
+        ).await
+    }
+    
     pub async fn get_node_info(&self) -> Result<Option<NodeInfo>> {
         debug!("Fetching node info");
         
@@ -156,6 +263,41 @@ impl NearClient {
         Ok(Some(node_info))
     }
     
+    /// DEAI accrued to this node while auto-claim is off.
+    pub async fn get_pending_rewards(&self) -> Result<Balance> {
+        let result = self.view_contract_method(
+            "get_pending_rewards",
+            json!({ "node_id": self.signer.account_id }),
+        ).await?;
+        
+        result.as_str()
+            .and_then(|amount| amount.parse().ok())
+            .context("Failed to parse pending rewards response")
+    }
+    
+    pub async fn claim_rewards(&self) -> Result<FinalExecutionOutcomeView> {
+        info!("Claiming pending rewards");
+        
+        self.call_contract_method(
+            "claim_rewards",
+            json!({}),
+            50_000_000_000_000, // 50 TGas
+            1, // 1 yoctoNEAR
+        ).await
+    }
+    
+    /// Replace the task types the contract routes to this node.
+    pub async fn update_capabilities(&self, capabilities: &[&str]) -> Result<FinalExecutionOutcomeView> {
+        info!("Updating node capabilities: {:?}", capabilities);
+        
+        self.call_contract_method(
+            "update_node_info",
+            json!({ "capabilities": capabilities }),
+            50_000_000_000_000, // 50 TGas
+            1, // 1 yoctoNEAR
+        ).await
+    }
+    
     pub async fn deactivate_node(&self) -> Result<FinalExecutionOutcomeView> {
         info!("Deactivating node");
         
@@ -163,7 +305,18 @@ impl NearClient {
             "deactivate_node",
             json!({}),
             50_000_000_000_000, // 50 TGas
-            0,
+            1, // 1 yoctoNEAR
+        ).await
+    }
+    
+    pub async fn claim_unbonded_stake(&self) -> Result<FinalExecutionOutcomeView> {
+        info!("Claiming unbonded stake");
+        
+        self.call_contract_method(
+            "claim_unbonded_stake",
+            json!({}),
+            50_000_000_000_000, // 50 TGas
+            1, // 1 yoctoNEAR
         ).await
     }
     
@@ -174,35 +327,55 @@ impl NearClient {
         gas: Gas,
         deposit: Balance,
     ) -> Result<FinalExecutionOutcomeView> {
-        let access_key = self.get_access_key().await?;
-        
-        let transaction = Transaction {
-            signer_id: self.signer.account_id.clone(),
-            public_key: self.signer.public_key(),
-            nonce: access_key.nonce + 1,
-            receiver_id: self.contract_id.clone(),
-            block_hash: self.get_latest_block_hash().await?,
-            actions: vec![Action::FunctionCall(Box::new(FunctionCallAction {
-                method_name: method_name.to_string(),
-                args: args.to_string().into_bytes(),
-                gas,
-                deposit,
-            }))],
-        };
-        
-        let signed_transaction = SignedTransaction::new(
-            self.signer.sign(&transaction.get_hash_and_size().0),
-            transaction,
-        );
-        
-        let request = methods::send_tx::RpcSendTransactionRequest {
-            signed_transaction,
-            wait_until: near_primitives::views::TxExecutionStatus::Final,
+        let mut attempt = 1;
+        let response = loop {
+            let nonce = self.nonces.next(|| async {
+                Ok(self.get_access_key().await?.nonce)
+            }).await?;
+            
+            let transaction = Transaction {
+                signer_id: self.signer.account_id.clone(),
+                public_key: self.signer.public_key(),
+                nonce,
+                receiver_id: self.contract_id.clone(),
+                block_hash: self.get_latest_block_hash().await?,
+                actions: vec![Action::FunctionCall(Box::new(FunctionCallAction {
+                    method_name: method_name.to_string(),
+                    args: args.to_string().into_bytes(),
+                    gas,
+                    deposit,
+                }))],
+            };
+            
+            let signed_transaction = SignedTransaction::new(
+                self.signer.sign(&transaction.get_hash_and_size().0),
+                transaction,
+            );
+            
+            let request = methods::send_tx::RpcSendTransactionRequest {
+                signed_transaction,
+                wait_until: near_primitives::views::TxExecutionStatus::Final,
+            };
+            
+            match self.client.call(request).await {
+                Ok(response) => break response,
+                Err(e) => {
+                    // Whether the nonce was used is unknown, so resync with chain
+                    self.nonces.invalidate().await;
+                    
+                    let message = format!("{:?}", e);
+                    if is_invalid_nonce(&message) && attempt < MAX_NONCE_ATTEMPTS {
+                        warn!("Nonce {} rejected for {} (attempt {}/{}), retrying with a fresh nonce",
+                              nonce, method_name, attempt, MAX_NONCE_ATTEMPTS);
+                        attempt += 1;
+                        continue;
+                    }
+                    
+                    return Err(anyhow::Error::new(e).context("Failed to send transaction"));
+                }
+            }
         };
         
-        let response = self.client.call(request).await
-            .context("Failed to send transaction")?;
-        
         if let Some(failure) = &response.status.as_failure() {
             error!("Transaction failed: {:?}", failure);
             anyhow::bail!("Transaction failed: {:?}", failure);
@@ -284,4 +457,40 @@ impl NearClient {
             anyhow::bail!("Unexpected account response type");
         }
     }
-}
\ No newline at end of file
+}
+fn chunk_storage_deposit(chunk: &str) -> Balance {
+    (chunk.len() + CHUNK_STORAGE_OVERHEAD) as Balance * STORAGE_BYTE_COST
+}
+
+// Splits at char boundaries, so a chunk may fall a few bytes short of `max_len`
+fn split_output(output: &str, max_len: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = output;
+    
+    while !rest.is_empty() {
+        let mut end = max_len.min(rest.len());
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_output_keeps_chars_whole() {
+        assert_eq!(split_output("abcdefg", 3), vec!["abc", "def", "g"]);
+        
+        // "é" is two bytes and can't straddle a chunk
+        let chunks = split_output("aéé", 2);
+        assert_eq!(chunks, vec!["a", "é", "é"]);
+        assert_eq!(chunks.concat(), "aéé");
+    }
+}
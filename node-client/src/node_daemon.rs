@@ -1,18 +1,53 @@
 use anyhow::{Result, Context};
 use tokio::time::{interval, Duration};
 use log::{info, warn, error, debug};
+use std::collections::HashSet;
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use crate::config::NodeConfig;
-use crate::near_client::NearClient;
+use tokio::sync::Semaphore;
+use tracing::Instrument;
+use crate::config::{NodeConfig, SubmissionConfig};
+use crate::near_client::{NearClient, NodeInfo, TaskInfo, MAX_RESULT_OUTPUT_LEN};
+use crate::backend::supported_task_types;
 use crate::task_processor::TaskProcessor;
+use crate::task_runner::TaskRunner;
 use crate::heartbeat::HeartbeatManager;
+use crate::logging::task_span;
+use crate::health_server::HealthServer;
+use crate::resource_monitor::ResourceMonitor;
+use crate::stake_topup::StakeTopUp;
+use crate::result_batcher::{PendingResult, ResultBatcher};
+use crate::result_queue::ResultQueue;
+
+const SUBMIT_MAX_ATTEMPTS: u32 = 4;
+const REGISTER_MAX_ATTEMPTS: u32 = 3;
+const SUBMIT_INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+const DRAINING_DECLINE_REASON: &str = "Node is deactivating";
+
+// Contract panics that retrying can't fix; the result is dropped instead
+const PERMANENT_SUBMIT_ERRORS: &[&str] = &[
+    "Task has timed out",
+    "Task not found",
+    "Not assigned to this node",
+    "Task not in assignable state",
+    "Exactly 1 yoctoNEAR required",
+];
+
+// An earlier attempt was included although its RPC call failed, e.g. timed out
+const ALREADY_SUBMITTED_ERROR: &str = "Result already submitted";
+
+enum SubmitOutcome {
+    Submitted(String), // Transaction hash
+    Rejected(String),  // Reason the contract refused the result
+    AlreadySubmitted,  // A previous attempt landed on chain
+}
 
 pub struct NodeDaemon {
     config: NodeConfig,
     near_client: Arc<NearClient>,
-    task_processor: Arc<Mutex<TaskProcessor>>,
+    task_processor: Arc<TaskProcessor>,
     heartbeat_manager: Arc<HeartbeatManager>,
+    resource_monitor: ResourceMonitor,
+    task_permits: Arc<Semaphore>,
 }
 
 impl NodeDaemon {
@@ -24,20 +59,30 @@ impl NodeDaemon {
                 .context("Failed to initialize Near client")?
         );
         
-        let task_processor = Arc::new(Mutex::new(
-            TaskProcessor::new(&config).await
-                .context("Failed to initialize task processor")?
-        ));
+        let task_processor = TaskProcessor::new(&config).await
+            .context("Failed to initialize task processor")?;
         
-        let heartbeat_manager = Arc::new(
-            HeartbeatManager::new(near_client.clone())
+        let resource_monitor = ResourceMonitor::new(
+            task_processor.task_permits(),
+            config.hardware.max_concurrent_tasks as usize,
         );
         
+        let mut heartbeat_manager = HeartbeatManager::new(near_client.clone());
+        if config.hardware.report_load_in_heartbeat {
+            heartbeat_manager = heartbeat_manager.with_resource_monitor(resource_monitor.clone());
+        }
+        if config.node.auto_topup.enabled {
+            let stake_topup = StakeTopUp::new(near_client.clone(), &config.node.auto_topup)?;
+            heartbeat_manager = heartbeat_manager.with_stake_topup(stake_topup);
+        }
+        
         Ok(Self {
             config,
             near_client,
-            task_processor,
-            heartbeat_manager,
+            heartbeat_manager: Arc::new(heartbeat_manager),
+            resource_monitor,
+            task_permits: task_processor.task_permits(),
+            task_processor: Arc::new(task_processor),
         })
     }
     
@@ -47,6 +92,7 @@ impl NodeDaemon {
         // Check if already registered
         if let Some(node_info) = self.near_client.get_node_info().await? {
             warn!("Node already registered: {:?}", node_info);
+            self.sync_capabilities(&node_info).await;
             return Ok(());
         }
         
@@ -68,16 +114,39 @@ impl NodeDaemon {
         // Construct API endpoint
         let api_endpoint = format!("http://{}:{}", self.config.node.public_ip, self.config.node.api_port);
         
-        // Register with the contract
-        let result = self.near_client.register_node(
-            &self.config.node.public_ip,
-            &self.config.hardware.gpu_specs,
-            &self.config.hardware.cpu_specs,
-            &api_endpoint,
-            stake_amount,
-        ).await?;
-        
-        info!("Node registered successfully! Transaction: {}", result.transaction.hash);
+        // Register with the contract. A submit that looks failed may still have
+        // landed (e.g. the RPC timed out after inclusion), so check the chain
+        // before trying again.
+        let mut attempt = 1;
+        loop {
+            match self.near_client.register_node(
+                &self.config.node.public_ip,
+                &self.config.hardware.gpu_specs,
+                &self.config.hardware.cpu_specs,
+                &api_endpoint,
+                supported_task_types(&self.config.ai.backend),
+                stake_amount,
+            ).await {
+                Ok(result) => {
+                    info!("Node registered successfully! Transaction: {}", result.transaction.hash);
+                    break;
+                }
+                Err(e) => {
+                    if let Some(node_info) = self.near_client.get_node_info().await? {
+                        warn!("Registration submit reported an error, but the node is registered: {:#}", e);
+                        info!("Registration verified: {:?}", node_info);
+                        return Ok(());
+                    }
+                    if attempt >= REGISTER_MAX_ATTEMPTS {
+                        return Err(e.context(format!("Registration failed after {} attempts", attempt)));
+                    }
+
+                    warn!("Registration attempt {}/{} failed, retrying: {:#}", attempt, REGISTER_MAX_ATTEMPTS, e);
+                    tokio::time::sleep(SUBMIT_INITIAL_BACKOFF * attempt).await;
+                    attempt += 1;
+                }
+            }
+        }
         
         // Verify registration
         match self.near_client.get_node_info().await? {
@@ -92,6 +161,19 @@ impl NodeDaemon {
         Ok(())
     }
     
+    // Keep the contract routing the task types this node's backend runs, e.g.
+    // after switching backends. Failing leaves the old list, so it only warns.
+    async fn sync_capabilities(&self, node_info: &NodeInfo) {
+        let capabilities = supported_task_types(&self.config.ai.backend);
+        if !capabilities_changed(&node_info.capabilities, capabilities) {
+            return;
+        }
+        
+        if let Err(e) = self.near_client.update_capabilities(capabilities).await {
+            warn!("Failed to update node capabilities: {:#}", e);
+        }
+    }
+    
     pub async fn start(&self) -> Result<()> {
         info!("Starting node daemon...");
         
@@ -113,12 +195,45 @@ impl NodeDaemon {
             })
         };
         
+        // Start health server
+        let health_handle = {
+            let health_server = HealthServer::new(
+                self.config.node.api_port,
+                self.heartbeat_manager.clone(),
+                self.task_permits.clone(),
+                self.config.hardware.max_concurrent_tasks as usize,
+            );
+            tokio::spawn(async move {
+                if let Err(e) = health_server.start().await {
+                    error!("{:#}", e);
+                }
+            })
+        };
+        
+        // Replay results that failed to submit before the last shutdown
+        let mut result_queue = ResultQueue::open(&self.config.submission.pending_results_path)
+            .context("Failed to open pending result queue")?;
+        if !result_queue.pending().is_empty() {
+            info!("Recovering {} queued results", result_queue.pending().len());
+            Self::replay_queued_results(&self.near_client, &mut result_queue).await;
+        }
+        
         // Start task polling loop
         let task_handle = {
             let near_client = self.near_client.clone();
             let task_processor = self.task_processor.clone();
+            let submission = self.config.submission.clone();
+            let poll_interval = Duration::from_secs(self.config.node.poll_interval_seconds);
+            let max_concurrent_tasks = self.config.hardware.max_concurrent_tasks as usize;
             tokio::spawn(async move {
-                Self::task_polling_loop(near_client, task_processor).await;
+                Self::task_polling_loop(
+                    near_client,
+                    task_processor,
+                    submission,
+                    result_queue,
+                    poll_interval,
+                    max_concurrent_tasks,
+                ).await;
             })
         };
         
@@ -135,6 +250,9 @@ impl NodeDaemon {
             _ = task_handle => {
                 error!("Task polling stopped unexpectedly");
             }
+            _ = health_handle => {
+                error!("Health server stopped unexpectedly");
+            }
         }
         
         info!("Shutting down node daemon...");
@@ -157,6 +275,7 @@ impl NodeDaemon {
                 println!("  Last Heartbeat: {}", node_info.last_heartbeat);
                 println!("  Tasks Completed: {}", node_info.total_tasks_completed);
                 println!("  Reputation Score: {}", node_info.reputation_score);
+                println!("  Pending Rewards: {}", self.near_client.get_pending_rewards().await?);
                 
                 // Check assigned tasks
                 let tasks = self.near_client.get_assigned_tasks().await?;
@@ -174,43 +293,329 @@ impl NodeDaemon {
             }
         }
         
+        let load = self.resource_monitor.snapshot().await;
+        println!("Live Load:");
+        println!("  Running Tasks: {}/{}", load.active_tasks, self.config.hardware.max_concurrent_tasks);
+        if let (Some(used), Some(total)) = (load.memory_used_mb, load.memory_total_mb) {
+            println!("  Memory: {} / {} MB", used, total);
+        }
+        if load.gpus.is_empty() {
+            println!("  GPUs: none detected (nvidia-smi unavailable)");
+        }
+        for gpu in &load.gpus {
+            let utilization = gpu.utilization_percent.map_or("n/a".to_string(), |p| format!("{}%", p));
+            let memory = match (gpu.memory_used_mb, gpu.memory_total_mb) {
+                (Some(used), Some(total)) => format!("{} / {} MB", used, total),
+                _ => "n/a".to_string(),
+            };
+            println!("  GPU {} ({}): {} utilization, {} memory", gpu.index, gpu.name, utilization, memory);
+        }
+        
         Ok(())
     }
     
-    pub async fn deactivate(&self) -> Result<()> {
+    /// Deactivate the node. The contract refuses while tasks are assigned, so
+    /// in-flight tasks are finished and submitted first; with `force` they are
+    /// handed back unfinished instead.
+    pub async fn deactivate(&self, force: bool) -> Result<()> {
+        if force {
+            self.abandon_assigned_tasks().await?;
+        } else {
+            self.drain_tasks().await?;
+        }
+        
         info!("Deactivating node...");
         
         let result = self.near_client.deactivate_node().await?;
         info!("Node deactivated successfully! Transaction: {}", result.transaction.hash);
+        info!("Stake is unbonding; run `claim-stake` once the unbonding period has ended");
+        
+        Ok(())
+    }
+    
+    /// Run the tasks assigned when draining starts to completion and submit
+    /// their results. Tasks assigned after that are declined, so the count
+    /// only goes down.
+    async fn drain_tasks(&self) -> Result<()> {
+        let mut result_queue = ResultQueue::open(&self.config.submission.pending_results_path)
+            .context("Failed to open pending result queue")?;
+        let in_flight: HashSet<u64> = self.near_client.get_assigned_tasks().await?
+            .iter()
+            .map(|task| task.id)
+            .collect();
+        
+        if in_flight.is_empty() && result_queue.pending().is_empty() {
+            return Ok(());
+        }
+        
+        info!("Draining {} in-flight tasks before deactivating. Press Ctrl+C to stop, \
+               or rerun with --force to abandon them", in_flight.len());
+        
+        let max_concurrent_tasks = self.config.hardware.max_concurrent_tasks as usize;
+        let mut interval = interval(Duration::from_secs(self.config.node.poll_interval_seconds));
+        let mut runner = TaskRunner::new();
+        
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    if !result_queue.pending().is_empty() {
+                        Self::replay_queued_results(&self.near_client, &mut result_queue).await;
+                    }
+                    
+                    let assigned = match self.near_client.get_assigned_tasks().await {
+                        Ok(tasks) => tasks.len(),
+                        Err(e) => {
+                            warn!("Failed to fetch assigned tasks, retrying: {}", e);
+                            continue;
+                        }
+                    };
+                    if assigned == 0 && runner.running_count() == 0 && result_queue.pending().is_empty() {
+                        info!("All in-flight tasks drained");
+                        return Ok(());
+                    }
+                    
+                    if let Err(e) = Self::dispatch_assigned_tasks(
+                        &self.near_client,
+                        &self.task_processor,
+                        &mut runner,
+                        max_concurrent_tasks,
+                        None,
+                        &result_queue,
+                        Some(&in_flight),
+                    ).await {
+                        error!("Error processing tasks: {}", e);
+                    }
+                    
+                    info!("{}", drain_progress(runner.running_count(), result_queue.pending().len(), assigned));
+                }
+                Some((task_id, (task, execution))) = runner.next_completed() => {
+                    Self::handle_completed_task(&self.near_client, task, execution, None, &mut result_queue)
+                        .instrument(task_span(task_id))
+                        .await;
+                }
+                _ = tokio::signal::ctrl_c() => {
+                    anyhow::bail!("Draining interrupted; the node is still active. Rerun `deactivate` to resume, or with --force to abandon in-flight tasks");
+                }
+            }
+        }
+    }
+    
+    /// Hand every assigned task back unfinished so the contract lets the node
+    /// go inactive right away.
+    async fn abandon_assigned_tasks(&self) -> Result<()> {
+        let tasks = self.near_client.get_assigned_tasks().await?;
+        if tasks.is_empty() {
+            return Ok(());
+        }
+        
+        warn!("Abandoning {} in-flight tasks", tasks.len());
+        for task in tasks {
+            self.near_client.decline_task(task.id, DRAINING_DECLINE_REASON).await
+                .with_context(|| format!("Failed to hand back task {}", task.id))?;
+        }
+        
+        Ok(())
+    }
+    
+    pub async fn claim_stake(&self) -> Result<()> {
+        info!("Claiming unbonded stake...");
+        
+        let result = self.near_client.claim_unbonded_stake().await?;
+        info!("Unbonded stake claimed! Transaction: {}", result.transaction.hash);
+        
+        Ok(())
+    }
+    
+    pub async fn claim_rewards(&self) -> Result<()> {
+        let pending = self.near_client.get_pending_rewards().await?;
+        if pending == 0 {
+            info!("No pending rewards to claim");
+            return Ok(());
+        }
+        
+        info!("Claiming {} pending reward tokens...", pending);
+        let result = self.near_client.claim_rewards().await?;
+        info!("Rewards claimed! Transaction: {}", result.transaction.hash);
         
         Ok(())
     }
     
     async fn task_polling_loop(
         near_client: Arc<NearClient>,
-        task_processor: Arc<Mutex<TaskProcessor>>,
+        task_processor: Arc<TaskProcessor>,
+        submission: SubmissionConfig,
+        mut result_queue: ResultQueue,
+        poll_interval: Duration,
+        max_concurrent_tasks: usize,
     ) {
-        let mut interval = interval(Duration::from_secs(10)); // Poll every 10 seconds
+        let mut interval = interval(poll_interval);
+        let mut batcher = submission.batch_enabled.then(|| ResultBatcher::new(&submission));
+        let mut runner = TaskRunner::new();
         
         loop {
-            interval.tick().await;
-            
-            match Self::process_pending_tasks(&near_client, &task_processor).await {
-                Ok(processed_count) => {
-                    if processed_count > 0 {
-                        debug!("Processed {} tasks", processed_count);
+            tokio::select! {
+                _ = interval.tick() => {
+                    if !result_queue.pending().is_empty() {
+                        Self::replay_queued_results(&near_client, &mut result_queue).await;
+                    }
+                    
+                    match Self::dispatch_assigned_tasks(
+                        &near_client,
+                        &task_processor,
+                        &mut runner,
+                        max_concurrent_tasks,
+                        batcher.as_ref(),
+                        &result_queue,
+                        None,
+                    ).await {
+                        Ok(started) => {
+                            if started > 0 {
+                                debug!("Started {} tasks", started);
+                            }
+                        }
+                        Err(e) => {
+                            error!("Error processing tasks: {}", e);
+                        }
                     }
                 }
+                // Results are submitted as each task finishes, not once per tick
+                Some((task_id, (task, execution))) = runner.next_completed() => {
+                    Self::handle_completed_task(&near_client, task, execution, batcher.as_mut(), &mut result_queue)
+                        .instrument(task_span(task_id))
+                        .await;
+                }
+            }
+            
+            if let Some(batcher) = batcher.as_mut() {
+                if batcher.should_flush() {
+                    Self::flush_results(&near_client, batcher, &mut result_queue).await;
+                }
+            }
+        }
+    }
+    
+    async fn flush_results(near_client: &NearClient, batcher: &mut ResultBatcher, result_queue: &mut ResultQueue) {
+        let batch = batcher.take_batch();
+        
+        match near_client.submit_results_batch(&batch).await {
+            Ok(result) => {
+                info!("Submitted {} results in one batch! Transaction: {}", 
+                      batch.len(), result.transaction.hash);
+            }
+            Err(e) => {
+                warn!("Batch submission failed, falling back to individual submission: {}", e);
+                
+                for pending in batch {
+                    let span = task_span(pending.task_id);
+                    Self::submit_or_queue(near_client, pending, result_queue).instrument(span).await;
+                }
+            }
+        }
+    }
+    
+    /// Submit a result with retries. If every attempt fails it is persisted to
+    /// the queue; results the contract rejects outright are dropped.
+    async fn submit_or_queue(near_client: &NearClient, pending: PendingResult, result_queue: &mut ResultQueue) -> bool {
+        match Self::submit_with_retry(near_client, &pending).await {
+            Ok(SubmitOutcome::Submitted(transaction_hash)) => {
+                tracing::Span::current().record("tx_hash", transaction_hash.as_str());
+                info!("Task {} completed successfully! Transaction: {}", pending.task_id, transaction_hash);
+                true
+            }
+            Ok(SubmitOutcome::Rejected(reason)) => {
+                warn!("Contract rejected result for task {}, dropping it: {}", pending.task_id, reason);
+                false
+            }
+            Ok(SubmitOutcome::AlreadySubmitted) => {
+                info!("Result for task {} was already submitted by an earlier attempt", pending.task_id);
+                true
+            }
+            Err(e) => {
+                error!("Failed to submit result for task {}, queueing for later: {}", pending.task_id, e);
+                let task_id = pending.task_id;
+                if let Err(e) = result_queue.push(pending) {
+                    error!("Failed to persist result for task {}: {}", task_id, e);
+                }
+                false
+            }
+        }
+    }
+    
+    async fn submit_with_retry(near_client: &NearClient, pending: &PendingResult) -> Result<SubmitOutcome> {
+        let mut backoff = SUBMIT_INITIAL_BACKOFF;
+        let mut attempt = 1;
+        
+        loop {
+            match near_client.submit_result(pending.task_id, &pending.proof_hash, &pending.output).await {
+                Ok(result) => return Ok(SubmitOutcome::Submitted(result.transaction.hash.to_string())),
                 Err(e) => {
-                    error!("Error processing tasks: {}", e);
+                    if is_already_submitted(&e) {
+                        return Ok(SubmitOutcome::AlreadySubmitted);
+                    }
+                    if let Some(reason) = permanent_submit_error(&e) {
+                        return Ok(SubmitOutcome::Rejected(reason.to_string()));
+                    }
+                    if attempt >= SUBMIT_MAX_ATTEMPTS {
+                        return Err(e);
+                    }
+                    
+                    warn!("Submitting result for task {} failed (attempt {}/{}), retrying in {:?}: {}",
+                          pending.task_id, attempt, SUBMIT_MAX_ATTEMPTS, backoff, e);
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
                 }
             }
         }
     }
     
-    async fn process_pending_tasks(
+    async fn replay_queued_results(near_client: &NearClient, result_queue: &mut ResultQueue) {
+        for pending in result_queue.pending().to_vec() {
+            let task_id = pending.task_id;
+            
+            if !Self::replay_queued_result(near_client, &pending).instrument(task_span(task_id)).await {
+                continue;
+            }
+            
+            if let Err(e) = result_queue.remove(task_id) {
+                error!("Failed to remove task {} from result queue: {}", task_id, e);
+            }
+        }
+    }
+    
+    /// Returns whether the result is done with and can leave the queue.
+    async fn replay_queued_result(near_client: &NearClient, pending: &PendingResult) -> bool {
+        match Self::submit_with_retry(near_client, pending).await {
+            Ok(SubmitOutcome::Submitted(transaction_hash)) => {
+                tracing::Span::current().record("tx_hash", transaction_hash.as_str());
+                info!("Recovered result for task {} submitted! Transaction: {}", pending.task_id, transaction_hash);
+                true
+            }
+            Ok(SubmitOutcome::Rejected(reason)) => {
+                warn!("Dropping queued result for task {}: {}", pending.task_id, reason);
+                true
+            }
+            Ok(SubmitOutcome::AlreadySubmitted) => {
+                info!("Queued result for task {} was already submitted", pending.task_id);
+                true
+            }
+            Err(e) => {
+                warn!("Queued result for task {} still failing, keeping it: {}", pending.task_id, e);
+                false
+            }
+        }
+    }
+    
+    /// Start executions for newly assigned tasks, up to the free task slots.
+    /// The task processor's semaphore still bounds how many run at once.
+    async fn dispatch_assigned_tasks(
         near_client: &NearClient,
-        task_processor: &Arc<Mutex<TaskProcessor>>,
+        task_processor: &Arc<TaskProcessor>,
+        runner: &mut TaskRunner<(TaskInfo, Result<(String, String)>)>,
+        max_concurrent_tasks: usize,
+        batcher: Option<&ResultBatcher>,
+        result_queue: &ResultQueue,
+        draining: Option<&HashSet<u64>>,
     ) -> Result<usize> {
         let tasks = near_client.get_assigned_tasks().await?;
         
@@ -219,36 +624,90 @@ impl NodeDaemon {
         }
         
         debug!("Found {} assigned tasks", tasks.len());
-        let mut processed_count = 0;
+        let mut started_count = 0;
         
         for task in tasks {
-            if task.status == "Assigned" {
-                info!("Processing task {}: {}", task.id, task.description);
-                
-                let processor = task_processor.lock().await;
-                match processor.execute_task(&task).await {
-                    Ok((proof_hash, output)) => {
-                        drop(processor); // Release lock before network call
-                        
-                        match near_client.submit_result(task.id, &proof_hash, &output).await {
-                            Ok(result) => {
-                                info!("Task {} completed successfully! Transaction: {}", 
-                                      task.id, result.transaction.hash);
-                                processed_count += 1;
-                            }
-                            Err(e) => {
-                                error!("Failed to submit result for task {}: {}", task.id, e);
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        error!("Failed to execute task {}: {}", task.id, e);
+            if task.status != "Assigned" || runner.is_running(task.id) {
+                continue;
+            }
+            
+            // Already executed, waiting in the batch or queue for submission
+            if batcher.map_or(false, |b| b.contains(task.id)) || result_queue.contains(task.id) {
+                continue;
+            }
+            
+            let span = task_span(task.id);
+            
+            // Hand back tasks this node can't run so they are reassigned
+            // promptly, and while draining any task assigned since it began
+            let decline_reason = match draining {
+                Some(in_flight) if !in_flight.contains(&task.id) => Some(DRAINING_DECLINE_REASON.to_string()),
+                _ => task_processor.decline_reason(&task),
+            };
+            if let Some(reason) = decline_reason {
+                async {
+                    warn!("Declining task {}: {}", task.id, reason);
+                    if let Err(e) = near_client.decline_task(task.id, &reason).await {
+                        error!("Failed to decline task {}: {}", task.id, e);
                     }
                 }
+                .instrument(span)
+                .await;
+                continue;
+            }
+            
+            // The rest are picked up on a later tick once slots free up
+            if runner.running_count() >= max_concurrent_tasks {
+                break;
             }
+            
+            span.in_scope(|| info!("Processing task {}: {}", task.id, task.description));
+            
+            let task_processor = task_processor.clone();
+            runner.spawn(task.id, async move {
+                let execution = task_processor.execute_task(&task).await;
+                (task, execution)
+            }.instrument(span));
+            started_count += 1;
         }
         
-        Ok(processed_count)
+        Ok(started_count)
+    }
+    
+    async fn handle_completed_task(
+        near_client: &NearClient,
+        task: TaskInfo,
+        execution: Result<(String, String)>,
+        batcher: Option<&mut ResultBatcher>,
+        result_queue: &mut ResultQueue,
+    ) {
+        let (proof_hash, output) = match execution {
+            Ok(result) => result,
+            Err(e) => {
+                error!("Failed to execute task {}: {}", task.id, e);
+                return;
+            }
+        };
+        
+        let pending = PendingResult {
+            task_id: task.id,
+            proof_hash,
+            output,
+        };
+        
+        // Urgent tasks skip the batch and are submitted right away, as do
+        // outputs too long for a batch, which are streamed in chunks
+        let urgent = task.priority.as_deref() == Some("Urgent");
+        let chunked = pending.output.len() > MAX_RESULT_OUTPUT_LEN;
+        match batcher {
+            Some(batcher) if !urgent && !chunked => {
+                debug!("Queued result for task {} for batch submission", task.id);
+                batcher.push(pending);
+            }
+            _ => {
+                Self::submit_or_queue(near_client, pending, result_queue).await;
+            }
+        }
     }
     
     fn parse_stake_amount(&self) -> Result<u128> {
@@ -258,4 +717,77 @@ impl NodeDaemon {
         let stake_yocto = (stake_near * 1e24) as u128;
         Ok(stake_yocto)
     }
-}
\ No newline at end of file
+}
+
+fn permanent_submit_error(error: &anyhow::Error) -> Option<&'static str> {
+    let message = format!("{:#}", error);
+    PERMANENT_SUBMIT_ERRORS.iter().copied().find(|reason| message.contains(reason))
+}
+
+fn is_already_submitted(error: &anyhow::Error) -> bool {
+    format!("{:#}", error).contains(ALREADY_SUBMITTED_ERROR)
+}
+
+fn drain_progress(running: usize, queued: usize, assigned: usize) -> String {
+    format!("Draining: {} tasks running, {} results awaiting submission, {} tasks still assigned",
+            running, queued, assigned)
+}
+
+fn capabilities_changed(registered: &[String], supported: &[&str]) -> bool {
+    let mut supported: Vec<&str> = supported.to_vec();
+    supported.sort_unstable();
+    supported.dedup();
+    
+    let mut registered: Vec<&str> = registered.iter().map(String::as_str).collect();
+    registered.sort_unstable();
+    
+    registered != supported
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timed_out_task_is_a_permanent_error() {
+        let error = anyhow::anyhow!("Transaction failed: ActionError {{ kind: FunctionCallError(ExecutionError(\"Smart contract panicked: Task has timed out\")) }}");
+        assert_eq!(permanent_submit_error(&error), Some("Task has timed out"));
+    }
+
+    #[test]
+    fn test_already_submitted_result_counts_as_success() {
+        let error = anyhow::anyhow!("Transaction failed: ActionError {{ kind: FunctionCallError(ExecutionError(\"Smart contract panicked: Result already submitted\")) }}");
+        assert!(is_already_submitted(&error));
+        assert_eq!(permanent_submit_error(&error), None);
+    }
+
+    #[test]
+    fn test_missing_deposit_is_a_permanent_error() {
+        let error = anyhow::anyhow!("Smart contract panicked: Exactly 1 yoctoNEAR required for security");
+        assert_eq!(permanent_submit_error(&error), Some("Exactly 1 yoctoNEAR required"));
+        assert!(!is_already_submitted(&error));
+    }
+
+    #[test]
+    fn test_rpc_errors_are_retried() {
+        let error = anyhow::anyhow!("Failed to send transaction: connection reset by peer");
+        assert_eq!(permanent_submit_error(&error), None);
+    }
+
+    #[test]
+    fn test_capabilities_compare_as_sets() {
+        let registered = vec!["embedding".to_string(), "inference".to_string()];
+
+        assert!(!capabilities_changed(&registered, &["inference", "embedding"]));
+        assert!(capabilities_changed(&registered, &["inference"]));
+        assert!(capabilities_changed(&[], &["inference"]));
+    }
+
+    #[test]
+    fn test_drain_progress() {
+        assert_eq!(
+            drain_progress(2, 1, 3),
+            "Draining: 2 tasks running, 1 results awaiting submission, 3 tasks still assigned"
+        );
+    }
+}
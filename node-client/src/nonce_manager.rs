@@ -0,0 +1,107 @@
+use anyhow::Result;
+use near_primitives::types::Nonce;
+use std::future::Future;
+use tokio::sync::Mutex;
+
+/// Hands out access key nonces for outgoing transactions. The heartbeat loop
+/// and result submission sign with the same key, so reading the chain nonce
+/// per transaction lets two of them pick the same one and one fails.
+pub struct NonceManager {
+    // Last nonce handed out; `None` until fetched or after an invalidation
+    last_used: Mutex<Option<Nonce>>,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self {
+            last_used: Mutex::new(None),
+        }
+    }
+
+    /// Reserve the next nonce, fetching the access key's current nonce from
+    /// chain with `fetch_chain_nonce` when nothing is cached.
+    pub async fn next<F, Fut>(&self, fetch_chain_nonce: F) -> Result<Nonce>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Nonce>>,
+    {
+        // Held across the fetch so concurrent callers wait instead of fetching the same nonce
+        let mut last_used = self.last_used.lock().await;
+
+        let nonce = match *last_used {
+            Some(nonce) => nonce + 1,
+            None => fetch_chain_nonce().await? + 1,
+        };
+        *last_used = Some(nonce);
+
+        Ok(nonce)
+    }
+
+    /// Forget the cached nonce so the next transaction refetches it from chain.
+    pub async fn invalidate(&self) {
+        *self.last_used.lock().await = None;
+    }
+}
+
+impl Default for NonceManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn is_invalid_nonce(error: &str) -> bool {
+    error.contains("InvalidNonce")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_concurrent_transactions_get_distinct_nonces() {
+        let manager = Arc::new(NonceManager::new());
+        let fetches = Arc::new(AtomicU32::new(0));
+
+        let handles: Vec<_> = (0..5)
+            .map(|_| {
+                let manager = manager.clone();
+                let fetches = fetches.clone();
+                tokio::spawn(async move {
+                    manager.next(|| async move {
+                        fetches.fetch_add(1, Ordering::SeqCst);
+                        Ok(100)
+                    }).await.unwrap()
+                })
+            })
+            .collect();
+
+        let mut nonces = Vec::new();
+        for handle in handles {
+            nonces.push(handle.await.unwrap());
+        }
+        nonces.sort();
+
+        assert_eq!(nonces, vec![101, 102, 103, 104, 105]);
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_refetches_from_chain() {
+        let manager = NonceManager::new();
+
+        assert_eq!(manager.next(|| async { Ok(10) }).await.unwrap(), 11);
+        assert_eq!(manager.next(|| async { Ok(10) }).await.unwrap(), 12);
+
+        // Another client used the key meanwhile
+        manager.invalidate().await;
+        assert_eq!(manager.next(|| async { Ok(40) }).await.unwrap(), 41);
+    }
+
+    #[test]
+    fn test_is_invalid_nonce() {
+        assert!(is_invalid_nonce("InvalidTransaction { context: InvalidNonce { tx_nonce: 5, ak_nonce: 7 } }"));
+        assert!(!is_invalid_nonce("Expired"));
+    }
+}
@@ -0,0 +1,204 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::process::Stdio;
+use std::sync::Arc;
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+use log::debug;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GpuUsage {
+    pub index: u32,
+    pub name: String,
+    pub utilization_percent: Option<u32>,
+    pub memory_used_mb: Option<u64>,
+    pub memory_total_mb: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceSnapshot {
+    pub gpus: Vec<GpuUsage>, // Empty when no GPU or nvidia-smi is available
+    pub memory_used_mb: Option<u64>,
+    pub memory_total_mb: Option<u64>,
+    pub active_tasks: usize,
+}
+
+impl ResourceSnapshot {
+    /// Summary for the contract's `heartbeat(resources)` argument. Utilization
+    /// is averaged and memory summed over all GPUs.
+    pub fn heartbeat_payload(&self) -> Value {
+        let utilizations: Vec<u32> = self.gpus.iter().filter_map(|gpu| gpu.utilization_percent).collect();
+        let average_utilization = (!utilizations.is_empty())
+            .then(|| utilizations.iter().sum::<u32>() / utilizations.len() as u32);
+
+        let sum_memory = |field: fn(&GpuUsage) -> Option<u64>| -> Option<u64> {
+            self.gpus.iter().map(field).sum::<Option<u64>>().filter(|_| !self.gpus.is_empty())
+        };
+
+        json!({
+            "gpu_utilization_percent": average_utilization,
+            "gpu_memory_used_mb": sum_memory(|gpu| gpu.memory_used_mb),
+            "gpu_memory_total_mb": sum_memory(|gpu| gpu.memory_total_mb),
+            "active_tasks": self.active_tasks,
+        })
+    }
+}
+
+/// Samples live GPU and memory load. GPUs are read through `nvidia-smi`;
+/// without it only system memory from `/proc/meminfo` is reported.
+/// The task count comes from the task processor's concurrency permits.
+#[derive(Clone)]
+pub struct ResourceMonitor {
+    task_permits: Arc<Semaphore>,
+    max_concurrent_tasks: usize,
+}
+
+impl ResourceMonitor {
+    pub fn new(task_permits: Arc<Semaphore>, max_concurrent_tasks: usize) -> Self {
+        Self { task_permits, max_concurrent_tasks }
+    }
+
+    pub async fn snapshot(&self) -> ResourceSnapshot {
+        let (memory_used_mb, memory_total_mb) = match tokio::fs::read_to_string("/proc/meminfo").await {
+            Ok(meminfo) => parse_meminfo(&meminfo),
+            Err(_) => (None, None),
+        };
+
+        ResourceSnapshot {
+            gpus: query_gpus().await,
+            memory_used_mb,
+            memory_total_mb,
+            active_tasks: self.max_concurrent_tasks.saturating_sub(self.task_permits.available_permits()),
+        }
+    }
+}
+
+async fn query_gpus() -> Vec<GpuUsage> {
+    let output = Command::new("nvidia-smi")
+        .args([
+            "--query-gpu=index,name,utilization.gpu,memory.used,memory.total",
+            "--format=csv,noheader,nounits",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output()
+        .await;
+
+    match output {
+        Ok(output) if output.status.success() => parse_nvidia_smi(&String::from_utf8_lossy(&output.stdout)),
+        Ok(output) => {
+            debug!("nvidia-smi exited with {}, reporting no GPUs", output.status);
+            Vec::new()
+        }
+        Err(e) => {
+            debug!("nvidia-smi unavailable, reporting no GPUs: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+// One GPU per line; fields the driver can't report read "[N/A]"
+fn parse_nvidia_smi(output: &str) -> Vec<GpuUsage> {
+    output.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            if fields.len() != 5 {
+                return None;
+            }
+
+            Some(GpuUsage {
+                index: fields[0].parse().ok()?,
+                name: fields[1].to_string(),
+                utilization_percent: fields[2].parse().ok(),
+                memory_used_mb: fields[3].parse().ok(),
+                memory_total_mb: fields[4].parse().ok(),
+            })
+        })
+        .collect()
+}
+
+fn parse_meminfo(meminfo: &str) -> (Option<u64>, Option<u64>) {
+    let field_kb = |name: &str| {
+        meminfo.lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .and_then(|value| value.trim().trim_end_matches("kB").trim().parse::<u64>().ok())
+    };
+
+    let total_mb = field_kb("MemTotal").map(|kb| kb / 1024);
+    let available_mb = field_kb("MemAvailable").map(|kb| kb / 1024);
+    let used_mb = total_mb.zip(available_mb).map(|(total, available)| total.saturating_sub(available));
+
+    (used_mb, total_mb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nvidia_smi() {
+        let output = "0, NVIDIA GeForce RTX 4090, 87, 18432, 24564\n1, NVIDIA A100-SXM4-40GB, [N/A], 512, 40960\n";
+
+        let gpus = parse_nvidia_smi(output);
+
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(gpus[0].name, "NVIDIA GeForce RTX 4090");
+        assert_eq!(gpus[0].utilization_percent, Some(87));
+        assert_eq!(gpus[0].memory_used_mb, Some(18432));
+        assert_eq!(gpus[1].utilization_percent, None);
+        assert_eq!(gpus[1].memory_total_mb, Some(40960));
+    }
+
+    #[test]
+    fn test_parse_nvidia_smi_ignores_malformed_lines() {
+        assert!(parse_nvidia_smi("").is_empty());
+        assert!(parse_nvidia_smi("No devices were found\n").is_empty());
+    }
+
+    #[test]
+    fn test_parse_meminfo() {
+        let meminfo = "MemTotal:       32768000 kB\nMemFree:         1024000 kB\nMemAvailable:   16384000 kB\n";
+
+        assert_eq!(parse_meminfo(meminfo), (Some(16000), Some(32000)));
+        assert_eq!(parse_meminfo(""), (None, None));
+    }
+
+    #[test]
+    fn test_heartbeat_payload_without_gpus() {
+        let snapshot = ResourceSnapshot {
+            gpus: Vec::new(),
+            memory_used_mb: Some(1024),
+            memory_total_mb: Some(4096),
+            active_tasks: 1,
+        };
+
+        let payload = snapshot.heartbeat_payload();
+
+        assert_eq!(payload["gpu_utilization_percent"], Value::Null);
+        assert_eq!(payload["gpu_memory_used_mb"], Value::Null);
+        assert_eq!(payload["active_tasks"], 1);
+    }
+
+    #[test]
+    fn test_heartbeat_payload_aggregates_gpus() {
+        let gpu = |index, utilization, used| GpuUsage {
+            index,
+            name: "RTX 4090".to_string(),
+            utilization_percent: Some(utilization),
+            memory_used_mb: Some(used),
+            memory_total_mb: Some(24564),
+        };
+        let snapshot = ResourceSnapshot {
+            gpus: vec![gpu(0, 80, 10000), gpu(1, 40, 2000)],
+            memory_used_mb: None,
+            memory_total_mb: None,
+            active_tasks: 2,
+        };
+
+        let payload = snapshot.heartbeat_payload();
+
+        assert_eq!(payload["gpu_utilization_percent"], 60);
+        assert_eq!(payload["gpu_memory_used_mb"], 12000);
+        assert_eq!(payload["gpu_memory_total_mb"], 49128);
+    }
+}
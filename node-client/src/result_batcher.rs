@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+use tokio::time::{Duration, Instant};
+use crate::config::SubmissionConfig;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingResult {
+    pub task_id: u64,
+    pub proof_hash: String,
+    pub output: String,
+}
+
+pub struct ResultBatcher {
+    max_size: usize,
+    max_wait: Duration,
+    pending: Vec<PendingResult>,
+    oldest: Option<Instant>,
+}
+
+impl ResultBatcher {
+    pub fn new(config: &SubmissionConfig) -> Self {
+        Self {
+            max_size: config.batch_max_size.max(1),
+            max_wait: Duration::from_millis(config.batch_max_wait_ms),
+            pending: Vec::new(),
+            oldest: None,
+        }
+    }
+
+    pub fn push(&mut self, result: PendingResult) {
+        if self.pending.is_empty() {
+            self.oldest = Some(Instant::now());
+        }
+        self.pending.push(result);
+    }
+
+    pub fn contains(&self, task_id: u64) -> bool {
+        self.pending.iter().any(|r| r.task_id == task_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn should_flush(&self) -> bool {
+        if self.pending.len() >= self.max_size {
+            return true;
+        }
+
+        match self.oldest {
+            Some(oldest) => oldest.elapsed() >= self.max_wait,
+            None => false,
+        }
+    }
+
+    /// Take up to `max_size` results for submission, oldest first.
+    pub fn take_batch(&mut self) -> Vec<PendingResult> {
+        let count = self.pending.len().min(self.max_size);
+        let batch: Vec<PendingResult> = self.pending.drain(..count).collect();
+
+        self.oldest = if self.pending.is_empty() { None } else { Some(Instant::now()) };
+        batch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_config(batch_max_size: usize, batch_max_wait_ms: u64) -> SubmissionConfig {
+        SubmissionConfig {
+            batch_enabled: true,
+            batch_max_size,
+            batch_max_wait_ms,
+            ..SubmissionConfig::default()
+        }
+    }
+
+    fn create_result(task_id: u64) -> PendingResult {
+        PendingResult {
+            task_id,
+            proof_hash: "a".repeat(64),
+            output: format!(r#"{{"result": "{}"}}"#, task_id),
+        }
+    }
+
+    #[test]
+    fn test_flush_on_max_size() {
+        let mut batcher = ResultBatcher::new(&create_test_config(3, 60_000));
+
+        batcher.push(create_result(1));
+        batcher.push(create_result(2));
+        assert!(!batcher.should_flush());
+        assert!(batcher.contains(2));
+
+        batcher.push(create_result(3));
+        batcher.push(create_result(4));
+        assert!(batcher.should_flush());
+
+        let batch = batcher.take_batch();
+        assert_eq!(batch.iter().map(|r| r.task_id).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(batcher.len(), 1);
+        assert!(!batcher.contains(1));
+    }
+
+    #[test]
+    fn test_flush_on_max_wait() {
+        let mut batcher = ResultBatcher::new(&create_test_config(10, 0));
+        assert!(!batcher.should_flush());
+
+        // A zero wait flushes as soon as anything is pending
+        batcher.push(create_result(1));
+        assert!(batcher.should_flush());
+
+        assert_eq!(batcher.take_batch().len(), 1);
+        assert!(batcher.is_empty());
+        assert!(!batcher.should_flush());
+    }
+}
@@ -0,0 +1,105 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::result_batcher::PendingResult;
+
+/// Results whose submission kept failing, persisted so the compute isn't lost
+/// if the node restarts. The daemon replays them on startup and while polling.
+pub struct ResultQueue {
+    path: PathBuf,
+    pending: Vec<PendingResult>,
+}
+
+impl ResultQueue {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        let pending = if path.exists() {
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read result queue: {}", path.display()))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse result queue: {}", path.display()))?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self { path, pending })
+    }
+
+    pub fn pending(&self) -> &[PendingResult] {
+        &self.pending
+    }
+
+    pub fn contains(&self, task_id: u64) -> bool {
+        self.pending.iter().any(|r| r.task_id == task_id)
+    }
+
+    pub fn push(&mut self, result: PendingResult) -> Result<()> {
+        self.pending.retain(|r| r.task_id != result.task_id);
+        self.pending.push(result);
+        self.save()
+    }
+
+    pub fn remove(&mut self, task_id: u64) -> Result<()> {
+        self.pending.retain(|r| r.task_id != task_id);
+        self.save()
+    }
+
+    // Written to a temporary file and renamed so a crash never leaves half a queue
+    fn save(&self) -> Result<()> {
+        let tmp_path = self.path.with_extension("tmp");
+        let content = serde_json::to_string_pretty(&self.pending)?;
+
+        fs::write(&tmp_path, content)
+            .with_context(|| format!("Failed to write result queue: {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("Failed to replace result queue: {}", self.path.display()))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn create_result(task_id: u64) -> PendingResult {
+        PendingResult {
+            task_id,
+            proof_hash: "a".repeat(64),
+            output: format!(r#"{{"result": "{}"}}"#, task_id),
+        }
+    }
+
+    #[test]
+    fn test_queue_survives_reopen() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("pending_results.json");
+
+        let mut queue = ResultQueue::open(&path).unwrap();
+        assert!(queue.pending().is_empty());
+        queue.push(create_result(1)).unwrap();
+        queue.push(create_result(2)).unwrap();
+
+        let mut reopened = ResultQueue::open(&path).unwrap();
+        assert_eq!(reopened.pending(), &[create_result(1), create_result(2)]);
+
+        reopened.remove(1).unwrap();
+        assert!(!ResultQueue::open(&path).unwrap().contains(1));
+        assert!(ResultQueue::open(&path).unwrap().contains(2));
+    }
+
+    #[test]
+    fn test_requeued_task_replaces_older_entry() {
+        let dir = tempdir().unwrap();
+        let mut queue = ResultQueue::open(dir.path().join("pending_results.json")).unwrap();
+
+        queue.push(create_result(7)).unwrap();
+        let mut updated = create_result(7);
+        updated.output = r#"{"result": "retried"}"#.to_string();
+        queue.push(updated.clone()).unwrap();
+
+        assert_eq!(queue.pending(), &[updated]);
+    }
+}
@@ -0,0 +1,170 @@
+use anyhow::{Context, Result};
+use log::{error, info, warn};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+use crate::config::AutoTopupConfig;
+use crate::near_client::{NearClient, NodeInfo};
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, PartialEq)]
+enum TopUpDecision {
+    NotNeeded,
+    TopUp(u128),
+    DailyCapReached { spent_today: u128 },
+    InsufficientBalance { balance: u128 },
+}
+
+#[derive(Debug)]
+struct TopUpPolicy {
+    threshold: u128,
+    amount: u128,
+    max_per_day: u128,
+}
+
+impl TopUpPolicy {
+    fn decide(&self, effective_stake: u128, spent_today: u128, balance: u128) -> TopUpDecision {
+        if effective_stake >= self.threshold {
+            TopUpDecision::NotNeeded
+        } else if spent_today + self.amount > self.max_per_day {
+            TopUpDecision::DailyCapReached { spent_today }
+        } else if balance < self.amount {
+            TopUpDecision::InsufficientBalance { balance }
+        } else {
+            TopUpDecision::TopUp(self.amount)
+        }
+    }
+}
+
+/// Restores effective stake (`stake - slashed_amount`) after slashing by
+/// topping it up from the account balance, at most `max_per_day` NEAR per
+/// rolling 24 hours.
+pub struct StakeTopUp {
+    near_client: Arc<NearClient>,
+    policy: TopUpPolicy,
+    // (unix seconds, yoctoNEAR) of top-ups in the last 24 hours; the lock also
+    // keeps concurrent health checks from topping up twice
+    recent: Mutex<VecDeque<(u64, u128)>>,
+}
+
+impl StakeTopUp {
+    pub fn new(near_client: Arc<NearClient>, config: &AutoTopupConfig) -> Result<Self> {
+        Ok(Self {
+            near_client,
+            policy: TopUpPolicy {
+                threshold: near_to_yocto(&config.threshold).context("Invalid auto_topup threshold")?,
+                amount: near_to_yocto(&config.amount).context("Invalid auto_topup amount")?,
+                max_per_day: near_to_yocto(&config.max_per_day).context("Invalid auto_topup max_per_day")?,
+            },
+            recent: Mutex::new(VecDeque::new()),
+        })
+    }
+
+    /// Top up the stake if it fell below the threshold. Returns the amount
+    /// added, if any.
+    pub async fn check(&self, node_info: &NodeInfo) -> Result<Option<u128>> {
+        let stake: u128 = node_info.stake.parse().context("Invalid stake in node info")?;
+        let slashed: u128 = node_info.slashed_amount.parse().unwrap_or(0);
+        let effective_stake = stake.saturating_sub(slashed);
+        if effective_stake >= self.policy.threshold {
+            return Ok(None);
+        }
+
+        let mut recent = self.recent.lock().await;
+        let now = unix_now();
+        while recent.front().map_or(false, |(at, _)| now.saturating_sub(*at) >= DAY.as_secs()) {
+            recent.pop_front();
+        }
+        let spent_today = recent.iter().map(|(_, amount)| amount).sum::<u128>();
+
+        let balance = self.near_client.get_account_balance().await?;
+
+        match self.policy.decide(effective_stake, spent_today, balance) {
+            TopUpDecision::NotNeeded => Ok(None),
+            TopUpDecision::DailyCapReached { spent_today } => {
+                warn!("Effective stake {} yoctoNEAR is below the top-up threshold, but {} yoctoNEAR \
+                       was already added in the last 24 hours", effective_stake, spent_today);
+                Ok(None)
+            }
+            TopUpDecision::InsufficientBalance { balance } => {
+                error!("Refusing stake top-up of {} yoctoNEAR: account balance is only {} yoctoNEAR",
+                       self.policy.amount, balance);
+                Ok(None)
+            }
+            TopUpDecision::TopUp(amount) => {
+                let result = self.near_client.top_up_stake(amount).await
+                    .context("Failed to top up stake")?;
+                recent.push_back((now, amount));
+
+                info!("Topped up stake by {} yoctoNEAR (effective stake was {} yoctoNEAR). Transaction: {}",
+                      amount, effective_stake, result.transaction.hash);
+                Ok(Some(amount))
+            }
+        }
+    }
+}
+
+// Parsed exactly rather than through f64, which can't represent 1e24 yocto
+pub fn near_to_yocto(near: &str) -> Result<u128> {
+    let near = near.trim();
+    let (whole, fraction) = near.split_once('.').unwrap_or((near, ""));
+    let digits_ok = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if whole.is_empty() || !digits_ok(whole) || !digits_ok(fraction) || fraction.len() > 24 {
+        anyhow::bail!("Invalid NEAR amount: {}", near);
+    }
+
+    let whole: u128 = whole.parse().context("NEAR amount too large")?;
+    let fraction: u128 = format!("{:0<24}", fraction).parse().context("Invalid NEAR amount")?;
+
+    whole.checked_mul(10u128.pow(24))
+        .and_then(|yocto| yocto.checked_add(fraction))
+        .context("NEAR amount too large")
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE_NEAR: u128 = 1_000_000_000_000_000_000_000_000;
+
+    fn decide(effective_stake: u128, spent_today: u128, balance: u128) -> TopUpDecision {
+        let policy = TopUpPolicy {
+            threshold: ONE_NEAR,
+            amount: ONE_NEAR / 2,
+            max_per_day: ONE_NEAR,
+        };
+        policy.decide(effective_stake, spent_today, balance)
+    }
+
+    #[test]
+    fn test_near_to_yocto() {
+        assert_eq!(near_to_yocto("1").unwrap(), ONE_NEAR);
+        assert_eq!(near_to_yocto("0.5").unwrap(), ONE_NEAR / 2);
+        assert_eq!(near_to_yocto("2.000000000000000000000001").unwrap(), 2 * ONE_NEAR + 1);
+        assert!(near_to_yocto("-1").is_err());
+        assert!(near_to_yocto("lots").is_err());
+    }
+
+    #[test]
+    fn test_top_up_decisions() {
+        assert_eq!(decide(ONE_NEAR, 0, 10 * ONE_NEAR), TopUpDecision::NotNeeded);
+        assert_eq!(decide(ONE_NEAR * 9 / 10, 0, 10 * ONE_NEAR), TopUpDecision::TopUp(ONE_NEAR / 2));
+        assert_eq!(
+            decide(ONE_NEAR * 9 / 10, ONE_NEAR, 10 * ONE_NEAR),
+            TopUpDecision::DailyCapReached { spent_today: ONE_NEAR }
+        );
+        assert_eq!(
+            decide(ONE_NEAR * 9 / 10, 0, ONE_NEAR / 4),
+            TopUpDecision::InsufficientBalance { balance: ONE_NEAR / 4 }
+        );
+    }
+}
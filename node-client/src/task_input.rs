@@ -0,0 +1,145 @@
+use anyhow::{Context, Result};
+use log::debug;
+use near_crypto::SecretKey;
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+use std::time::Duration;
+use crate::config::NodeConfig;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where the gateway keeps the input of a task submitted with
+/// `off_chain_input`, in place of the `input` field of the description.
+#[derive(Debug, Deserialize)]
+struct InputRef {
+    task_id: String,
+    url: String,
+    sha256: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TaskInputResponse {
+    input: String,
+}
+
+/// Fetches off-chain task input from the gateway, signing each request with
+/// the node's NEAR key so only the assigned node can read it.
+pub struct InputFetcher {
+    client: reqwest::Client,
+    account_id: String,
+    private_key: String,
+}
+
+impl InputFetcher {
+    pub fn new(config: &NodeConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .build()
+            .context("Failed to build task input client")?;
+
+        Ok(Self {
+            client,
+            account_id: config.node.account_id.clone(),
+            private_key: config.node.private_key.clone(),
+        })
+    }
+
+    /// The description with any `input_ref` replaced by the input it points
+    /// to. Descriptions carrying their input inline are returned unchanged.
+    pub async fn resolve(&self, description: &str) -> Result<String> {
+        let Ok(mut task_desc) = serde_json::from_str::<Value>(description) else {
+            // Left for task validation to reject
+            return Ok(description.to_string());
+        };
+        let Some(input_ref) = task_desc.get("input_ref").cloned() else {
+            return Ok(description.to_string());
+        };
+        let input_ref: InputRef = serde_json::from_value(input_ref)
+            .context("Invalid input_ref in task description")?;
+
+        let input = self.fetch(&input_ref).await?;
+        if sha256_hex(&input) != input_ref.sha256 {
+            anyhow::bail!("Input of task {} does not match its hash", input_ref.task_id);
+        }
+
+        task_desc["input"] = Value::String(input);
+        if let Some(fields) = task_desc.as_object_mut() {
+            fields.remove("input_ref");
+        }
+        Ok(task_desc.to_string())
+    }
+
+    async fn fetch(&self, input_ref: &InputRef) -> Result<String> {
+        // Parsed here rather than in `new` so a node that never sees
+        // off-chain input doesn't need a valid key to start processing
+        let secret_key = SecretKey::from_str(&self.private_key)
+            .context("Invalid private key format")?;
+        let timestamp = chrono::Utc::now().timestamp();
+        let signature = secret_key.sign(task_input_message(&input_ref.task_id, timestamp).as_bytes());
+
+        debug!("Fetching off-chain input of task {}", input_ref.task_id);
+        let response = self.client.get(&input_ref.url)
+            .header("X-Node-Account-Id", &self.account_id)
+            .header("X-Node-Public-Key", secret_key.public_key().to_string())
+            .header("X-Node-Timestamp", timestamp.to_string())
+            .header("X-Node-Signature", signature.to_string())
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach {}", input_ref.url))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Gateway returned {} for task input: {}", status, body);
+        }
+
+        let body: TaskInputResponse = response.json().await
+            .context("Invalid task input response")?;
+        Ok(body.input)
+    }
+}
+
+// Must match the gateway's `node_auth::task_input_message`
+fn task_input_message(task_id: &str, timestamp: i64) -> String {
+    format!("deai-task-input|{}|{}", task_id, timestamp)
+}
+
+fn sha256_hex(input: &str) -> String {
+    hex::encode(Sha256::digest(input.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_inline_input_is_unchanged() {
+        let fetcher = InputFetcher::new(&NodeConfig::default()).unwrap();
+        let description = serde_json::json!({
+            "model": "gpt2",
+            "input": "hello",
+            "task_type": "inference"
+        }).to_string();
+
+        assert_eq!(fetcher.resolve(&description).await.unwrap(), description);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_input_ref_is_rejected() {
+        let fetcher = InputFetcher::new(&NodeConfig::default()).unwrap();
+        let description = serde_json::json!({
+            "model": "gpt2",
+            "input_ref": { "url": "http://localhost:1/input" },
+            "task_type": "inference"
+        }).to_string();
+
+        assert!(fetcher.resolve(&description).await.is_err());
+    }
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(sha256_hex("hello"), "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824");
+    }
+}
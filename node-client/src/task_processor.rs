@@ -4,11 +4,21 @@ use std::sync::Arc;
 use tokio::sync::Semaphore;
 use crate::config::NodeConfig;
 use crate::ai_engine::{AiEngine, TaskExecution};
-use crate::near_client::TaskInfo;
+use crate::backend::supported_task_types;
+use crate::near_client::{TaskInfo, MAX_CHUNKED_OUTPUT_LEN};
+use crate::task_input::InputFetcher;
+
+const SUPPORTED_TASK_TYPES: [&str; 4] = ["inference", "text_generation", "classification", "embedding"];
+
+// fp16 weights, used to estimate a model's size from its parameter count
+const BYTES_PER_PARAMETER: f64 = 2.0;
+// Activations, KV cache and runtime on top of the weights
+const MEMORY_OVERHEAD_FACTOR: f64 = 1.2;
 
 pub struct TaskProcessor {
     config: NodeConfig,
     ai_engine: AiEngine,
+    input_fetcher: InputFetcher,
     semaphore: Arc<Semaphore>,
 }
 
@@ -21,6 +31,8 @@ impl TaskProcessor {
         ai_engine.check_environment().await
             .context("AI environment check failed")?;
         
+        let input_fetcher = InputFetcher::new(config)?;
+        
         let max_concurrent = config.hardware.max_concurrent_tasks as usize;
         let semaphore = Arc::new(Semaphore::new(max_concurrent));
         
@@ -29,6 +41,7 @@ impl TaskProcessor {
         Ok(Self {
             config: config.clone(),
             ai_engine,
+            input_fetcher,
             semaphore,
         })
     }
@@ -40,6 +53,11 @@ impl TaskProcessor {
         
         info!("Starting execution of task {}", task.id);
         
+        // Inputs kept off-chain are fetched from the gateway first
+        let description = self.input_fetcher.resolve(&task.description).await
+            .context("Failed to fetch task input")?;
+        let task = &TaskInfo { description, ..task.clone() };
+        
         // Validate task before execution
         self.validate_task(task)?;
         
@@ -80,6 +98,11 @@ impl TaskProcessor {
             anyhow::bail!("Model name cannot be empty");
         }
         
+        // Reject before spending compute on a model this node can't hold
+        if let Some(shortfall) = capacity_shortfall(&self.config, &task_desc) {
+            anyhow::bail!("Task exceeds node capacity: {}", shortfall);
+        }
+        
         // Basic model name validation (should be Hugging Face format)
         if !model.contains('/') && !model.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
             warn!("Model name '{}' may not be valid Hugging Face format", model);
@@ -97,8 +120,7 @@ impl TaskProcessor {
         let task_type = task_desc["task_type"].as_str()
             .ok_or_else(|| anyhow::anyhow!("Task type must be a string"))?;
         
-        let supported_types = ["inference", "text_generation", "classification", "embedding"];
-        if !supported_types.contains(&task_type) {
+        if !SUPPORTED_TASK_TYPES.contains(&task_type) {
             anyhow::bail!("Unsupported task type: {}", task_type);
         }
         
@@ -106,6 +128,12 @@ impl TaskProcessor {
         Ok(())
     }
     
+    /// Why this node can't run the task, checked before execution so it can be
+    /// declined and reassigned rather than fail in the worker. `None` if it can.
+    pub fn decline_reason(&self, task: &TaskInfo) -> Option<String> {
+        capability_mismatch(&self.config, &task.description)
+    }
+    
     fn validate_execution_result(&self, result: &TaskExecution) -> Result<()> {
         // Check proof hash format
         if result.proof_hash.is_empty() {
@@ -129,23 +157,25 @@ impl TaskProcessor {
         let _output_json: serde_json::Value = serde_json::from_str(&result.output)
             .context("Output is not valid JSON")?;
         
-        // Check output size (reasonable limit)
-        if result.output.len() > 1_000_000 {
-            anyhow::bail!("Output too large: {} bytes (max 1MB)", result.output.len());
+        // The contract takes at most this much, even streamed in chunks
+        if result.output.len() > MAX_CHUNKED_OUTPUT_LEN {
+            anyhow::bail!("Output too large: {} bytes (max {})", result.output.len(), MAX_CHUNKED_OUTPUT_LEN);
         }
         
         debug!("Execution result validation passed");
         Ok(())
     }
     
-    pub async fn test_execution(&self) -> Result<()> {
-        info!("Running task processor test...");
+    /// Run a sample inference task locally, without touching the chain.
+    /// Returns its proof hash and output.
+    pub async fn test_execution(&self, model: &str) -> Result<(String, String)> {
+        info!("Running task processor test with model {}...", model);
         
         // Create a simple test task
         let test_task = TaskInfo {
             id: 0,
             description: serde_json::json!({
-                "model": "distilbert-base-uncased",
+                "model": model,
                 "input": "This is a test input for task processor validation.",
                 "task_type": "inference",
                 "parameters": {
@@ -157,6 +187,7 @@ impl TaskProcessor {
             created_at: chrono::Utc::now().timestamp() as u64,
             reward_amount: "100000000000000000000000".to_string(), // 0.1 NEAR
             requester: "test.testnet".parse().unwrap(),
+            priority: None,
         };
         
         // Execute the test task
@@ -166,7 +197,7 @@ impl TaskProcessor {
         debug!("Proof hash: {}", proof_hash);
         debug!("Output length: {} bytes", output.len());
         
-        Ok(())
+        Ok((proof_hash, output))
     }
     
     pub fn get_current_load(&self) -> usize {
@@ -175,6 +206,11 @@ impl TaskProcessor {
         max_permits - available_permits
     }
     
+    /// Permits limiting concurrent tasks; one is held per running task.
+    pub fn task_permits(&self) -> Arc<Semaphore> {
+        self.semaphore.clone()
+    }
+    
     pub fn is_at_capacity(&self) -> bool {
         self.semaphore.available_permits() == 0
     }
@@ -187,6 +223,65 @@ impl TaskProcessor {
     }
 }
 
+// Malformed descriptions aren't declined; no node could run them and
+// validation reports them when they are executed
+fn capability_mismatch(config: &NodeConfig, description: &str) -> Option<String> {
+    let task_desc: serde_json::Value = serde_json::from_str(description).ok()?;
+    let ai = &config.ai;
+    
+    let task_type = task_desc["task_type"].as_str()?;
+    if !supported_task_types(&ai.backend).contains(&task_type) {
+        return Some(format!("Unsupported task type: {}", task_type));
+    }
+    
+    if let Some(framework) = task_desc["framework"].as_str() {
+        if !ai.supported_frameworks.iter().any(|f| f.eq_ignore_ascii_case(framework)) {
+            return Some(format!("Unsupported framework: {}", framework));
+        }
+    }
+    
+    capacity_shortfall(config, &task_desc)
+}
+
+// Models of unknown size are let through; the worker reports real failures
+fn capacity_shortfall(config: &NodeConfig, task_desc: &serde_json::Value) -> Option<String> {
+    let model = task_desc["model"].as_str()?;
+    let size_gb = task_desc["model_size_gb"].as_f64()
+        .or_else(|| estimate_model_size_gb(model))?;
+    
+    if size_gb > config.ai.max_model_size_gb as f64 {
+        return Some(format!(
+            "Model {} needs about {:.1} GB, above this node's {} GB limit",
+            model, size_gb, config.ai.max_model_size_gb
+        ));
+    }
+    
+    let footprint_gb = size_gb * MEMORY_OVERHEAD_FACTOR;
+    if footprint_gb > config.hardware.memory_gb as f64 {
+        return Some(format!(
+            "Model {} needs about {:.1} GB of memory to run, this node has {} GB",
+            model, footprint_gb, config.hardware.memory_gb
+        ));
+    }
+    
+    None
+}
+
+// Reads a parameter count such as "7b" or "350m" from a model name like "meta-llama/Llama-2-7b-hf"
+fn estimate_model_size_gb(model: &str) -> Option<f64> {
+    model.to_lowercase()
+        .split(|c: char| c == '-' || c == '_' || c == '/')
+        .find_map(|part| {
+            let (count, scale) = match part.chars().last()? {
+                'b' => (part.strip_suffix('b')?, 1e9),
+                'm' => (part.strip_suffix('m')?, 1e6),
+                _ => return None,
+            };
+            let parameters = count.parse::<f64>().ok()? * scale;
+            Some(parameters * BYTES_PER_PARAMETER / 1e9)
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -213,6 +308,7 @@ mod tests {
             created_at: 1234567890,
             reward_amount: "1000".to_string(),
             requester: "user.testnet".parse().unwrap(),
+            priority: None,
         }
     }
     
@@ -249,4 +345,103 @@ mod tests {
         // Test capacity management would require async setup
         // and proper semaphore testing
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_accepts_supported_task() {
+        let config = create_test_config();
+        let task = create_test_task();
+        
+        assert_eq!(capability_mismatch(&config, &task.description), None);
+    }
+    
+    #[test]
+    fn test_declines_unsupported_task_type() {
+        let config = create_test_config();
+        let description = serde_json::json!({
+            "model": "bert-base-uncased",
+            "input": "test input",
+            "task_type": "training"
+        }).to_string();
+        
+        let reason = capability_mismatch(&config, &description).unwrap();
+        assert!(reason.contains("Unsupported task type"));
+    }
+    
+    #[test]
+    fn test_declines_model_above_size_limit() {
+        let mut config = create_test_config();
+        config.ai.max_model_size_gb = 20;
+        
+        let large = serde_json::json!({
+            "model": "meta-llama/Llama-2-70b-chat-hf",
+            "input": "Hello",
+            "task_type": "text_generation"
+        }).to_string();
+        let small = serde_json::json!({
+            "model": "meta-llama/Llama-2-7b-chat-hf",
+            "input": "Hello",
+            "task_type": "text_generation"
+        }).to_string();
+        
+        assert!(capability_mismatch(&config, &large).unwrap().contains("20 GB limit"));
+        assert_eq!(capability_mismatch(&config, &small), None);
+    }
+    
+    #[test]
+    fn test_explicit_model_size_overrides_estimate() {
+        let mut config = create_test_config();
+        config.ai.max_model_size_gb = 10;
+        
+        let description = serde_json::json!({
+            "model": "custom-model",
+            "input": "Hello",
+            "task_type": "inference",
+            "model_size_gb": 12.5
+        }).to_string();
+        
+        assert!(capability_mismatch(&config, &description).is_some());
+    }
+    
+    #[test]
+    fn test_estimate_model_size_gb() {
+        assert_eq!(estimate_model_size_gb("facebook/opt-350m"), Some(0.7));
+        assert_eq!(estimate_model_size_gb("mistralai/Mistral-7B-v0.1"), Some(14.0));
+        assert_eq!(estimate_model_size_gb("bert-base-uncased"), None);
+    }
+    
+    #[test]
+    fn test_oversized_model_is_rejected() {
+        let mut config = create_test_config();
+        config.ai.max_model_size_gb = 200;
+        config.hardware.memory_gb = 32;
+        
+        let task_desc = serde_json::json!({
+            "model": "tiiuae/falcon-40b",
+            "input": "Hello",
+            "task_type": "text_generation"
+        });
+        
+        let shortfall = capacity_shortfall(&config, &task_desc).unwrap();
+        assert!(shortfall.contains("memory"));
+    }
+    
+    #[test]
+    fn test_small_model_fits() {
+        let mut config = create_test_config();
+        config.hardware.memory_gb = 32;
+        
+        let small = serde_json::json!({
+            "model": "facebook/opt-1.3b",
+            "input": "Hello",
+            "task_type": "text_generation"
+        });
+        let unknown_size = serde_json::json!({
+            "model": "bert-base-uncased",
+            "input": "Hello",
+            "task_type": "inference"
+        });
+        
+        assert_eq!(capacity_shortfall(&config, &small), None);
+        assert_eq!(capacity_shortfall(&config, &unknown_size), None);
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::HashSet;
+use std::future::Future;
+use tokio::sync::mpsc;
+
+/// Runs task executions concurrently and hands back each outcome as soon as
+/// it finishes, so a slow task never holds up results of faster ones.
+pub struct TaskRunner<T> {
+    running: HashSet<u64>,
+    completed_tx: mpsc::UnboundedSender<(u64, T)>,
+    completed_rx: mpsc::UnboundedReceiver<(u64, T)>,
+}
+
+impl<T: Send + 'static> TaskRunner<T> {
+    pub fn new() -> Self {
+        let (completed_tx, completed_rx) = mpsc::unbounded_channel();
+
+        Self {
+            running: HashSet::new(),
+            completed_tx,
+            completed_rx,
+        }
+    }
+
+    pub fn spawn<F>(&mut self, task_id: u64, execution: F)
+    where
+        F: Future<Output = T> + Send + 'static,
+    {
+        self.running.insert(task_id);
+
+        let completed_tx = self.completed_tx.clone();
+        tokio::spawn(async move {
+            let outcome = execution.await;
+            // The receiver lives as long as the runner, so this only fails on shutdown
+            let _ = completed_tx.send((task_id, outcome));
+        });
+    }
+
+    pub fn is_running(&self, task_id: u64) -> bool {
+        self.running.contains(&task_id)
+    }
+
+    pub fn running_count(&self) -> usize {
+        self.running.len()
+    }
+
+    /// Wait for the next execution to finish, in completion order. Pending
+    /// forever while nothing is running.
+    pub async fn next_completed(&mut self) -> Option<(u64, T)> {
+        let (task_id, outcome) = self.completed_rx.recv().await?;
+        self.running.remove(&task_id);
+        Some((task_id, outcome))
+    }
+}
+
+impl<T: Send + 'static> Default for TaskRunner<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_slow_task_does_not_block_fast_one() {
+        let mut runner = TaskRunner::new();
+
+        runner.spawn(1, async {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            "slow"
+        });
+        runner.spawn(2, async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            "fast"
+        });
+        assert_eq!(runner.running_count(), 2);
+
+        assert_eq!(runner.next_completed().await, Some((2, "fast")));
+        assert!(runner.is_running(1));
+        assert!(!runner.is_running(2));
+
+        assert_eq!(runner.next_completed().await, Some((1, "slow")));
+        assert_eq!(runner.running_count(), 0);
+    }
+}
@@ -0,0 +1,264 @@
+use anyhow::{Context, Result};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::{Mutex, Semaphore};
+use crate::ai_engine::TaskExecution;
+
+// A task whose worker dies is re-queued once on a fresh worker before failing
+const MAX_TASK_ATTEMPTS: u32 = 2;
+
+#[derive(Serialize)]
+struct WorkerRequest<'a> {
+    id: u64,
+    task: &'a Value,
+}
+
+#[derive(Deserialize)]
+struct WorkerResponse {
+    id: u64,
+    proof_hash: String,
+    output: String,
+}
+
+/// A long-lived `ai_worker.py --serve` process. It reads its config, then one
+/// JSON request per line on stdin and answers each with one line on stdout,
+/// so models stay loaded between tasks.
+struct PythonWorker {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_request_id: u64,
+}
+
+impl PythonWorker {
+    async fn spawn(python_path: &Path, script_path: &Path, worker_config: &Value) -> Result<Self> {
+        let mut child = Command::new(python_path)
+            .arg(script_path)
+            .arg("--serve")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit()) // Worker logs go to stderr
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to start Python AI worker")?;
+
+        let mut stdin = child.stdin.take().context("Python worker has no stdin")?;
+        let stdout = BufReader::new(child.stdout.take().context("Python worker has no stdout")?);
+
+        // The config is sent once rather than per task, and stays out of the process list
+        let mut config_line = serde_json::to_string(worker_config)?;
+        config_line.push('\n');
+        stdin.write_all(config_line.as_bytes()).await
+            .context("Failed to send config to Python worker")?;
+        stdin.flush().await?;
+
+        debug!("Started Python worker (pid {:?})", child.id());
+
+        Ok(Self {
+            child,
+            stdin,
+            stdout,
+            next_request_id: 0,
+        })
+    }
+
+    async fn run(&mut self, task_data: &Value) -> Result<TaskExecution> {
+        self.next_request_id += 1;
+        let id = self.next_request_id;
+
+        let mut request = serde_json::to_string(&WorkerRequest { id, task: task_data })?;
+        request.push('\n');
+        self.stdin.write_all(request.as_bytes()).await
+            .context("Failed to send task to Python worker")?;
+        self.stdin.flush().await?;
+
+        let mut line = String::new();
+        let read = self.stdout.read_line(&mut line).await
+            .context("Failed to read Python worker response")?;
+        if read == 0 {
+            anyhow::bail!("Python worker exited while running a task");
+        }
+
+        let response: WorkerResponse = serde_json::from_str(&line)
+            .context("Failed to parse Python worker response")?;
+        if response.id != id {
+            anyhow::bail!("Python worker answered request {} while {} was pending", response.id, id);
+        }
+
+        Ok(TaskExecution {
+            proof_hash: response.proof_hash,
+            output: response.output,
+        })
+    }
+
+    // Kills the process and waits for it, so no zombie is left behind
+    async fn terminate(&mut self) {
+        if let Err(e) = self.child.kill().await {
+            warn!("Failed to kill Python worker: {}", e);
+        }
+    }
+}
+
+/// Pool of Python workers, at most `size` of them. Workers are started on
+/// demand and kept for later tasks; one that crashes or breaks the protocol
+/// is discarded and its task retried on a replacement. A task that runs past
+/// `task_timeout` has its worker killed and fails without a retry.
+pub struct WorkerPool {
+    python_path: PathBuf,
+    script_path: PathBuf,
+    worker_config: Value,
+    idle: Mutex<Vec<PythonWorker>>,
+    permits: Semaphore,
+    task_timeout: Duration,
+}
+
+impl WorkerPool {
+    pub fn new(
+        python_path: PathBuf,
+        script_path: PathBuf,
+        worker_config: Value,
+        size: usize,
+        task_timeout: Duration,
+    ) -> Self {
+        let size = size.max(1);
+        info!("Python worker pool sized for {} workers", size);
+
+        Self {
+            python_path,
+            script_path,
+            worker_config,
+            idle: Mutex::new(Vec::with_capacity(size)),
+            permits: Semaphore::new(size),
+            task_timeout,
+        }
+    }
+
+    pub async fn execute(&self, task_data: &Value) -> Result<TaskExecution> {
+        // One permit per worker, so an idle worker or room for a new one is guaranteed
+        let _permit = self.permits.acquire().await
+            .context("Python worker pool is closed")?;
+
+        let mut last_error = None;
+        for attempt in 1..=MAX_TASK_ATTEMPTS {
+            let idle_worker = self.idle.lock().await.pop();
+            let mut worker = match idle_worker {
+                Some(worker) => worker,
+                None => PythonWorker::spawn(&self.python_path, &self.script_path, &self.worker_config).await?,
+            };
+
+            match tokio::time::timeout(self.task_timeout, worker.run(task_data)).await {
+                Ok(Ok(execution)) => {
+                    self.idle.lock().await.push(worker);
+                    return Ok(execution);
+                }
+                Ok(Err(e)) => {
+                    warn!("Python worker failed (attempt {}/{}): {:#}", attempt, MAX_TASK_ATTEMPTS, e);
+                    // Its state is unknown, so the worker is replaced rather than reused
+                    worker.terminate().await;
+                    last_error = Some(e);
+                }
+                Err(_) => {
+                    // Retrying would most likely hang a fresh worker the same way
+                    worker.terminate().await;
+                    anyhow::bail!("Task timed out after {}s and its worker was killed", self.task_timeout.as_secs_f64());
+                }
+            }
+        }
+
+        Err(last_error.expect("at least one attempt is made"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    // Speaks the worker protocol without torch. Exits before answering its
+    // first task if `crash_marker` names a file that doesn't exist yet.
+    const FAKE_WORKER: &str = r#"
+import json, os, sys
+config = json.loads(sys.stdin.readline())
+marker = config.get("crash_marker")
+for line in sys.stdin:
+    request = json.loads(line)
+    if marker and not os.path.exists(marker):
+        open(marker, "w").close()
+        sys.exit(1)
+    print(json.dumps({"id": request["id"], "proof_hash": str(os.getpid()), "output": request["task"]["description"]}), flush=True)
+"#;
+
+    // Records its pid, then never answers
+    const SLEEPING_WORKER: &str = r#"
+import json, os, sys, time
+config = json.loads(sys.stdin.readline())
+with open(config["pid_file"], "w") as f:
+    f.write(str(os.getpid()))
+for line in sys.stdin:
+    time.sleep(600)
+"#;
+
+    fn create_pool_with(script_source: &str, worker_config: Value, task_timeout: Duration) -> (WorkerPool, NamedTempFile) {
+        let mut script = NamedTempFile::new().unwrap();
+        script.write_all(script_source.as_bytes()).unwrap();
+
+        let pool = WorkerPool::new(PathBuf::from("python3"), script.path().to_path_buf(), worker_config, 1, task_timeout);
+        (pool, script)
+    }
+
+    fn create_pool(worker_config: Value) -> (WorkerPool, NamedTempFile) {
+        create_pool_with(FAKE_WORKER, worker_config, Duration::from_secs(30))
+    }
+
+    #[tokio::test]
+    async fn test_worker_is_reused_between_tasks() {
+        let (pool, _script) = create_pool(json!({}));
+
+        let first = pool.execute(&json!({ "description": "first" })).await.unwrap();
+        let second = pool.execute(&json!({ "description": "second" })).await.unwrap();
+
+        assert_eq!(first.output, "first");
+        assert_eq!(second.output, "second");
+        // Same process answered both
+        assert_eq!(first.proof_hash, second.proof_hash);
+    }
+
+    #[tokio::test]
+    async fn test_crashed_worker_is_replaced_and_task_retried() {
+        let marker_dir = tempfile::tempdir().unwrap();
+        let marker = marker_dir.path().join("crashed");
+        let (pool, _script) = create_pool(json!({ "crash_marker": marker }));
+
+        let execution = pool.execute(&json!({ "description": "survives" })).await.unwrap();
+
+        assert!(marker.exists());
+        assert_eq!(execution.output, "survives");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_hung_task_times_out_and_worker_is_reaped() {
+        let pid_dir = tempfile::tempdir().unwrap();
+        let pid_file = pid_dir.path().join("worker.pid");
+        let (pool, _script) = create_pool_with(
+            SLEEPING_WORKER,
+            json!({ "pid_file": pid_file }),
+            Duration::from_secs(1),
+        );
+
+        let error = pool.execute(&json!({ "description": "hangs" })).await.unwrap_err();
+        assert!(error.to_string().contains("timed out"));
+
+        // A zombie would still have a /proc entry; a reaped process does not
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        assert!(!Path::new(&format!("/proc/{}", pid.trim())).exists());
+    }
+}
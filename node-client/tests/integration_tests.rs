@@ -262,5 +262,6 @@ fn create_test_task(description: serde_json::Value) -> TaskInfo {
         created_at: 1640995200, // 2022-01-01
         reward_amount: "100000000000000000000000".to_string(), // 0.1 NEAR
         requester: "user.testnet".parse().unwrap(),
+        priority: None,
     }
 }
\ No newline at end of file
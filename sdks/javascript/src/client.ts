@@ -88,7 +88,25 @@ export class DeAIClient extends EventEmitter {
   }
 
   /**
-   * Login with Near wallet signature
+   * Request a single-use challenge for Near wallet login. Sign the returned
+   * `message` with the wallet and pass it to `loginWithNear`.
+   */
+  async requestNearChallenge(
+    accountId: string
+  ): Promise<{ challenge: string; message: string; expires_in: number }> {
+    try {
+      const response = await this.api.post('/api/v1/auth/near-challenge', {
+        account_id: accountId,
+      });
+
+      return response.data;
+    } catch (error) {
+      throw new AuthError('Failed to get Near login challenge', { cause: error });
+    }
+  }
+
+  /**
+   * Login with Near wallet signature over a message from `requestNearChallenge`
    */
   async loginWithNear(
     accountId: string,